@@ -0,0 +1,189 @@
+use anyhow::Result;
+use serde::Serialize;
+use crate::git_manager::GitManager;
+
+#[derive(Clone, Serialize)]
+pub struct PayloadField {
+    name: String,
+    // "string", "bool_string" ("true" or "false"), "number", "bool", or "string_array".
+    field_type: String,
+    required: bool,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CommandInfo {
+    id: String,
+    label: String,
+    // "none", "string" for a bare string payload, or "object" for an object with payload_fields.
+    payload_type: String,
+    payload_fields: Vec<PayloadField>,
+    needs_open_repo: bool,
+    needs_clean_tree: bool,
+    enabled: bool,
+}
+
+impl CommandInfo {
+    fn new(id: &str, label: &str, payload_type: &str, payload_fields: &[(&str, &str, bool)], needs_open_repo: bool, needs_clean_tree: bool) -> Self {
+        Self {
+            id: String::from(id),
+            label: String::from(label),
+            payload_type: String::from(payload_type),
+            payload_fields: payload_fields.iter().map(|(name, field_type, required)| {
+                PayloadField {
+                    name: String::from(*name),
+                    field_type: String::from(*field_type),
+                    required: *required,
+                }
+            }).collect(),
+            needs_open_repo,
+            needs_clean_tree,
+            enabled: false,
+        }
+    }
+}
+
+// Every event the back-end listens for. Keep this in sync with the listeners in main.rs.
+fn get_all_commands() -> Vec<CommandInfo> {
+    vec![
+        CommandInfo::new("init", "Init New Repo", "string", &[], false, false),
+        CommandInfo::new("open", "Open Repo", "string", &[], false, false),
+        CommandInfo::new("clone", "Clone Repo", "object", &[("clone_url", "string", true), ("clone_path", "string", true)], false, false),
+        CommandInfo::new("save-preferences", "Save Preferences", "object", &[("limit_commits", "bool", true), ("commit_count", "number", true), ("max_blob_size", "number", false), ("auto_fetch_minutes", "number", false), ("rename_threshold", "number", false), ("copy_threshold", "number", false), ("recurse_submodules", "bool", false), ("prefill_commit_from_branch", "bool", false), ("branch_prefill_pattern", "string", false), ("branch_prefill_template", "string", false)], false, false),
+        CommandInfo::new("save-https-credentials", "Set HTTPS Credentials", "object", &[("username", "string", true), ("password", "string", true)], false, false),
+        CommandInfo::new("save-ssh-credentials", "Set SSH Credentials", "object", &[("public_key_path", "string", true), ("private_key_path", "string", true), ("passphrase", "string", true)], false, false),
+        CommandInfo::new("generate-ssh-key", "Generate SSH Key", "object", &[("file_name", "string", true), ("comment", "string", true), ("passphrase", "string", true)], false, false),
+        CommandInfo::new("list-commands", "List Commands", "none", &[], false, false),
+        CommandInfo::new("get-full-state", "Get Full State", "none", &[], false, false),
+        CommandInfo::new("open-window", "Open Window", "object", &[("kind", "string", true)], false, false),
+        CommandInfo::new("save-repo-session", "Save Repo Session", "object", &[("selected_sha", "string", false), ("active_tab", "string", false), ("scroll_anchor_sha", "string", false)], true, false),
+        CommandInfo::new("get-commit-info", "Show Commit Info", "string", &[], true, false),
+        CommandInfo::new("get-copyable-commit-fields", "Get Copyable Commit Fields", "string", &[], true, false),
+        CommandInfo::new("copy-commit-field", "Copy Commit Field", "object", &[("sha", "string", true), ("field", "string", true)], true, false),
+        CommandInfo::new("file-diff", "Show File Diff", "object", &[("file_path", "string", true), ("change_type", "string", true), ("sha", "string", true), ("context_lines", "string", false)], true, false),
+        CommandInfo::new("get-workdir-diff", "Compare With Working Directory", "string", &[], true, false),
+        CommandInfo::new("diff-merge-base", "Compare Since Merge Base", "object", &[("branch_a", "string", true), ("branch_b", "string", true)], true, false),
+        CommandInfo::new("preview-merge", "Preview Merge", "object", &[("ours", "string", true), ("theirs", "string", true), ("favor", "string", false), ("find_renames", "bool_string", false), ("rename_threshold", "string", false), ("rename_limit", "string", false), ("diff_algorithm", "string", false)], true, false),
+        CommandInfo::new("get-staged-patch", "Show Staged Patch", "none", &[], true, false),
+        CommandInfo::new("get-more-hunks", "Load More Diff Hunks", "object", &[("file_path", "string", true), ("change_type", "string", true), ("sha", "string", true), ("hunk_offset", "string", true), ("context_lines", "string", false)], true, false),
+        CommandInfo::new("expand-hunk-context", "Show More Context Around a Hunk", "object", &[("file_path", "string", true), ("change_type", "string", true), ("sha", "string", true), ("hunk_index", "string", true), ("extra_lines", "string", true), ("context_lines", "string", false)], true, false),
+        CommandInfo::new("get-blame", "Blame File", "object", &[("file_path", "string", true), ("sha", "string", true), ("use_ignore_revs", "bool_string", false), ("detect_copies", "bool_string", false)], true, false),
+        CommandInfo::new("get-file-history", "File History", "object", &[("file_path", "string", true), ("sha", "string", true), ("follow_renames", "bool_string", false), ("detect_copies", "bool_string", false)], true, false),
+        CommandInfo::new("stage", "Stage File", "object", &[("path", "string", true), ("status", "number", true)], true, false),
+        CommandInfo::new("unstage", "Unstage File", "object", &[("path", "string", true), ("status", "number", true)], true, false),
+        CommandInfo::new("stage-all", "Stage All", "none", &[], true, false),
+        CommandInfo::new("discard-changes", "Discard Changes", "object", &[("path", "string", true), ("change_type", "string", true), ("status", "string", true)], true, false),
+        CommandInfo::new("checkout-file-from", "Restore File From Commit", "object", &[("sha", "string", true), ("path", "string", true), ("target", "string", false)], true, false),
+        CommandInfo::new("checkout-with-strategy", "Checkout With Local Changes", "object", &[("target", "string", true), ("target_type", "string", true), ("strategy", "string", true)], true, false),
+        CommandInfo::new("wip-commit", "WIP Commit", "none", &[], true, false),
+        CommandInfo::new("unwip", "Undo WIP Commit", "none", &[], true, false),
+        CommandInfo::new("validate-commit", "Validate Commit", "none", &[], true, false),
+        CommandInfo::new("get-conflicts-info", "Show Conflict", "string", &[], true, false),
+        CommandInfo::new("get-conflict-regions", "Resolve Conflicts", "string", &[], true, false),
+        CommandInfo::new("resolve-conflict-region", "Resolve Conflict Region", "object", &[("path", "string", true), ("region_index", "string", true), ("resolution", "string", true), ("text", "string", false)], true, false),
+        CommandInfo::new("resolve-binary", "Resolve by Taking a Side", "object", &[("path", "string", true), ("side", "string", true)], true, false),
+        CommandInfo::new("commit", "Commit", "object", &[("summaryText", "string", true), ("messageText", "string", true), ("signOff", "bool_string", false), ("authorTime", "string", false)], true, false),
+        CommandInfo::new("commit-push", "Commit and Push", "object", &[("summaryText", "string", true), ("messageText", "string", true), ("signOff", "bool_string", false), ("authorTime", "string", false)], true, false),
+        CommandInfo::new("checkout", "Checkout Branch", "string", &[], true, false),
+        CommandInfo::new("checkout-detached-head", "Checkout Commit", "string", &[], true, false),
+        CommandInfo::new("checkout-remote", "Checkout Remote Branch", "object", &[("full_branch_name", "string", true), ("branch_shorthand", "string", true), ("local_name", "string", false)], true, false),
+        CommandInfo::new("branch", "Create Branch", "object", &[("branch_name", "string", true), ("checkout_on_create", "bool_string", true)], true, false),
+        CommandInfo::new("delete-local-branch", "Delete Local Branch", "object", &[("branch_shorthand", "string", true), ("delete_remote_branch", "bool_string", true)], true, false),
+        CommandInfo::new("delete-remote-branch", "Delete Remote Branch", "string", &[], true, false),
+        CommandInfo::new("fast-forward-branch", "Fast Forward Branch", "string", &[], true, false),
+        CommandInfo::new("merge", "Merge", "string", &[], true, true),
+        CommandInfo::new("rebase", "Rebase", "string", &[], true, true),
+        CommandInfo::new("cherrypick", "Cherry-pick", "object", &[("sha", "string", true), ("isCommitting", "bool_string", true)], true, true),
+        CommandInfo::new("revert", "Revert", "object", &[("sha", "string", true), ("isCommitting", "bool_string", true), ("mainline", "string", false)], true, true),
+        CommandInfo::new("cherrypick-multiple", "Cherry-pick Selected Commits", "object", &[("shas", "string_array", true)], true, true),
+        CommandInfo::new("revert-multiple", "Revert Selected Commits", "object", &[("shas", "string_array", true)], true, true),
+        CommandInfo::new("export-patches", "Export Selected Commits as Patches", "object", &[("shas", "string_array", true), ("path", "string", true)], true, false),
+        CommandInfo::new("get-shas-text", "Copy Selected SHAs", "object", &[("shas", "string_array", true)], true, false),
+        CommandInfo::new("reset", "Reset", "object", &[("sha", "string", true), ("type", "string", true)], true, false),
+        CommandInfo::new("abort", "Abort Operation", "none", &[], true, false),
+        CommandInfo::new("continue-cherrypick", "Continue Cherry-pick", "none", &[], true, false),
+        CommandInfo::new("continue-revert", "Continue Revert", "none", &[], true, false),
+        CommandInfo::new("continue-merge", "Continue Merge", "none", &[], true, false),
+        CommandInfo::new("abort-rebase", "Abort Rebase", "none", &[], true, false),
+        CommandInfo::new("continue-rebase", "Continue Rebase", "none", &[], true, false),
+        CommandInfo::new("get-operation-history", "Operation History", "none", &[], true, false),
+        CommandInfo::new("list-snapshots", "Safety Snapshots", "none", &[], true, false),
+        CommandInfo::new("restore-snapshot", "Restore Snapshot", "string", &[], true, false),
+        CommandInfo::new("renormalize", "Renormalize Line Endings", "none", &[], true, false),
+        CommandInfo::new("list-ignored-files", "Ignored Files", "string", &[], true, false),
+        CommandInfo::new("force-add", "Force Add Ignored File", "string", &[], true, false),
+        CommandInfo::new("expand-untracked", "Expand Untracked Directory", "string", &[], true, false),
+        CommandInfo::new("lfs-list-locks", "LFS Locks", "none", &[], true, false),
+        CommandInfo::new("lfs-lock", "Lock File (LFS)", "string", &[], true, false),
+        CommandInfo::new("lfs-unlock", "Unlock File (LFS)", "object", &[("path", "string", true), ("force", "bool_string", true)], true, false),
+        CommandInfo::new("track-with-lfs", "Track With LFS", "object", &[("pattern", "string", true), ("path", "string", true)], true, false),
+        CommandInfo::new("list-hooks", "Hooks", "none", &[], true, false),
+        CommandInfo::new("get-hook", "Read Hook", "string", &[], true, false),
+        CommandInfo::new("save-hook", "Save Hook", "object", &[("name", "string", true), ("contents", "string", true)], true, false),
+        CommandInfo::new("set-hook-executable", "Set Hook Executable", "object", &[("name", "string", true), ("executable", "bool_string", true)], true, false),
+        CommandInfo::new("install-sample-hook", "Install Sample Hook", "object", &[("sample_id", "string", true)], true, false),
+        CommandInfo::new("get-issue-link-rules", "Issue Links", "none", &[], true, false),
+        CommandInfo::new("save-issue-link-rules", "Save Issue Links", "object", &[("patterns", "string_array", true), ("url_templates", "string_array", true)], true, false),
+        CommandInfo::new("set-branch-favorite", "Favorite Branch", "object", &[("full_branch_name", "string", true), ("is_favorite", "bool_string", true)], true, false),
+        CommandInfo::new("set-index-flag", "Set Index Flag", "object", &[("path", "string", true), ("flag", "string", true), ("value", "bool_string", true)], true, false),
+        CommandInfo::new("continue-stash-apply", "Finish Stash Apply", "none", &[], true, false),
+        CommandInfo::new("abandon-stash-apply", "Abandon Stash Apply", "none", &[], true, false),
+        CommandInfo::new("apply-patch-text", "Apply Patch Text", "object", &[("content", "string", true), ("to_index", "bool_string", true), ("dry_run", "bool_string", false)], true, false),
+        CommandInfo::new("add-remote", "Add Remote", "object", &[("remote_name", "string", true), ("remote_url", "string", true), ("push_url", "string", false)], true, false),
+        CommandInfo::new("set-remote-urls", "Set Remote URLs", "object", &[("remote_name", "string", true), ("url", "string", true), ("push_url", "string", true)], true, false),
+        CommandInfo::new("add-remote-refspec", "Add Remote Refspec", "object", &[("remote_name", "string", true), ("direction", "string", true), ("refspec", "string", true)], true, false),
+        CommandInfo::new("remove-remote-refspec", "Remove Remote Refspec", "object", &[("remote_name", "string", true), ("direction", "string", true), ("refspec", "string", true)], true, false),
+        CommandInfo::new("get-branch-remotes", "Show Branch Remotes", "string", &[], true, false),
+        CommandInfo::new("set-branch-remotes", "Configure Branch Remotes", "object", &[("branch_shorthand", "string", true), ("remote", "string", true), ("push_remote", "string", true)], true, false),
+        CommandInfo::new("get-remote-head", "Show Remote HEAD", "string", &[], true, false),
+        CommandInfo::new("set-remote-head", "Set Remote HEAD", "object", &[("remote_name", "string", true), ("branch", "string", true)], true, false),
+        CommandInfo::new("add-submodule", "Add Submodule", "object", &[("url", "string", true), ("path", "string", true)], true, false),
+        CommandInfo::new("remove-submodule", "Remove Submodule", "object", &[("path", "string", true), ("force", "bool_string", false)], true, false),
+        CommandInfo::new("subtree", "Subtree", "object", &[("operation", "string", true), ("prefix", "string", true), ("remote", "string", true), ("branch", "string", true), ("squash", "bool_string", false)], true, true),
+        CommandInfo::new("fetch", "Fetch", "none", &[], true, false),
+        CommandInfo::new("deepen", "Fetch More History", "object", &[("depth", "string", false), ("unshallow", "bool_string", true)], true, false),
+        CommandInfo::new("sync", "Sync (Fetch and Fast-forward All)", "none", &[], true, false),
+        CommandInfo::new("pull", "Pull", "none", &[], true, true),
+        CommandInfo::new("push", "Push", "object", &[("selectedRemote", "string", true), ("isForcePush", "bool_string", true)], true, false),
+        CommandInfo::new("pull-branch", "Pull Branch", "string", &[], true, false),
+        CommandInfo::new("push-branch", "Push Branch", "object", &[("branch_shorthand", "string", true), ("isForcePush", "bool_string", true), ("selectedRemote", "string", false)], true, false),
+        CommandInfo::new("push-gerrit", "Push for Review (Gerrit)", "object", &[("selectedRemote", "string", true), ("targetBranch", "string", true), ("topic", "string", true), ("reviewers", "string", true)], true, false),
+        CommandInfo::new("stash", "Stash", "object", &[("message", "string", true), ("include_untracked", "bool_string", true), ("keep_index", "bool_string", false), ("staged_only", "bool_string", false)], true, false),
+        CommandInfo::new("apply-stash", "Apply Stash", "object", &[("index", "string", true), ("delete_stash", "bool_string", true)], true, false),
+        CommandInfo::new("get-stash-diff", "Preview Stash", "string", &[], true, false),
+        CommandInfo::new("stash-to-branch", "Create Branch from Stash", "object", &[("index", "string", true), ("branch_name", "string", true)], true, false),
+        CommandInfo::new("delete-stash", "Delete Stash", "string", &[], true, false),
+        CommandInfo::new("tag", "Create Tag", "object", &[("tag_sha", "string", true), ("is_lightweight", "bool_string", true), ("name", "string", true), ("message", "string", true), ("sign", "bool_string", false)], true, false),
+        CommandInfo::new("delete-tag", "Delete Tag", "string", &[], true, false),
+        CommandInfo::new("delete-ref", "Delete Branch or Tag", "string", &[], true, false),
+        CommandInfo::new("replace", "Replace Object", "object", &[("original", "string", true), ("replacement", "string", true), ("force", "bool_string", false)], true, false),
+        CommandInfo::new("delete-replace", "Delete Replace Ref", "string", &[], true, false),
+        CommandInfo::new("push-tag", "Push Tag", "object", &[("tagFullName", "string", true), ("selectedRemote", "string", true), ("isForcePush", "bool_string", true)], true, false),
+        CommandInfo::new("get-tag-info", "Show Tag Info", "string", &[], true, false),
+        CommandInfo::new("get-tag-details", "Get Tag Details", "string", &[], true, false),
+        CommandInfo::new("show-more-tags", "Show More Tags", "none", &[], true, false),
+        CommandInfo::new("get-release-tag-info", "Suggest Release Tag", "none", &[], true, false),
+        CommandInfo::new("create-release-tag", "Create Release Tag", "object", &[("name", "string", true), ("message", "string", true), ("push", "bool_string", true), ("selectedRemote", "string", false), ("sign", "bool_string", false)], true, false),
+        CommandInfo::new("remove-stale-lock", "Remove Stale Lock", "object", &[("lock_path", "string", true), ("force", "bool_string", false)], true, false),
+        CommandInfo::new("get-graph-rows", "Get Graph Rows", "object", &[("start", "string", true), ("end", "string", true)], true, false),
+        CommandInfo::new("get-all-badges", "Show All Branches and Tags", "string", &[], true, false),
+        CommandInfo::new("filter-branches", "Filter Branches", "string", &[], true, false),
+        CommandInfo::new("get-graph-minimap", "Get Graph Minimap", "object", &[("rows_per_entry", "string", true)], true, false),
+        CommandInfo::new("export-graph", "Export Graph", "object", &[("path", "string", true), ("format", "string", true)], true, false),
+        CommandInfo::new("export-log", "Export Log", "object", &[("range", "string", true), ("format", "string", true), ("path", "string", true)], true, false),
+        CommandInfo::new("run-git-command", "Run Git Command", "object", &[("args", "string_array", true)], true, false),
+    ]
+}
+
+pub fn get_command_list(git_manager: &GitManager) -> Result<Vec<CommandInfo>> {
+    let has_open_repo = git_manager.has_open_repo();
+    let mut is_clean_tree = false;
+    if has_open_repo && *git_manager.get_repo_capabilities()?.borrow_has_workdir() {
+        is_clean_tree = !git_manager.has_unstaged_changes()? && !git_manager.has_staged_changes()?;
+    }
+
+    let mut commands = get_all_commands();
+    for command in &mut commands {
+        command.enabled = (!command.needs_open_repo || has_open_repo) && (!command.needs_clean_tree || is_clean_tree);
+    }
+    Ok(commands)
+}
@@ -1,9 +1,64 @@
+use std::collections::HashMap;
 use std::fs;
 use std::fs::create_dir_all;
 use std::path::PathBuf;
 use anyhow::{bail, Result};
 use serde::{Serialize, Deserialize};
 use directories::ProjectDirs;
+use regex::Regex;
+
+// Blobs bigger than this (in bytes) aren't sent to the front-end.
+pub const DEFAULT_MAX_BLOB_SIZE: usize = 10 * 1024 * 1024;
+// How similar (as a percentage) two files need to be to count as a rename or copy when following a file's history.
+pub const DEFAULT_SIMILARITY_THRESHOLD: u16 = 50;
+// How many safety snapshots are kept per repo before the oldest are pruned.
+pub const DEFAULT_SNAPSHOT_LIMIT: usize = 20;
+// Picks a ticket key like ABC-123 out of the branch name, which the template then turns into "ABC-123: ".
+pub const DEFAULT_BRANCH_PREFILL_PATTERN: &str = "([A-Z][A-Z0-9]+-[0-9]+)";
+pub const DEFAULT_BRANCH_PREFILL_TEMPLATE: &str = "$1: ";
+// Committing a file bigger than this (in bytes) gets a warning, since it's stuck in the history for good. GitHub warns at the same size.
+pub const DEFAULT_LARGE_FILE_WARNING_SIZE: usize = 50 * 1024 * 1024;
+// The diff algorithms that can be picked, myers is git's default.
+pub const DIFF_ALGORITHMS: [&str; 4] = ["myers", "minimal", "patience", "histogram"];
+// "recent" puts the branches with the newest tip commits first.
+pub const BRANCH_SORT_MODES: [&str; 2] = ["alphabetical", "recent"];
+// The attribute GitHub uses to collapse generated files in diffs.
+pub const DEFAULT_COLLAPSE_ATTRIBUTE: &str = "linguist-generated";
+
+// Turns text in commit messages matching pattern into a link, with $1 style capture groups in url_template.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct IssueLinkRule {
+    pattern: String,
+    url_template: String,
+}
+
+impl IssueLinkRule {
+    pub fn new(pattern: String, url_template: String) -> Self {
+        Self {
+            pattern,
+            url_template,
+        }
+    }
+
+    pub fn borrow_pattern(&self) -> &String {
+        &self.pattern
+    }
+
+    pub fn borrow_url_template(&self) -> &String {
+        &self.url_template
+    }
+}
+
+// Where the user was in a repo, so reopening the app can put them back there.
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct RepoSession {
+    selected_sha: Option<String>,
+    // "commits" or "changes".
+    active_tab: Option<String>,
+    // The commit in the middle of the graph, since scroll offsets change as commits are added.
+    scroll_anchor_sha: Option<String>,
+}
 
 #[serde_with::skip_serializing_none]
 #[derive(Clone, Serialize, Deserialize)]
@@ -15,6 +70,55 @@ pub struct Config {
     public_key_path: Option<PathBuf>,
     private_key_path: Option<PathBuf>,
     uses_passphrase: Option<bool>,
+    max_blob_size: Option<usize>,
+    // Menu item id -> accelerator, overriding the defaults from get_default_keymap.
+    keymap: Option<HashMap<String, String>>,
+    // How often to fetch in the background, 0 means never.
+    auto_fetch_minutes: Option<u64>,
+    rename_threshold: Option<u16>,
+    copy_threshold: Option<u16>,
+    // Whether fetching and pulling should recurse into submodules.
+    recurse_submodules: Option<bool>,
+    // How many safety snapshots to keep per repo, 0 turns them off.
+    snapshot_limit: Option<usize>,
+    // Hide changes that are only to whitespace at the end of lines (which includes CRLF vs LF) in diffs.
+    ignore_eol_changes: Option<bool>,
+    // One of DIFF_ALGORITHMS, used for every diff that's shown.
+    diff_algorithm: Option<String>,
+    // Show the function (or class, heading, etc.) each hunk is in after its line numbers, like git does.
+    show_function_names: Option<bool>,
+    // Hunks with this many unchanged lines or fewer between them are shown as one, like git's --inter-hunk-context.
+    interhunk_lines: Option<u32>,
+    // Files with any of these attributes set in .gitattributes have their diffs collapsed until they're clicked.
+    collapse_attributes: Option<Vec<String>>,
+    // Prefill the commit summary from the branch name, using a regex and a template with $1 style capture groups.
+    prefill_commit_from_branch: Option<bool>,
+    branch_prefill_pattern: Option<String>,
+    branch_prefill_template: Option<String>,
+    // Repo path -> rules, since each repo links to its own issue tracker.
+    issue_link_rules: Option<HashMap<String, Vec<IssueLinkRule>>>,
+    large_file_warning_size: Option<usize>,
+    // Pathspec patterns for files that should never be committed, like secrets.
+    forbidden_paths: Option<Vec<String>>,
+    // Put in front of PATH for anything the app runs (git, hooks, gpg, etc.), since apps started from the dock don't get the shell's PATH.
+    extra_path_entries: Option<Vec<String>>,
+    // Name -> value, set for anything the app runs.
+    extra_env_vars: Option<HashMap<String, String>>,
+    // The repo to reopen on startup.
+    last_open_repo: Option<String>,
+    // Repo path -> where the user was in it.
+    repo_sessions: Option<HashMap<String, RepoSession>>,
+    // One of BRANCH_SORT_MODES, for the branches sidebar.
+    branch_sort_mode: Option<String>,
+    // Repo path -> full names of the local branches pinned to the top of the sidebar.
+    favorite_branches: Option<HashMap<String, Vec<String>>>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CredentialsStatus {
+    cred_type: Option<String>,
+    // Whether everything the credential type needs is in the config. Secrets in the keychain aren't checked.
+    is_configured: bool,
 }
 
 impl Config {
@@ -27,6 +131,30 @@ impl Config {
             public_key_path: None,
             private_key_path: None,
             uses_passphrase: None,
+            max_blob_size: Some(DEFAULT_MAX_BLOB_SIZE),
+            keymap: None,
+            auto_fetch_minutes: Some(0),
+            rename_threshold: Some(DEFAULT_SIMILARITY_THRESHOLD),
+            copy_threshold: Some(DEFAULT_SIMILARITY_THRESHOLD),
+            recurse_submodules: Some(false),
+            snapshot_limit: Some(DEFAULT_SNAPSHOT_LIMIT),
+            ignore_eol_changes: Some(false),
+            diff_algorithm: Some(String::from("myers")),
+            show_function_names: Some(true),
+            interhunk_lines: Some(0),
+            collapse_attributes: Some(vec![String::from(DEFAULT_COLLAPSE_ATTRIBUTE)]),
+            prefill_commit_from_branch: Some(false),
+            branch_prefill_pattern: Some(String::from(DEFAULT_BRANCH_PREFILL_PATTERN)),
+            branch_prefill_template: Some(String::from(DEFAULT_BRANCH_PREFILL_TEMPLATE)),
+            issue_link_rules: None,
+            large_file_warning_size: Some(DEFAULT_LARGE_FILE_WARNING_SIZE),
+            forbidden_paths: Some(vec![]),
+            extra_path_entries: Some(vec![]),
+            extra_env_vars: Some(HashMap::new()),
+            last_open_repo: None,
+            repo_sessions: None,
+            branch_sort_mode: Some(String::from("alphabetical")),
+            favorite_branches: None,
         }
     }
 
@@ -58,6 +186,158 @@ impl Config {
         &self.uses_passphrase
     }
 
+    pub fn borrow_max_blob_size(&self) -> &Option<usize> {
+        &self.max_blob_size
+    }
+
+    pub fn borrow_keymap(&self) -> &Option<HashMap<String, String>> {
+        &self.keymap
+    }
+
+    pub fn borrow_auto_fetch_minutes(&self) -> &Option<u64> {
+        &self.auto_fetch_minutes
+    }
+
+    pub fn borrow_rename_threshold(&self) -> &Option<u16> {
+        &self.rename_threshold
+    }
+
+    pub fn borrow_copy_threshold(&self) -> &Option<u16> {
+        &self.copy_threshold
+    }
+
+    pub fn borrow_recurse_submodules(&self) -> &Option<bool> {
+        &self.recurse_submodules
+    }
+
+    pub fn borrow_snapshot_limit(&self) -> &Option<usize> {
+        &self.snapshot_limit
+    }
+
+    pub fn borrow_ignore_eol_changes(&self) -> &Option<bool> {
+        &self.ignore_eol_changes
+    }
+
+    pub fn borrow_diff_algorithm(&self) -> &Option<String> {
+        &self.diff_algorithm
+    }
+
+    pub fn borrow_show_function_names(&self) -> &Option<bool> {
+        &self.show_function_names
+    }
+
+    pub fn borrow_interhunk_lines(&self) -> &Option<u32> {
+        &self.interhunk_lines
+    }
+
+    pub fn borrow_collapse_attributes(&self) -> &Option<Vec<String>> {
+        &self.collapse_attributes
+    }
+
+    pub fn borrow_prefill_commit_from_branch(&self) -> &Option<bool> {
+        &self.prefill_commit_from_branch
+    }
+
+    pub fn borrow_branch_prefill_pattern(&self) -> &Option<String> {
+        &self.branch_prefill_pattern
+    }
+
+    pub fn borrow_branch_prefill_template(&self) -> &Option<String> {
+        &self.branch_prefill_template
+    }
+
+    pub fn borrow_large_file_warning_size(&self) -> &Option<usize> {
+        &self.large_file_warning_size
+    }
+
+    pub fn borrow_forbidden_paths(&self) -> &Option<Vec<String>> {
+        &self.forbidden_paths
+    }
+
+    pub fn borrow_extra_path_entries(&self) -> &Option<Vec<String>> {
+        &self.extra_path_entries
+    }
+
+    pub fn borrow_extra_env_vars(&self) -> &Option<HashMap<String, String>> {
+        &self.extra_env_vars
+    }
+
+    pub fn get_issue_link_rules(&self, repo_path: &str) -> Vec<IssueLinkRule> {
+        match &self.issue_link_rules {
+            Some(rules) => rules.get(repo_path).cloned().unwrap_or_default(),
+            None => vec![],
+        }
+    }
+
+    pub fn set_issue_link_rules(&mut self, repo_path: &str, rules: Vec<IssueLinkRule>) -> Result<()> {
+        for rule in &rules {
+            if let Err(e) = Regex::new(&rule.pattern) {
+                bail!("Issue link pattern '{}' isn't a valid regex: {}", rule.pattern, e);
+            }
+        }
+        let issue_link_rules = self.issue_link_rules.get_or_insert_with(HashMap::new);
+        if rules.is_empty() {
+            issue_link_rules.remove(repo_path);
+        } else {
+            issue_link_rules.insert(String::from(repo_path), rules);
+        }
+        Ok(())
+    }
+
+    pub fn borrow_last_open_repo(&self) -> &Option<String> {
+        &self.last_open_repo
+    }
+
+    pub fn set_last_open_repo(&mut self, repo_path: &str) {
+        self.last_open_repo = Some(String::from(repo_path));
+    }
+
+    pub fn get_repo_session(&self, repo_path: &str) -> Option<RepoSession> {
+        match &self.repo_sessions {
+            Some(sessions) => sessions.get(repo_path).cloned(),
+            None => None,
+        }
+    }
+
+    pub fn set_repo_session(&mut self, repo_path: &str, repo_session: RepoSession) {
+        self.repo_sessions.get_or_insert_with(HashMap::new).insert(String::from(repo_path), repo_session);
+    }
+
+    pub fn borrow_branch_sort_mode(&self) -> &Option<String> {
+        &self.branch_sort_mode
+    }
+
+    pub fn get_favorite_branches(&self, repo_path: &str) -> Vec<String> {
+        match &self.favorite_branches {
+            Some(favorites) => favorites.get(repo_path).cloned().unwrap_or_default(),
+            None => vec![],
+        }
+    }
+
+    pub fn set_favorite_branch(&mut self, repo_path: &str, full_branch_name: &str, is_favorite: bool) {
+        let favorite_branches = self.favorite_branches.get_or_insert_with(HashMap::new);
+        let repo_favorites = favorite_branches.entry(String::from(repo_path)).or_default();
+        repo_favorites.retain(|name| name != full_branch_name);
+        if is_favorite {
+            repo_favorites.push(String::from(full_branch_name));
+        }
+        if repo_favorites.is_empty() {
+            favorite_branches.remove(repo_path);
+        }
+    }
+
+    pub fn get_credentials_status(&self) -> CredentialsStatus {
+        let is_configured = match self.cred_type.as_deref() {
+            Some("HTTPS") => self.https_username.is_some(),
+            Some("SSH") => self.public_key_path.is_some() && self.private_key_path.is_some() && self.uses_passphrase.is_some(),
+            _ => false,
+        };
+        CredentialsStatus {
+            cred_type: self.cred_type.clone(),
+            is_configured,
+        }
+    }
+
     pub fn set_cred_type(&mut self, cred_type: String) {
         self.cred_type = Some(cred_type);
     }
@@ -78,6 +358,14 @@ impl Config {
         self.uses_passphrase = Some(uses_passphrase);
     }
 
+    pub fn set_max_blob_size(&mut self, max_blob_size: usize) {
+        self.max_blob_size = Some(max_blob_size);
+    }
+
+    pub fn set_keymap(&mut self, keymap: HashMap<String, String>) {
+        self.keymap = Some(keymap);
+    }
+
     pub fn save(&self) -> Result<()> {
         let config_path_buf = get_config_path()?;
         let config_path = config_path_buf.as_path();
@@ -113,11 +401,123 @@ fn save_default_config() -> Result<()> {
 }
 
 pub fn save_config_from_json(payload: &str) -> Result<()> {
-    let config: Config = serde_json::from_str(payload)?;
+    let new_config: Config = serde_json::from_str(payload)?;
+    // Only overwrite what the front-end sent so credentials and the keymap aren't lost.
+    let mut config = get_config()?;
+    if new_config.limit_commits.is_some() {
+        config.limit_commits = new_config.limit_commits;
+    }
+    if new_config.commit_count.is_some() {
+        config.commit_count = new_config.commit_count;
+    }
+    if new_config.max_blob_size.is_some() {
+        config.max_blob_size = new_config.max_blob_size;
+    }
+    if new_config.keymap.is_some() {
+        config.keymap = new_config.keymap;
+    }
+    if new_config.auto_fetch_minutes.is_some() {
+        config.auto_fetch_minutes = new_config.auto_fetch_minutes;
+    }
+    if new_config.rename_threshold.is_some() {
+        config.rename_threshold = new_config.rename_threshold;
+    }
+    if new_config.copy_threshold.is_some() {
+        config.copy_threshold = new_config.copy_threshold;
+    }
+    if new_config.recurse_submodules.is_some() {
+        config.recurse_submodules = new_config.recurse_submodules;
+    }
+    if new_config.snapshot_limit.is_some() {
+        config.snapshot_limit = new_config.snapshot_limit;
+    }
+    if new_config.ignore_eol_changes.is_some() {
+        config.ignore_eol_changes = new_config.ignore_eol_changes;
+    }
+    if let Some(diff_algorithm) = &new_config.diff_algorithm {
+        if !DIFF_ALGORITHMS.contains(&diff_algorithm.as_str()) {
+            bail!("Unknown diff algorithm: {}", diff_algorithm);
+        }
+        config.diff_algorithm = new_config.diff_algorithm;
+    }
+    if new_config.show_function_names.is_some() {
+        config.show_function_names = new_config.show_function_names;
+    }
+    if let Some(branch_sort_mode) = &new_config.branch_sort_mode {
+        if !BRANCH_SORT_MODES.contains(&branch_sort_mode.as_str()) {
+            bail!("Unknown branch sort mode: {}", branch_sort_mode);
+        }
+        config.branch_sort_mode = new_config.branch_sort_mode;
+    }
+    if new_config.interhunk_lines.is_some() {
+        config.interhunk_lines = new_config.interhunk_lines;
+    }
+    if let Some(collapse_attributes) = &new_config.collapse_attributes {
+        if let Some(attribute) = collapse_attributes.iter().find(|a| a.is_empty() || a.contains(char::is_whitespace)) {
+            bail!("\"{}\" isn't a valid attribute name.", attribute);
+        }
+        config.collapse_attributes = new_config.collapse_attributes;
+    }
+    if new_config.prefill_commit_from_branch.is_some() {
+        config.prefill_commit_from_branch = new_config.prefill_commit_from_branch;
+    }
+    if let Some(pattern) = &new_config.branch_prefill_pattern {
+        // Catch a bad pattern here instead of on every refresh.
+        if let Err(e) = Regex::new(pattern) {
+            bail!("Branch prefill pattern isn't a valid regex: {}", e);
+        }
+        config.branch_prefill_pattern = new_config.branch_prefill_pattern;
+    }
+    if new_config.branch_prefill_template.is_some() {
+        config.branch_prefill_template = new_config.branch_prefill_template;
+    }
+    if new_config.large_file_warning_size.is_some() {
+        config.large_file_warning_size = new_config.large_file_warning_size;
+    }
+    if new_config.forbidden_paths.is_some() {
+        config.forbidden_paths = new_config.forbidden_paths;
+    }
+    if new_config.extra_path_entries.is_some() {
+        config.extra_path_entries = new_config.extra_path_entries;
+    }
+    if let Some(extra_env_vars) = &new_config.extra_env_vars {
+        for name in extra_env_vars.keys() {
+            if name.is_empty() || name.contains(|c| c == '=' || c == '\0') {
+                bail!("{} isn't a valid environment variable name.", name);
+            }
+            // PATH from here would replace it entirely, which is almost never what's wanted.
+            if name.eq_ignore_ascii_case("PATH") {
+                bail!("Use the extra PATH entries to add to PATH instead of setting it as an environment variable.");
+            }
+        }
+        config.extra_env_vars = new_config.extra_env_vars;
+    }
     config.save()?;
     Ok(())
 }
 
+pub fn get_default_keymap() -> HashMap<String, String> {
+    HashMap::from([
+        (String::from("open"), String::from("CommandOrControl+O")),
+        (String::from("fetch"), String::from("CommandOrControl+Shift+F")),
+        (String::from("push"), String::from("CommandOrControl+P")),
+        (String::from("refresh"), String::from("F5")),
+    ])
+}
+
+// Gets the default keymap with any overrides from the config file. An empty accelerator removes the shortcut.
+pub fn get_keymap() -> HashMap<String, String> {
+    let mut keymap = get_default_keymap();
+    if let Ok(config) = get_config() {
+        if let Some(keymap_overrides) = config.borrow_keymap() {
+            for (id, accelerator) in keymap_overrides {
+                keymap.insert(id.clone(), accelerator.clone());
+            }
+        }
+    }
+    keymap
+}
+
 pub fn get_config() -> Result<Config> {
     let config_path_buf = get_config_path()?;
     let config_path = config_path_buf.as_path();
@@ -1,15 +1,27 @@
-use std::collections::{HashMap, VecDeque};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs::create_dir_all;
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
-use std::{fs, str};
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+use std::{env, fs, io, str};
 use anyhow::{bail, Result};
-use git2::{AutotagOption, Branch, BranchType, Commit, Cred, Delta, Diff, DiffFindOptions, DiffLine, DiffLineType, DiffOptions, ErrorCode, FetchOptions, FetchPrune, IndexAddOption, ObjectType, Oid, Patch, PushOptions, Rebase, Reference, RemoteCallbacks, Repository, ResetType, Signature, Sort, StashFlags};
+use git2::{message_prettify, message_trailers_strs, ApplyLocation, ApplyOptions, AttrCheckFlags, CheckoutNotificationType, AttrValue, AutotagOption, BlameOptions, Branch, BranchType, Commit, ConfigLevel, Cred, Delta, Diff, DiffFile, DiffFindOptions, DiffHunk, DiffFormat, DiffLine, DiffLineType, DiffOptions, Direction, Email, EmailCreateOptions, ErrorCode, FetchOptions, FetchPrune, FileFavor, FileMode, Index, IndexAddOption, IndexConflict, IndexEntryExtendedFlag, IndexEntryFlag, MergeOptions, Object, ObjectType, Oid, Patch, Pathspec, PathspecFlags, PushOptions, Rebase, RebaseOptions, Reference, RemoteCallbacks, Repository, RepositoryOpenFlags, ResetType, RevertOptions, Signature, Sort, StashApplyOptions, StatusOptions, StashFlags, Submodule, SubmoduleIgnore, SubmoduleStatus, SubmoduleUpdateOptions, Tag, Time, Tree, TreeWalkMode, TreeWalkResult};
 use git2::build::{CheckoutBuilder, RepoBuilder};
-use serde::{Serialize, Serializer};
-use serde_json::Value;
-use time::UtcOffset;
-use crate::parseable_info::{get_parseable_diff_delta, ParseableDiffDelta};
+use serde::{Deserialize, Serialize, Serializer};
+use serde::ser::SerializeStruct;
+use serde_json::{json, Value};
+use ssh_key::{Algorithm, LineEnding, PrivateKey};
+use ssh_key::rand_core::OsRng;
+use regex::Regex;
+use time::{OffsetDateTime, UtcOffset};
+use time::format_description::well_known::Rfc3339;
+use crate::parseable_info::{get_parseable_diff_delta, FlaggedFile, GraphLayout, ParseableDiffDelta};
 use crate::config_manager;
+use crate::lru_cache::LruCache;
+use crate::config_manager::{IssueLinkRule, RepoSession};
+use crate::structured_error::StructuredError;
 
 fn trim_newline(s: &mut String) {
     if s.ends_with('\n') {
@@ -27,6 +39,113 @@ fn get_content_from_diff_line(diff_line: &DiffLine) -> Result<String> {
     Ok(content_string)
 }
 
+// Maps a file name to the highlight.js language name used by the front-end.
+fn get_language_from_file_name(file_name: &str) -> &'static str {
+    match file_name {
+        "Makefile" | "makefile" | "GNUmakefile" => return "makefile",
+        "Dockerfile" => return "dockerfile",
+        "CMakeLists.txt" => return "cmake",
+        _ => (),
+    };
+    let extension = match file_name.rsplit_once('.') {
+        Some((_, ext)) => ext.to_lowercase(),
+        None => return "plaintext",
+    };
+    match extension.as_str() {
+        "rs" => "rust",
+        "js" | "mjs" | "cjs" | "jsx" => "javascript",
+        "ts" | "tsx" => "typescript",
+        "py" | "pyw" => "python",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => "cpp",
+        "cs" => "csharp",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "go" => "go",
+        "rb" => "ruby",
+        "php" => "php",
+        "swift" => "swift",
+        "lua" => "lua",
+        "sh" | "bash" | "zsh" => "bash",
+        "ps1" => "powershell",
+        "html" | "htm" | "xml" | "svg" | "vue" => "xml",
+        "css" => "css",
+        "scss" => "scss",
+        "json" => "json",
+        "yml" | "yaml" => "yaml",
+        "toml" | "ini" | "cfg" => "ini",
+        "md" | "markdown" => "markdown",
+        "sql" => "sql",
+        _ => "plaintext",
+    }
+}
+
+// Marks files whose diffs should start collapsed, which are ones with one of the collapse attributes set (linguist-generated by
+// default) or known lockfiles. Unsetting the attribute (e.g. -linguist-generated) keeps a file expanded.
+pub fn set_collapsed_files(repo: &Repository, files: &mut [ParseableDiffDelta]) -> Result<()> {
+    let collapse_attributes = config_manager::get_config()?.borrow_collapse_attributes().clone().unwrap_or_else(|| vec![String::from(config_manager::DEFAULT_COLLAPSE_ATTRIBUTE)]);
+    for file in files.iter_mut() {
+        let path = decode_path(file.get_path())?;
+        let mut collapsed_opt = None;
+        for attribute in &collapse_attributes {
+            match AttrValue::from_string(repo.get_attr(&path, attribute, AttrCheckFlags::FILE_THEN_INDEX)?) {
+                AttrValue::True => collapsed_opt = Some(true),
+                AttrValue::String(value) => collapsed_opt = Some(value != "false"),
+                AttrValue::False => collapsed_opt = Some(false),
+                _ => continue,
+            };
+            break;
+        }
+        let collapsed = match collapsed_opt {
+            Some(c) => c,
+            None => path.file_name().map_or(false, |name| GENERATED_FILE_NAMES.iter().any(|n| name == *n)),
+        };
+        file.set_collapsed(collapsed);
+    }
+    Ok(())
+}
+
+// Patterns for the line a hunk is in (like a function or class), based on git's builtin diff drivers. The first group is used as
+// the name if there is one, and lines matching the second pattern are skipped like git's "!" patterns.
+fn get_function_name_patterns(language: &str) -> Option<(&'static str, Option<&'static str>)> {
+    let patterns = match language {
+        "rust" => (r#"^[\t ]*((pub(\([^)]+\))?[\t ]+)?((async|const|unsafe|extern([\t ]+"[^"]+"))[\t ]+)?(struct|enum|union|mod|trait|fn|impl|macro_rules!)[<\t ]+[^;]*)$"#, None),
+        "python" => (r"^[\t ]*((class|(async[\t ]+)?def)[\t ].*)$", None),
+        "go" => (r"^[\t ]*(func[\t ]*.*|type[\t ].*(struct|interface)[\t ]*(\{[\t ]*)?)$", None),
+        "c" | "cpp" => (r"^((::[\t ]*)?[A-Za-z_].*)$", Some(r"^[\t ]*[A-Za-z_][A-Za-z_0-9]*:[\t ]*($|/[/*])")),
+        "java" => (r"^[\t ]*(([A-Za-z_<>\[\],.][A-Za-z_0-9<>\[\],.]*[\t ]+)+[A-Za-z_][A-Za-z_0-9]*[\t ]*\([^;]*)$", Some(r"^[\t ]*(catch|do|for|if|instanceof|new|return|switch|throw|while)\b")),
+        "kotlin" => (r"^[\t ]*(([a-z]+[\t ]+)*(fun|class|interface|object)[\t ]+.*)$", None),
+        "csharp" => (r"^[\t ]*((((static|public|internal|private|protected|new|virtual|sealed|override|unsafe|async|abstract|partial)[\t ]+)*(class|enum|interface|struct|record|namespace)[\t ]+.*)|(((static|public|internal|private|protected|new|virtual|sealed|override|unsafe|async)[\t ]+)*[\[\]<>@.~_[:alnum:]]+[\t ]+[<>@._[:alnum:]]+[\t ]*\(.*\)))[\t ]*$", Some(r"^[\t ]*(do|while|for|if|else|instanceof|new|return|switch|case|throw|catch|using)\b")),
+        "php" => (r"^[\t ]*((((public|protected|private|static|abstract|final)[\t ]+)*function.*)|(((final|abstract)[\t ]+)?(class|enum|interface|trait).*))$", None),
+        "ruby" => (r"^[\t ]*((class|module|def)[\t ].*)$", None),
+        "javascript" | "typescript" => (r"^[\t ]*(((export[\t ]+)?(default[\t ]+)?(abstract[\t ]+)?(async[\t ]+)?(function\*?|class|interface|enum|type)[\t ].*)|([A-Za-z_$][A-Za-z0-9_$]*[\t ]*[:=][\t ]*(async[\t ]+)?(function|\([^)]*\)[\t ]*=>).*)|(((static|async|public|private|protected|get|set)[\t ]+)*[A-Za-z_$][A-Za-z0-9_$]*[\t ]*\([^;]*\)[\t ]*\{[\t ]*))$", Some(r"^[\t ]*(if|for|while|switch|catch|return|else)\b")),
+        "css" | "scss" => (r"^[:\[@.#]?[_a-zA-Z0-9].*$", Some(r"[:;][\t ]*$")),
+        "markdown" => (r"^ {0,3}#{1,6}[\t ].*", None),
+        "bash" => (r"^[\t ]*((function[\t ]+)?[A-Za-z_][A-Za-z_0-9]*[\t ]*\(\)[\t ]*\{?.*|function[\t ]+[A-Za-z_][A-Za-z_0-9]*.*)$", None),
+        _ => return None,
+    };
+    Some(patterns)
+}
+
+// Like git, the name for a hunk is the closest line above it that matches, cut to 80 bytes.
+fn find_function_name(patterns: &(Regex, Option<Regex>), lines: &[&str], before_line: usize) -> Option<String> {
+    let (pattern, skip_pattern_opt) = patterns;
+    lines[..before_line.min(lines.len())].iter().rev().find_map(|line| {
+        if let Some(skip_pattern) = skip_pattern_opt {
+            if skip_pattern.is_match(line) {
+                return None;
+            }
+        }
+        let captures = pattern.captures(line)?;
+        let function_name = captures.get(1).or_else(|| captures.get(0))?.as_str().trim_end();
+        let mut end = function_name.len().min(80);
+        while !function_name.is_char_boundary(end) {
+            end -= 1;
+        }
+        Some(String::from(&function_name[..end]))
+    })
+}
+
 #[derive(Clone, Serialize)]
 pub struct FileLineInfo {
     old_lineno: Option<u32>,
@@ -65,19 +184,111 @@ impl Serialize for LineInfo {
     }
 }
 
+// The number of hunks sent per file diff request so huge (e.g. generated) files don't flood the front-end.
+const DIFF_HUNK_PAGE_SIZE: usize = 50;
+
+// The unchanged lines shown around each change, the same as git's default.
+const DEFAULT_CONTEXT_LINES: u32 = 3;
+
+// How many selected commits and file diffs are kept around so selecting one again is instant.
+const COMMIT_INFO_CACHE_SIZE: usize = 50;
+const FILE_DIFF_CACHE_SIZE: usize = 50;
+
+// How many tags the branch tree lists at first, and how many more each time more are asked for. Some repos have thousands.
+const TAG_PAGE_SIZE: usize = 200;
+
+// Untracked directories are counted on every refresh, so stop counting past this and show it as "1000+" instead.
+const UNTRACKED_FILE_COUNT_LIMIT: usize = 1000;
+
+// The same message oh-my-zsh's gwip uses, so WIP commits made elsewhere can be undone too.
+const WIP_COMMIT_MESSAGE: &str = "--wip-- [skip ci]";
+
+// The number of bytes git checks for a NUL byte when deciding if a file is binary.
+const BINARY_CHECK_SIZE: usize = 8000;
+
+// Lockfiles that Linguist treats as generated, so they're collapsed without needing an attribute.
+const GENERATED_FILE_NAMES: [&str; 15] = ["package-lock.json", "npm-shrinkwrap.json", "yarn.lock", "pnpm-lock.yaml", "Cargo.lock", "Gemfile.lock", "composer.lock", "poetry.lock", "Pipfile.lock", "go.sum", "flake.lock", "packages.lock.json", "mix.lock", "pubspec.lock", "Podfile.lock"];
+
+#[derive(Clone)]
+pub enum BlobContent {
+    Text(String),
+    Binary(usize),
+    TooLarge(usize),
+}
+
+impl Serialize for BlobContent {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> where S: Serializer {
+        let mut state = serializer.serialize_struct("BlobContent", 3)?;
+        match &self {
+            BlobContent::Text(content) => {
+                state.serialize_field("kind", "text")?;
+                state.serialize_field("size", &content.len())?;
+                state.serialize_field("content", content)?;
+            },
+            BlobContent::Binary(size) => {
+                state.serialize_field("kind", "binary")?;
+                state.serialize_field("size", size)?;
+                state.serialize_field("content", &None::<String>)?;
+            },
+            BlobContent::TooLarge(size) => {
+                state.serialize_field("kind", "too_large")?;
+                state.serialize_field("size", size)?;
+                state.serialize_field("content", &None::<String>)?;
+            },
+        };
+        state.end()
+    }
+}
+
 #[derive(Clone, Serialize)]
 pub struct FileInfo {
+    file_path: String,
     change_type: String,
+    sha: String,
     file_lines: Vec<LineInfo>,
+    language: String,
+    truncated: bool,
+    next_hunk_offset: usize,
+    // Sent back when loading more hunks or expanding one so they line up with the hunks already shown.
+    context_lines: u32,
+    // Set when one side of the diff is binary or too large, in which case file_lines is empty.
+    skipped_blob: Option<BlobContent>,
+    // Set when every changed line only differs by its line ending, along with the settings that could explain it.
+    eol_only: bool,
+    eol_settings: Vec<String>,
 }
 
 impl FileInfo {
-    pub fn new(change_type: String, file_lines: Vec<LineInfo>) -> Self {
+    pub fn new(file_path: String, change_type: String, sha: String, file_lines: Vec<LineInfo>, language: String, truncated: bool, next_hunk_offset: usize, context_lines: u32, skipped_blob: Option<BlobContent>) -> Self {
         Self {
+            file_path,
             change_type,
+            sha,
             file_lines,
+            language,
+            truncated,
+            next_hunk_offset,
+            context_lines,
+            skipped_blob,
+            eol_only: false,
+            eol_settings: vec![],
         }
     }
+
+    pub fn set_eol_info(&mut self, eol_only: bool, eol_settings: Vec<String>) {
+        self.eol_only = eol_only;
+        self.eol_settings = eol_settings;
+    }
+}
+
+// Unchanged lines from above and below a hunk that weren't in its context.
+#[derive(Clone, Serialize)]
+pub struct HunkContext {
+    file_path: String,
+    change_type: String,
+    hunk_index: usize,
+    lines_before: Vec<FileLineInfo>,
+    lines_after: Vec<FileLineInfo>,
 }
 
 #[derive(Clone, Serialize)]
@@ -86,937 +297,5137 @@ pub struct CommitInfo {
     summary: String,
     message: String,
     author_name: String,
+    author_email: String,
     author_time: i64,
+    author_time_offset: i32,
     committer_name: String,
+    committer_email: String,
     committer_time: i64,
+    committer_time_offset: i32,
+    change_id: Option<String>,
     changed_files: Vec<ParseableDiffDelta>,
+    issue_links: Vec<IssueLink>,
+    // The SHA from refs/replace/<sha>, if the commit has been replaced.
+    replaced_by: Option<String>,
+}
+
+// A reference to an issue in a commit message. start and end are in UTF-16 code units of the (escaped) message so the
+// front-end can slice it directly.
+#[derive(Clone, Serialize)]
+pub struct IssueLink {
+    start: usize,
+    end: usize,
+    url: String,
 }
 
 impl CommitInfo {
     pub fn from_commit(commit: Commit, repo: &Repository) -> Result<Self> {
         let author_signature = commit.author();
         let author_name = String::from(GitManager::get_utf8_string(author_signature.name(), "Author Name")?);
+        let author_email = String::from(GitManager::get_utf8_string(author_signature.email(), "Author Email")?);
         let author_time = author_signature.when().seconds();
+        let author_time_offset = author_signature.when().offset_minutes();
 
         let committer_signature = commit.committer();
         let committer_name = String::from(GitManager::get_utf8_string(committer_signature.name(), "Committer Name")?);
+        let committer_email = String::from(GitManager::get_utf8_string(committer_signature.email(), "Committer Email")?);
         let committer_time = committer_signature.when().seconds();
+        let committer_time_offset = committer_signature.when().offset_minutes();
+
+        let diff = get_commit_changes(&commit, repo, false, DEFAULT_CONTEXT_LINES)?;
+        let mut parseable_diff_delta = get_parseable_diff_delta(diff)?;
+        set_collapsed_files(repo, &mut parseable_diff_delta)?;
+
+        let full_message = GitManager::get_utf8_string(commit.message(), "Commit Message")?;
 
-        let diff = get_commit_changes(&commit, repo)?;
-        let parseable_diff_delta = get_parseable_diff_delta(diff)?;
+        // Gerrit identifies reviews by the Change-Id trailer, so surface it if present.
+        let mut change_id = None;
+        for (key, value) in message_trailers_strs(full_message)?.iter() {
+            if key == "Change-Id" {
+                change_id = Some(String::from(value));
+            }
+        }
 
         let new_commit_info = Self {
             sha: commit.id().to_string(),
             summary: html_escape::encode_text(&String::from(GitManager::get_utf8_string(commit.summary(), "Commit Summary")?)).parse()?,
-            message: html_escape::encode_text(&String::from(GitManager::get_utf8_string(commit.message(), "Commit Message")?)).parse()?,
+            message: html_escape::encode_text(&String::from(full_message)).parse()?,
             author_name: html_escape::encode_text(&author_name).parse()?,
+            author_email: html_escape::encode_text(&author_email).parse()?,
             author_time,
+            author_time_offset,
             committer_name: html_escape::encode_text(&committer_name).parse()?,
+            committer_email: html_escape::encode_text(&committer_email).parse()?,
             committer_time,
+            committer_time_offset,
+            change_id,
             changed_files: parseable_diff_delta,
+            issue_links: vec![],
+            replaced_by: None,
         };
 
         Ok(new_commit_info)
     }
 }
 
-fn get_commit_changes<'a, 'b>(commit: &'a Commit, repo: &'b Repository) -> Result<Diff<'b>> {
-    let commit_tree = commit.tree()?;
-
-    let mut diff_opt = None;
-    for parent_commit in commit.parents() {
-        let mut diff = repo.diff_tree_to_tree(Some(&parent_commit.tree()?), Some(&commit_tree), None)?;
-        GitManager::set_diff_find_similar(&mut diff)?;
-        // For merge commits, the diff between a merge commit and the parent from the branch that was merged will be empty,
-        // so find the diff that's populated.
-        if diff.stats()?.files_changed() > 0 {
-            return Ok(diff);
-        }
-        diff_opt = Some(diff);
-    }
-
-    // If there are parents but no diff was returned earlier, then the commit is empty and should return the empty diff.
-    if let Some(d) = diff_opt {
-        return Ok(d);
-    }
+// A conflicted file's content with conflict markers in the repo's merge.conflictStyle (merge, diff3 or zdiff3).
+#[derive(Clone, Serialize)]
+pub struct ConflictsInfo {
+    path: String,
+    display_path: String,
+    conflict_style: String,
+    // "both_modified", "both_added", "deleted_by_us", or "deleted_by_them".
+    kind: String,
+    // None when a side deleted the file or it's binary, since there's nothing to put markers in.
+    content: Option<String>,
+    conflict_count: usize,
+}
 
-    // If there are no parents, get the diff between this commit and nothing.
-    let mut diff = repo.diff_tree_to_tree(None, Some(&commit_tree), None)?;
-    GitManager::set_diff_find_similar(&mut diff)?;
+// One <<<<<<< ... >>>>>>> region in a conflicted file in the working directory.
+#[derive(Clone, Serialize)]
+pub struct ConflictRegion {
+    // 1-based line of the <<<<<<< marker.
+    start_line: usize,
+    ours: String,
+    // Only there for the diff3 and zdiff3 styles.
+    base: Option<String>,
+    theirs: String,
+}
 
-    Ok(diff)
+#[derive(Clone, Serialize)]
+pub struct ConflictRegions {
+    path: String,
+    display_path: String,
+    regions: Vec<ConflictRegion>,
+    // Staged once every region is resolved, which marks the file as resolved.
+    is_staged: bool,
 }
 
-pub struct GitManager {
-    repo: Option<Repository>,
-    old_graph_starting_shas: Vec<String>,
-    current_local_offset: UtcOffset,
+// Something that would go wrong (or probably shouldn't happen) if the staged changes were committed.
+#[derive(Clone, Serialize)]
+pub struct CommitProblem {
+    code: String,
+    // "error" means the commit should be blocked, "warning" means the user should confirm it first.
+    severity: String,
+    message: String,
+    paths: Vec<String>,
 }
 
-impl GitManager {
-    pub fn new(current_local_offset: UtcOffset) -> Self {
+impl CommitProblem {
+    fn new(code: &str, severity: &str, message: String, paths: Vec<String>) -> Self {
         Self {
-            repo: None,
-            old_graph_starting_shas: vec![],
-            current_local_offset,
+            code: String::from(code),
+            severity: String::from(severity),
+            message,
+            paths,
         }
     }
+}
 
-    pub fn get_utf8_string<'a, 'b>(value: Option<&'a str>, str_name_type: &'b str) -> Result<&'a str> {
-        match value {
-            Some(n) => Ok(n),
-            None => bail!(format!("{} uses invalid utf-8!", str_name_type)),
-        }
-    }
+#[derive(Clone, Deserialize)]
+pub struct CommitTrailer {
+    key: String,
+    value: String,
+}
 
-    fn get_string_from_serde_string(value: Option<&str>) -> Result<&str> {
-        match value {
-            Some(s) => Ok(s),
-            None => bail!("Invalid JSON String!"),
+#[derive(Clone, Serialize)]
+pub struct CommitParentInfo {
+    sha: String,
+    summary: String,
+}
+
+impl CommitParentInfo {
+    pub fn new(sha: String, summary: String) -> Self {
+        Self {
+            sha,
+            summary,
         }
     }
+}
 
-    pub fn borrow_current_local_offset(&self) -> &UtcOffset {
-        &self.current_local_offset
-    }
+#[derive(Clone, Serialize)]
+pub struct RevertMainlineInfo {
+    sha: String,
+    is_committing: bool,
+    parents: Vec<CommitParentInfo>,
+}
 
-    pub fn borrow_repo(&self) -> Result<&Repository> {
-        let repo_temp_opt = &self.repo;
-        match repo_temp_opt {
-            Some(repo) => Ok(repo),
-            None => bail!("No repo loaded to perform operation on."),
+impl RevertMainlineInfo {
+    pub fn from_commit(commit: &Commit, is_committing: bool) -> Result<Self> {
+        let mut parents = vec![];
+        for parent in commit.parents() {
+            parents.push(CommitParentInfo::new(
+                parent.id().to_string(),
+                String::from(GitManager::get_utf8_string(parent.summary(), "Commit Summary")?),
+            ));
         }
-    }
 
-    pub fn borrow_repo_mut(&mut self) -> Result<&mut Repository> {
-        let repo_temp_opt = &mut self.repo;
-        match repo_temp_opt {
-            Some(repo) => Ok(repo),
-            None => bail!("No repo loaded to perform operation on."),
-        }
+        Ok(Self {
+            sha: commit.id().to_string(),
+            is_committing,
+            parents,
+        })
     }
+}
 
-    pub fn has_open_repo(&self) -> bool {
-        match self.borrow_repo() {
-            Ok(_) => true,
-            Err(_) => false,
+#[derive(Clone, Serialize)]
+pub struct ReleaseTagInfo {
+    previous_tag: Option<String>,
+    suggested_tag: String,
+    changelog: String,
+}
+
+impl ReleaseTagInfo {
+    pub fn new(previous_tag: Option<String>, suggested_tag: String, changelog: String) -> Self {
+        Self {
+            previous_tag,
+            suggested_tag,
+            changelog,
         }
     }
+}
 
-    pub fn init_repo(&mut self, json_str: &str) -> Result<()> {
-        let path_value: Value = serde_json::from_str(json_str)?;
-        let path_str = GitManager::get_string_from_serde_string(path_value.as_str())?;
-        self.repo = Some(Repository::init(Path::new(path_str))?);
-        Ok(())
-    }
+#[derive(Clone, Serialize)]
+pub struct GitCommandOutput {
+    stream: String,
+    line: String,
+}
 
-    pub fn open_repo(&mut self, json_str: &str) -> Result<()> {
-        let path_value: Value = serde_json::from_str(json_str)?;
-        let path_str = GitManager::get_string_from_serde_string(path_value.as_str())?;
-        self.repo = Some(Repository::open(Path::new(path_str))?);
-        Ok(())
-    }
+#[derive(Clone, Serialize)]
+pub struct GitCommandResult {
+    exit_code: Option<i32>,
+    // What needs to be refreshed afterwards: "none", "changes", or "all".
+    refresh: String,
+}
 
-    pub fn clone_repo(&mut self, json_str: &str) -> Result<()> {
-        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
-        let clone_url = match json_hm.get("clone_url") {
-            Some(s) => s,
-            None => bail!("clone_url not included in payload from the front-end"),
-        };
-        let clone_path = match json_hm.get("clone_path") {
-            Some(s) => s,
-            None => bail!("clone_path not included in payload from the front-end"),
-        };
+impl GitCommandResult {
+    pub fn borrow_refresh(&self) -> &String {
+        &self.refresh
+    }
+}
 
-        let callbacks = GitManager::get_remote_callbacks();
-        let mut fetch_options = FetchOptions::new();
-        fetch_options.download_tags(AutotagOption::All);
-        fetch_options.remote_callbacks(callbacks);
+// Git subcommands that only read from the repo, and ones that only touch the index/working directory.
+const READ_ONLY_GIT_COMMANDS: [&str; 14] = ["status", "log", "show", "diff", "blame", "grep", "ls-files", "ls-tree", "ls-remote", "rev-parse", "describe", "shortlog", "cat-file", "version"];
+const CHANGES_ONLY_GIT_COMMANDS: [&str; 7] = ["add", "rm", "mv", "restore", "clean", "apply", "update-index"];
+
+// Hook names git knows how to run, so the front-end can tell these apart from scripts that just happen to be in the hooks directory.
+const KNOWN_HOOKS: [&str; 22] = ["applypatch-msg", "pre-applypatch", "post-applypatch", "pre-commit", "pre-merge-commit", "prepare-commit-msg", "commit-msg", "post-commit", "pre-rebase", "post-checkout", "post-merge", "pre-push", "pre-receive", "update", "proc-receive", "post-receive", "post-update", "reference-transaction", "push-to-checkout", "pre-auto-gc", "post-rewrite", "sendemail-validate"];
+
+// Hooks that can be installed from the app: (id, hook name, description, script).
+const SAMPLE_HOOKS: [(&str, &str, &str, &str); 4] = [
+    ("protect-main-branches", "pre-commit", "Refuses commits made directly on main or master.", r#"#!/bin/sh
+# Refuses commits made directly on main or master.
+branch=$(git symbolic-ref --short -q HEAD)
+case "$branch" in
+    main|master)
+        echo "Commits to $branch aren't allowed, make a branch first." >&2
+        exit 1
+        ;;
+esac
+"#),
+    ("check-whitespace", "pre-commit", "Refuses commits that add trailing whitespace or leftover conflict markers.", r#"#!/bin/sh
+# Refuses commits that add trailing whitespace or leftover conflict markers.
+exec git diff --cached --check --
+"#),
+    ("subject-length", "commit-msg", "Refuses commit messages with a subject over 72 characters.", r#"#!/bin/sh
+# Refuses commit messages with a subject over 72 characters.
+subject=$(sed -n '1p' "$1")
+if [ ${#subject} -gt 72 ]; then
+    echo "The commit subject is ${#subject} characters, keep it to 72 or less." >&2
+    exit 1
+fi
+"#),
+    ("block-wip-push", "pre-push", "Refuses to push commits whose subject starts with WIP, fixup! or squash!.", r#"#!/bin/sh
+# Refuses to push commits whose subject starts with WIP, fixup! or squash!.
+zero=$(git hash-object --stdin </dev/null | tr '0-9a-f' '0')
+while read local_ref local_sha remote_ref remote_sha; do
+    if [ "$local_sha" = "$zero" ]; then
+        continue
+    fi
+    if [ "$remote_sha" = "$zero" ]; then
+        commit=$(git rev-list -n 1 --grep='^\(WIP\|fixup!\|squash!\)' "$local_sha" --not --remotes)
+    else
+        commit=$(git rev-list -n 1 --grep='^\(WIP\|fixup!\|squash!\)' "$remote_sha..$local_sha")
+    fi
+    if [ -n "$commit" ]; then
+        echo "$local_ref has an unfinished commit ($commit), squash or reword it before pushing." >&2
+        exit 1
+    fi
+done
+"#),
+];
 
-        let mut repo_builder = RepoBuilder::new();
-        repo_builder.fetch_options(fetch_options);
+#[derive(Clone, Serialize)]
+pub struct GitHook {
+    name: String,
+    is_executable: bool,
+    // One of the *.sample files git init puts in the hooks directory, which git never runs.
+    is_sample: bool,
+    is_known: bool,
+}
 
-        let project_name = match clone_url.split("/").last() {
-            Some(s) => {
-                &s[..(s.len() - 4)]
-            },
-            None => bail!("Clone url was empty?"),
-        };
+#[derive(Clone, Serialize)]
+pub struct SampleHook {
+    id: String,
+    hook_name: String,
+    description: String,
+}
 
-        let mut path_buf = PathBuf::from(clone_path);
-        path_buf.push(project_name);
+#[derive(Clone, Serialize)]
+pub struct HooksInfo {
+    hooks_dir: String,
+    hooks: Vec<GitHook>,
+    samples: Vec<SampleHook>,
+    // For suggesting names when making a new hook.
+    known_hooks: Vec<String>,
+}
 
-        create_dir_all(path_buf.as_path())?;
+#[derive(Clone, Serialize)]
+pub struct GitHookContents {
+    name: String,
+    contents: String,
+}
 
-        self.repo = Some(repo_builder.clone(clone_url, path_buf.as_path())?);
+#[derive(Clone, Serialize)]
+pub struct RepoCapabilities {
+    has_workdir: bool,
+    is_unborn: bool,
+    can_commit: bool,
+    // The repo is on a read-only filesystem or isn't writable by this user, so it can only be browsed.
+    read_only: bool,
+    // Human readable descriptions of anything wrong with the repo, e.g. a corrupt object database.
+    problems: Vec<String>,
+}
 
-        Ok(())
+impl RepoCapabilities {
+    pub fn borrow_has_workdir(&self) -> &bool {
+        &self.has_workdir
     }
 
-    fn get_message_without_summary(full_message: &str) -> (String, bool) {
-        let mut separate_pieces: VecDeque<&str> = full_message.split("\r\n\r\n").collect();
-        let mut use_crlf = true;
-
-        if separate_pieces.len() == 1 {
-            separate_pieces = full_message.split("\n\n").collect();
-            use_crlf = false;
-        }
+    pub fn borrow_is_unborn(&self) -> &bool {
+        &self.is_unborn
+    }
 
-        // Remove the summary
-        separate_pieces.pop_front();
+    pub fn borrow_can_commit(&self) -> &bool {
+        &self.can_commit
+    }
 
-        if use_crlf {
-            (separate_pieces.make_contiguous().join("\r\n\r\n"), use_crlf)
-        } else {
-            (separate_pieces.make_contiguous().join("\n\n"), use_crlf)
-        }
+    pub fn borrow_read_only(&self) -> &bool {
+        &self.read_only
     }
 
-    fn old_shas_eq_sorted_new_oids(&self, new_oids: &Vec<Oid>) -> bool {
-        // NOTE: This function assumes the new_oids are sorted by date!
-        if self.old_graph_starting_shas.len() == new_oids.len() {
-            for i in 0..self.old_graph_starting_shas.len() {
-                if self.old_graph_starting_shas[i] != new_oids[i].to_string() {
-                    return false;
-                }
-            }
-            return true;
-        }
-        false
+    pub fn is_readable(&self) -> bool {
+        self.problems.is_empty()
     }
+}
 
-    pub fn git_revwalk(&mut self, force_refresh: bool) -> Result<Option<Vec<Oid>>> {
-        let mut oid_vec: Vec<Oid> = vec![];
-        // This closure allows self to be borrowed mutably later for setting the new graph starting shas.
-        {
-            let repo = self.borrow_repo()?;
-            for branch_result in repo.branches(None)? {
-                let (branch, _) = branch_result?;
-                match branch.get().target() {
-                    Some(oid) => {
-                        if !oid_vec.contains(&oid) {
-                            oid_vec.push(oid);
-                        }
-                    },
-                    None => (),
-                };
-            };
+const PGP_SIGNATURE_BEGIN: &str = "-----BEGIN PGP SIGNATURE-----";
+const SSH_SIGNATURE_BEGIN: &str = "-----BEGIN SSH SIGNATURE-----";
 
-            if repo.head_detached()? {
-                match repo.head()?.target() {
-                    Some(oid) => {
-                        if !oid_vec.contains(&oid) {
-                            oid_vec.push(oid);
-                        }
-                    },
-                    None => (),
-                };
-            }
+#[derive(Clone, Serialize)]
+pub struct TagInfo {
+    name: String,
+    target_sha: String,
+    is_lightweight: bool,
+    tagger_name: Option<String>,
+    tagger_email: Option<String>,
+    tagger_time: Option<i64>,
+    message: Option<String>,
+    // One of "unsigned", "good", "bad", or "unknown" (the signature couldn't be checked).
+    signature_status: String,
+    signature_details: Option<String>,
+}
 
-            // Sort Oids by date first
-            oid_vec.sort_by(|a, b| {
-                repo.find_commit(*b).unwrap().time().seconds().partial_cmp(&repo.find_commit(*a).unwrap().time().seconds()).unwrap()
-            });
-        }
+// What the branch tree leaves out for each tag until it's selected, since peeling thousands of annotated tags is slow.
+#[derive(Clone, Serialize)]
+pub struct TagDetails {
+    full_tag_name: String,
+    target_sha: String,
+    is_annotated: bool,
+    // When it was tagged, or the commit's time for lightweight tags.
+    time: Option<i64>,
+}
 
-        if force_refresh {
-            self.old_graph_starting_shas = vec![];
-        }
+#[derive(Clone, Serialize)]
+pub struct BranchRemotesInfo {
+    branch_shorthand: String,
+    // branch.<name>.remote, the remote pulled from by default.
+    remote: Option<String>,
+    // branch.<name>.pushRemote, the remote pushed to by default if it's different from the one pulled from.
+    push_remote: Option<String>,
+    remotes: Vec<String>,
+}
 
-        if self.old_shas_eq_sorted_new_oids(&oid_vec) {
-            return Ok(None);
-        }
+#[derive(Clone, Serialize)]
+pub struct RemoteHeadInfo {
+    remote_name: String,
+    // The branch refs/remotes/<remote>/HEAD points to locally.
+    local_head: Option<String>,
+    // The branch the remote itself says is its default.
+    remote_default_branch: Option<String>,
+}
 
-        // If you've reached here, the old and new starting oids are different. Update the old and perform the revwalk.
-        self.old_graph_starting_shas = oid_vec.iter().map(|new_oid| {
-            new_oid.to_string()
-        }).collect();
+#[derive(Clone, Serialize)]
+pub struct WorkdirDiff {
+    sha: String,
+    summary: String,
+    changed_files: Vec<ParseableDiffDelta>,
+}
 
-        let repo = self.borrow_repo()?;
-        let mut revwalk = repo.revwalk()?;
+#[derive(Clone, Serialize)]
+pub struct StagedPatchFile {
+    delta: ParseableDiffDelta,
+    is_binary: bool,
+    // Each hunk's text, starting with its @@ header line.
+    hunks: Vec<String>,
+}
 
-        for oid in oid_vec {
-            revwalk.push(oid)?;
-        }
-        revwalk.set_sorting(Sort::TOPOLOGICAL)?;
+// Everything that's staged as one patch, which is what the next commit will contain.
+#[derive(Clone, Serialize)]
+pub struct StagedPatch {
+    patch: String,
+    files: Vec<StagedPatchFile>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct MergeBaseDiff {
+    branch_a: String,
+    branch_b: String,
+    merge_base_sha: String,
+    // The A...B range to pass as the sha when getting the hunks for a file with the "merge-base" change type.
+    range: String,
+    changed_files: Vec<ParseableDiffDelta>,
+}
+
+// What merging theirs into ours would do, worked out in memory without touching the working directory.
+#[derive(Clone, Serialize)]
+pub struct MergePreview {
+    ours: String,
+    theirs: String,
+    // Empty if the branches don't share any history.
+    merge_base_sha: String,
+    // "up_to_date", "fast_forward", "clean", or "conflicts".
+    outcome: String,
+    conflicted_files: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct StashDiff {
+    index: usize,
+    sha: String,
+    message: String,
+    changed_files: Vec<ParseableDiffDelta>,
+    // The first page of hunks for each file in changed_files, in the same order.
+    file_infos: Vec<FileInfo>,
+}
+
+// Everything the front-end can copy from a commit, without loading the whole CommitInfo.
+#[derive(Clone, Serialize)]
+pub struct CopyableCommitFields {
+    sha: String,
+    short_sha: String,
+    subject: String,
+    message: String,
+    // Formatted like "Name <email>".
+    author: String,
+}
+
+impl CopyableCommitFields {
+    pub fn get_field(&self, field_name: &str) -> Result<&String> {
+        match field_name {
+            "sha" => Ok(&self.sha),
+            "short_sha" => Ok(&self.short_sha),
+            "subject" => Ok(&self.subject),
+            "message" => Ok(&self.message),
+            "author" => Ok(&self.author),
+            _ => bail!("Unrecognized commit field: {}", field_name),
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct BlameLine {
+    line_number: usize,
+    content: String,
+    sha: String,
+    author_name: String,
+    author_time: i64,
+    summary: String,
+    // Where the line came from in the commit that last changed it.
+    orig_path: String,
+    orig_line_number: usize,
+    // The ignored revision the line was passed over from, if any.
+    ignored_sha: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct BlameInfo {
+    file_path: String,
+    sha: String,
+    ignored_revs: Vec<String>,
+    lines: Vec<BlameLine>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct FileHistoryEntry {
+    sha: String,
+    // The file's path in this commit, which changes as renames are followed.
+    path: String,
+    old_path: Option<String>,
+    // One of "added", "modified", "renamed", or "copied".
+    status: String,
+    author_name: String,
+    author_time: i64,
+    summary: String,
+}
+
+// What happened to one of the commits in a multi-select operation.
+#[derive(Clone, Serialize)]
+pub struct CommitOperationResult {
+    sha: String,
+    // "applied", "empty", "conflicted", "failed", "skipped", or "exported".
+    status: String,
+    message: String,
+}
+
+impl CommitOperationResult {
+    fn new(sha: String, status: &str, message: String) -> Self {
+        Self {
+            sha,
+            status: String::from(status),
+            message,
+        }
+    }
+}
+
+// What happened to one of the local branches during a sync.
+#[derive(Clone, Serialize)]
+pub struct BranchSyncResult {
+    branch_shorthand: String,
+    // "updated", "up_to_date", "needs_merge", "no_upstream", or "failed".
+    status: String,
+    message: String,
+}
+
+impl BranchSyncResult {
+    fn new(branch_shorthand: String, status: &str, message: String) -> Self {
+        Self {
+            branch_shorthand,
+            status: String::from(status),
+            message,
+        }
+    }
+}
+
+// How far along fetching a submodule is, sent to the front-end as it happens.
+#[derive(Clone, Serialize)]
+pub struct SubmoduleProgress {
+    path: String,
+    received_objects: usize,
+    total_objects: usize,
+}
+
+// What happened to one submodule when recursing a fetch or pull into it.
+#[derive(Clone, Serialize)]
+pub struct SubmoduleUpdateResult {
+    path: String,
+    // "moved", "unchanged", "fetched", "not_initialized", or "failed".
+    status: String,
+    old_sha: Option<String>,
+    new_sha: Option<String>,
+    message: String,
+}
+
+impl SubmoduleUpdateResult {
+    fn new(path: String, status: &str, message: String) -> Self {
+        Self {
+            path,
+            status: String::from(status),
+            old_sha: None,
+            new_sha: None,
+            message,
+        }
+    }
+}
+
+// Whether one hunk of a pasted patch would apply cleanly.
+#[derive(Clone, Serialize)]
+pub struct PatchHunkCheck {
+    file_path: String,
+    hunk_header: String,
+    applies: bool,
+    message: String,
+}
+
+// A copy of the working tree and index that was saved before an operation that could throw changes away.
+#[derive(Clone, Serialize)]
+pub struct SnapshotInfo {
+    ref_name: String,
+    sha: String,
+    message: String,
+    // Where HEAD pointed when the snapshot was taken.
+    head_sha: String,
+    time: i64,
+}
+
+// An ignored file or directory and the gitignore rule that ignores it.
+#[derive(Clone, Serialize)]
+pub struct IgnoredFile {
+    path: String,
+    // The file the rule is in (like .gitignore or .git/info/exclude), the line it's on, and the rule itself.
+    source: String,
+    line_number: String,
+    pattern: String,
+}
+
+// A staged file over the large file warning size, with a gitattributes pattern that would move it (and others like it) to LFS.
+#[derive(Clone, Serialize)]
+pub struct LargeFile {
+    // Percent-encoded like the paths in ParseableDiffDelta.
+    path: String,
+    display_path: String,
+    size: usize,
+    suggested_pattern: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LfsLock {
+    id: String,
+    path: String,
+    owner: String,
+    locked_at: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LogEntry {
+    sha: String,
+    author_name: String,
+    author_email: String,
+    // RFC 3339 in the author's own time zone.
+    date: String,
+    subject: String,
+    refs: Vec<String>,
+}
+
+// Maps a line in the new side of a diff with no context lines to the line it most likely came from in the old side.
+// Changed lines are matched by their position in the hunk, which is how git blame handles ignored revisions too.
+fn get_old_line_number(patch: &Patch, new_line_number: usize) -> Result<Option<usize>> {
+    let mut line_offset: isize = 0;
+    for hunk_index in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(hunk_index)?;
+        let old_lines = hunk.old_lines() as usize;
+        let new_lines = hunk.new_lines() as usize;
+        // An empty side of a hunk starts at the line before it.
+        let old_start = if old_lines == 0 { hunk.old_start() as usize + 1 } else { hunk.old_start() as usize };
+        let new_start = if new_lines == 0 { hunk.new_start() as usize + 1 } else { hunk.new_start() as usize };
+        if new_line_number < new_start {
+            break;
+        }
+        if new_line_number < new_start + new_lines {
+            if old_lines == 0 {
+                // The line was added by this change, so there's nothing to pass it on to.
+                return Ok(None);
+            }
+            return Ok(Some(old_start + (new_line_number - new_start).min(old_lines - 1)));
+        }
+        line_offset = (old_start + old_lines) as isize - (new_start + new_lines) as isize;
+    }
+    Ok(Some((new_line_number as isize + line_offset) as usize))
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') || field.contains('\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+// Builds a file name like "0001-Fix-the-thing.patch" the same way git format-patch does.
+fn get_patch_file_name(patch_number: usize, summary: &str) -> String {
+    let mut slug = String::new();
+    for c in summary.chars() {
+        if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let mut slug = slug.trim_matches(|c| c == '-' || c == '.').to_string();
+    slug.truncate(52);
+    format!("{:04}-{}.patch", patch_number, slug.trim_end_matches('-'))
+}
+
+fn parse_semver_tag(tag_name: &str) -> Option<(u64, u64, u64)> {
+    let version_str = tag_name.strip_prefix('v').unwrap_or(tag_name);
+    let parts: Vec<&str> = version_str.split('.').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    let major = parts[0].parse::<u64>().ok()?;
+    let minor = parts[1].parse::<u64>().ok()?;
+    let patch = parts[2].parse::<u64>().ok()?;
+    Some((major, minor, patch))
+}
+
+// Lock files younger than this are assumed to belong to a git process that's still running.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(60);
+
+#[cfg(target_os = "linux")]
+fn get_lock_owner_pid(lock_path: &Path) -> Option<u32> {
+    let proc_entries = fs::read_dir("/proc").ok()?;
+    for proc_entry in proc_entries.flatten() {
+        let pid = match proc_entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) {
+            Some(p) => p,
+            None => continue,
+        };
+        let fd_entries = match fs::read_dir(proc_entry.path().join("fd")) {
+            Ok(f) => f,
+            Err(_) => continue,
+        };
+        for fd_entry in fd_entries.flatten() {
+            if let Ok(target) = fs::read_link(fd_entry.path()) {
+                if target == lock_path {
+                    return Some(pid);
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_lock_owner_pid(_lock_path: &Path) -> Option<u32> {
+    None
+}
+
+// libgit2 can't open paths with the \\?\ prefix Windows uses for long paths (which canonicalize and some file dialogs
+// add), but it handles long paths itself, so strip the prefix before handing a path to it.
+pub fn normalize_path(path_str: &str) -> PathBuf {
+    if cfg!(windows) {
+        let path_string = path_str.replace('/', "\\");
+        if let Some(unc_path) = path_string.strip_prefix(r"\\?\UNC\") {
+            return PathBuf::from(format!(r"\\{}", unc_path));
+        }
+        if let Some(local_path) = path_string.strip_prefix(r"\\?\") {
+            return PathBuf::from(local_path);
+        }
+        return PathBuf::from(path_string);
+    }
+    PathBuf::from(path_str)
+}
+
+// Git stores paths as bytes, so filenames in legacy encodings may not be valid UTF-8. Those bytes are percent-encoded
+// so the path survives the trip to the front-end and back, and '%' is encoded too so decoding is never ambiguous.
+pub fn encode_path_bytes(path_bytes: &[u8]) -> String {
+    let mut encoded_path = String::new();
+    let mut remaining_bytes = path_bytes;
+    while !remaining_bytes.is_empty() {
+        let (valid_len, invalid_len) = match std::str::from_utf8(remaining_bytes) {
+            Ok(_) => (remaining_bytes.len(), 0),
+            // error_len is None when the bytes end part way through a character, so the rest of them are invalid.
+            Err(e) => (e.valid_up_to(), e.error_len().unwrap_or(remaining_bytes.len() - e.valid_up_to())),
+        };
+        for c in String::from_utf8_lossy(&remaining_bytes[..valid_len]).chars() {
+            if c == '%' {
+                encoded_path.push_str("%25");
+            } else {
+                encoded_path.push(c);
+            }
+        }
+        for byte in &remaining_bytes[valid_len..valid_len + invalid_len] {
+            encoded_path.push_str(&format!("%{:02X}", byte));
+        }
+        remaining_bytes = &remaining_bytes[valid_len + invalid_len..];
+    }
+    encoded_path
+}
+
+pub fn decode_path(encoded_path: &str) -> Result<PathBuf> {
+    let encoded_bytes = encoded_path.as_bytes();
+    let mut path_bytes: Vec<u8> = Vec::with_capacity(encoded_bytes.len());
+    let mut i = 0;
+    while i < encoded_bytes.len() {
+        if encoded_bytes[i] == b'%' {
+            let hex = match encoded_path.get(i + 1..i + 3) {
+                Some(h) => h,
+                None => bail!("Path {} has an incomplete percent-encoded byte.", encoded_path),
+            };
+            path_bytes.push(u8::from_str_radix(hex, 16)?);
+            i += 3;
+        } else {
+            path_bytes.push(encoded_bytes[i]);
+            i += 1;
+        }
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Ok(PathBuf::from(std::ffi::OsStr::from_bytes(&path_bytes)))
+    }
+    // Windows paths are always valid unicode, so anything that isn't can't exist there anyway.
+    #[cfg(not(unix))]
+    {
+        Ok(PathBuf::from(String::from_utf8(path_bytes)?))
+    }
+}
+
+// Encodes a path from libgit2 the same way as encode_path_bytes so it can go to the front-end.
+pub fn encode_path(path: &Path) -> Result<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Ok(encode_path_bytes(path.as_os_str().as_bytes()))
+    }
+    // Windows paths are always valid unicode.
+    #[cfg(not(unix))]
+    {
+        Ok(encode_path_bytes(GitManager::get_utf8_string(path.to_str(), "File Path")?.as_bytes()))
+    }
+}
+
+// Makes a Command for an external program (git, hooks, gpg, etc.) with the extra PATH entries and environment variables from the preferences.
+fn new_tool_command<S: AsRef<std::ffi::OsStr>>(program: S) -> Result<Command> {
+    let config = config_manager::get_config()?;
+    let mut command = Command::new(program);
+    let extra_path_entries = config.borrow_extra_path_entries().clone().unwrap_or_default();
+    if !extra_path_entries.is_empty() {
+        let mut path_dirs: Vec<PathBuf> = extra_path_entries.iter().map(PathBuf::from).collect();
+        if let Some(current_path) = env::var_os("PATH") {
+            path_dirs.extend(env::split_paths(&current_path));
+        }
+        // The program is looked up in the PATH it's given, so tools in the extra entries are found too.
+        command.env("PATH", env::join_paths(path_dirs)?);
+    }
+    if let Some(extra_env_vars) = config.borrow_extra_env_vars() {
+        command.envs(extra_env_vars);
+    }
+    Ok(command)
+}
+
+// Apps started from a desktop environment don't get the PATH from the user's shell profile, so node and python (which husky,
+// lefthook and pre-commit hooks need) usually aren't on it. This adds the login shell's PATH and the usual install locations.
+fn get_hook_path_env(working_dir: &Path) -> Result<std::ffi::OsString> {
+    let mut path_dirs: Vec<PathBuf> = vec![working_dir.join("node_modules").join(".bin")];
+    path_dirs.extend(config_manager::get_config()?.borrow_extra_path_entries().clone().unwrap_or_default().iter().map(PathBuf::from));
+    #[cfg(unix)]
+    if let Ok(shell) = env::var("SHELL") {
+        let output_result = Command::new(shell)
+            .args(["-lc", "printf '%s' \"$PATH\""])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+        if let Ok(output) = output_result {
+            if output.status.success() {
+                path_dirs.extend(env::split_paths(&String::from_utf8_lossy(&output.stdout).into_owned()));
+            }
+        }
+    }
+    if let Some(current_path) = env::var_os("PATH") {
+        path_dirs.extend(env::split_paths(&current_path));
+    }
+    #[cfg(unix)]
+    {
+        path_dirs.push(PathBuf::from("/opt/homebrew/bin"));
+        path_dirs.push(PathBuf::from("/usr/local/bin"));
+        if let Some(base_dirs) = directories::BaseDirs::new() {
+            let home_dir = base_dirs.home_dir();
+            for dir in [".volta/bin", ".local/bin", ".asdf/shims", ".pyenv/shims"] {
+                path_dirs.push(home_dir.join(dir));
+            }
+            // nvm only adds the node version it's using from the shell's rc file, so fall back to the newest one installed.
+            if let Ok(entries) = fs::read_dir(home_dir.join(".nvm/versions/node")) {
+                let mut node_dirs: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+                node_dirs.sort_by_key(|d| {
+                    d.file_name().and_then(|n| n.to_str()).unwrap_or("").trim_start_matches('v').split('.').map(|p| p.parse::<u32>().unwrap_or(0)).collect::<Vec<u32>>()
+                });
+                if let Some(newest_node_dir) = node_dirs.last() {
+                    path_dirs.push(newest_node_dir.join("bin"));
+                }
+            }
+        }
+    }
+
+    let mut seen_dirs = HashSet::new();
+    path_dirs.retain(|d| seen_dirs.insert(d.clone()));
+    Ok(env::join_paths(path_dirs)?)
+}
+
+// Git for Windows keeps its sh in usr/bin of the install, which usually isn't on PATH, so find it from git's exec path (mingw64/libexec/git-core).
+#[cfg(not(unix))]
+fn get_git_sh_path() -> Result<PathBuf> {
+    if let Ok(output) = new_tool_command("git")?.arg("--exec-path").stdin(Stdio::null()).output() {
+        let exec_path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        if let Some(git_root) = exec_path.ancestors().nth(3) {
+            let sh_path = git_root.join("usr").join("bin").join("sh.exe");
+            if sh_path.is_file() {
+                return Ok(sh_path);
+            }
+        }
+    }
+    Ok(PathBuf::from("sh"))
+}
+
+// Conflict markers are 7 characters, followed by a label or nothing.
+fn is_conflict_marker(line: &str, marker: char) -> bool {
+    let trimmed_line = line.trim_end_matches(|c| c == '\r' || c == '\n');
+    let marker_str: String = std::iter::repeat(marker).take(7).collect();
+    match trimmed_line.strip_prefix(&marker_str) {
+        Some(rest) => rest.is_empty() || rest.starts_with(' '),
+        None => false,
+    }
+}
+
+// Gets each conflict region along with the range of lines (markers included) that it covers.
+fn parse_conflict_regions(lines: &[&str]) -> Result<Vec<(std::ops::Range<usize>, ConflictRegion)>> {
+    let mut regions = vec![];
+    let mut i = 0;
+    while i < lines.len() {
+        if !is_conflict_marker(lines[i], '<') {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut sections: Vec<String> = vec![String::new()];
+        let mut has_base = false;
+        i += 1;
+        loop {
+            if i >= lines.len() {
+                bail!("The conflict starting on line {} doesn't have an end marker (>>>>>>>).", start + 1);
+            }
+            let line = lines[i];
+            if is_conflict_marker(line, '|') && sections.len() == 1 {
+                has_base = true;
+                sections.push(String::new());
+            } else if is_conflict_marker(line, '=') && sections.len() == if has_base { 2 } else { 1 } {
+                sections.push(String::new());
+            } else if is_conflict_marker(line, '>') && sections.len() == if has_base { 3 } else { 2 } {
+                break;
+            } else if is_conflict_marker(line, '<') {
+                bail!("The conflict starting on line {} has another one inside it, which can't be resolved here.", start + 1);
+            } else {
+                sections.last_mut().unwrap().push_str(line);
+            }
+            i += 1;
+        }
+        i += 1;
+        let theirs = sections.pop().unwrap_or_default();
+        let base = if has_base { sections.pop() } else { None };
+        let ours = sections.pop().unwrap_or_default();
+        regions.push((start..i, ConflictRegion {
+            start_line: start + 1,
+            ours,
+            base,
+            theirs,
+        }));
+    }
+    Ok(regions)
+}
+
+// Builds MergeOptions from a merge or rebase payload, where every option is optional and given as a string like the rest of the payload.
+fn get_merge_options(options_value: &Value) -> Result<MergeOptions> {
+    let mut merge_options = MergeOptions::new();
+    let get_option = |name: &str| options_value.get(name).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+    if let Some(favor) = get_option("favor") {
+        merge_options.file_favor(match favor {
+            "normal" => FileFavor::Normal,
+            "ours" => FileFavor::Ours,
+            "theirs" => FileFavor::Theirs,
+            "union" => FileFavor::Union,
+            _ => bail!("Unknown merge favor: {}", favor),
+        });
+    }
+    if let Some(find_renames) = get_option("find_renames") {
+        merge_options.find_renames(find_renames == "true");
+    }
+    if let Some(rename_threshold_str) = get_option("rename_threshold") {
+        let rename_threshold = rename_threshold_str.parse::<u32>()?;
+        if rename_threshold > 100 {
+            bail!("The rename threshold is a percentage, so it can't be over 100.");
+        }
+        merge_options.rename_threshold(rename_threshold);
+    }
+    // The most files to look at for renames, like merge.renameLimit.
+    if let Some(rename_limit) = get_option("rename_limit") {
+        merge_options.target_limit(rename_limit.parse::<u32>()?);
+    }
+    if let Some(diff_algorithm) = get_option("diff_algorithm") {
+        match diff_algorithm {
+            "myers" => (),
+            "minimal" => {
+                merge_options.minimal(true);
+            },
+            // libgit2 doesn't have histogram, which is an extension of patience so it's the closest there is.
+            "patience" | "histogram" => {
+                merge_options.patience(true);
+            },
+            _ => bail!("Unknown diff algorithm: {}", diff_algorithm),
+        }
+    }
+    Ok(merge_options)
+}
+
+// Every diff starts from this so they all use the diff algorithm and hunk settings from the settings.
+fn new_diff_options() -> Result<DiffOptions> {
+    let config = config_manager::get_config()?;
+    let mut diff_options = DiffOptions::new();
+    diff_options.interhunk_lines(config.borrow_interhunk_lines().unwrap_or(0));
+    match config.borrow_diff_algorithm().as_deref() {
+        Some("minimal") => {
+            diff_options.minimal(true);
+        },
+        // libgit2 doesn't have histogram, which is an extension of patience so it's the closest there is.
+        Some("patience") | Some("histogram") => {
+            diff_options.patience(true);
+        },
+        _ => (),
+    }
+    Ok(diff_options)
+}
+
+// The settings that change how a diff comes out, so a cached diff isn't reused after they're changed.
+fn get_diff_settings_key() -> Result<String> {
+    let config = config_manager::get_config()?;
+    Ok(format!("{:?} {:?} {:?} {:?} {:?}", config.borrow_ignore_eol_changes(), config.borrow_diff_algorithm(), config.borrow_interhunk_lines(), config.borrow_show_function_names(), config.borrow_collapse_attributes()))
+}
+
+fn get_eol_diff_options(ignore_eol: bool, context_lines: u32) -> Result<DiffOptions> {
+    let mut diff_options = new_diff_options()?;
+    diff_options.ignore_whitespace_eol(ignore_eol);
+    diff_options.context_lines(context_lines);
+    Ok(diff_options)
+}
+
+// The 0-based old and new line ranges a hunk covers. A side with no lines starts after its start line rather than at it.
+fn get_hunk_line_ranges(hunk: &DiffHunk) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+    let get_range = |start: u32, lines: u32| {
+        let range_start = if lines == 0 { start } else { start - 1 } as usize;
+        range_start..range_start + lines as usize
+    };
+    (get_range(hunk.old_start(), hunk.old_lines()), get_range(hunk.new_start(), hunk.new_lines()))
+}
+
+// True if every changed line only differs from the line it replaced by its line ending (i.e. CRLF vs LF).
+fn is_eol_only_patch(patch: &Patch) -> Result<bool> {
+    if patch.num_hunks() == 0 {
+        return Ok(false);
+    }
+    for hunk_index in 0..patch.num_hunks() {
+        let mut removed_lines: Vec<&[u8]> = vec![];
+        let mut added_lines: Vec<&[u8]> = vec![];
+        for line_index in 0..patch.num_lines_in_hunk(hunk_index)? {
+            let diff_line = patch.line_in_hunk(hunk_index, line_index)?;
+            let content = diff_line.content();
+            let content = content.strip_suffix(b"\n").unwrap_or(content);
+            let content = content.strip_suffix(b"\r").unwrap_or(content);
+            match diff_line.origin_value() {
+                DiffLineType::Deletion => removed_lines.push(content),
+                DiffLineType::Addition => added_lines.push(content),
+                _ => {},
+            };
+        }
+        if removed_lines != added_lines {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// A stash commit's first parent is the commit it was made on, and its third parent (if there is one) holds the untracked files.
+fn get_stash_changes<'a, 'b>(stash_commit: &'a Commit, repo: &'b Repository, ignore_eol: bool, context_lines: u32) -> Result<Diff<'b>> {
+    let mut diff = repo.diff_tree_to_tree(Some(&stash_commit.parent(0)?.tree()?), Some(&stash_commit.tree()?), Some(&mut get_eol_diff_options(ignore_eol, context_lines)?))?;
+    if stash_commit.parent_count() > 2 {
+        let untracked_diff = repo.diff_tree_to_tree(None, Some(&stash_commit.parent(2)?.tree()?), Some(&mut get_eol_diff_options(ignore_eol, context_lines)?))?;
+        diff.merge(&untracked_diff)?;
+    }
+    GitManager::set_diff_find_similar(&mut diff)?;
+    Ok(diff)
+}
+
+// Like git diff <commit>, compares the commit to the working directory's tracked files, staged or not.
+fn get_workdir_changes<'a, 'b>(commit: &'a Commit, repo: &'b Repository, ignore_eol: bool, context_lines: u32) -> Result<Diff<'b>> {
+    let mut diff = repo.diff_tree_to_workdir_with_index(Some(&commit.tree()?), Some(&mut get_eol_diff_options(ignore_eol, context_lines)?))?;
+    GitManager::set_diff_find_similar(&mut diff)?;
+    Ok(diff)
+}
+
+// Like git diff A...B, gets what B changed since it split off from A. Also returns the merge base.
+fn get_merge_base_changes<'a, 'b>(a_commit: &'a Commit, b_commit: &'a Commit, repo: &'b Repository, ignore_eol: bool, context_lines: u32) -> Result<(Oid, Diff<'b>)> {
+    let merge_base_oid = match repo.merge_base(a_commit.id(), b_commit.id()) {
+        Ok(oid) => oid,
+        Err(e) if e.code() == ErrorCode::NotFound => bail!("These commits don't have any history in common."),
+        Err(e) => return Err(e.into()),
+    };
+    let merge_base_tree = repo.find_commit(merge_base_oid)?.tree()?;
+    let mut diff = repo.diff_tree_to_tree(Some(&merge_base_tree), Some(&b_commit.tree()?), Some(&mut get_eol_diff_options(ignore_eol, context_lines)?))?;
+    GitManager::set_diff_find_similar(&mut diff)?;
+    Ok((merge_base_oid, diff))
+}
+
+fn get_commit_changes<'a, 'b>(commit: &'a Commit, repo: &'b Repository, ignore_eol: bool, context_lines: u32) -> Result<Diff<'b>> {
+    let commit_tree = commit.tree()?;
+
+    let mut diff_opt = None;
+    for parent_commit in commit.parents() {
+        let mut diff = repo.diff_tree_to_tree(Some(&parent_commit.tree()?), Some(&commit_tree), Some(&mut get_eol_diff_options(ignore_eol, context_lines)?))?;
+        GitManager::set_diff_find_similar(&mut diff)?;
+        // For merge commits, the diff between a merge commit and the parent from the branch that was merged will be empty,
+        // so find the diff that's populated.
+        if diff.stats()?.files_changed() > 0 {
+            return Ok(diff);
+        }
+        diff_opt = Some(diff);
+    }
+
+    // If there are parents but no diff was returned earlier, then the commit is empty and should return the empty diff.
+    if let Some(d) = diff_opt {
+        return Ok(d);
+    }
+
+    // If there are no parents, get the diff between this commit and nothing.
+    let mut diff = repo.diff_tree_to_tree(None, Some(&commit_tree), Some(&mut get_eol_diff_options(ignore_eol, context_lines)?))?;
+    GitManager::set_diff_find_similar(&mut diff)?;
+
+    Ok(diff)
+}
+
+// The changes list from the last refresh, so a refresh after staging or discarding a few files only has to look at those files.
+struct StatusCache {
+    head_oid: Option<Oid>,
+    unstaged_files: Vec<ParseableDiffDelta>,
+    staged_files: Vec<ParseableDiffDelta>,
+    case_renames: Vec<ParseableDiffDelta>,
+}
+
+// Everything that goes into a file diff between two blobs. The same blobs (like a file that wasn't changed again) show the same
+// diff no matter which commit they're selected from.
+#[derive(PartialEq)]
+struct FileDiffKey {
+    old_oid: Oid,
+    new_oid: Oid,
+    old_file_mode: u32,
+    new_file_mode: u32,
+    old_path: Vec<u8>,
+    file_path: String,
+    hunk_offset: usize,
+    context_lines: u32,
+    diff_settings_key: String,
+}
+
+pub struct GitManager {
+    repo: Option<Repository>,
+    old_graph_starting_shas: Vec<String>,
+    graph_layout: Option<GraphLayout>,
+    current_local_offset: UtcOffset,
+    lfs_locks: Vec<LfsLock>,
+    // The subdirectory the user actually picked when the repo was found by searching up from it.
+    discovered_from: Option<String>,
+    status_cache: RefCell<Option<StatusCache>>,
+    // The paths the app itself has changed since the changes list was last read. None means anything could have changed, which
+    // is the case for every refresh that doesn't follow one of those operations.
+    dirty_paths: RefCell<Option<HashSet<String>>>,
+    commit_info_cache: RefCell<LruCache<(Oid, String), CommitInfo>>,
+    file_diff_cache: RefCell<LruCache<FileDiffKey, FileInfo>>,
+    tag_limit: usize,
+    // Probed once when the repo is opened, since checking means writing a file.
+    is_writable: bool,
+}
+
+impl GitManager {
+    pub fn new(current_local_offset: UtcOffset) -> Self {
+        Self {
+            repo: None,
+            old_graph_starting_shas: vec![],
+            graph_layout: None,
+            current_local_offset,
+            lfs_locks: vec![],
+            discovered_from: None,
+            status_cache: RefCell::new(None),
+            dirty_paths: RefCell::new(None),
+            commit_info_cache: RefCell::new(LruCache::new(COMMIT_INFO_CACHE_SIZE)),
+            file_diff_cache: RefCell::new(LruCache::new(FILE_DIFF_CACHE_SIZE)),
+            tag_limit: TAG_PAGE_SIZE,
+            is_writable: true,
+        }
+    }
+
+    pub fn borrow_lfs_locks(&self) -> &Vec<LfsLock> {
+        &self.lfs_locks
+    }
+
+    pub fn borrow_discovered_from(&self) -> &Option<String> {
+        &self.discovered_from
+    }
+
+    pub fn get_utf8_string<'a, 'b>(value: Option<&'a str>, str_name_type: &'b str) -> Result<&'a str> {
+        match value {
+            Some(n) => Ok(n),
+            None => bail!(format!("{} uses invalid utf-8!", str_name_type)),
+        }
+    }
+
+    // Paths from libgit2 and the front-end use forward slashes, which Windows stops accepting once a path is long enough
+    // to need the \\?\ prefix, so rebuild them with the native separator.
+    fn get_workdir_file_path(workdir: &Path, relative_path: &Path) -> PathBuf {
+        let mut full_path = workdir.to_path_buf();
+        for component in relative_path.components() {
+            full_path.push(component);
+        }
+        full_path
+    }
+
+    fn get_string_from_serde_string(value: Option<&str>) -> Result<&str> {
+        match value {
+            Some(s) => Ok(s),
+            None => bail!("Invalid JSON String!"),
+        }
+    }
+
+    pub fn get_tag_limit(&self) -> usize {
+        self.tag_limit
+    }
+
+    pub fn show_more_tags(&mut self) -> Result<()> {
+        self.borrow_repo()?;
+        self.tag_limit += TAG_PAGE_SIZE;
+        Ok(())
+    }
+
+    pub fn borrow_current_local_offset(&self) -> &UtcOffset {
+        &self.current_local_offset
+    }
+
+    pub fn borrow_graph_layout(&self) -> &Option<GraphLayout> {
+        &self.graph_layout
+    }
+
+    pub fn set_graph_layout(&mut self, graph_layout: GraphLayout) {
+        self.graph_layout = Some(graph_layout);
+    }
+
+    pub fn borrow_repo(&self) -> Result<&Repository> {
+        let repo_temp_opt = &self.repo;
+        match repo_temp_opt {
+            Some(repo) => Ok(repo),
+            None => bail!("No repo loaded to perform operation on."),
+        }
+    }
+
+    pub fn borrow_repo_mut(&mut self) -> Result<&mut Repository> {
+        let repo_temp_opt = &mut self.repo;
+        match repo_temp_opt {
+            Some(repo) => Ok(repo),
+            None => bail!("No repo loaded to perform operation on."),
+        }
+    }
+
+    pub fn has_open_repo(&self) -> bool {
+        match self.borrow_repo() {
+            Ok(_) => true,
+            Err(_) => false,
+        }
+    }
+
+    pub fn init_repo(&mut self, json_str: &str) -> Result<()> {
+        let path_value: Value = serde_json::from_str(json_str)?;
+        let path_str = GitManager::get_string_from_serde_string(path_value.as_str())?;
+        self.repo = Some(Repository::init(normalize_path(path_str))?);
+        self.lfs_locks = vec![];
+        self.tag_limit = TAG_PAGE_SIZE;
+        self.is_writable = GitManager::is_repo_writable(self.borrow_repo()?);
+        self.clear_repo_caches();
+        self.discovered_from = None;
+        self.remember_open_repo();
+        Ok(())
+    }
+
+    pub fn open_repo(&mut self, json_str: &str) -> Result<RepoCapabilities> {
+        let path_value: Value = serde_json::from_str(json_str)?;
+        let path_str = GitManager::get_string_from_serde_string(path_value.as_str())?;
+        let mut path = normalize_path(path_str);
+        // A .git file (from a linked worktree or a submodule) only points at the real git directory, so open the working
+        // directory it's in and let libgit2 follow it.
+        if path.is_file() && path.file_name().map_or(false, |n| n == ".git") {
+            if let Some(parent_path) = path.parent() {
+                path = parent_path.to_path_buf();
+            }
+        }
+        // Like git, search up from a subdirectory for the enclosing repo, but stop at GIT_CEILING_DIRECTORIES and at filesystem
+        // boundaries (libgit2 only crosses those with RepositoryOpenFlags::CROSS_FS).
+        let ceiling_dirs: Vec<PathBuf> = match env::var_os("GIT_CEILING_DIRECTORIES") {
+            Some(ceiling_dirs_str) => env::split_paths(&ceiling_dirs_str).filter(|p| !p.as_os_str().is_empty()).collect(),
+            None => vec![],
+        };
+        let repo = Repository::open_ext(&path, RepositoryOpenFlags::empty(), &ceiling_dirs)?;
+        let repo_root = match repo.workdir() {
+            Some(p) => p,
+            None => repo.path(),
+        };
+        self.discovered_from = None;
+        if let (Ok(canonical_path), Ok(canonical_root)) = (path.canonicalize(), repo_root.canonicalize()) {
+            if canonical_path.starts_with(&canonical_root) && canonical_path != canonical_root {
+                self.discovered_from = Some(String::from(GitManager::get_utf8_string(path.to_str(), "Opened Path")?));
+            }
+        }
+        self.repo = Some(repo);
+        self.lfs_locks = vec![];
+        self.tag_limit = TAG_PAGE_SIZE;
+        self.is_writable = GitManager::is_repo_writable(self.borrow_repo()?);
+        self.clear_repo_caches();
+        self.remember_open_repo();
+        self.get_repo_capabilities()
+    }
+
+    // Checks what can be done with the open repo so that bare, unborn, or corrupt repos don't break the whole UI.
+    pub fn get_repo_capabilities(&self) -> Result<RepoCapabilities> {
+        let repo = self.borrow_repo()?;
+
+        let has_workdir = !repo.is_bare() && repo.workdir().is_some();
+        let mut is_unborn = false;
+        let mut problems = vec![];
+
+        match repo.head() {
+            Ok(head_ref) => {
+                if let Some(oid) = head_ref.target() {
+                    if let Err(e) = repo.find_commit(oid) {
+                        problems.push(format!("HEAD points to a commit that couldn't be read, the object database may be corrupt: {}", e.message()));
+                    }
+                }
+            },
+            Err(e) => {
+                if e.code() == ErrorCode::UnbornBranch {
+                    is_unborn = true;
+                } else {
+                    problems.push(format!("HEAD couldn't be read: {}", e.message()));
+                }
+            },
+        };
+        if let Err(e) = repo.odb() {
+            problems.push(format!("The object database couldn't be opened: {}", e.message()));
+        }
+
+        let read_only = !self.is_writable;
+
+        let mut can_commit = has_workdir && problems.is_empty() && !read_only;
+        if can_commit {
+            if let Err(e) = repo.index() {
+                problems.push(format!("The index couldn't be read: {}", e.message()));
+                can_commit = false;
+            }
+        }
+
+        Ok(RepoCapabilities {
+            has_workdir,
+            is_unborn,
+            can_commit,
+            read_only,
+            problems,
+        })
+    }
+
+    // Permission bits don't account for read-only mounts (network shares, discs, etc.), so actually try writing a file.
+    fn is_dir_writable(dir: &Path) -> bool {
+        let test_path = dir.join(format!(".oxidized-git-write-test-{}", std::process::id()));
+        match fs::File::create(&test_path) {
+            Ok(_) => fs::remove_file(&test_path).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    fn is_repo_writable(repo: &Repository) -> bool {
+        if !GitManager::is_dir_writable(repo.path()) {
+            return false;
+        }
+        match repo.workdir() {
+            Some(workdir) => GitManager::is_dir_writable(workdir),
+            None => true,
+        }
+    }
+
+    // Lets operations that write to the repo fail up front with an explanation instead of partway through in libgit2.
+    pub fn ensure_writable(&self) -> Result<()> {
+        let repo = self.borrow_repo()?;
+        if !self.is_writable {
+            let repo_path = match repo.workdir() {
+                Some(d) => d,
+                None => repo.path(),
+            };
+            return Err(StructuredError::new(
+                "read_only_repo",
+                format!("{} isn't writable (it may be on a read-only filesystem), so it can only be browsed.", repo_path.display()),
+                Some(json!({ "repo_path": repo_path })),
+            ).into());
+        }
+        Ok(())
+    }
+
+    pub fn clone_repo(&mut self, json_str: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let clone_url = match json_hm.get("clone_url") {
+            Some(s) => s,
+            None => bail!("clone_url not included in payload from the front-end"),
+        };
+        let clone_path = match json_hm.get("clone_path") {
+            Some(s) => s,
+            None => bail!("clone_path not included in payload from the front-end"),
+        };
+
+        let callbacks = GitManager::get_remote_callbacks();
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.download_tags(AutotagOption::All);
+        fetch_options.remote_callbacks(callbacks);
+
+        let mut repo_builder = RepoBuilder::new();
+        repo_builder.fetch_options(fetch_options);
+
+        let project_name = match clone_url.split("/").last() {
+            Some(s) => {
+                &s[..(s.len() - 4)]
+            },
+            None => bail!("Clone url was empty?"),
+        };
+
+        let mut path_buf = normalize_path(clone_path);
+        path_buf.push(project_name);
+
+        create_dir_all(path_buf.as_path())?;
+
+        self.repo = Some(repo_builder.clone(clone_url, path_buf.as_path())?);
+        self.lfs_locks = vec![];
+        self.tag_limit = TAG_PAGE_SIZE;
+        self.is_writable = GitManager::is_repo_writable(self.borrow_repo()?);
+        self.clear_repo_caches();
+        self.discovered_from = None;
+        self.remember_open_repo();
+
+        Ok(())
+    }
+
+    // Saves the open repo as the one to reopen on startup. This is only a convenience, so failing to save it is ignored.
+    fn remember_open_repo(&self) {
+        let repo_path_result = self.borrow_repo().map(|repo| match repo.workdir() {
+            Some(p) => p.to_path_buf(),
+            None => repo.path().to_path_buf(),
+        });
+        if let (Ok(repo_path), Ok(mut config)) = (repo_path_result, config_manager::get_config()) {
+            if let Some(repo_path_str) = repo_path.to_str() {
+                config.set_last_open_repo(repo_path_str);
+                let _ = config.save();
+            }
+        }
+    }
+
+    pub fn open_last_repo(&mut self) -> Result<()> {
+        let config = config_manager::get_config()?;
+        if let Some(repo_path) = config.borrow_last_open_repo() {
+            self.open_repo(&serde_json::to_string(repo_path)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn save_repo_session(&self, json_string: &str) -> Result<()> {
+        let repo_session: RepoSession = serde_json::from_str(json_string)?;
+        let mut config = config_manager::get_config()?;
+        config.set_repo_session(&self.get_canonical_repo_path()?, repo_session);
+        config.save()
+    }
+
+    // What to start the commit summary with, based on the branch name. Empty if there's nothing to prefill.
+    pub fn get_commit_prefill(&self) -> Result<String> {
+        let config = config_manager::get_config()?;
+        if !config.borrow_prefill_commit_from_branch().unwrap_or(false) {
+            return Ok(String::new());
+        }
+        let repo = self.borrow_repo()?;
+        let head_ref = match repo.head() {
+            Ok(r) => r,
+            Err(_) => return Ok(String::new()),
+        };
+        if !head_ref.is_branch() {
+            return Ok(String::new());
+        }
+        let branch_shorthand = GitManager::get_utf8_string(head_ref.shorthand(), "Branch Name")?;
+
+        let pattern = config.borrow_branch_prefill_pattern().clone().unwrap_or(String::from(config_manager::DEFAULT_BRANCH_PREFILL_PATTERN));
+        let template = config.borrow_branch_prefill_template().clone().unwrap_or(String::from(config_manager::DEFAULT_BRANCH_PREFILL_TEMPLATE));
+        let captures = match Regex::new(&pattern)?.captures(branch_shorthand) {
+            Some(c) => c,
+            None => return Ok(String::new()),
+        };
+        let mut prefill = String::new();
+        captures.expand(&template, &mut prefill);
+        Ok(prefill)
+    }
+
+    fn get_message_without_summary(full_message: &str) -> (String, bool) {
+        let mut separate_pieces: VecDeque<&str> = full_message.split("\r\n\r\n").collect();
+        let mut use_crlf = true;
+
+        if separate_pieces.len() == 1 {
+            separate_pieces = full_message.split("\n\n").collect();
+            use_crlf = false;
+        }
+
+        // Remove the summary
+        separate_pieces.pop_front();
+
+        if use_crlf {
+            (separate_pieces.make_contiguous().join("\r\n\r\n"), use_crlf)
+        } else {
+            (separate_pieces.make_contiguous().join("\n\n"), use_crlf)
+        }
+    }
+
+    fn old_shas_eq_sorted_new_oids(&self, new_oids: &Vec<Oid>) -> bool {
+        // NOTE: This function assumes the new_oids are sorted by date!
+        if self.old_graph_starting_shas.len() == new_oids.len() {
+            for i in 0..self.old_graph_starting_shas.len() {
+                if self.old_graph_starting_shas[i] != new_oids[i].to_string() {
+                    return false;
+                }
+            }
+            return true;
+        }
+        false
+    }
+
+    // Gets the tips the commit graph is drawn from, sorted by date.
+    pub fn get_graph_starting_oids(&self) -> Result<Vec<Oid>> {
+        let mut oid_vec: Vec<Oid> = vec![];
+        let repo = self.borrow_repo()?;
+        for branch_result in repo.branches(None)? {
+            // A broken ref shouldn't keep the rest of the graph from being drawn.
+            let (branch, _) = match branch_result {
+                Ok(b) => b,
+                Err(_) => continue,
+            };
+            match branch.get().target() {
+                Some(oid) => {
+                    if !oid_vec.contains(&oid) {
+                        oid_vec.push(oid);
+                    }
+                },
+                None => (),
+            };
+        };
+
+        if repo.head_detached()? {
+            match repo.head()?.target() {
+                Some(oid) => {
+                    if !oid_vec.contains(&oid) {
+                        oid_vec.push(oid);
+                    }
+                },
+                None => (),
+            };
+        }
+
+        // Sort Oids by date first, ties are broken by Oid so the order doesn't depend on the order the branches are listed in.
+        let mut dated_oids: Vec<(i64, Oid)> = Vec::with_capacity(oid_vec.len());
+        for oid in oid_vec {
+            // Skip branches pointing at commits missing from the object database, the revwalk can't start from them.
+            if let Ok(commit) = repo.find_commit(oid) {
+                dated_oids.push((commit.time().seconds(), oid));
+            }
+        }
+        dated_oids.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        Ok(dated_oids.into_iter().map(|(_, oid)| oid).collect())
+    }
+
+    pub fn get_graph_oid_list(&self, starting_oids: &Vec<Oid>) -> Result<Vec<Oid>> {
+        let repo = self.borrow_repo()?;
+        let mut revwalk = repo.revwalk()?;
+
+        for oid in starting_oids {
+            revwalk.push(*oid)?;
+        }
+        // Sorting by time as well keeps new commits at the top and the rest in the same order, so the layout of the rows that were
+        // already there can be kept. Topological order alone can move whole branches around when one of them gets a commit.
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+        let preferences = config_manager::get_config()?;
+        let limit_commits = match preferences.borrow_limit_commits() {
+            Some(b) => b.clone(),
+            None => bail!("limit_commits not present in config file!"),
+        };
+        let commit_count = match preferences.borrow_commit_count() {
+            Some(i) => i.clone(),
+            None => bail!("commit_count not present in config file!"),
+        };
+
+        let mut oid_list: Vec<Oid> = vec![];
+        for (i, commit_oid_result) in revwalk.enumerate() {
+            if limit_commits && i >= commit_count {
+                break;
+            }
+            oid_list.push(commit_oid_result?);
+        }
+        Ok(oid_list)
+    }
+
+    // Returns true if the graph has changed since it was last sent to the front-end.
+    pub fn update_graph_starting_shas(&mut self, force_refresh: bool) -> Result<bool> {
+        let oid_vec = self.get_graph_starting_oids()?;
+
+        if force_refresh {
+            self.old_graph_starting_shas = vec![];
+            self.graph_layout = None;
+        }
+
+        if self.old_shas_eq_sorted_new_oids(&oid_vec) {
+            return Ok(false);
+        }
+
+        self.old_graph_starting_shas = oid_vec.iter().map(|new_oid| {
+            new_oid.to_string()
+        }).collect();
+        Ok(true)
+    }
+
+    // Gets the commits in a range like "v1.0..main", or everything reachable from a single ref. An empty range means HEAD.
+    fn get_log_entries(&self, range: &str) -> Result<Vec<LogEntry>> {
+        let repo = self.borrow_repo()?;
+
+        let mut oid_refs: HashMap<Oid, Vec<String>> = HashMap::new();
+        for reference_result in repo.references()? {
+            let reference = reference_result?;
+            let ref_shorthand = match reference.shorthand() {
+                Some(s) => String::from(s),
+                None => continue,
+            };
+            // Refs like notes or the stash may not point at anything in the log.
+            if let Ok(commit) = reference.peel_to_commit() {
+                oid_refs.entry(commit.id()).or_insert(vec![]).push(ref_shorthand);
+            }
+        }
+
+        let mut revwalk = repo.revwalk()?;
+        if range == "" {
+            revwalk.push_head()?;
+        } else if range.contains("..") {
+            revwalk.push_range(range)?;
+        } else {
+            revwalk.push(repo.revparse_single(range)?.peel_to_commit()?.id())?;
+        }
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+        let mut log_entries = vec![];
+        for oid_result in revwalk {
+            let oid = oid_result?;
+            let commit = repo.find_commit(oid)?;
+            let author = commit.author();
+            let author_offset = UtcOffset::from_whole_seconds(author.when().offset_minutes() * 60)?;
+            let date = OffsetDateTime::from_unix_timestamp(author.when().seconds())?.to_offset(author_offset).format(&Rfc3339)?;
+            log_entries.push(LogEntry {
+                sha: oid.to_string(),
+                author_name: String::from(GitManager::get_utf8_string(author.name(), "Author Name")?),
+                author_email: String::from(GitManager::get_utf8_string(author.email(), "Author Email")?),
+                date,
+                subject: String::from(GitManager::get_utf8_string(commit.summary(), "Commit Summary")?),
+                refs: oid_refs.remove(&oid).unwrap_or(vec![]),
+            });
+        }
+        Ok(log_entries)
+    }
+
+    pub fn export_log(&self, json_str: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let range = match json_hm.get("range") {
+            Some(r) => r.trim(),
+            None => bail!("range not included in payload from front-end."),
+        };
+        let format = match json_hm.get("format") {
+            Some(f) => f,
+            None => bail!("format not included in payload from front-end."),
+        };
+        let path = match json_hm.get("path") {
+            Some(p) => PathBuf::from(p),
+            None => bail!("path not included in payload from front-end."),
+        };
+
+        let log_entries = self.get_log_entries(range)?;
+        let mut output = String::new();
+        match format.as_str() {
+            "json" => output = serde_json::to_string_pretty(&log_entries)?,
+            "csv" => {
+                output += "sha,author_name,author_email,date,subject,refs\n";
+                for log_entry in &log_entries {
+                    let fields = [&log_entry.sha, &log_entry.author_name, &log_entry.author_email, &log_entry.date, &log_entry.subject, &log_entry.refs.join(" ")];
+                    let escaped_fields: Vec<String> = fields.iter().map(|f| escape_csv_field(f)).collect();
+                    output += &escaped_fields.join(",");
+                    output += "\n";
+                }
+            },
+            "text" => {
+                for log_entry in &log_entries {
+                    output += &format!("{} {} <{}> {} {}", log_entry.sha, log_entry.author_name, log_entry.author_email, log_entry.date, log_entry.subject);
+                    if log_entry.refs.len() > 0 {
+                        output += &format!(" ({})", log_entry.refs.join(", "));
+                    }
+                    output += "\n";
+                }
+            },
+            _ => bail!("Unrecognized log export format: {}", format),
+        };
+        fs::write(path, output)?;
+        Ok(())
+    }
+
+    pub fn get_commit_info(&self, json_str: &str) -> Result<CommitInfo> {
+        let sha_value: Value = serde_json::from_str(json_str)?;
+        let sha: &str = GitManager::get_string_from_serde_string(sha_value.as_str())?;
+        let repo = self.borrow_repo()?;
+
+        let commit = repo.find_commit(Oid::from_str(sha)?)?;
+        let cache_key = (commit.id(), get_diff_settings_key()?);
+        let cached_commit_info_opt = self.commit_info_cache.borrow_mut().get(&cache_key);
+        let mut commit_info = match cached_commit_info_opt {
+            Some(c) => c,
+            None => {
+                let commit_info = CommitInfo::from_commit(commit, repo)?;
+                self.commit_info_cache.borrow_mut().insert(cache_key, commit_info.clone());
+                commit_info
+            },
+        };
+        // These can change without the commit changing, so they aren't cached.
+        commit_info.issue_links = self.get_issue_links(&commit_info.message)?;
+        if let Ok(replace_ref) = repo.find_reference(&format!("refs/replace/{}", sha)) {
+            commit_info.replaced_by = replace_ref.target().map(|oid| oid.to_string());
+        }
+
+        Ok(commit_info)
+    }
+
+    // For a linked worktree, the working directory (or git directory if it's bare) of the repository it was added to.
+    pub fn get_main_repo_path(&self) -> Result<Option<String>> {
+        let repo = self.borrow_repo()?;
+        if !repo.is_worktree() {
+            return Ok(None);
+        }
+        // The worktree's git directory has a commondir file pointing at the main repository's git directory, usually as "../..".
+        let commondir_contents = fs::read_to_string(repo.path().join("commondir"))?;
+        let common_dir = repo.path().join(commondir_contents.trim()).canonicalize()?;
+        let main_repo = Repository::open(&common_dir)?;
+        let main_repo_path = match main_repo.workdir() {
+            Some(p) => p.to_path_buf(),
+            None => common_dir,
+        };
+        Ok(Some(String::from(GitManager::get_utf8_string(main_repo_path.to_str(), "Main Repo Path")?)))
+    }
+
+    // True if the working directory's .git is a file pointing somewhere else, like in linked worktrees and submodules.
+    pub fn has_gitdir_redirect(&self) -> Result<bool> {
+        Ok(match self.borrow_repo()?.workdir() {
+            Some(workdir) => workdir.join(".git").is_file(),
+            None => false,
+        })
+    }
+
+    pub fn get_canonical_repo_path(&self) -> Result<String> {
+        let repo_path = self.borrow_repo()?.path().canonicalize()?;
+        Ok(String::from(GitManager::get_utf8_string(repo_path.to_str(), "Repo Path")?))
+    }
+
+    pub fn get_issue_link_rules(&self) -> Result<Vec<IssueLinkRule>> {
+        Ok(config_manager::get_config()?.get_issue_link_rules(&self.get_canonical_repo_path()?))
+    }
+
+    pub fn set_issue_link_rules(&self, json_string: &str) -> Result<()> {
+        let json_hm: HashMap<String, Vec<String>> = serde_json::from_str(json_string)?;
+        let patterns = match json_hm.get("patterns") {
+            Some(v) => v,
+            None => bail!("patterns not included in payload from front-end."),
+        };
+        let url_templates = match json_hm.get("url_templates") {
+            Some(v) => v,
+            None => bail!("url_templates not included in payload from front-end."),
+        };
+        if patterns.len() != url_templates.len() {
+            bail!("Every issue link pattern needs a URL template.");
+        }
+        let rules = patterns.iter().zip(url_templates).map(|(p, u)| IssueLinkRule::new(p.clone(), u.clone())).collect();
+        let mut config = config_manager::get_config()?;
+        config.set_issue_link_rules(&self.get_canonical_repo_path()?, rules)?;
+        config.save()
+    }
+
+    pub fn set_branch_favorite(&self, json_string: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_string)?;
+        let full_branch_name = match json_hm.get("full_branch_name") {
+            Some(s) => s,
+            None => bail!("full_branch_name not included in payload from front-end."),
+        };
+        let is_favorite = match json_hm.get("is_favorite") {
+            Some(s) => s == "true",
+            None => bail!("is_favorite not included in payload from front-end."),
+        };
+        let mut config = config_manager::get_config()?;
+        config.set_favorite_branch(&self.get_canonical_repo_path()?, full_branch_name, is_favorite);
+        config.save()
+    }
+
+    // Earlier rules win where matches overlap.
+    fn get_issue_links(&self, message: &str) -> Result<Vec<IssueLink>> {
+        let mut byte_ranges: Vec<(usize, usize, String)> = vec![];
+        for rule in self.get_issue_link_rules()? {
+            let regex = Regex::new(rule.borrow_pattern())?;
+            for captures in regex.captures_iter(message) {
+                let whole_match = match captures.get(0) {
+                    Some(m) => m,
+                    None => continue,
+                };
+                // The message is HTML escaped, so don't cut an entity like &amp; in half.
+                if whole_match.is_empty() || whole_match.as_str().contains('&') {
+                    continue;
+                }
+                if byte_ranges.iter().any(|(start, end, _)| whole_match.start() < *end && *start < whole_match.end()) {
+                    continue;
+                }
+                let mut url = String::new();
+                captures.expand(rule.borrow_url_template(), &mut url);
+                byte_ranges.push((whole_match.start(), whole_match.end(), url));
+            }
+        }
+        byte_ranges.sort_by_key(|(start, _, _)| *start);
+
+        let get_utf16_index = |byte_index: usize| message[..byte_index].encode_utf16().count();
+        Ok(byte_ranges.into_iter().map(|(start, end, url)| IssueLink {
+            start: get_utf16_index(start),
+            end: get_utf16_index(end),
+            url,
+        }).collect())
+    }
+
+    fn get_copyable_fields_for_sha(&self, sha: &str) -> Result<CopyableCommitFields> {
+        let repo = self.borrow_repo()?;
+        let commit = repo.find_commit(Oid::from_str(sha)?)?;
+        let short_id = commit.as_object().short_id()?;
+        let author = commit.author();
+        Ok(CopyableCommitFields {
+            sha: commit.id().to_string(),
+            short_sha: String::from(GitManager::get_utf8_string(short_id.as_str(), "Short SHA")?),
+            subject: String::from(GitManager::get_utf8_string(commit.summary(), "Commit Summary")?),
+            message: String::from(GitManager::get_utf8_string(commit.message(), "Commit Message")?),
+            author: format!("{} <{}>", GitManager::get_utf8_string(author.name(), "Author Name")?, GitManager::get_utf8_string(author.email(), "Author Email")?),
+        })
+    }
+
+    pub fn get_copyable_commit_fields(&self, json_str: &str) -> Result<CopyableCommitFields> {
+        let sha_value: Value = serde_json::from_str(json_str)?;
+        let sha: &str = GitManager::get_string_from_serde_string(sha_value.as_str())?;
+        self.get_copyable_fields_for_sha(sha)
+    }
+
+    // Gets the text for the "Copy" context menu items, so they work even before the commit's info has been loaded.
+    pub fn get_commit_field_to_copy(&self, json_str: &str) -> Result<String> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let sha = match json_hm.get("sha") {
+            Some(s) => s,
+            None => bail!("sha not included in payload from front-end."),
+        };
+        let field = match json_hm.get("field") {
+            Some(f) => f,
+            None => bail!("field not included in payload from front-end."),
+        };
+        let copyable_commit_fields = self.get_copyable_fields_for_sha(sha)?;
+        Ok(copyable_commit_fields.get_field(field)?.clone())
+    }
+
+    fn has_conflicts(&self) -> Result<bool> {
+        let unstaged_diff = self.get_unstaged_changes()?;
+        let staged_diff = self.get_staged_changes()?;
+
+        for delta in unstaged_diff.deltas() {
+            if delta.status() == Delta::Conflicted {
+                return Ok(true);
+            }
+        }
+
+        for delta in staged_diff.deltas() {
+            if delta.status() == Delta::Conflicted {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    pub fn has_unstaged_changes(&self) -> Result<bool> {
+        let diff = self.get_unstaged_changes()?;
+
+        if diff.stats()?.files_changed() > 0 {
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub fn has_staged_changes(&self) -> Result<bool> {
+        let diff = self.get_staged_changes()?;
+
+        if diff.stats()?.files_changed() > 0 {
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    // A merge target can either be a SHA or the full name of a ref from one of the branch badges.
+    fn get_merge_target_oid(repo: &Repository, merge_target: &str) -> Result<Oid> {
+        match repo.find_reference(merge_target) {
+            Ok(reference) => Ok(reference.peel_to_commit()?.id()),
+            Err(_) => Ok(Oid::from_str(merge_target)?),
+        }
+    }
+
+    pub fn git_merge(&self, json_str: &str) -> Result<()> {
+        let payload_value: Value = serde_json::from_str(json_str)?;
+        // Either just the target(s), or an object with them as "target" along with the merge options.
+        let (merge_target_value, merge_options_value) = match payload_value.get("target") {
+            Some(v) => (v, &payload_value),
+            None => (&payload_value, &Value::Null),
+        };
+        // Several targets (e.g. from selecting multiple commits) make an octopus merge.
+        if let Some(merge_target_values) = merge_target_value.as_array() {
+            let merge_targets = merge_target_values.iter()
+                .map(|v| GitManager::get_string_from_serde_string(v.as_str()))
+                .collect::<Result<Vec<&str>>>()?;
+            if merge_targets.len() > 1 {
+                return self.git_octopus_merge(&merge_targets, merge_options_value);
+            } else if merge_targets.is_empty() {
+                bail!("No branches to merge were given.");
+            }
+            return self.git_merge_target(merge_targets[0], merge_options_value);
+        }
+        let merge_target: &str = GitManager::get_string_from_serde_string(merge_target_value.as_str())?;
+        self.git_merge_target(merge_target, merge_options_value)
+    }
+
+    fn git_merge_target(&self, merge_target: &str, merge_options_value: &Value) -> Result<()> {
+        let repo = self.borrow_repo()?;
+        let merge_oid = GitManager::get_merge_target_oid(repo, merge_target)?;
+        let sha = merge_oid.to_string();
+        let annotated_commit = repo.find_annotated_commit(merge_oid)?;
+
+        let (merge_analysis, _) = repo.merge_analysis(&[&annotated_commit])?;
+        if merge_analysis.is_up_to_date() {
+            bail!("Already up to date.");
+        }
+
+        repo.merge(&[&annotated_commit], Some(&mut get_merge_options(merge_options_value)?), None)?;
+
+        if !self.has_conflicts()? {
+            let head_commit = match repo.head()?.target() {
+                Some(oid) => repo.find_commit(oid)?,
+                None => bail!("HEAD has no target, failed to commit after merging. It should fail earlier than this since there'd be no HEAD to merge into."),
+            };
+            let merge_parent_two = repo.find_commit(merge_oid)?;
+            let parent_commits = vec![&head_commit, &merge_parent_two];
+            let committer = repo.signature()?;
+
+            let mut short_sha = sha.clone();
+            short_sha.truncate(5);
+            let mut head_short_sha = head_commit.id().to_string();
+            head_short_sha.truncate(5);
+            let message = String::from("Merge commit ") + short_sha.as_str() + " into commit " + head_short_sha.as_str();
+
+            self.git_commit(message, &committer, &committer, parent_commits)?;
+
+            repo.cleanup_state()?;
+        }
+
+        Ok(())
+    }
+
+    // libgit2 can only merge one branch at a time, so this merges each one into the tree so far in memory (like git's octopus strategy),
+    // and only touches the working directory once they've all merged cleanly.
+    fn git_octopus_merge(&self, merge_targets: &[&str], merge_options_value: &Value) -> Result<()> {
+        let repo = self.borrow_repo()?;
+        if self.has_staged_changes()? {
+            bail!("Commit or stash your staged changes before merging several branches at once.");
+        }
+        let head_commit = repo.head()?.peel_to_commit()?;
+        // HEAD and every branch merged in so far, which the next branch's merge base is found against.
+        let mut merged_oids = vec![head_commit.id()];
+        let mut merged_tree = head_commit.tree()?;
+        let mut merge_commits = vec![];
+        for merge_target in merge_targets {
+            let merge_oid = GitManager::get_merge_target_oid(repo, merge_target)?;
+            let mut merge_base_oids = vec![merge_oid];
+            merge_base_oids.extend(&merged_oids);
+            let merge_base_oid = match repo.merge_base_many(&merge_base_oids) {
+                Ok(oid) => oid,
+                Err(e) if e.code() == ErrorCode::NotFound => bail!("{} doesn't share any history with the other branches, so it can't be merged with them.", merge_target),
+                Err(e) => return Err(e.into()),
+            };
+            // Already merged, so it doesn't need to be a parent.
+            if merge_base_oid == merge_oid || merged_oids.contains(&merge_oid) {
+                continue;
+            }
+            let merge_commit = repo.find_commit(merge_oid)?;
+            let merge_options = get_merge_options(merge_options_value)?;
+            let mut merged_index = repo.merge_trees(&repo.find_commit(merge_base_oid)?.tree()?, &merged_tree, &merge_commit.tree()?, Some(&merge_options))?;
+            if merged_index.has_conflicts() {
+                let mut conflicted_paths = vec![];
+                for conflict_result in merged_index.conflicts()? {
+                    let conflict = conflict_result?;
+                    if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+                        conflicted_paths.push(String::from_utf8_lossy(&entry.path).into_owned());
+                    }
+                }
+                bail!("Merging {} would conflict in {}, and merging several branches at once only works when they all merge cleanly. Merge them one at a time instead.", merge_target, conflicted_paths.join(", "));
+            }
+            merged_tree = repo.find_tree(merged_index.write_tree_to(repo)?)?;
+            merged_oids.push(merge_oid);
+            merge_commits.push(merge_commit);
+        }
+        if merge_commits.is_empty() {
+            bail!("Everything selected is already merged.");
+        }
+
+        // A safe checkout leaves local changes alone, and fails instead of overwriting them.
+        let mut checkout_builder = CheckoutBuilder::new();
+        checkout_builder.safe();
+        repo.checkout_tree(merged_tree.as_object(), Some(&mut checkout_builder))?;
+        let mut index = repo.index()?;
+        index.read_tree(&merged_tree)?;
+        index.write()?;
+
+        let mut short_shas: Vec<String> = merge_commits.iter().map(|c| c.id().to_string()).collect();
+        short_shas.iter_mut().for_each(|s| s.truncate(5));
+        let mut head_short_sha = head_commit.id().to_string();
+        head_short_sha.truncate(5);
+        let message = format!("Merge commits {} into commit {}", short_shas.join(", "), head_short_sha);
+
+        let mut parent_commits = vec![&head_commit];
+        parent_commits.extend(merge_commits.iter());
+        let committer = repo.signature()?;
+        self.git_commit(message, &committer, &committer, parent_commits)?;
+        Ok(())
+    }
+
+    fn iterate_through_rebase(&self, repo: &Repository, rebase: &mut Rebase) -> Result<()> {
+        // Unfortunately, using 'rebase' like an iterator doesn't allow us to commit since
+        // rebase has to be borrowed mutably to commit.
+        let mut reached_end = false;
+        while !reached_end {
+            let step = rebase.next();
+            match step {
+                Some(r) => {
+                    r?;
+
+                    // If there are conflicts, need to let the user fix them before resuming
+                    // inside "git_continue_rebase"
+                    if self.has_conflicts()? || self.has_unstaged_changes()? {
+                        return Ok(());
+                    } else if self.has_staged_changes()? {
+                        rebase.commit(None, &repo.signature()?, None)?;
+                    }
+                },
+                None => reached_end = true,
+            };
+        }
+        rebase.finish(None)?;
+
+        Ok(())
+    }
+
+    pub fn git_rebase(&self, json_str: &str) -> Result<()> {
+        let payload_value: Value = serde_json::from_str(json_str)?;
+        // Either just the sha, or an object with it as "target" along with the merge options.
+        let (sha_value, merge_options_value) = match payload_value.get("target") {
+            Some(v) => (v, &payload_value),
+            None => (&payload_value, &Value::Null),
+        };
+        let sha: &str = GitManager::get_string_from_serde_string(sha_value.as_str())?;
+        let repo = self.borrow_repo()?;
+        let annotated_commit = repo.find_annotated_commit(Oid::from_str(sha)?)?;
+        self.create_safety_snapshot("rebase", None);
+        // libgit2 doesn't save these with the rebase, so commits applied after continuing from a conflict use the defaults.
+        let mut rebase_options = RebaseOptions::new();
+        rebase_options.merge_options(get_merge_options(merge_options_value)?);
+        let mut rebase = repo.rebase(None, None, Some(&annotated_commit), Some(&mut rebase_options))?;
+
+        self.iterate_through_rebase(repo, &mut rebase)?;
+
+        Ok(())
+    }
+
+    pub fn git_cherrypick(&self, json_str: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+
+        let sha = match json_hm.get("sha") {
+            Some(s) => s,
+            None => bail!("sha wasn't included in payload from the front-end."),
+        };
+        let is_committing = match json_hm.get("isCommitting") {
+            Some(s) => s == "true",
+            None => bail!("isCommitting wasn't included in payload from the front-end."),
+        };
+
+        self.git_cherrypick_oid(Oid::from_str(sha)?, is_committing)
+    }
+
+    fn git_cherrypick_oid(&self, oid: Oid, is_committing: bool) -> Result<()> {
+        let repo = self.borrow_repo()?;
+        let commit = repo.find_commit(oid)?;
+
+        repo.cherrypick(&commit, None)?;
+
+        if !self.has_conflicts()? {
+            repo.cleanup_state()?;
+        }
+
+        if is_committing && !self.has_conflicts()? && self.has_staged_changes()? {
+            let committer = repo.signature()?;
+            let head_commit = match repo.head()?.target() {
+                Some(oid) => repo.find_commit(oid)?,
+                None => bail!("HEAD has no target, failed to commit after cherrypick."),
+            };
+            self.git_commit(String::from(GitManager::get_utf8_string(commit.message(), "Commit Message")?), &commit.author(), &committer, vec![&head_commit])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn git_revert(&self, json_str: &str) -> Result<Option<RevertMainlineInfo>> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+
+        let sha = match json_hm.get("sha") {
+            Some(s) => s,
+            None => bail!("sha wasn't included in payload from the front-end."),
+        };
+        let is_committing = match json_hm.get("isCommitting") {
+            Some(s) => s == "true",
+            None => bail!("isCommitting wasn't included in payload from the front-end."),
+        };
+        // The mainline is only sent once the user has picked which parent of a merge commit to keep.
+        let mainline_opt = match json_hm.get("mainline") {
+            Some(s) => Some(s.parse::<u32>()?),
+            None => None,
+        };
+
+        let repo = self.borrow_repo()?;
+        let commit = repo.find_commit(Oid::from_str(sha)?)?;
+
+        // Reverting a merge commit requires knowing which parent is the mainline, so send the parents
+        // back to the front-end to choose from instead of reverting.
+        if commit.parent_count() > 1 && mainline_opt.is_none() {
+            return Ok(Some(RevertMainlineInfo::from_commit(&commit, is_committing)?));
+        }
+
+        self.git_revert_oid(commit.id(), is_committing, mainline_opt)?;
+        Ok(None)
+    }
+
+    fn git_revert_oid(&self, oid: Oid, is_committing: bool, mainline_opt: Option<u32>) -> Result<()> {
+        let repo = self.borrow_repo()?;
+        let commit = repo.find_commit(oid)?;
+
+        let mut revert_options = RevertOptions::new();
+        if let Some(mainline) = mainline_opt {
+            if mainline < 1 || mainline as usize > commit.parent_count() {
+                bail!("mainline must be between 1 and the number of parents of the commit being reverted.");
+            }
+            revert_options.mainline(mainline);
+        }
+
+        repo.revert(&commit, Some(&mut revert_options))?;
+
+        if !self.has_conflicts()? {
+            repo.cleanup_state()?;
+        }
+
+        if is_committing && !self.has_conflicts()? && self.has_staged_changes()? {
+            let committer = repo.signature()?;
+            let head_commit = match repo.head()?.target() {
+                Some(oid) => repo.find_commit(oid)?,
+                None => bail!("HEAD has no target, failed to commit after revert."),
+            };
+
+            let mut new_full_message = String::from("Revert \"") + GitManager::get_utf8_string(commit.summary(), "Commit Summary")? + "\"";
+            let (message_without_summary, uses_crlf) = GitManager::get_message_without_summary(GitManager::get_utf8_string(commit.message(), "Commit Message")?);
+            if uses_crlf {
+                new_full_message += "\r\n\r\n";
+            } else {
+                new_full_message += "\n\n";
+            }
+            new_full_message += message_without_summary.as_str();
+
+            self.git_commit(new_full_message, &commit.author(), &committer, vec![&head_commit])?;
+        }
+
+        Ok(())
+    }
+
+    fn get_shas_from_json(json_str: &str) -> Result<Vec<String>> {
+        let json_value: Value = serde_json::from_str(json_str)?;
+        let shas: Vec<String> = match json_value.get("shas") {
+            Some(v) => serde_json::from_value(v.clone())?,
+            None => bail!("shas not included in payload from front-end."),
+        };
+        if shas.len() == 0 {
+            bail!("No commits were selected.");
+        }
+        Ok(shas)
+    }
+
+    // Gets the selected commits in the order they were made, oldest first.
+    fn get_sorted_commit_oids(&self, shas: &Vec<String>) -> Result<Vec<Oid>> {
+        let repo = self.borrow_repo()?;
+        let mut selected_oids: HashSet<Oid> = HashSet::new();
+        for sha in shas {
+            selected_oids.insert(repo.find_commit(Oid::from_str(sha)?)?.id());
+        }
+
+        // Children always come before their parents in a topological walk, so it can stop as soon as every selected commit is found.
+        let mut revwalk = repo.revwalk()?;
+        for oid in &selected_oids {
+            revwalk.push(*oid)?;
+        }
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        let mut sorted_oids: Vec<Oid> = vec![];
+        for oid_result in revwalk {
+            let oid = oid_result?;
+            if selected_oids.contains(&oid) {
+                sorted_oids.push(oid);
+                if sorted_oids.len() == selected_oids.len() {
+                    break;
+                }
+            }
+        }
+        sorted_oids.reverse();
+        Ok(sorted_oids)
+    }
+
+    fn is_in_head(&self, oid: Oid) -> Result<bool> {
+        let repo = self.borrow_repo()?;
+        let head_oid = match repo.head()?.target() {
+            Some(o) => o,
+            None => bail!("HEAD has no target commit."),
+        };
+        Ok(head_oid == oid || repo.graph_descendant_of(head_oid, oid)?)
+    }
+
+    fn bail_if_merge_commit(&self, oid: Oid, operation_name: &str) -> Result<()> {
+        if self.borrow_repo()?.find_commit(oid)?.parent_count() > 1 {
+            bail!("{} is a merge commit, so it can't be {}.", oid, operation_name);
+        }
+        Ok(())
+    }
+
+    // Runs the operation on each commit in order, stopping at the first one that conflicts or fails so the rest can be done after it's resolved.
+    fn apply_each_commit(&self, oids: Vec<Oid>, apply: &dyn Fn(Oid) -> Result<()>) -> Result<Vec<CommitOperationResult>> {
+        let repo = self.borrow_repo()?;
+        let mut results: Vec<CommitOperationResult> = vec![];
+        let mut is_stopped = false;
+        for oid in oids {
+            if is_stopped {
+                results.push(CommitOperationResult::new(oid.to_string(), "skipped", String::from("Skipped because an earlier commit didn't apply cleanly.")));
+                continue;
+            }
+            let old_head_oid = repo.head()?.target();
+            if let Err(e) = apply(oid) {
+                results.push(CommitOperationResult::new(oid.to_string(), "failed", e.to_string()));
+                is_stopped = true;
+            } else if self.has_conflicts()? {
+                results.push(CommitOperationResult::new(oid.to_string(), "conflicted", String::from("Resolve the conflicts and continue, then do the remaining commits.")));
+                is_stopped = true;
+            } else if repo.head()?.target() == old_head_oid {
+                results.push(CommitOperationResult::new(oid.to_string(), "empty", String::from("There were no changes left to commit.")));
+            } else {
+                results.push(CommitOperationResult::new(oid.to_string(), "applied", String::new()));
+            }
+        }
+        Ok(results)
+    }
+
+    // Cherry-picks each selected commit onto HEAD in the order they were made, committing each one.
+    pub fn git_cherrypick_multiple(&self, json_str: &str) -> Result<Vec<CommitOperationResult>> {
+        let shas = GitManager::get_shas_from_json(json_str)?;
+        let oids = self.get_sorted_commit_oids(&shas)?;
+        for oid in &oids {
+            self.bail_if_merge_commit(*oid, "cherry-picked along with other commits")?;
+            if self.is_in_head(*oid)? {
+                bail!("{} is already on the current branch, so it can't be cherry-picked onto it.", oid);
+            }
+        }
+        self.apply_each_commit(oids, &|oid| self.git_cherrypick_oid(oid, true))
+    }
+
+    // Reverts each selected commit newest first, committing each one.
+    pub fn git_revert_multiple(&self, json_str: &str) -> Result<Vec<CommitOperationResult>> {
+        let shas = GitManager::get_shas_from_json(json_str)?;
+        let mut oids = self.get_sorted_commit_oids(&shas)?;
+        for oid in &oids {
+            self.bail_if_merge_commit(*oid, "reverted along with other commits")?;
+            if !self.is_in_head(*oid)? {
+                bail!("{} isn't on the current branch, so it can't be reverted.", oid);
+            }
+        }
+        oids.reverse();
+        self.apply_each_commit(oids, &|oid| self.git_revert_oid(oid, true, None))
+    }
+
+    // Writes a numbered patch file for each selected commit into a directory, like git format-patch.
+    pub fn export_patches(&self, json_str: &str) -> Result<Vec<CommitOperationResult>> {
+        let json_value: Value = serde_json::from_str(json_str)?;
+        let path = match json_value.get("path").and_then(|p| p.as_str()) {
+            Some(p) => PathBuf::from(p),
+            None => bail!("path not included in payload from front-end."),
+        };
+        let shas = GitManager::get_shas_from_json(json_str)?;
+        let oids = self.get_sorted_commit_oids(&shas)?;
+
+        // A patch series only applies cleanly if each patch is built on top of the one before it.
+        let repo = self.borrow_repo()?;
+        for i in 0..oids.len() {
+            let commit = repo.find_commit(oids[i])?;
+            self.bail_if_merge_commit(oids[i], "exported as a patch")?;
+            if i > 0 && commit.parent_id(0)? != oids[i - 1] {
+                bail!("The selected commits have to be a contiguous range to export them as patches, but {} isn't built on {}.", oids[i], oids[i - 1]);
+            }
+        }
+
+        create_dir_all(&path)?;
+        let mut results: Vec<CommitOperationResult> = vec![];
+        for (i, oid) in oids.iter().enumerate() {
+            let commit = repo.find_commit(*oid)?;
+            let summary = GitManager::get_utf8_string(commit.summary(), "Commit Summary")?;
+            let (body, _) = GitManager::get_message_without_summary(GitManager::get_utf8_string(commit.message(), "Commit Message")?);
+            let diff = get_commit_changes(&commit, repo, false, DEFAULT_CONTEXT_LINES)?;
+            let email = Email::from_diff(&diff, i + 1, oids.len(), oid, summary, body.as_str(), &commit.author(), &mut EmailCreateOptions::new())?;
+
+            let patch_path = path.join(get_patch_file_name(i + 1, summary));
+            fs::write(&patch_path, email.as_slice())?;
+            results.push(CommitOperationResult::new(oid.to_string(), "exported", patch_path.to_string_lossy().to_string()));
+        }
+        Ok(results)
+    }
+
+    // Gets the selected SHAs newest first, one per line, for copying to the clipboard.
+    pub fn get_shas_text(&self, json_str: &str) -> Result<String> {
+        let shas = GitManager::get_shas_from_json(json_str)?;
+        let mut oids = self.get_sorted_commit_oids(&shas)?;
+        oids.reverse();
+        let sha_lines: Vec<String> = oids.iter().map(|oid| oid.to_string()).collect();
+        Ok(sha_lines.join("\n"))
+    }
+
+    pub fn git_abort(&self) -> Result<()> {
+        let repo = self.borrow_repo()?;
+
+        let head_commit = match repo.head()?.target() {
+            Some(oid) => repo.find_commit(oid)?,
+            None => bail!("HEAD doesn't have a target commit, cannot abort to HEAD"),
+        };
+
+        self.create_safety_snapshot("aborting", None);
+        repo.reset(head_commit.as_object(), ResetType::Hard, None)?;
+
+        repo.cleanup_state()?;
+
+        Ok(())
+    }
+
+    pub fn git_continue_cherrypick(&self) -> Result<()> {
+        if !self.has_conflicts()? {
+            let repo = self.borrow_repo()?;
+
+            let head_commit = match repo.head()?.target() {
+                Some(oid) => repo.find_commit(oid)?,
+                None => bail!("HEAD doesn't have a target commit (which is where a cherrypick operation starts on), can't complete cherrypick."),
+            };
+
+            let mut cherrypick_head_file = repo.path().to_path_buf();
+            cherrypick_head_file.push("CHERRY_PICK_HEAD");
+            let cherrypick_head_string = fs::read_to_string(cherrypick_head_file)?;
+            let sha = cherrypick_head_string.trim();
+
+            let commit_from_op = repo.find_commit(Oid::from_str(sha)?)?;
+
+            let committer = repo.signature()?;
+
+            self.git_commit(String::from(GitManager::get_utf8_string(commit_from_op.message(), "Commit Message")?), &commit_from_op.author(), &committer, vec![&head_commit])?;
+
+            repo.cleanup_state()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn git_continue_revert(&self) -> Result<()> {
+        if !self.has_conflicts()? {
+            let repo = self.borrow_repo()?;
+
+            let head_commit = match repo.head()?.target() {
+                Some(oid) => repo.find_commit(oid)?,
+                None => bail!("HEAD doesn't have a target commit (which is where a revert operation starts on), can't complete revert."),
+            };
+
+            let mut revert_file = repo.path().to_path_buf();
+            revert_file.push("REVERT_HEAD");
+            let revert_head_string = fs::read_to_string(revert_file)?;
+            let sha = revert_head_string.trim();
+
+            let commit_from_op = repo.find_commit(Oid::from_str(sha)?)?;
+
+            let committer = repo.signature()?;
+
+            let mut new_full_message = String::from("Revert \"") + GitManager::get_utf8_string(commit_from_op.summary(), "Commit Summary")? + "\"";
+            let (message_without_summary, uses_crlf) = GitManager::get_message_without_summary(GitManager::get_utf8_string(commit_from_op.message(), "Commit Message")?);
+            if uses_crlf {
+                new_full_message += "\r\n\r\n";
+            } else {
+                new_full_message += "\n\n";
+            }
+            new_full_message += message_without_summary.as_str();
+
+            self.git_commit(new_full_message, &commit_from_op.author(), &committer, vec![&head_commit])?;
+
+            repo.cleanup_state()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn git_continue_merge(&self) -> Result<()> {
+        if !self.has_conflicts()? {
+            let repo = self.borrow_repo()?;
+
+            let head_commit = match repo.head()?.target() {
+                Some(oid) => repo.find_commit(oid)?,
+                None => bail!("HEAD doesn't have a target commit (which is where a merge operation starts on), can't complete merge."),
+            };
+
+            let mut merge_file = repo.path().to_path_buf();
+            merge_file.push("MERGE_HEAD");
+            let merge_head_string = fs::read_to_string(merge_file)?;
+            let sha = merge_head_string.trim();
+
+            let commit_from_op = repo.find_commit(Oid::from_str(sha)?)?;
+
+            let mut short_sha = String::from(sha);
+            short_sha.truncate(5);
+
+            let mut head_short_sha = head_commit.id().to_string();
+            head_short_sha.truncate(5);
+            let message = String::from("Merge commit ") + short_sha.as_str() + " into commit " + head_short_sha.as_str();
+
+            let committer = repo.signature()?;
+            self.git_commit(message, &committer, &committer, vec![&head_commit, &commit_from_op])?;
+
+            repo.cleanup_state()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn git_abort_rebase(&self) -> Result<()> {
+        let repo = self.borrow_repo()?;
+        let mut rebase = repo.open_rebase(None)?;
+
+        self.create_safety_snapshot("aborting a rebase", None);
+        rebase.abort()?;
+        Ok(())
+    }
+
+    pub fn git_continue_rebase(&self) -> Result<()> {
+        let repo = self.borrow_repo()?;
+        let mut rebase = repo.open_rebase(None)?;
+
+        // Need to make sure there are no conflicts and we've committed before continuing.
+        if self.has_conflicts()? || self.has_unstaged_changes()? {
+            return Ok(());
+        } else if self.has_staged_changes()? {
+            rebase.commit(None, &repo.signature()?, None)?;
+        }
+
+        self.iterate_through_rebase(repo, &mut rebase)?;
+
+        Ok(())
+    }
+
+    pub fn git_reset(&self, json_str: &str) -> Result<()> {
+        let repo = self.borrow_repo()?;
+
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+
+        let sha = match json_hm.get("sha") {
+            Some(s) => s,
+            None => bail!("sha wasn't included in payload from the front-end."),
+        };
+        let reset_type_string = match json_hm.get("type") {
+            Some(s) => s,
+            None => bail!("type wasn't included in payload from the front-end."),
+        };
+
+        let reset_type;
+        if reset_type_string == "soft" {
+            reset_type = ResetType::Soft;
+        } else if reset_type_string == "mixed" {
+            reset_type = ResetType::Mixed;
+        } else if reset_type_string == "hard" {
+            reset_type = ResetType::Hard;
+        } else {
+            bail!("type from front-end payload isn't a valid option. Choices are 'soft', 'mixed', or 'hard'");
+        }
+
+        let commit = repo.find_commit(Oid::from_str(sha)?)?;
+
+        if reset_type == ResetType::Hard {
+            self.create_safety_snapshot("hard reset", None);
+        }
+        repo.reset(commit.as_object(), reset_type, None)?;
+
+        Ok(())
+    }
+
+    // The snapshot is only a safety net, so failing to make one is logged instead of stopping the operation itself.
+    // paths limits the snapshot to the files the operation touches, otherwise it covers every tracked file.
+    fn create_safety_snapshot(&self, operation: &str, paths: Option<&[&Path]>) {
+        if let Err(e) = self.try_create_safety_snapshot(operation, paths) {
+            println!("Failed to create a snapshot before {}: {}", operation, e);
+        }
+    }
+
+    // Saves the working tree and index the same way a stash does (HEAD and a commit of the index as the parents), but
+    // under refs/ogf/backups so it doesn't show up in the stash list and nothing in the working tree gets touched.
+    fn try_create_safety_snapshot(&self, operation: &str, paths: Option<&[&Path]>) -> Result<()> {
+        let snapshot_limit = config_manager::get_config()?.borrow_snapshot_limit().unwrap_or(config_manager::DEFAULT_SNAPSHOT_LIMIT);
+        if snapshot_limit == 0 {
+            return Ok(());
+        }
+
+        let repo = self.borrow_repo()?;
+        if repo.is_bare() {
+            return Ok(());
+        }
+        let head_commit = match repo.head() {
+            Ok(r) => r.peel_to_commit()?,
+            // Nothing has been committed yet, so there's nothing a reset or rebase could lose.
+            Err(e) if e.code() == ErrorCode::UnbornBranch => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut index = repo.index()?;
+        // A conflicted index can't be written as a tree, so HEAD's tree stands in for the index in that case.
+        let index_tree_oid = if index.has_conflicts() {
+            head_commit.tree_id()
+        } else {
+            index.write_tree()?
+        };
+        // Stage the changes in memory to get a tree of the working directory, then reload the index so nothing stays staged.
+        // Untracked files are only picked up under the given paths, since walking the whole working directory for them
+        // is slow and only operations on specific paths (like discarding an untracked file) delete them.
+        let stage_result = match paths {
+            Some(p) => index.add_all(p.iter(), IndexAddOption::DISABLE_PATHSPEC_MATCH, None).and_then(|_| index.update_all(p.iter(), None)),
+            None => index.update_all(["*"].iter(), None),
+        };
+        let workdir_tree_result = stage_result.and_then(|_| index.write_tree());
+        index.read(true)?;
+        let workdir_tree_oid = workdir_tree_result?;
+
+        // Don't let a missing user.name or user.email get in the way of the snapshot.
+        let signature = match repo.signature() {
+            Ok(s) => s,
+            Err(_) => Signature::now("Oxidized Git", "oxidized-git@localhost")?,
+        };
+        let mut head_short_sha = head_commit.id().to_string();
+        head_short_sha.truncate(7);
+        let index_commit_oid = repo.commit(None, &signature, &signature, format!("index on {}", head_short_sha).as_str(), &repo.find_tree(index_tree_oid)?, &[&head_commit])?;
+        let index_commit = repo.find_commit(index_commit_oid)?;
+        let snapshot_oid = repo.commit(None, &signature, &signature, format!("Snapshot before {} on {}", operation, head_short_sha).as_str(), &repo.find_tree(workdir_tree_oid)?, &[&head_commit, &index_commit])?;
+        repo.reference(format!("refs/ogf/backups/{}", snapshot_oid).as_str(), snapshot_oid, true, "snapshot: create")?;
+
+        // Prune the oldest snapshots past the limit.
+        let snapshots = self.get_snapshots()?;
+        for snapshot in snapshots.iter().skip(snapshot_limit) {
+            repo.find_reference(snapshot.ref_name.as_str())?.delete()?;
+        }
+        Ok(())
+    }
+
+    // Newest first.
+    pub fn get_snapshots(&self) -> Result<Vec<SnapshotInfo>> {
+        let repo = self.borrow_repo()?;
+        let mut snapshots: Vec<SnapshotInfo> = vec![];
+        for reference_result in repo.references_glob("refs/ogf/backups/*")? {
+            let reference = reference_result?;
+            let commit = reference.peel_to_commit()?;
+            snapshots.push(SnapshotInfo {
+                ref_name: String::from(GitManager::get_utf8_string(reference.name(), "Snapshot Ref Name")?),
+                sha: commit.id().to_string(),
+                message: String::from(GitManager::get_utf8_string(commit.message(), "Snapshot Message")?),
+                head_sha: commit.parent_id(0)?.to_string(),
+                time: commit.time().seconds(),
+            });
+        }
+        snapshots.sort_by(|a, b| b.time.cmp(&a.time));
+        Ok(snapshots)
+    }
+
+    // Moves the current branch back to where HEAD was when the snapshot was taken and puts back its working tree and index.
+    // The current state is snapshotted first so restoring the wrong snapshot can be undone too.
+    pub fn restore_snapshot(&self, json_str: &str) -> Result<()> {
+        let sha_value: Value = serde_json::from_str(json_str)?;
+        let sha: &str = GitManager::get_string_from_serde_string(sha_value.as_str())?;
+        let repo = self.borrow_repo()?;
+        let snapshot_commit = match repo.find_reference(format!("refs/ogf/backups/{}", sha).as_str()) {
+            Ok(r) => r.peel_to_commit()?,
+            Err(e) if e.code() == ErrorCode::NotFound => bail!("There's no snapshot with SHA {}.", sha),
+            Err(e) => return Err(e.into()),
+        };
+        let head_commit = snapshot_commit.parent(0)?;
+        let index_tree = snapshot_commit.parent(1)?.tree()?;
+
+        self.create_safety_snapshot("restoring a snapshot", None);
+
+        repo.reset(head_commit.as_object(), ResetType::Hard, None)?;
+        let mut checkout_builder = CheckoutBuilder::new();
+        checkout_builder.force();
+        repo.checkout_tree(snapshot_commit.as_object(), Some(&mut checkout_builder))?;
+
+        let mut index = repo.index()?;
+        index.read_tree(&index_tree)?;
+        index.write()?;
+        Ok(())
+    }
+
+    pub fn git_add_remote(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let repo = self.borrow_repo()?;
+
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let remote_name = match json_hm.get("remote_name") {
+            Some(s) => s,
+            None => bail!("remote_name not included in payload from the front-end"),
+        };
+        let remote_url = match json_hm.get("remote_url") {
+            Some(s) => s,
+            None => bail!("remote_url not included in payload from the front-end"),
+        };
+
+        repo.remote(remote_name.as_str(), remote_url.as_str())?;
+        // Optional, for forks and mirrors that push somewhere other than where they fetch from.
+        if let Some(push_url) = json_hm.get("push_url") {
+            if push_url != "" {
+                repo.remote_set_pushurl(remote_name.as_str(), Some(push_url.as_str()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Sets remote.<name>.url and remote.<name>.pushurl separately. An empty push URL unsets it so pushes go to the fetch URL again.
+    pub fn set_remote_urls(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let repo = self.borrow_repo()?;
+
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let remote_name = match json_hm.get("remote_name") {
+            Some(s) => s,
+            None => bail!("remote_name not included in payload from front-end."),
+        };
+        let url = match json_hm.get("url") {
+            Some(s) => s,
+            None => bail!("url not included in payload from front-end."),
+        };
+        let push_url = match json_hm.get("push_url") {
+            Some(s) => s,
+            None => bail!("push_url not included in payload from front-end."),
+        };
+        if url == "" {
+            bail!("A remote needs a fetch URL.");
+        }
+
+        // Make sure the remote actually exists, since setting the URLs would create it otherwise.
+        repo.find_remote(remote_name)?;
+        repo.remote_set_url(remote_name, url)?;
+        if push_url == "" {
+            match repo.config()?.remove(&format!("remote.{}.pushurl", remote_name)) {
+                Ok(()) => (),
+                Err(e) if e.code() == ErrorCode::NotFound => (),
+                Err(e) => return Err(e.into()),
+            };
+        } else {
+            repo.remote_set_pushurl(remote_name, Some(push_url.as_str()))?;
+        }
+        Ok(())
+    }
+
+    fn get_refspec_payload(json_str: &str) -> Result<(String, String, String)> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let remote_name = match json_hm.get("remote_name") {
+            Some(s) => s,
+            None => bail!("remote_name not included in payload from front-end."),
+        };
+        let direction = match json_hm.get("direction") {
+            Some(s) if s == "fetch" || s == "push" => s,
+            Some(s) => bail!("Unknown refspec direction: {}", s),
+            None => bail!("direction not included in payload from front-end."),
+        };
+        let refspec = match json_hm.get("refspec") {
+            Some(s) => s.trim(),
+            None => bail!("refspec not included in payload from front-end."),
+        };
+        if refspec == "" {
+            bail!("Refspec can't be empty.");
+        }
+        Ok((remote_name.clone(), direction.clone(), String::from(refspec)))
+    }
+
+    // Adds to remote.<name>.fetch or remote.<name>.push, like +refs/merge-requests/*/head:refs/remotes/origin/mr/*.
+    pub fn add_remote_refspec(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let (remote_name, direction, refspec) = GitManager::get_refspec_payload(json_str)?;
+        let repo = self.borrow_repo()?;
+        let remote = repo.find_remote(&remote_name)?;
+        let existing_refspecs = if direction == "fetch" { remote.fetch_refspecs()? } else { remote.push_refspecs()? };
+        if existing_refspecs.iter().any(|r| r == Some(refspec.as_str())) {
+            bail!("{} already has the {} refspec {}", remote_name, direction, refspec);
+        }
+
+        // These parse the refspec first, so invalid ones never make it into the config.
+        if direction == "fetch" {
+            repo.remote_add_fetch(&remote_name, &refspec)?;
+        } else {
+            repo.remote_add_push(&remote_name, &refspec)?;
+        }
+        Ok(())
+    }
+
+    pub fn remove_remote_refspec(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let (remote_name, direction, refspec) = GitManager::get_refspec_payload(json_str)?;
+        let repo = self.borrow_repo()?;
+        repo.find_remote(&remote_name)?;
+
+        // There's no libgit2 call for removing a refspec, so remove the matching value from the multivar directly.
+        let mut config = repo.config()?;
+        config.remove_multivar(&format!("remote.{}.{}", remote_name, direction), &format!("^{}$", regex::escape(&refspec)))?;
+        Ok(())
+    }
+
+    // Applies unified diff text (e.g. copied from a code review) to the working directory or the index. Every hunk is checked on its own first
+    // so the front-end can show which ones don't apply, and nothing is applied unless all of them do (or if it's a dry run).
+    pub fn apply_patch_text(&self, json_str: &str) -> Result<Vec<PatchHunkCheck>> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let content = match json_hm.get("content") {
+            Some(s) => s,
+            None => bail!("content not included in payload from front-end."),
+        };
+        let apply_location = match json_hm.get("to_index") {
+            Some(s) if s == "true" => ApplyLocation::Index,
+            Some(_) => ApplyLocation::WorkDir,
+            None => bail!("to_index not included in payload from front-end."),
+        };
+        let is_dry_run = match json_hm.get("dry_run") {
+            Some(s) => s == "true",
+            None => false,
+        };
+        if !is_dry_run {
+            self.ensure_writable()?;
+        }
+
+        let repo = self.borrow_repo()?;
+        let diff = match Diff::from_buffer(content.as_bytes()) {
+            Ok(d) => d,
+            Err(e) => bail!("Couldn't read the patch, make sure it's a unified diff: {}", e.message()),
+        };
+        if diff.deltas().len() == 0 {
+            bail!("The patch doesn't change any files.");
+        }
+
+        let mut patch_hunk_checks: Vec<PatchHunkCheck> = vec![];
+        for delta_index in 0..diff.deltas().len() {
+            let patch = match Patch::from_diff(&diff, delta_index)? {
+                Some(p) => p,
+                None => continue,
+            };
+            let file_path_opt = patch.delta().new_file().path().or(patch.delta().old_file().path());
+            let file_path = String::from(GitManager::get_utf8_string(file_path_opt.and_then(|p| p.to_str()), "File Path")?);
+            for hunk_index in 0..patch.num_hunks() {
+                let (hunk, _) = patch.hunk(hunk_index)?;
+                let hunk_header = String::from_utf8_lossy(hunk.header()).trim_end().to_string();
+
+                // Only let this one hunk through so each is checked separately.
+                let current_delta_index = Cell::new(0);
+                let current_hunk_index = Cell::new(0);
+                let mut apply_options = ApplyOptions::new();
+                apply_options.check(true);
+                apply_options.delta_callback(|_delta| {
+                    let is_this_delta = current_delta_index.get() == delta_index;
+                    current_delta_index.set(current_delta_index.get() + 1);
+                    is_this_delta
+                });
+                apply_options.hunk_callback(|_hunk| {
+                    let is_this_hunk = current_hunk_index.get() == hunk_index;
+                    current_hunk_index.set(current_hunk_index.get() + 1);
+                    is_this_hunk
+                });
+                let (applies, message) = match repo.apply(&diff, apply_location, Some(&mut apply_options)) {
+                    Ok(()) => (true, String::new()),
+                    Err(e) => (false, String::from(e.message())),
+                };
+                patch_hunk_checks.push(PatchHunkCheck {
+                    file_path: file_path.clone(),
+                    hunk_header,
+                    applies,
+                    message,
+                });
+            }
+        }
+
+        if !is_dry_run {
+            let failed_hunk_count = patch_hunk_checks.iter().filter(|c| !c.applies).count();
+            if failed_hunk_count > 0 {
+                bail!("{} hunk(s) of the patch don't apply, so nothing was changed. Try a dry run to see which ones.", failed_hunk_count);
+            }
+            repo.apply(&diff, apply_location, None)?;
+        }
+        Ok(patch_hunk_checks)
+    }
+
+    pub fn git_add_submodule(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let submodule_url = match json_hm.get("url") {
+            Some(s) => s,
+            None => bail!("url not included in payload from front-end."),
+        };
+        let submodule_path = match json_hm.get("path") {
+            Some(s) => s,
+            None => bail!("path not included in payload from front-end."),
+        };
+
+        let repo = self.borrow_repo()?;
+        // This writes .gitmodules and the config, then the clone fills in the submodule's repo.
+        let mut submodule = repo.submodule(submodule_url, Path::new(submodule_path), true)?;
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(GitManager::get_remote_callbacks());
+        let mut update_options = SubmoduleUpdateOptions::new();
+        update_options.fetch(fetch_options);
+        submodule.clone(Some(&mut update_options))?;
+        // Stages .gitmodules and the submodule's commit.
+        submodule.add_finalize()?;
+        Ok(())
+    }
+
+    // Does everything `git rm <submodule>` plus cleaning up .git/modules would: .gitmodules, config, index, and both directories.
+    pub fn git_remove_submodule(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let submodule_path = match json_hm.get("path") {
+            Some(s) => s.as_str(),
+            None => bail!("path not included in payload from front-end."),
+        };
+        let is_force = match json_hm.get("force") {
+            Some(s) => s == "true",
+            None => false,
+        };
+
+        let repo = self.borrow_repo()?;
+        let workdir = match repo.workdir() {
+            Some(p) => p,
+            None => bail!("Can't remove a submodule from a bare repository."),
+        };
+        let submodule_name = {
+            let submodule = repo.find_submodule(submodule_path)?;
+            let submodule_name = String::from(GitManager::get_utf8_string(submodule.name(), "Submodule Name")?);
+            if !is_force {
+                let local_work = GitManager::get_submodule_local_work(repo, &submodule, &submodule_name)?;
+                if !local_work.is_empty() {
+                    return Err(StructuredError::new(
+                        "submodule_has_local_work",
+                        format!("Removing {} would lose work since it has {}.", submodule_path, local_work.join(", ")),
+                        Some(json!({ "path": submodule_path, "local_work": local_work })),
+                    ).into());
+                }
+            }
+            submodule_name
+        };
+        let section_prefix = format!("submodule.{}.", submodule_name);
+
+        let gitmodules_path = workdir.join(".gitmodules");
+        if gitmodules_path.exists() {
+            GitManager::remove_config_entries(&mut git2::Config::open(&gitmodules_path)?, &section_prefix)?;
+            // libgit2 leaves the empty section header behind, which would otherwise end up committed.
+            let section_header = format!("[submodule \"{}\"]", submodule_name);
+            let gitmodules_string = fs::read_to_string(&gitmodules_path)?;
+            let gitmodules_lines: Vec<&str> = gitmodules_string.lines().filter(|line| line.trim() != section_header).collect();
+            let mut new_gitmodules_string = gitmodules_lines.join("\n");
+            if !new_gitmodules_string.is_empty() {
+                new_gitmodules_string.push('\n');
+            }
+            fs::write(&gitmodules_path, new_gitmodules_string)?;
+        }
+        GitManager::remove_config_entries(&mut repo.config()?.open_level(ConfigLevel::Local)?, &section_prefix)?;
+
+        let mut index = repo.index()?;
+        index.remove_path(Path::new(submodule_path))?;
+        if gitmodules_path.exists() {
+            index.add_path(Path::new(".gitmodules"))?;
+        }
+        index.write()?;
+
+        let submodule_workdir = GitManager::get_workdir_file_path(workdir, Path::new(submodule_path));
+        if submodule_workdir.exists() {
+            fs::remove_dir_all(submodule_workdir)?;
+        }
+        let submodule_git_dir = repo.path().join("modules").join(&submodule_name);
+        if submodule_git_dir.exists() {
+            fs::remove_dir_all(submodule_git_dir)?;
+        }
+        Ok(())
+    }
+
+    // Lists what deleting the submodule's directories would lose: uncommitted changes, untracked files, or a HEAD that isn't
+    // on any of its remote-tracking branches.
+    fn get_submodule_local_work(repo: &Repository, submodule: &Submodule, submodule_name: &str) -> Result<Vec<String>> {
+        let mut local_work = vec![];
+        let submodule_status = repo.submodule_status(submodule_name, SubmoduleIgnore::None)?;
+        if submodule_status.intersects(SubmoduleStatus::WD_INDEX_MODIFIED | SubmoduleStatus::WD_WD_MODIFIED) {
+            local_work.push(String::from("uncommitted changes"));
+        }
+        if submodule_status.contains(SubmoduleStatus::WD_UNTRACKED) {
+            local_work.push(String::from("untracked files"));
+        }
+
+        // A submodule that was never cloned has no commits of its own.
+        let submodule_repo = match submodule.open() {
+            Ok(r) => r,
+            Err(_) => return Ok(local_work),
+        };
+        let head_oid = match submodule_repo.head().ok().and_then(|r| r.target()) {
+            Some(oid) => oid,
+            None => return Ok(local_work),
+        };
+        let mut is_pushed = false;
+        for reference_result in submodule_repo.references_glob("refs/remotes/*")? {
+            // Symbolic refs like origin/HEAD don't have a target of their own.
+            if let Some(remote_oid) = reference_result?.target() {
+                if remote_oid == head_oid || submodule_repo.graph_descendant_of(remote_oid, head_oid)? {
+                    is_pushed = true;
+                    break;
+                }
+            }
+        }
+        if !is_pushed {
+            local_work.push(String::from("a HEAD commit that isn't on any of its remote branches"));
+        }
+        Ok(local_work)
+    }
+
+    fn remove_config_entries(config: &mut git2::Config, name_prefix: &str) -> Result<()> {
+        let mut entry_names: Vec<String> = vec![];
+        config.entries(None)?.for_each(|entry| {
+            if let Some(entry_name) = entry.name() {
+                if entry_name.starts_with(name_prefix) {
+                    entry_names.push(String::from(entry_name));
+                }
+            }
+        })?;
+        for entry_name in entry_names {
+            config.remove(&entry_name)?;
+        }
+        Ok(())
+    }
+
+    fn git_checkout(&self, local_ref: &Reference) -> Result<()> {
+        self.git_checkout_branch(local_ref, false)
+    }
+
+    fn git_checkout_branch(&self, local_ref: &Reference, force: bool) -> Result<()> {
+        let repo = self.borrow_repo()?;
+
+        let local_full_name = GitManager::get_utf8_string(local_ref.name(), "Branch Name")?;
+        let commit = match local_ref.target() {
+            Some(oid) => repo.find_commit(oid)?,
+            None => bail!("Trying to check out branch that has no target commit."),
+        };
+        let tree = commit.tree()?;
+
+        self.checkout_tree_guarded(&tree, local_full_name, "ref", force)?;
+        repo.set_head(local_full_name)?;
+        Ok(())
+    }
+
+    // libgit2 only says a checkout conflicts with local changes, so collect which files are in the way and return them
+    // along with what was being checked out so the front-end can offer to stash, force, or merge instead.
+    fn checkout_tree_guarded(&self, tree: &Tree, target: &str, target_type: &str, force: bool) -> Result<()> {
+        self.check_case_collisions(tree)?;
+        let repo = self.borrow_repo()?;
+
+        let mut conflicting_paths: Vec<String> = vec![];
+        let checkout_result = {
+            let mut cb = CheckoutBuilder::new();
+            if force {
+                cb.force();
+            }
+            cb.notify_on(CheckoutNotificationType::CONFLICT);
+            cb.notify(|_, path_opt, _, _, _| {
+                if let Some(path) = path_opt {
+                    conflicting_paths.push(path.to_string_lossy().into_owned());
+                }
+                true
+            });
+            repo.checkout_tree(tree.as_object(), Some(&mut cb))
+        };
+        match checkout_result {
+            Ok(()) => Ok(()),
+            Err(e) if e.code() == ErrorCode::Conflict && !conflicting_paths.is_empty() => Err(StructuredError::new(
+                "checkout_conflict",
+                format!("Your local changes to these files would be overwritten by checkout: {}", conflicting_paths.join(", ")),
+                Some(json!({ "paths": conflicting_paths, "target": target, "target_type": target_type })),
+            ).into()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Retries a checkout that failed because of local changes. target and target_type come from the checkout_conflict error.
+    pub fn git_checkout_with_strategy(&mut self, json_string: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_string)?;
+        let target = match json_hm.get("target") {
+            Some(s) => s,
+            None => bail!("target not included in payload from front-end."),
+        };
+        let target_type = match json_hm.get("target_type") {
+            Some(s) => s,
+            None => bail!("target_type not included in payload from front-end."),
+        };
+        let strategy = match json_hm.get("strategy") {
+            Some(s) => s,
+            None => bail!("strategy not included in payload from front-end."),
+        };
+
+        if strategy == "stash" {
+            let repo = self.borrow_repo_mut()?;
+            let message = format!("Stashed before checking out {}", target);
+            repo.stash_save2(&repo.signature()?, Some(&message), Some(StashFlags::INCLUDE_UNTRACKED))?;
+            self.checkout_target(target, target_type, false)
+        } else if strategy == "force" {
+            self.create_safety_snapshot("force checkout", None);
+            self.checkout_target(target, target_type, true)
+        } else if strategy == "merge" {
+            // libgit2 can't carry local changes across a checkout with a three-way merge, so use the CLI's checkout -m.
+            let repo = self.borrow_repo()?;
+            let working_dir = match repo.workdir() {
+                Some(d) => d,
+                None => bail!("Can't check out in a bare repository."),
+            };
+            let mut args = vec!["checkout", "-m"];
+            let checkout_target = match target.strip_prefix("refs/heads/") {
+                Some(branch_name) => branch_name,
+                None => {
+                    args.push("--detach");
+                    target.as_str()
+                },
+            };
+            args.push(checkout_target);
+            let output = new_tool_command("git")?
+                .args(&args)
+                .current_dir(working_dir)
+                .env("GIT_TERMINAL_PROMPT", "0")
+                .stdin(Stdio::null())
+                .output()?;
+            if !output.status.success() {
+                bail!("Failed to check out with merge: {}", String::from_utf8_lossy(&output.stderr).trim());
+            }
+            Ok(())
+        } else {
+            bail!("strategy needs to be 'stash', 'force', or 'merge'.");
+        }
+    }
+
+    fn checkout_target(&self, target: &str, target_type: &str, force: bool) -> Result<()> {
+        if target_type == "sha" {
+            self.checkout_detached_oid(Oid::from_str(target)?, force)
+        } else {
+            let reference = self.borrow_repo()?.find_reference(target)?;
+            self.git_checkout_branch(&reference, force)
+        }
+    }
+
+    fn get_remote_names(&self) -> Result<Vec<String>> {
+        let mut remote_names: Vec<String> = vec![];
+        for remote_name_opt in self.borrow_repo()?.remotes()?.iter() {
+            remote_names.push(String::from(GitManager::get_utf8_string(remote_name_opt, "Remote Name")?));
+        }
+        Ok(remote_names)
+    }
+
+    pub fn get_branch_remotes(&self, json_str: &str) -> Result<BranchRemotesInfo> {
+        let branch_shorthand_value: Value = serde_json::from_str(json_str)?;
+        let branch_shorthand: &str = GitManager::get_string_from_serde_string(branch_shorthand_value.as_str())?;
+        let repo = self.borrow_repo()?;
+        // Make sure the branch actually exists before reading its config.
+        repo.find_branch(branch_shorthand, BranchType::Local)?;
+
+        let config = repo.config()?;
+        Ok(BranchRemotesInfo {
+            branch_shorthand: String::from(branch_shorthand),
+            remote: config.get_string(&format!("branch.{}.remote", branch_shorthand)).ok(),
+            push_remote: config.get_string(&format!("branch.{}.pushRemote", branch_shorthand)).ok(),
+            remotes: self.get_remote_names()?,
+        })
+    }
+
+    // Sets which remotes a branch pulls from and pushes to by default. An empty remote name unsets it.
+    pub fn set_branch_remotes(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let branch_shorthand = match json_hm.get("branch_shorthand") {
+            Some(b) => b,
+            None => bail!("branch_shorthand not included in payload from front-end."),
+        };
+        let remote = match json_hm.get("remote") {
+            Some(r) => r,
+            None => bail!("remote not included in payload from front-end."),
+        };
+        let push_remote = match json_hm.get("push_remote") {
+            Some(r) => r,
+            None => bail!("push_remote not included in payload from front-end."),
+        };
+
+        let repo = self.borrow_repo()?;
+        repo.find_branch(branch_shorthand, BranchType::Local)?;
+        let remote_names = self.get_remote_names()?;
+        for remote_name in [remote, push_remote] {
+            if remote_name != "" && !remote_names.contains(remote_name) {
+                bail!("There's no remote named {}.", remote_name);
+            }
+        }
+
+        let mut config = repo.config()?;
+        for (config_key, remote_name) in [("remote", remote), ("pushRemote", push_remote)] {
+            let full_config_key = format!("branch.{}.{}", branch_shorthand, config_key);
+            if remote_name == "" {
+                match config.remove(&full_config_key) {
+                    Ok(()) => (),
+                    Err(e) if e.code() == ErrorCode::NotFound => (),
+                    Err(e) => return Err(e.into()),
+                };
+            } else {
+                config.set_str(&full_config_key, remote_name)?;
+            }
+        }
+        Ok(())
+    }
 
-        let preferences = config_manager::get_config()?;
-        let limit_commits = match preferences.borrow_limit_commits() {
-            Some(b) => b.clone(),
-            None => bail!("limit_commits not present in config file!"),
+    pub fn get_remote_head(&self, json_str: &str) -> Result<RemoteHeadInfo> {
+        let remote_name_value: Value = serde_json::from_str(json_str)?;
+        let remote_name: &str = GitManager::get_string_from_serde_string(remote_name_value.as_str())?;
+        let repo = self.borrow_repo()?;
+
+        let mut local_head = None;
+        if let Ok(head_ref) = repo.find_reference(&format!("refs/remotes/{}/HEAD", remote_name)) {
+            if let Some(target) = head_ref.symbolic_target() {
+                local_head = target.strip_prefix(&format!("refs/remotes/{}/", remote_name)).map(String::from);
+            }
+        }
+
+        Ok(RemoteHeadInfo {
+            remote_name: String::from(remote_name),
+            local_head,
+            remote_default_branch: self.get_remote_default_branch(remote_name)?,
+        })
+    }
+
+    // Asks the remote which branch its HEAD points to.
+    fn get_remote_default_branch(&self, remote_name: &str) -> Result<Option<String>> {
+        let mut remote = self.borrow_repo()?.find_remote(remote_name)?;
+        let connection = remote.connect_auth(Direction::Fetch, Some(GitManager::get_remote_callbacks()), None)?;
+        let default_branch = match connection.default_branch() {
+            Ok(b) => b,
+            // Empty remotes don't have a HEAD yet.
+            Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
         };
-        let commit_count = match preferences.borrow_commit_count() {
-            Some(i) => i.clone(),
-            None => bail!("commit_count not present in config file!"),
+        let default_branch_name = GitManager::get_utf8_string(default_branch.as_str(), "Branch Name")?;
+        Ok(Some(String::from(default_branch_name.strip_prefix("refs/heads/").unwrap_or(default_branch_name))))
+    }
+
+    // Like git remote set-head. An empty branch asks the remote for its HEAD, like --auto.
+    pub fn set_remote_head(&self, json_str: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let remote_name = match json_hm.get("remote_name") {
+            Some(r) => r,
+            None => bail!("remote_name not included in payload from front-end."),
+        };
+        let branch_from_frontend = match json_hm.get("branch") {
+            Some(b) => b,
+            None => bail!("branch not included in payload from front-end."),
         };
 
-        let mut oid_list: Vec<Oid> = vec![];
-        for (i, commit_oid_result) in revwalk.enumerate() {
-            if limit_commits && i >= commit_count {
-                break;
+        let branch = if branch_from_frontend == "" {
+            match self.get_remote_default_branch(remote_name)? {
+                Some(b) => b,
+                None => bail!("{} doesn't have a HEAD to copy.", remote_name),
             }
-            oid_list.push(commit_oid_result?);
+        } else {
+            branch_from_frontend.clone()
+        };
+
+        let repo = self.borrow_repo()?;
+        let target_ref_name = format!("refs/remotes/{}/{}", remote_name, branch);
+        if repo.find_reference(&target_ref_name).is_err() {
+            bail!("{}/{} hasn't been fetched yet, so HEAD can't point to it. Try fetching first.", remote_name, branch);
         }
-        Ok(Some(oid_list))
+        repo.reference_symbolic(&format!("refs/remotes/{}/HEAD", remote_name), &target_ref_name, true, "oxidized_git remote set-head")?;
+        Ok(())
     }
 
-    pub fn get_commit_info(&self, json_str: &str) -> Result<CommitInfo> {
+    // Takes the full name of any ref, like the ones on the branch badges, so remote branches and tags can be checked out too.
+    pub fn git_checkout_from_json(&self, json_str: &str) -> Result<()> {
+        let ref_name_value: Value = serde_json::from_str(json_str)?;
+        let ref_name: &str = GitManager::get_string_from_serde_string(ref_name_value.as_str())?;
+        let reference = self.borrow_repo()?.find_reference(ref_name)?;
+        if reference.is_branch() {
+            self.git_checkout(&reference)
+        } else if reference.is_remote() {
+            let remote_branch_shortname = GitManager::get_utf8_string(reference.shorthand(), "Branch Shorthand")?;
+            self.git_checkout_remote_branch(remote_branch_shortname, ref_name, None)
+        } else {
+            // Tags and a detached HEAD can only be checked out as a detached HEAD.
+            self.git_checkout_detached_oid(reference.peel_to_commit()?.id())
+        }
+    }
+
+    fn git_checkout_detached_oid(&self, oid: Oid) -> Result<()> {
+        self.checkout_detached_oid(oid, false)
+    }
+
+    fn checkout_detached_oid(&self, oid: Oid, force: bool) -> Result<()> {
+        let repo = self.borrow_repo()?;
+        let tree = repo.find_commit(oid)?.tree()?;
+
+        self.checkout_tree_guarded(&tree, &oid.to_string(), "sha", force)?;
+        repo.set_head_detached(oid)?;
+        Ok(())
+    }
+
+    pub fn git_checkout_detached_head(&self, json_str: &str) -> Result<()> {
         let sha_value: Value = serde_json::from_str(json_str)?;
         let sha: &str = GitManager::get_string_from_serde_string(sha_value.as_str())?;
+        self.git_checkout_detached_oid(Oid::from_str(sha)?)
+    }
+
+    pub fn git_checkout_remote(&self, json_string: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_string)?;
+        let remote_branch_shortname = match json_hm.get("branch_shorthand") {
+            Some(n) => n,
+            None => bail!("JSON Data is missing branch_shorthand attribute."),
+        };
+        let remote_branch_full_name = match json_hm.get("full_branch_name") {
+            Some(n) => n,
+            None => bail!("JSON Data is missing full_branch_name attribute."),
+        };
+        // Optional, for when the remote branch's name is already taken by an unrelated local branch.
+        let local_name_opt = json_hm.get("local_name").filter(|n| !n.is_empty()).map(|n| n.as_str());
+        self.git_checkout_remote_branch(remote_branch_shortname, remote_branch_full_name, local_name_opt)
+    }
+
+    fn git_checkout_remote_branch(&self, remote_branch_shortname: &str, remote_branch_full_name: &str, local_name_opt: Option<&str>) -> Result<()> {
         let repo = self.borrow_repo()?;
 
-        let commit = repo.find_commit(Oid::from_str(sha)?)?;
-        let commit_info = CommitInfo::from_commit(commit, repo)?;
+        // Look for a local branch that already exists for the specified remote branch. If one exists,
+        // check it out instead.
+        for local_b_result in repo.branches(Some(BranchType::Local))? {
+            let (local_b, _) = local_b_result?;
+            let local_upstream = match local_b.upstream() {
+                Ok(b) => b,
+                Err(_) => {
+                    continue;
+                },
+            };
+            let local_upstream_full_name = GitManager::get_utf8_string(local_upstream.get().name(), "Branch Name")?;
+            if local_upstream_full_name == remote_branch_full_name {
+                return self.git_checkout(local_b.get());
+            }
+        }
 
-        Ok(commit_info)
+        // If there's no local branch, create a new one with the name used by the remote branch.
+        let local_branch_shortname = match local_name_opt {
+            Some(local_name) => String::from(local_name),
+            None => {
+                let remote_branch_name_parts: Vec<&str> = remote_branch_shortname.split("/").collect();
+                let mut local_branch_shortname = String::new();
+                for i in 1..remote_branch_name_parts.len() {
+                    local_branch_shortname += remote_branch_name_parts[i];
+                    if i < remote_branch_name_parts.len() - 1 {
+                        local_branch_shortname += "/";
+                    }
+                }
+                local_branch_shortname
+            },
+        };
+        let remote_branch = repo.find_branch(remote_branch_shortname, BranchType::Remote)?;
+        let commit = match remote_branch.get().target() {
+            Some(oid) => repo.find_commit(oid)?,
+            None => bail!("Selected remote branch isn't targeting a commit, can't checkout!"),
+        };
+
+        // A local branch with the same name but no upstream is taken over if it's on the same commit, otherwise the
+        // user needs to pick another name.
+        if let Ok(mut existing_branch) = repo.find_branch(&local_branch_shortname, BranchType::Local) {
+            if existing_branch.get().target() == Some(commit.id()) {
+                existing_branch.set_upstream(Some(remote_branch_shortname))?;
+                return self.git_checkout(existing_branch.get());
+            }
+            return Err(StructuredError::new(
+                "branch_name_taken",
+                format!("A local branch named {} already exists and points to a different commit than {}.", local_branch_shortname, remote_branch_shortname),
+                Some(json!({
+                    "branch_name": local_branch_shortname,
+                    "branch_shorthand": remote_branch_shortname,
+                    "full_branch_name": remote_branch_full_name,
+                })),
+            ).into());
+        }
+
+        let mut local_branch = repo.branch(&*local_branch_shortname, &commit, false)?;
+        local_branch.set_upstream(Some(remote_branch_shortname))?;
+
+        self.git_checkout(local_branch.get())
     }
 
-    fn has_conflicts(&self) -> Result<bool> {
-        let unstaged_diff = self.get_unstaged_changes()?;
-        let staged_diff = self.get_staged_changes()?;
+    fn git_stage(&self, status: u8, path: &String) -> Result<()> {
+        let repo = self.borrow_repo()?;
 
-        for delta in unstaged_diff.deltas() {
-            if delta.status() == Delta::Conflicted {
-                return Ok(true);
+        let mut index = repo.index()?;
+        let decoded_path = decode_path(path)?;
+        if status == 2 {  // If file is deleted
+            index.remove_path(&decoded_path)?;
+        } else if path.ends_with('/') {  // If it's a collapsed untracked directory
+            index.add_all([&decoded_path], IndexAddOption::DEFAULT, None)?;
+        } else {
+            index.add_path(&decoded_path)?;
+        }
+        index.write()?;
+
+        Ok(())
+    }
+
+    // Returns any files that were just staged and are over the large file warning size, so the user can move them to LFS.
+    pub fn git_stage_from_json(&self, json_str: &str) -> Result<Vec<LargeFile>> {
+        self.ensure_writable()?;
+        let diff_delta: ParseableDiffDelta = serde_json::from_str(json_str)?;
+
+        if let Some(old_path) = diff_delta.borrow_old_path() {
+            // A case-insensitive index keeps the existing entry's case when re-adding a path, so the old entry has to go first.
+            let mut index = self.borrow_repo()?.index()?;
+            index.remove_path(&decode_path(old_path)?)?;
+            index.add_path(&decode_path(diff_delta.get_path())?)?;
+            index.write()?;
+            self.mark_paths_dirty(&[diff_delta.get_path(), old_path]);
+        } else {
+            self.git_stage(diff_delta.get_status(), diff_delta.get_path())?;
+            self.mark_paths_dirty(&[diff_delta.get_path()]);
+        }
+
+        let staged_path = decode_path(diff_delta.get_path().trim_end_matches('/'))?;
+        self.get_large_staged_files(&self.get_staged_changes()?, Some(&staged_path))
+    }
+
+    // Counts the files that aren't ignored in an untracked directory, a nested repo counts as one file like in git status.
+    // The bool is set when there were more than UNTRACKED_FILE_COUNT_LIMIT and counting stopped there.
+    pub fn count_untracked_files(&self, path: &str) -> Result<(usize, bool)> {
+        let repo = self.borrow_repo()?;
+        let workdir = match repo.workdir() {
+            Some(d) => d,
+            None => bail!("Repo doesn't have a working directory."),
+        };
+        let mut untracked_file_count = 0;
+        let mut dirs_to_visit = vec![decode_path(path)?];
+        while let Some(relative_dir) = dirs_to_visit.pop() {
+            let full_dir = GitManager::get_workdir_file_path(workdir, &relative_dir);
+            if full_dir.join(".git").exists() {
+                untracked_file_count += 1;
+                continue;
+            }
+            for dir_entry in fs::read_dir(&full_dir)? {
+                if untracked_file_count > UNTRACKED_FILE_COUNT_LIMIT {
+                    return Ok((UNTRACKED_FILE_COUNT_LIMIT, true));
+                }
+                let dir_entry = dir_entry?;
+                let relative_path = relative_dir.join(dir_entry.file_name());
+                if repo.status_should_ignore(&relative_path)? {
+                    continue;
+                }
+                if dir_entry.file_type()?.is_dir() {
+                    dirs_to_visit.push(relative_path);
+                } else {
+                    untracked_file_count += 1;
+                }
             }
         }
+        if untracked_file_count > UNTRACKED_FILE_COUNT_LIMIT {
+            return Ok((UNTRACKED_FILE_COUNT_LIMIT, true));
+        }
+        Ok((untracked_file_count, false))
+    }
 
-        for delta in staged_diff.deltas() {
-            if delta.status() == Delta::Conflicted {
-                return Ok(true);
+    // Lists what's directly in an untracked directory, with any directories in it still collapsed.
+    pub fn expand_untracked(&self, json_string: &str) -> Result<HashMap<String, serde_json::Value>> {
+        let path: String = serde_json::from_str(json_string)?;
+        let repo = self.borrow_repo()?;
+        let workdir = match repo.workdir() {
+            Some(d) => d,
+            None => bail!("Repo doesn't have a working directory."),
+        };
+        let relative_dir = decode_path(&path)?;
+
+        let mut dir_entries = fs::read_dir(GitManager::get_workdir_file_path(workdir, &relative_dir))?.collect::<std::io::Result<Vec<fs::DirEntry>>>()?;
+        dir_entries.sort_by_key(|e| e.file_name());
+        let mut files = vec![];
+        for dir_entry in dir_entries {
+            let relative_path = relative_dir.join(dir_entry.file_name());
+            if repo.status_should_ignore(&relative_path)? {
+                continue;
+            }
+            #[cfg(unix)]
+            let mut path_bytes = {
+                use std::os::unix::ffi::OsStrExt;
+                relative_path.as_os_str().as_bytes().to_vec()
+            };
+            // Windows paths are always valid unicode.
+            #[cfg(not(unix))]
+            let mut path_bytes = GitManager::get_utf8_string(relative_path.to_str(), "File Path")?.as_bytes().to_vec();
+            if dir_entry.file_type()?.is_dir() {
+                path_bytes.push(b'/');
+                let encoded_path = encode_path_bytes(&path_bytes);
+                let (untracked_file_count, is_capped) = self.count_untracked_files(&encoded_path)?;
+                if untracked_file_count == 0 {
+                    continue;
+                }
+                let mut diff_delta = ParseableDiffDelta::new(Delta::Untracked as u8, encoded_path, String::from_utf8_lossy(&path_bytes).into_owned(), None);
+                diff_delta.set_untracked_file_count(untracked_file_count, is_capped);
+                files.push(diff_delta);
+            } else {
+                files.push(ParseableDiffDelta::new(Delta::Untracked as u8, encode_path_bytes(&path_bytes), String::from_utf8_lossy(&path_bytes).into_owned(), None));
+            }
+        }
+
+        let mut expanded = HashMap::new();
+        expanded.insert(String::from("path"), serde_json::to_value(path)?);
+        expanded.insert(String::from("files"), serde_json::to_value(files)?);
+        Ok(expanded)
+    }
+
+    pub fn get_flagged_files(&self) -> Result<Vec<FlaggedFile>> {
+        let repo = self.borrow_repo()?;
+        let mut flagged_files = vec![];
+        for index_entry in repo.index()?.iter() {
+            let assume_unchanged = IndexEntryFlag::from_bits_truncate(index_entry.flags).is_valid();
+            let skip_worktree = IndexEntryExtendedFlag::from_bits_truncate(index_entry.flags_extended).is_skip_worktree();
+            if assume_unchanged || skip_worktree {
+                flagged_files.push(FlaggedFile::new(encode_path_bytes(&index_entry.path), String::from_utf8_lossy(&index_entry.path).into_owned(), assume_unchanged, skip_worktree));
+            }
+        }
+        Ok(flagged_files)
+    }
+
+    // Sets or clears assume-unchanged or skip-worktree on a file in the index, like git update-index would.
+    pub fn set_index_flag(&self, json_string: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_string)?;
+        let path = match json_hm.get("path") {
+            Some(s) => s,
+            None => bail!("path not included in payload from front-end."),
+        };
+        let flag = match json_hm.get("flag") {
+            Some(s) => s,
+            None => bail!("flag not included in payload from front-end."),
+        };
+        let value = match json_hm.get("value") {
+            Some(s) => s == "true",
+            None => bail!("value not included in payload from front-end."),
+        };
+
+        let repo = self.borrow_repo()?;
+        let mut index = repo.index()?;
+        let mut index_entry = match index.get_path(&decode_path(path)?, 0) {
+            Some(e) => e,
+            None => bail!("{} isn't tracked, so it can't be flagged.", path),
+        };
+        if flag == "assume_unchanged" {
+            let mut flags = IndexEntryFlag::from_bits_retain(index_entry.flags);
+            flags.set(IndexEntryFlag::VALID, value);
+            index_entry.flags = flags.bits();
+        } else if flag == "skip_worktree" {
+            let mut flags_extended = IndexEntryExtendedFlag::from_bits_retain(index_entry.flags_extended);
+            flags_extended.set(IndexEntryExtendedFlag::SKIP_WORKTREE, value);
+            index_entry.flags_extended = flags_extended.bits();
+        } else {
+            bail!("flag needs to be 'assume_unchanged' or 'skip_worktree'.");
+        }
+        index.add(&index_entry)?;
+        index.write()?;
+
+        Ok(())
+    }
+
+    fn is_ignoring_case(&self) -> Result<bool> {
+        Ok(self.borrow_repo()?.config()?.get_bool("core.ignorecase").unwrap_or(false))
+    }
+
+    // Finding case renames means listing every directory with tracked files in it, so use the ones from the last refresh.
+    pub fn get_case_renames(&self) -> Result<Vec<ParseableDiffDelta>> {
+        if let Some(cache) = self.status_cache.borrow().as_ref() {
+            return Ok(cache.case_renames.clone());
+        }
+        self.find_case_renames()
+    }
+
+    // On case-insensitive filesystems, libgit2 matches a file renamed only by case to its index entry and reports no
+    // change at all, so compare each index path against the actual names on disk instead.
+    fn find_case_renames(&self) -> Result<Vec<ParseableDiffDelta>> {
+        let mut case_renames = vec![];
+        let repo = self.borrow_repo()?;
+        let workdir = match repo.workdir() {
+            Some(d) => d,
+            None => return Ok(case_renames),
+        };
+        if !self.is_ignoring_case()? {
+            return Ok(case_renames);
+        }
+
+        // Each directory is only read once, into its exact names and its names by lowercase for the entries that don't match exactly.
+        let mut dir_names_cache: HashMap<PathBuf, (HashSet<String>, HashMap<String, String>)> = HashMap::new();
+        for entry in repo.index()?.iter() {
+            let index_path = String::from_utf8_lossy(&entry.path).into_owned();
+            let mut disk_dir = workdir.to_path_buf();
+            let mut disk_path_parts: Vec<String> = vec![];
+            for part in index_path.split('/') {
+                let (exact_names, lowercase_names) = dir_names_cache.entry(disk_dir.clone()).or_insert_with(|| {
+                    let mut exact_names = HashSet::new();
+                    let mut lowercase_names = HashMap::new();
+                    if let Ok(read_dir) = fs::read_dir(&disk_dir) {
+                        for dir_entry in read_dir.flatten() {
+                            let name = dir_entry.file_name().to_string_lossy().into_owned();
+                            lowercase_names.insert(name.to_lowercase(), name.clone());
+                            exact_names.insert(name);
+                        }
+                    }
+                    (exact_names, lowercase_names)
+                });
+                let disk_name_opt = match exact_names.get(part) {
+                    Some(n) => Some(n),
+                    None => lowercase_names.get(&part.to_lowercase()),
+                };
+                match disk_name_opt {
+                    Some(disk_name) => {
+                        disk_dir.push(disk_name);
+                        disk_path_parts.push(disk_name.clone());
+                    },
+                    // Deleted, which libgit2 already reports.
+                    None => break,
+                }
+            }
+            let disk_path = disk_path_parts.join("/");
+            if disk_path != index_path && disk_path.to_lowercase() == index_path.to_lowercase() {
+                case_renames.push(ParseableDiffDelta::new(Delta::Renamed as u8, encode_path_bytes(disk_path.as_bytes()), format!("{} -> {}", index_path, disk_path), Some(encode_path_bytes(&entry.path))));
+            }
+        }
+        Ok(case_renames)
+    }
+
+    // Checking out a tree with paths that only differ by case on a case-insensitive filesystem makes one overwrite the
+    // other and shows up as a modification that can't be gotten rid of, so refuse up front.
+    fn check_case_collisions(&self, tree: &Tree) -> Result<()> {
+        if !self.is_ignoring_case()? {
+            return Ok(());
+        }
+        let mut lowercase_paths: HashMap<String, String> = HashMap::new();
+        let mut collisions: Vec<String> = vec![];
+        tree.walk(TreeWalkMode::PreOrder, |parent_path, entry| {
+            let path = format!("{}{}", parent_path, String::from_utf8_lossy(entry.name_bytes()));
+            match lowercase_paths.get(&path.to_lowercase()) {
+                Some(other_path) => collisions.push(format!("{} and {}", other_path, path)),
+                None => {
+                    lowercase_paths.insert(path.to_lowercase(), path);
+                },
+            };
+            TreeWalkResult::Ok
+        })?;
+        if !collisions.is_empty() {
+            return Err(StructuredError::new(
+                "case_collision",
+                format!("This checkout has paths that only differ by case, which can't both exist on this filesystem: {}", collisions.join(", ")),
+                Some(json!({ "collisions": collisions })),
+            ).into());
+        }
+        Ok(())
+    }
+
+    pub fn git_unstage(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let repo = self.borrow_repo()?;
+        let diff_delta: ParseableDiffDelta = serde_json::from_str(json_str)?;
+
+        let mut index = repo.index()?;
+        let status = diff_delta.get_status();
+        if status == 2 || status == 3 || status == 8 {  // If file is deleted, modified, or changed type
+            let head_commit = match repo.head()?.target() {
+                Some(oid) => {
+                    repo.find_commit(oid)?
+                },
+                None => bail!("Head has no target commit"),
+            };
+            repo.reset_default(Some(head_commit.as_object()), [decode_path(diff_delta.get_path())?])?;
+        } else {
+            index.remove_path(&decode_path(diff_delta.get_path())?)?;
+        }
+        index.write()?;
+        self.mark_paths_dirty(&[diff_delta.get_path()]);
+
+        Ok(())
+    }
+
+    fn set_diff_find_similar(diff: &mut Diff) -> Result<()> {
+        let mut opts = DiffFindOptions::new();
+        opts.renames(true);
+        opts.copies(true);
+
+        diff.find_similar(Some(&mut opts))?;
+        Ok(())
+    }
+
+    pub fn get_unstaged_changes(&self) -> Result<Diff> {
+        self.get_unstaged_diff(false, DEFAULT_CONTEXT_LINES)
+    }
+
+    fn clear_repo_caches(&mut self) {
+        *self.status_cache.get_mut() = None;
+        *self.dirty_paths.get_mut() = None;
+        self.commit_info_cache.get_mut().clear();
+        self.file_diff_cache.get_mut().clear();
+    }
+
+    // Records paths the app just changed itself, so the refresh right after only has to check those again.
+    fn mark_paths_dirty(&self, paths: &[&str]) {
+        let mut dirty_paths = self.dirty_paths.borrow_mut();
+        let dirty_path_set = dirty_paths.get_or_insert_with(HashSet::new);
+        for path in paths {
+            dirty_path_set.insert(String::from(*path));
+        }
+    }
+
+    // Gets the unstaged and staged changes, reusing the last refresh's results for paths that haven't been touched since. Those
+    // paths are returned too when only they were checked again.
+    pub fn get_changed_files(&self) -> Result<(Vec<ParseableDiffDelta>, Vec<ParseableDiffDelta>, Option<HashSet<String>>)> {
+        let head_oid = match self.borrow_repo()?.head() {
+            Ok(head_ref) => head_ref.target(),
+            Err(_) => None,
+        };
+        let dirty_paths = self.dirty_paths.borrow_mut().take();
+        let mut status_cache = self.status_cache.borrow_mut();
+        if let (Some(dirty_paths), Some(cache)) = (dirty_paths, status_cache.as_mut()) {
+            if cache.head_oid == head_oid && self.update_status_cache(cache, &dirty_paths)? {
+                return Ok((cache.unstaged_files.clone(), cache.staged_files.clone(), Some(dirty_paths)));
+            }
+        }
+
+        let (mut unstaged_files, staged_files) = self.get_changes_statuses(&[])?;
+        for unstaged_file in unstaged_files.iter_mut() {
+            if unstaged_file.get_status() == Delta::Untracked as u8 && unstaged_file.get_path().ends_with('/') {
+                let (untracked_file_count, is_capped) = self.count_untracked_files(unstaged_file.get_path())?;
+                unstaged_file.set_untracked_file_count(untracked_file_count, is_capped);
+            }
+        }
+        *status_cache = Some(StatusCache {
+            head_oid,
+            unstaged_files: unstaged_files.clone(),
+            staged_files: staged_files.clone(),
+            case_renames: self.find_case_renames()?,
+        });
+        Ok((unstaged_files, staged_files, None))
+    }
+
+    // Swaps in fresh statuses for just the dirty paths. Returns false when that can't be done without a full refresh, like when
+    // a change could pair up differently in rename detection or when it's in an untracked directory that's shown as one entry.
+    fn update_status_cache(&self, cache: &mut StatusCache, dirty_paths: &HashSet<String>) -> Result<bool> {
+        if dirty_paths.is_empty() {
+            return Ok(true);
+        }
+        if dirty_paths.iter().any(|p| p.ends_with('/')) {
+            return Ok(false);
+        }
+        // libgit2 doesn't see case renames, so staging or discarding one needs them all found again.
+        let is_case_renamed = |p: &String| cache.case_renames.iter().any(|f| f.get_path().to_lowercase() == p.to_lowercase());
+        if dirty_paths.iter().any(is_case_renamed) {
+            return Ok(false);
+        }
+        let renamed_or_copied = |f: &ParseableDiffDelta| f.get_status() == Delta::Renamed as u8 || f.get_status() == Delta::Copied as u8;
+        if cache.unstaged_files.iter().chain(cache.staged_files.iter()).any(renamed_or_copied) {
+            return Ok(false);
+        }
+        let in_untracked_dir = |p: &String| cache.unstaged_files.iter().any(|f| f.borrow_untracked_file_count().is_some() && p.starts_with(f.get_path().as_str()));
+        if dirty_paths.iter().any(in_untracked_dir) {
+            return Ok(false);
+        }
+        // An untracked file might be the only thing in its directory, which would be shown as the directory instead.
+        let repo = self.borrow_repo()?;
+        let workdir = match repo.workdir() {
+            Some(d) => d,
+            None => return Ok(false),
+        };
+        let index = repo.index()?;
+        for dirty_path in dirty_paths {
+            let decoded_path = decode_path(dirty_path)?;
+            if index.get_path(&decoded_path, 0).is_none() && GitManager::get_workdir_file_path(workdir, &decoded_path).symlink_metadata().is_ok() {
+                return Ok(false);
+            }
+        }
+
+        let dirty_path_list: Vec<String> = dirty_paths.iter().cloned().collect();
+        let (unstaged_files, staged_files) = self.get_changes_statuses(&dirty_path_list)?;
+        for file in unstaged_files.iter().chain(staged_files.iter()) {
+            if !dirty_paths.contains(file.get_path()) || renamed_or_copied(file) {
+                return Ok(false);
             }
         }
 
-        Ok(false)
+        cache.unstaged_files.retain(|f| !dirty_paths.contains(f.get_path()));
+        cache.unstaged_files.extend(unstaged_files);
+        cache.unstaged_files.sort_by(|a, b| a.get_path().cmp(b.get_path()));
+        cache.staged_files.retain(|f| !dirty_paths.contains(f.get_path()));
+        cache.staged_files.extend(staged_files);
+        cache.staged_files.sort_by(|a, b| a.get_path().cmp(b.get_path()));
+        // A staged addition and deletion could now be detected as a rename.
+        let has_staged_status = |status: Delta| cache.staged_files.iter().any(|f| f.get_status() == status as u8);
+        Ok(!(has_staged_status(Delta::Added) && has_staged_status(Delta::Deleted)))
     }
 
-    fn has_unstaged_changes(&self) -> Result<bool> {
-        let diff = self.get_unstaged_changes()?;
+    // Gets the unstaged and staged changes in one pass like git status, optionally only for some paths. Untracked directories are
+    // a single entry each, so a huge one (like node_modules) doesn't have to be listed file by file.
+    fn get_changes_statuses(&self, paths: &[String]) -> Result<(Vec<ParseableDiffDelta>, Vec<ParseableDiffDelta>)> {
+        let repo = self.borrow_repo()?;
 
-        if diff.stats()?.files_changed() > 0 {
-            Ok(true)
-        } else {
-            Ok(false)
+        let mut status_options = StatusOptions::new();
+        status_options.include_untracked(true);
+        status_options.recurse_untracked_dirs(false);
+        status_options.renames_head_to_index(true);
+        // Saves the refreshed file stats to the index so the next status doesn't have to read unchanged files again.
+        status_options.update_index(self.is_writable);
+        if !paths.is_empty() {
+            status_options.disable_pathspec_match(true);
+            for path in paths {
+                status_options.pathspec(decode_path(path)?);
+            }
         }
-    }
-
-    fn has_staged_changes(&self) -> Result<bool> {
-        let diff = self.get_staged_changes()?;
+        let statuses = match repo.statuses(Some(&mut status_options)) {
+            Ok(s) => s,
+            // Another git process holding the index lock only stops the stats from being saved.
+            Err(e) if e.code() == ErrorCode::Locked => {
+                status_options.update_index(false);
+                repo.statuses(Some(&mut status_options))?
+            },
+            Err(e) => return Err(e.into()),
+        };
 
-        if diff.stats()?.files_changed() > 0 {
-            Ok(true)
-        } else {
-            Ok(false)
+        let mut unstaged_files = vec![];
+        let mut staged_files = vec![];
+        for status_entry in statuses.iter() {
+            if let Some(diff_delta) = status_entry.index_to_workdir() {
+                unstaged_files.push(ParseableDiffDelta::from_diff_delta(&diff_delta)?);
+            }
+            if let Some(diff_delta) = status_entry.head_to_index() {
+                staged_files.push(ParseableDiffDelta::from_diff_delta(&diff_delta)?);
+            }
         }
+        Ok((unstaged_files, staged_files))
     }
 
-    pub fn git_merge(&self, json_str: &str) -> Result<()> {
-        let sha_value: Value = serde_json::from_str(json_str)?;
-        let sha: &str = GitManager::get_string_from_serde_string(sha_value.as_str())?;
+    fn get_unstaged_diff(&self, ignore_eol: bool, context_lines: u32) -> Result<Diff> {
         let repo = self.borrow_repo()?;
-        let annotated_commit = repo.find_annotated_commit(Oid::from_str(sha)?)?;
 
-        repo.merge(&[&annotated_commit], None, None)?;
+        let mut diff_options = get_eol_diff_options(ignore_eol, context_lines)?;
+        diff_options.include_untracked(true);
+        diff_options.recurse_untracked_dirs(true);
+        diff_options.show_untracked_content(true);
 
-        if !self.has_conflicts()? {
-            if self.has_staged_changes()? {
-                let head_commit = match repo.head()?.target() {
-                    Some(oid) => repo.find_commit(oid)?,
-                    None => bail!("HEAD has no target, failed to commit after merging. It should fail earlier than this since there'd be no HEAD to merge into."),
+        let mut diff = repo.diff_index_to_workdir(None, Some(&mut diff_options))?;
+        GitManager::set_diff_find_similar(&mut diff)?;
+
+        Ok(diff)
+    }
+
+    pub fn get_staged_changes(&self) -> Result<Diff> {
+        self.get_staged_diff(false, DEFAULT_CONTEXT_LINES)
+    }
+
+    fn get_staged_diff(&self, ignore_eol: bool, context_lines: u32) -> Result<Diff> {
+        let repo = self.borrow_repo()?;
+
+        let mut tree = None;
+        match repo.head() {
+            Ok(head_ref) => {
+                let commit = match head_ref.target() {
+                    Some(oid) => Some(repo.find_commit(oid)?),
+                    None => None,
+                };
+                tree = match commit {
+                    Some(c) => Some(c.tree()?),
+                    None => None,
                 };
-                let merge_parent_two = repo.find_commit(Oid::from_str(sha)?)?;
-                let parent_commits = vec![&head_commit, &merge_parent_two];
-                let committer = repo.signature()?;
+            },
+            Err(e) => {
+                if e.code() != ErrorCode::UnbornBranch {
+                    return Err(e.into());
+                }
+            },
+        };
 
-                let mut short_sha = String::from(sha);
-                short_sha.truncate(5);
-                let mut head_short_sha = head_commit.id().to_string();
-                head_short_sha.truncate(5);
-                let message = String::from("Merge commit ") + short_sha.as_str() + " into commit " + head_short_sha.as_str();
+        let mut diff = repo.diff_tree_to_index(tree.as_ref(), None, Some(&mut get_eol_diff_options(ignore_eol, context_lines)?))?;
+        GitManager::set_diff_find_similar(&mut diff)?;
 
-                self.git_commit(message, &committer, &committer, parent_commits)?;
+        Ok(diff)
+    }
 
-                repo.cleanup_state()?;
-            } else {
-                bail!("Merge commit had no changes, not really sure what would cause this...");
+    fn get_file_index_in_diff(diff: &Diff, path: &str) -> Result<usize> {
+        let file_index_opt = diff.deltas().position(|dd| {
+            match dd.new_file().path_bytes() {
+                Some(b) => path == encode_path_bytes(b),
+                None => false,
             }
+        });
+        match file_index_opt {
+            Some(i) => Ok(i),
+            None => bail!("Selected file not found."),
         }
-
-        Ok(())
     }
 
-    fn iterate_through_rebase(&self, repo: &Repository, rebase: &mut Rebase) -> Result<()> {
-        // Unfortunately, using 'rebase' like an iterator doesn't allow us to commit since
-        // rebase has to be borrowed mutably to commit.
-        let mut reached_end = false;
-        while !reached_end {
-            let step = rebase.next();
-            match step {
-                Some(r) => {
-                    r?;
+    pub fn get_file_diff(&self, json_str: &str) -> Result<FileInfo> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
 
-                    // If there are conflicts, need to let the user fix them before resuming
-                    // inside "git_continue_rebase"
-                    if self.has_conflicts()? || self.has_unstaged_changes()? {
-                        return Ok(());
-                    } else if self.has_staged_changes()? {
-                        rebase.commit(None, &repo.signature()?, None)?;
-                    }
-                },
-                None => reached_end = true,
-            };
-        }
-        rebase.finish(None)?;
+        let file_path = match json_hm.get("file_path") {
+            Some(s) => s,
+            None => bail!("file_path not returned from front-end payload."),
+        };
+        let change_type = match json_hm.get("change_type") {
+            Some(s) => s,
+            None => bail!("change_type not returned from front-end payload."),
+        };
+        let sha = match json_hm.get("sha") {
+            Some(s) => s,
+            None => bail!("sha not returned from front-end payload."),
+        };
+        let context_lines = GitManager::get_context_lines(&json_hm)?;
 
-        Ok(())
+        self.get_file_diff_hunks(file_path, change_type, sha, 0, context_lines)
     }
 
-    pub fn git_rebase(&self, json_str: &str) -> Result<()> {
-        let sha_value: Value = serde_json::from_str(json_str)?;
-        let sha: &str = GitManager::get_string_from_serde_string(sha_value.as_str())?;
-        let repo = self.borrow_repo()?;
-        let annotated_commit = repo.find_annotated_commit(Oid::from_str(sha)?)?;
-        let mut rebase = repo.rebase(None, None, Some(&annotated_commit), None)?;
+    pub fn get_more_hunks(&self, json_str: &str) -> Result<FileInfo> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
 
-        self.iterate_through_rebase(repo, &mut rebase)?;
+        let file_path = match json_hm.get("file_path") {
+            Some(s) => s,
+            None => bail!("file_path not returned from front-end payload."),
+        };
+        let change_type = match json_hm.get("change_type") {
+            Some(s) => s,
+            None => bail!("change_type not returned from front-end payload."),
+        };
+        let sha = match json_hm.get("sha") {
+            Some(s) => s,
+            None => bail!("sha not returned from front-end payload."),
+        };
+        let hunk_offset = match json_hm.get("hunk_offset") {
+            Some(s) => s.parse::<usize>()?,
+            None => bail!("hunk_offset not returned from front-end payload."),
+        };
+        let context_lines = GitManager::get_context_lines(&json_hm)?;
 
-        Ok(())
+        self.get_file_diff_hunks(file_path, change_type, sha, hunk_offset, context_lines)
     }
 
-    pub fn git_cherrypick(&self, json_str: &str) -> Result<()> {
+    // Gets up to extra_lines more unchanged lines above and below a hunk, stopping at the hunks next to it so no line is shown twice.
+    pub fn get_hunk_context(&self, json_str: &str) -> Result<HunkContext> {
         let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
 
+        let file_path = match json_hm.get("file_path") {
+            Some(s) => s,
+            None => bail!("file_path not returned from front-end payload."),
+        };
+        let change_type = match json_hm.get("change_type") {
+            Some(s) => s,
+            None => bail!("change_type not returned from front-end payload."),
+        };
         let sha = match json_hm.get("sha") {
             Some(s) => s,
-            None => bail!("sha wasn't included in payload from the front-end."),
+            None => bail!("sha not returned from front-end payload."),
         };
-        let is_committing = match json_hm.get("isCommitting") {
-            Some(s) => s == "true",
-            None => bail!("isCommitting wasn't included in payload from the front-end."),
+        let hunk_index = match json_hm.get("hunk_index") {
+            Some(s) => s.parse::<usize>()?,
+            None => bail!("hunk_index not returned from front-end payload."),
         };
+        let extra_lines = match json_hm.get("extra_lines") {
+            Some(s) => s.parse::<usize>()?,
+            None => bail!("extra_lines not returned from front-end payload."),
+        };
+        let context_lines = GitManager::get_context_lines(&json_hm)?;
 
+        if self.get_textconv_command(file_path)?.is_some() {
+            bail!("Can't expand the context of a file that's diffed with textconv.");
+        }
         let repo = self.borrow_repo()?;
-        let commit = repo.find_commit(Oid::from_str(sha)?)?;
-
-        repo.cherrypick(&commit, None)?;
-
-        if !self.has_conflicts()? {
-            repo.cleanup_state()?;
+        let ignore_eol = config_manager::get_config()?.borrow_ignore_eol_changes().unwrap_or(false);
+        let diff = self.get_change_type_diff(change_type, sha, ignore_eol, context_lines)?;
+        let patch = match Patch::from_diff(&diff, GitManager::get_file_index_in_diff(&diff, file_path.as_str())?)? {
+            Some(p) => p,
+            None => bail!("Patch not found in diff."),
+        };
+        if hunk_index >= patch.num_hunks() {
+            bail!("Hunk not found, the file may have changed since its diff was shown.");
         }
 
-        let commit = repo.find_commit(Oid::from_str(sha)?)?;
+        // Unchanged lines are the same on both sides, so they're read from the new side.
+        let new_file = patch.delta().new_file();
+        let content = match new_file.path() {
+            Some(_) if change_type == "unstaged" || change_type == "workdir" => self.load_workdir_file(file_path)?,
+            _ if new_file.id().is_zero() => BlobContent::Text(String::new()),
+            _ => self.load_blob(new_file.id())?,
+        };
+        let content_str = match content {
+            BlobContent::Text(t) => t,
+            BlobContent::Binary(_) => bail!("Can't expand the context of a binary file."),
+            BlobContent::TooLarge(_) => bail!("File is too large to expand the context of."),
+        };
+        let file_lines: Vec<&str> = content_str.split_inclusive('\n').collect();
 
-        if is_committing && !self.has_conflicts()? && self.has_staged_changes()? {
-            let committer = repo.signature()?;
-            let head_commit = match repo.head()?.target() {
-                Some(oid) => repo.find_commit(oid)?,
-                None => bail!("HEAD has no target, failed to commit after cherrypick."),
-            };
-            self.git_commit(String::from(GitManager::get_utf8_string(commit.message(), "Commit Message")?), &commit.author(), &committer, vec![&head_commit])?;
-        }
+        let (old_range, new_range) = get_hunk_line_ranges(&patch.hunk(hunk_index)?.0);
+        let previous_end = match hunk_index {
+            0 => 0,
+            i => get_hunk_line_ranges(&patch.hunk(i - 1)?.0).1.end,
+        };
+        let next_start = if hunk_index + 1 < patch.num_hunks() {
+            get_hunk_line_ranges(&patch.hunk(hunk_index + 1)?.0).1.start
+        } else {
+            file_lines.len()
+        };
 
-        Ok(())
+        let file_type = self.get_file_language(file_path.as_str())?;
+        // new_offset is where the first line is on the new side, and old_offset is where the same line is on the old side.
+        let get_unchanged_lines = |new_offset: usize, old_offset: usize, count: usize| -> Result<Vec<FileLineInfo>> {
+            let mut context = vec![];
+            for i in 0..count {
+                let mut content_string = String::from(file_lines[new_offset + i]);
+                trim_newline(&mut content_string);
+                context.push(FileLineInfo {
+                    old_lineno: Some((old_offset + i + 1) as u32),
+                    new_lineno: Some((new_offset + i + 1) as u32),
+                    file_type: file_type.clone(),
+                    content: html_escape::encode_text(&content_string).parse()?,
+                    origin: ' ',
+                });
+            }
+            Ok(context)
+        };
+        let before_start = new_range.start.saturating_sub(extra_lines).max(previous_end).min(new_range.start);
+        let before_count = new_range.start - before_start;
+        let after_end = (new_range.end + extra_lines).min(next_start.min(file_lines.len())).max(new_range.end);
+
+        Ok(HunkContext {
+            file_path: file_path.clone(),
+            change_type: change_type.clone(),
+            hunk_index,
+            lines_before: get_unchanged_lines(before_start, old_range.start - before_count, before_count)?,
+            lines_after: get_unchanged_lines(new_range.end, old_range.end, after_end - new_range.end)?,
+        })
     }
 
-    pub fn git_revert(&self, json_str: &str) -> Result<()> {
-        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+    // Optional in diff payloads, so requests from before it existed still get git's default.
+    fn get_context_lines(json_hm: &HashMap<String, String>) -> Result<u32> {
+        match json_hm.get("context_lines") {
+            Some(s) => Ok(s.parse::<u32>()?),
+            None => Ok(DEFAULT_CONTEXT_LINES),
+        }
+    }
 
-        let sha = match json_hm.get("sha") {
-            Some(s) => s,
-            None => bail!("sha wasn't included in payload from the front-end."),
-        };
-        let is_committing = match json_hm.get("isCommitting") {
-            Some(s) => s == "true",
-            None => bail!("isCommitting wasn't included in payload from the front-end."),
-        };
+    fn get_change_type_diff(&self, change_type: &str, sha: &str, ignore_eol: bool, context_lines: u32) -> Result<Diff> {
+        let repo = self.borrow_repo()?;
+        let diff;
+        if change_type == "unstaged" {
+            diff = self.get_unstaged_diff(ignore_eol, context_lines)?;
+        } else if change_type == "staged" {
+            diff = self.get_staged_diff(ignore_eol, context_lines)?;
+        } else if change_type == "commit" {
+            let commit = repo.find_commit(Oid::from_str(sha)?)?;
+            diff = get_commit_changes(&commit, repo, ignore_eol, context_lines)?;
+        } else if change_type == "stash" {
+            let stash_commit = repo.find_commit(Oid::from_str(sha)?)?;
+            diff = get_stash_changes(&stash_commit, repo, ignore_eol, context_lines)?;
+        } else if change_type == "workdir" {
+            let commit = repo.find_commit(Oid::from_str(sha)?)?;
+            diff = get_workdir_changes(&commit, repo, ignore_eol, context_lines)?;
+        } else if change_type == "merge-base" {
+            // The sha is the A...B range, since both commits are needed to find the merge base again.
+            let (a_sha, b_sha) = match sha.split_once("...") {
+                Some(shas) => shas,
+                None => bail!("The merge base range needs to be in the form A...B."),
+            };
+            let a_commit = repo.find_commit(Oid::from_str(a_sha)?)?;
+            let b_commit = repo.find_commit(Oid::from_str(b_sha)?)?;
+            diff = get_merge_base_changes(&a_commit, &b_commit, repo, ignore_eol, context_lines)?.1;
+        } else {
+            bail!("change_type not a valid type. Needs to be 'staged', 'unstaged', 'commit', 'stash', 'workdir', or 'merge-base'");
+        }
+        Ok(diff)
+    }
 
+    fn get_file_diff_hunks(&self, file_path: &String, change_type: &String, sha: &String, hunk_offset: usize, context_lines: u32) -> Result<FileInfo> {
         let repo = self.borrow_repo()?;
-        let commit = repo.find_commit(Oid::from_str(sha)?)?;
 
-        repo.revert(&commit, None)?;
+        let ignore_eol = config_manager::get_config()?.borrow_ignore_eol_changes().unwrap_or(false);
+        let diff = self.get_change_type_diff(change_type, sha, ignore_eol, context_lines)?;
+        // Both sides come from the working directory's files for these instead of from blobs.
+        let is_workdir_diff = change_type == "unstaged" || change_type == "workdir";
 
-        if !self.has_conflicts()? {
-            repo.cleanup_state()?;
-        }
+        let file_index = match GitManager::get_file_index_in_diff(&diff, file_path.as_str()) {
+            Ok(i) => i,
+            Err(e) => {
+                // Case-only renames aren't in libgit2's diff on case-insensitive filesystems, and only the name changed anyway.
+                if change_type == "unstaged" && self.get_case_renames()?.iter().any(|d| d.get_path() == file_path) {
+                    return Ok(FileInfo::new(file_path.clone(), change_type.clone(), sha.clone(), vec![], String::from("plaintext"), false, hunk_offset, context_lines, None));
+                }
+                return Err(e);
+            },
+        };
 
-        let commit = repo.find_commit(Oid::from_str(sha)?)?;
+        // Files with a textconv diff driver are diffed as the command's output instead, which makes binary formats reviewable.
+        let textconv_texts_opt = match self.get_textconv_command(file_path)? {
+            Some(textconv_command) => match diff.get_delta(file_index) {
+                Some(delta) => {
+                    let old_text = self.get_textconv_text(&textconv_command, &delta.old_file(), false, change_type)?;
+                    let new_text = self.get_textconv_text(&textconv_command, &delta.new_file(), is_workdir_diff, change_type)?;
+                    Some((old_text, new_text))
+                },
+                None => None,
+            },
+            None => None,
+        };
 
-        if is_committing && !self.has_conflicts()? && self.has_staged_changes()? {
-            let committer = repo.signature()?;
-            let head_commit = match repo.head()?.target() {
-                Some(oid) => repo.find_commit(oid)?,
-                None => bail!("HEAD has no target, failed to commit after revert."),
-            };
+        // Blobs never change, but the working directory's files and textconv output can.
+        let cache_key_opt = match (diff.get_delta(file_index), &textconv_texts_opt) {
+            (Some(delta), None) if !is_workdir_diff => Some(FileDiffKey {
+                old_oid: delta.old_file().id(),
+                new_oid: delta.new_file().id(),
+                old_file_mode: u32::from(delta.old_file().mode()),
+                new_file_mode: u32::from(delta.new_file().mode()),
+                old_path: delta.old_file().path_bytes().unwrap_or_default().to_vec(),
+                file_path: file_path.clone(),
+                hunk_offset,
+                context_lines,
+                diff_settings_key: get_diff_settings_key()?,
+            }),
+            _ => None,
+        };
+        if let Some(cache_key) = &cache_key_opt {
+            let cached_file_info_opt = self.file_diff_cache.borrow_mut().get(cache_key);
+            if let Some(mut file_info) = cached_file_info_opt {
+                file_info.change_type = change_type.clone();
+                file_info.sha = sha.clone();
+                return Ok(file_info);
+            }
+        }
 
-            let mut new_full_message = String::from("Revert \"") + GitManager::get_utf8_string(commit.summary(), "Commit Summary")? + "\"";
-            let (message_without_summary, uses_crlf) = GitManager::get_message_without_summary(GitManager::get_utf8_string(commit.message(), "Commit Message")?);
-            if uses_crlf {
-                new_full_message += "\r\n\r\n";
-            } else {
-                new_full_message += "\n\n";
+        // Don't build a patch for binary or huge files since it can't be shown anyway.
+        if let (Some(delta), None) = (diff.get_delta(file_index), &textconv_texts_opt) {
+            let mut skipped_blob_opt = None;
+            for diff_file in [delta.old_file(), delta.new_file()] {
+                let blob_check_opt = if diff_file.is_binary() {
+                    Some(BlobContent::Binary(diff_file.size() as usize))
+                } else if !diff_file.id().is_zero() && repo.odb()?.exists(diff_file.id()) {
+                    self.check_blob(diff_file.id())?
+                } else if is_workdir_diff {
+                    self.check_workdir_file(file_path)?
+                } else {
+                    None
+                };
+                if blob_check_opt.is_some() {
+                    skipped_blob_opt = blob_check_opt;
+                    break;
+                }
             }
-            new_full_message += message_without_summary.as_str();
+            if skipped_blob_opt.is_some() {
+                return Ok(FileInfo::new(file_path.clone(), change_type.clone(), sha.clone(), vec![], String::from("plaintext"), false, hunk_offset, context_lines, skipped_blob_opt));
+            }
+        }
 
-            self.git_commit(new_full_message, &commit.author(), &committer, vec![&head_commit])?;
+        let patch_opt = match &textconv_texts_opt {
+            Some((old_text, new_text)) => Some(Patch::from_buffers(old_text, None, new_text, None, Some(new_diff_options()?.context_lines(context_lines)))?),
+            None => Patch::from_diff(&diff, file_index)?,
+        };
+        let mut file_lines = vec![];
+        let file_type = self.get_file_language(file_path.as_str())?;
+        // Git looks for the name in the old side of the file, so the same is done here.
+        let function_name_patterns_opt = match (&textconv_texts_opt, diff.get_delta(file_index)) {
+            (None, Some(delta)) if !delta.old_file().id().is_zero() => self.get_function_name_regexes(file_path, &file_type)?,
+            _ => None,
+        };
+        let old_content = match (&function_name_patterns_opt, diff.get_delta(file_index)) {
+            (Some(_), Some(delta)) => repo.find_blob(delta.old_file().id())?.content().to_vec(),
+            _ => vec![],
+        };
+        let old_content_str = String::from_utf8_lossy(&old_content);
+        let old_lines: Vec<&str> = old_content_str.lines().collect();
+        let hunk_end = hunk_offset + DIFF_HUNK_PAGE_SIZE;
+        let mut truncated = false;
+        let eol_only;
+        match patch_opt {
+            Some(mut patch) => {
+                eol_only = !ignore_eol && is_eol_only_patch(&patch)?;
+                // Index of the hunk currently being printed, the file header comes before the first hunk.
+                let mut hunk_index: Option<usize> = None;
+                patch.print(&mut |_diff_delta, diff_hunk_opt, diff_line| {
+                    if diff_line.origin_value() == DiffLineType::HunkHeader {
+                        hunk_index = Some(hunk_index.map_or(0, |i| i + 1));
+                    }
+                    let is_in_page = match hunk_index {
+                        Some(i) => i >= hunk_offset && i < hunk_end,
+                        None => hunk_offset == 0,
+                    };
+                    if !is_in_page {
+                        if let Some(i) = hunk_index {
+                            if i >= hunk_end {
+                                truncated = true;
+                            }
+                        }
+                        return true;
+                    }
+
+                    if diff_line.origin_value() == DiffLineType::FileHeader {
+                        if let Ok(s) = get_content_from_diff_line(&diff_line) {
+                            // Include file header if filemode has changed or the file was renamed.
+                            let is_filemode_change = !s.contains("+++") && (s.contains("old mode") || s.contains("new mode"));
+                            let is_renamed_file = s.contains("rename") || s.contains("similarity");
+                            if is_filemode_change || is_renamed_file {
+                                file_lines.push(LineInfo::SomeSeparator(s));
+                            }
+                        }
+                    } else if diff_line.origin_value() == DiffLineType::HunkHeader {
+                        if let Ok(mut s) = get_content_from_diff_line(&diff_line) {
+                            // Swap libgit2's guess at the name (any line starting with a letter) for the one from the language's pattern.
+                            if let (Some(function_name_patterns), Some(diff_hunk), Some(range_end)) = (&function_name_patterns_opt, &diff_hunk_opt, s.get(2..).and_then(|h| h.find("@@"))) {
+                                s.truncate(range_end + 4);
+                                if let Some(function_name) = find_function_name(function_name_patterns, &old_lines, get_hunk_line_ranges(diff_hunk).0.start) {
+                                    s.push(' ');
+                                    s.push_str(&html_escape::encode_text(&function_name));
+                                }
+                            }
+                            file_lines.push(LineInfo::SomeSeparator(s));
+                        }
+                    } else if let Ok(fli) = FileLineInfo::from_diff_line(diff_line, &file_type) {
+                        file_lines.push(LineInfo::SomeFileLineInfo(fli));
+                    }
+                    true
+                })?;
+            },
+            None => bail!("Patch not found in diff."),
         }
 
-        Ok(())
+        let mut file_info = FileInfo::new(file_path.clone(), change_type.clone(), sha.clone(), file_lines, file_type, truncated, hunk_end, context_lines, None);
+        if eol_only {
+            file_info.set_eol_info(true, self.get_eol_settings(file_path)?);
+        }
+        if let Some(cache_key) = cache_key_opt {
+            self.file_diff_cache.borrow_mut().insert(cache_key, file_info.clone());
+        }
+        Ok(file_info)
     }
 
-    pub fn git_abort(&self) -> Result<()> {
+    // The patterns for finding the name of the function a hunk is in. When .gitattributes sets a diff driver, libgit2 already uses
+    // its pattern (including diff.<driver>.xfuncname), so this is only for files without one.
+    fn get_function_name_regexes(&self, path: &str, language: &str) -> Result<Option<(Regex, Option<Regex>)>> {
+        if !config_manager::get_config()?.borrow_show_function_names().unwrap_or(true) {
+            return Ok(None);
+        }
         let repo = self.borrow_repo()?;
-
-        let head_commit = match repo.head()?.target() {
-            Some(oid) => repo.find_commit(oid)?,
-            None => bail!("HEAD doesn't have a target commit, cannot abort to HEAD"),
-        };
-
-        repo.reset(head_commit.as_object(), ResetType::Hard, None)?;
-
-        repo.cleanup_state()?;
-
-        Ok(())
+        let attr_value = repo.get_attr(&decode_path(path)?, "diff", AttrCheckFlags::FILE_THEN_INDEX)?;
+        if let AttrValue::String(_) = AttrValue::from_string(attr_value) {
+            return Ok(None);
+        }
+        match get_function_name_patterns(language) {
+            Some((pattern, skip_pattern_opt)) => Ok(Some((Regex::new(pattern)?, skip_pattern_opt.map(Regex::new).transpose()?))),
+            None => Ok(None),
+        }
     }
 
-    pub fn git_continue_cherrypick(&self) -> Result<()> {
-        if !self.has_conflicts()? {
-            let repo = self.borrow_repo()?;
-
-            let head_commit = match repo.head()?.target() {
-                Some(oid) => repo.find_commit(oid)?,
-                None => bail!("HEAD doesn't have a target commit (which is where a cherrypick operation starts on), can't complete cherrypick."),
-            };
-
-            let mut cherrypick_head_file = repo.path().to_path_buf();
-            cherrypick_head_file.push("CHERRY_PICK_HEAD");
-            let cherrypick_head_string = fs::read_to_string(cherrypick_head_file)?;
-            let sha = cherrypick_head_string.trim();
-
-            let commit_from_op = repo.find_commit(Oid::from_str(sha)?)?;
-
-            let committer = repo.signature()?;
-
-            self.git_commit(String::from(GitManager::get_utf8_string(commit_from_op.message(), "Commit Message")?), &commit_from_op.author(), &committer, vec![&head_commit])?;
-
-            repo.cleanup_state()?;
+    // The textconv command from the file's diff driver (diff=<driver> in .gitattributes), if one is configured.
+    fn get_textconv_command(&self, path: &str) -> Result<Option<String>> {
+        let repo = self.borrow_repo()?;
+        let attr_value = repo.get_attr(&decode_path(path)?, "diff", AttrCheckFlags::FILE_THEN_INDEX)?;
+        if let AttrValue::String(driver) = AttrValue::from_string(attr_value) {
+            if let Ok(textconv_command) = repo.config()?.get_string(&format!("diff.{}.textconv", driver)) {
+                return Ok(Some(textconv_command));
+            }
         }
-
-        Ok(())
+        Ok(None)
     }
 
-    pub fn git_continue_revert(&self) -> Result<()> {
-        if !self.has_conflicts()? {
-            let repo = self.borrow_repo()?;
-
-            let head_commit = match repo.head()?.target() {
-                Some(oid) => repo.find_commit(oid)?,
-                None => bail!("HEAD doesn't have a target commit (which is where a revert operation starts on), can't complete revert."),
+    // Runs textconv on one side of a diff, an empty side (like for an added file) stays empty.
+    fn get_textconv_text(&self, textconv_command: &str, diff_file: &DiffFile, is_workdir: bool, change_type: &str) -> Result<Vec<u8>> {
+        let repo = self.borrow_repo()?;
+        let path = match diff_file.path() {
+            Some(p) => p,
+            None => return Ok(vec![]),
+        };
+        if is_workdir {
+            let workdir = match repo.workdir() {
+                Some(p) => p,
+                None => bail!("Repo doesn't have a working directory."),
             };
-
-            let mut revert_file = repo.path().to_path_buf();
-            revert_file.push("REVERT_HEAD");
-            let revert_head_string = fs::read_to_string(revert_file)?;
-            let sha = revert_head_string.trim();
-
-            let commit_from_op = repo.find_commit(Oid::from_str(sha)?)?;
-
-            let committer = repo.signature()?;
-
-            let mut new_full_message = String::from("Revert \"") + GitManager::get_utf8_string(commit_from_op.summary(), "Commit Summary")? + "\"";
-            let (message_without_summary, uses_crlf) = GitManager::get_message_without_summary(GitManager::get_utf8_string(commit_from_op.message(), "Commit Message")?);
-            if uses_crlf {
-                new_full_message += "\r\n\r\n";
-            } else {
-                new_full_message += "\n\n";
+            let full_path = GitManager::get_workdir_file_path(workdir, path);
+            if !full_path.is_file() {
+                return Ok(vec![]);
             }
-            new_full_message += message_without_summary.as_str();
-
-            self.git_commit(new_full_message, &commit_from_op.author(), &committer, vec![&head_commit])?;
-
-            repo.cleanup_state()?;
+            return GitManager::run_textconv(textconv_command, &full_path);
+        }
+        if diff_file.id().is_zero() || !repo.odb()?.exists(diff_file.id()) {
+            if change_type == "unstaged" || change_type == "workdir" {
+                return Ok(vec![]);
+            }
+            bail!("Blob for {} not found.", path.display());
         }
 
-        Ok(())
+        // Like git, give the command a temp file with the same extension since some tools go by the file name.
+        let mut temp_name = format!("oxidized_git_textconv_{}", diff_file.id());
+        if let Some(extension) = path.extension() {
+            temp_name.push('.');
+            temp_name.push_str(&extension.to_string_lossy());
+        }
+        let temp_path = std::env::temp_dir().join(temp_name);
+        fs::write(&temp_path, repo.find_blob(diff_file.id())?.content())?;
+        let text_result = GitManager::run_textconv(textconv_command, &temp_path);
+        fs::remove_file(&temp_path)?;
+        text_result
     }
 
-    pub fn git_continue_merge(&self) -> Result<()> {
-        if !self.has_conflicts()? {
-            let repo = self.borrow_repo()?;
+    // Git runs textconv through the shell with the file appended as an argument, so commands with their own arguments work.
+    fn run_textconv(textconv_command: &str, file_path: &Path) -> Result<Vec<u8>> {
+        let output = new_tool_command("sh")?
+            .arg("-c")
+            .arg(format!("{} \"$@\"", textconv_command))
+            .arg(textconv_command)
+            .arg(file_path)
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            bail!("textconv command '{}' failed: {}", textconv_command, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(output.stdout)
+    }
 
-            let head_commit = match repo.head()?.target() {
-                Some(oid) => repo.find_commit(oid)?,
-                None => bail!("HEAD doesn't have a target commit (which is where a merge operation starts on), can't complete merge."),
+    // The config and gitattributes that decide how line endings are converted for a file, to explain line ending churn.
+    fn get_eol_settings(&self, path: &str) -> Result<Vec<String>> {
+        let repo = self.borrow_repo()?;
+        let mut eol_settings = vec![];
+        let config = repo.config()?;
+        for config_name in ["core.autocrlf", "core.eol"] {
+            if let Ok(value) = config.get_string(config_name) {
+                eol_settings.push(format!("{}={}", config_name, value));
+            }
+        }
+        let decoded_path = decode_path(path)?;
+        for attr_name in ["text", "eol"] {
+            let attr_value = match AttrValue::from_string(repo.get_attr(&decoded_path, attr_name, AttrCheckFlags::FILE_THEN_INDEX)?) {
+                AttrValue::True => String::from(attr_name),
+                AttrValue::False => format!("-{}", attr_name),
+                AttrValue::String(s) => format!("{}={}", attr_name, s),
+                _ => continue,
             };
-
-            let mut merge_file = repo.path().to_path_buf();
-            merge_file.push("MERGE_HEAD");
-            let merge_head_string = fs::read_to_string(merge_file)?;
-            let sha = merge_head_string.trim();
-
-            let commit_from_op = repo.find_commit(Oid::from_str(sha)?)?;
-
-            let mut short_sha = String::from(sha);
-            short_sha.truncate(5);
-
-            let mut head_short_sha = head_commit.id().to_string();
-            head_short_sha.truncate(5);
-            let message = String::from("Merge commit ") + short_sha.as_str() + " into commit " + head_short_sha.as_str();
-
-            let committer = repo.signature()?;
-            self.git_commit(message, &committer, &committer, vec![&head_commit, &commit_from_op])?;
-
-            repo.cleanup_state()?;
+            eol_settings.push(format!("{} (.gitattributes)", attr_value));
         }
+        Ok(eol_settings)
+    }
 
+    // Re-applies the text and eol attributes to every tracked file in the index, like after changing .gitattributes.
+    pub fn git_renormalize(&self) -> Result<()> {
+        self.ensure_writable()?;
+        let repo = self.borrow_repo()?;
+        let working_dir = match repo.workdir() {
+            Some(d) => d,
+            None => bail!("Can't renormalize a repo without a working directory."),
+        };
+        // libgit2 only re-adds files whose stat info changed, so this needs the CLI's add --renormalize.
+        let output = new_tool_command("git")?
+            .args(["add", "--renormalize", "."])
+            .current_dir(working_dir)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            bail!("Failed to renormalize: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
         Ok(())
     }
 
-    pub fn git_abort_rebase(&self) -> Result<()> {
+    // Uses the linguist-language gitattribute if it's set, otherwise guesses from the file name.
+    pub fn get_file_language(&self, path: &str) -> Result<String> {
         let repo = self.borrow_repo()?;
-        let mut rebase = repo.open_rebase(None)?;
+        let attr_value = repo.get_attr(&decode_path(path)?, "linguist-language", AttrCheckFlags::FILE_THEN_INDEX)?;
+        if let AttrValue::String(language) = AttrValue::from_string(attr_value) {
+            return Ok(language.to_lowercase());
+        }
+        let file_name = match path.rsplit_once('/') {
+            Some((_, name)) => name,
+            None => path,
+        };
+        Ok(String::from(get_language_from_file_name(file_name)))
+    }
 
-        rebase.abort()?;
-        Ok(())
+    fn get_max_blob_size() -> Result<usize> {
+        let config = config_manager::get_config()?;
+        Ok(config.borrow_max_blob_size().unwrap_or(config_manager::DEFAULT_MAX_BLOB_SIZE))
     }
 
-    pub fn git_continue_rebase(&self) -> Result<()> {
+    // Returns why a blob can't be shown (binary or too large), without loading large blobs into memory.
+    pub fn check_blob(&self, oid: Oid) -> Result<Option<BlobContent>> {
         let repo = self.borrow_repo()?;
-        let mut rebase = repo.open_rebase(None)?;
-
-        // Need to make sure there are no conflicts and we've committed before continuing.
-        if self.has_conflicts()? || self.has_unstaged_changes()? {
-            return Ok(());
-        } else if self.has_staged_changes()? {
-            rebase.commit(None, &repo.signature()?, None)?;
+        let (size, _) = repo.odb()?.read_header(oid)?;
+        if size > GitManager::get_max_blob_size()? {
+            return Ok(Some(BlobContent::TooLarge(size)));
+        }
+        if repo.find_blob(oid)?.is_binary() {
+            return Ok(Some(BlobContent::Binary(size)));
         }
+        Ok(None)
+    }
 
-        self.iterate_through_rebase(repo, &mut rebase)?;
+    // Same as check_blob, but for a file in the working directory.
+    pub fn check_workdir_file(&self, path: &str) -> Result<Option<BlobContent>> {
+        let repo = self.borrow_repo()?;
+        let workdir = match repo.workdir() {
+            Some(p) => p,
+            None => bail!("Repo doesn't have a working directory."),
+        };
+        let full_path = GitManager::get_workdir_file_path(workdir, &decode_path(path)?);
+        if !full_path.is_file() {
+            return Ok(None);
+        }
 
-        Ok(())
+        let size = fs::metadata(&full_path)?.len() as usize;
+        if size > GitManager::get_max_blob_size()? {
+            return Ok(Some(BlobContent::TooLarge(size)));
+        }
+        let mut buffer = vec![0; BINARY_CHECK_SIZE];
+        let bytes_read = fs::File::open(&full_path)?.read(&mut buffer)?;
+        if buffer[..bytes_read].contains(&0) {
+            return Ok(Some(BlobContent::Binary(size)));
+        }
+        Ok(None)
     }
 
-    pub fn git_reset(&self, json_str: &str) -> Result<()> {
+    pub fn load_blob(&self, oid: Oid) -> Result<BlobContent> {
+        if let Some(blob_content) = self.check_blob(oid)? {
+            return Ok(blob_content);
+        }
         let repo = self.borrow_repo()?;
+        let blob = repo.find_blob(oid)?;
+        Ok(BlobContent::Text(String::from_utf8_lossy(blob.content()).into_owned()))
+    }
 
-        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
-
-        let sha = match json_hm.get("sha") {
-            Some(s) => s,
-            None => bail!("sha wasn't included in payload from the front-end."),
-        };
-        let reset_type_string = match json_hm.get("type") {
-            Some(s) => s,
-            None => bail!("type wasn't included in payload from the front-end."),
+    // A file that was deleted from the working directory loads as empty.
+    pub fn load_workdir_file(&self, path: &str) -> Result<BlobContent> {
+        if let Some(blob_content) = self.check_workdir_file(path)? {
+            return Ok(blob_content);
+        }
+        let repo = self.borrow_repo()?;
+        let workdir = match repo.workdir() {
+            Some(p) => p,
+            None => bail!("Repo doesn't have a working directory."),
         };
+        let full_path = GitManager::get_workdir_file_path(workdir, &decode_path(path)?);
+        if !full_path.is_file() {
+            return Ok(BlobContent::Text(String::new()));
+        }
+        Ok(BlobContent::Text(String::from_utf8_lossy(&fs::read(full_path)?).into_owned()))
+    }
 
-        let reset_type;
-        if reset_type_string == "soft" {
-            reset_type = ResetType::Soft;
-        } else if reset_type_string == "mixed" {
-            reset_type = ResetType::Mixed;
-        } else if reset_type_string == "hard" {
-            reset_type = ResetType::Hard;
-        } else {
-            bail!("type from front-end payload isn't a valid option. Choices are 'soft', 'mixed', or 'hard'");
+    // Finds the path a file was renamed or copied from in a commit's first parent, using the thresholds from the config.
+    fn find_file_source(&self, commit: &Commit, path: &str, detect_copies: bool) -> Result<Option<(String, Delta)>> {
+        let repo = self.borrow_repo()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(c) => c.tree()?,
+            Err(_) => return Ok(None),
+        };
+        let mut diff_options = new_diff_options()?;
+        // Finding copies of files that weren't changed needs the unchanged files in the diff too.
+        diff_options.include_unmodified(detect_copies);
+        let mut diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&commit.tree()?), Some(&mut diff_options))?;
+
+        let config = config_manager::get_config()?;
+        let mut diff_find_options = DiffFindOptions::new();
+        diff_find_options.renames(true);
+        diff_find_options.rename_threshold(config.borrow_rename_threshold().unwrap_or(config_manager::DEFAULT_SIMILARITY_THRESHOLD));
+        if detect_copies {
+            diff_find_options.copies(true);
+            diff_find_options.copies_from_unmodified(true);
+            diff_find_options.copy_threshold(config.borrow_copy_threshold().unwrap_or(config_manager::DEFAULT_SIMILARITY_THRESHOLD));
         }
+        diff.find_similar(Some(&mut diff_find_options))?;
 
-        let commit = repo.find_commit(Oid::from_str(sha)?)?;
+        for delta in diff.deltas() {
+            if (delta.status() == Delta::Renamed || delta.status() == Delta::Copied) && delta.new_file().path() == Some(&decode_path(path)?) {
+                if let Some(old_path) = delta.old_file().path() {
+                    return Ok(Some((encode_path(old_path)?, delta.status())));
+                }
+            }
+        }
+        Ok(None)
+    }
 
-        repo.reset(commit.as_object(), reset_type, None)?;
+    // Gets a commit's first parent and the path the file came from in it, following renames and copies, along with a patch
+    // between the two with no context lines. If only_if_added is set, the file has to be new under this path in the commit.
+    fn get_parent_file_patch(&self, commit_oid: Oid, path: &str, only_if_added: bool, detect_copies: bool) -> Result<Option<(Oid, String, Patch)>> {
+        let repo = self.borrow_repo()?;
+        let commit = repo.find_commit(commit_oid)?;
+        let parent_commit = match commit.parent(0) {
+            Ok(c) => c,
+            Err(_) => return Ok(None),
+        };
+        let parent_tree = parent_commit.tree()?;
+        let parent_path = if parent_tree.get_path(&decode_path(path)?).is_ok() {
+            if only_if_added {
+                return Ok(None);
+            }
+            String::from(path)
+        } else {
+            match self.find_file_source(&commit, path, detect_copies)? {
+                Some((p, _)) => p,
+                None => return Ok(None),
+            }
+        };
 
-        Ok(())
+        let old_blob = repo.find_blob(parent_tree.get_path(&decode_path(&parent_path)?)?.id())?;
+        let new_blob = repo.find_blob(commit.tree()?.get_path(&decode_path(path)?)?.id())?;
+        let mut diff_options = new_diff_options()?;
+        diff_options.context_lines(0);
+        let patch = Patch::from_blobs(&old_blob, None, &new_blob, None, Some(&mut diff_options))?;
+        Ok(Some((parent_commit.id(), parent_path, patch)))
     }
 
-    pub fn git_add_remote(&self, json_str: &str) -> Result<()> {
+    pub fn get_file_history(&self, json_str: &str) -> Result<Vec<FileHistoryEntry>> {
         let repo = self.borrow_repo()?;
-
         let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
-        let remote_name = match json_hm.get("remote_name") {
+        let file_path = match json_hm.get("file_path") {
             Some(s) => s,
-            None => bail!("remote_name not included in payload from the front-end"),
+            None => bail!("file_path not included in payload from front-end."),
         };
-        let remote_url = match json_hm.get("remote_url") {
+        let sha = match json_hm.get("sha") {
             Some(s) => s,
-            None => bail!("remote_url not included in payload from the front-end"),
+            None => bail!("sha not included in payload from front-end."),
+        };
+        let follow_renames = match json_hm.get("follow_renames") {
+            Some(s) => s == "true",
+            None => true,
+        };
+        let detect_copies = match json_hm.get("detect_copies") {
+            Some(s) => s == "true",
+            None => false,
         };
 
-        repo.remote(remote_name.as_str(), remote_url.as_str())?;
-
-        Ok(())
-    }
-
-    fn git_checkout(&self, local_ref: &Reference) -> Result<()> {
-        let repo = self.borrow_repo()?;
+        let mut revwalk = repo.revwalk()?;
+        if sha == "" {
+            revwalk.push_head()?;
+        } else {
+            revwalk.push(Oid::from_str(sha)?)?;
+        }
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+        let mut current_path = file_path.clone();
+        let mut decoded_path = decode_path(&current_path)?;
+        let mut file_history = vec![];
+        for oid_result in revwalk {
+            let commit = repo.find_commit(oid_result?)?;
+            let entry_oid = match commit.tree()?.get_path(&decoded_path) {
+                Ok(e) => e.id(),
+                Err(_) => continue,
+            };
 
-        let local_full_name = GitManager::get_utf8_string(local_ref.name(), "Branch Name")?;
-        let commit = match local_ref.target() {
-            Some(oid) => repo.find_commit(oid)?,
-            None => bail!("Trying to check out branch that has no target commit."),
-        };
-        let tree = commit.tree()?;
+            // Like git log, skip commits (usually merges) where the file is the same as in one of the parents.
+            let mut parent_entry_oids = vec![];
+            for parent_commit in commit.parents() {
+                if let Ok(parent_entry) = parent_commit.tree()?.get_path(&decoded_path) {
+                    parent_entry_oids.push(parent_entry.id());
+                }
+            }
+            if parent_entry_oids.contains(&entry_oid) {
+                continue;
+            }
 
-        repo.checkout_tree(tree.as_object(), None)?;
-        repo.set_head(local_full_name)?;
-        Ok(())
-    }
+            let mut old_path = None;
+            let status = if parent_entry_oids.len() > 0 {
+                "modified"
+            } else if commit.parent_count() > 0 && follow_renames {
+                match self.find_file_source(&commit, &current_path, detect_copies)? {
+                    Some((source_path, delta)) => {
+                        old_path = Some(source_path);
+                        if delta == Delta::Copied { "copied" } else { "renamed" }
+                    },
+                    None => "added",
+                }
+            } else {
+                "added"
+            };
 
-    pub fn git_checkout_from_json(&self, json_str: &str) -> Result<()> {
-        let ref_name_value: Value = serde_json::from_str(json_str)?;
-        let ref_name: &str = GitManager::get_string_from_serde_string(ref_name_value.as_str())?;
-        self.git_checkout(&self.borrow_repo()?.find_reference(ref_name)?)?;
-        Ok(())
+            let author = commit.author();
+            file_history.push(FileHistoryEntry {
+                sha: commit.id().to_string(),
+                path: current_path.clone(),
+                old_path: old_path.clone(),
+                status: String::from(status),
+                author_name: String::from(GitManager::get_utf8_string(author.name(), "Author Name")?),
+                author_time: author.when().seconds(),
+                summary: String::from(GitManager::get_utf8_string(commit.summary(), "Commit Summary")?),
+            });
+            if let Some(p) = old_path {
+                decoded_path = decode_path(&p)?;
+                current_path = p;
+            }
+        }
+        Ok(file_history)
     }
 
-    pub fn git_checkout_detached_head(&self, json_str: &str) -> Result<()> {
-        let sha_value: Value = serde_json::from_str(json_str)?;
-        let sha: &str = GitManager::get_string_from_serde_string(sha_value.as_str())?;
+    // Reads the revisions blame should skip from blame.ignoreRevsFile, or .git-blame-ignore-revs if that isn't set.
+    fn get_ignore_revs(&self) -> Result<HashSet<Oid>> {
         let repo = self.borrow_repo()?;
-
-        let oid = Oid::from_str(sha)?;
-        let tree = repo.find_commit(oid)?.tree()?;
-
-        repo.checkout_tree(tree.as_object(), None)?;
-        repo.set_head_detached(oid)?;
-        Ok(())
+        let mut ignore_revs = HashSet::new();
+        let workdir = match repo.workdir() {
+            Some(p) => p,
+            None => return Ok(ignore_revs),
+        };
+        let ignore_revs_file_name = match repo.config()?.get_string("blame.ignoreRevsFile") {
+            Ok(s) => s,
+            Err(_) => String::from(".git-blame-ignore-revs"),
+        };
+        let ignore_revs_path = GitManager::get_workdir_file_path(workdir, Path::new(&ignore_revs_file_name));
+        if !ignore_revs_path.is_file() {
+            return Ok(ignore_revs);
+        }
+        for line in fs::read_to_string(ignore_revs_path)?.lines() {
+            let rev = match line.split('#').next() {
+                Some(r) => r.trim(),
+                None => continue,
+            };
+            if rev == "" {
+                continue;
+            }
+            // Skip revisions that don't exist (e.g. ones from a branch that was never fetched) like git does.
+            if let Ok(commit) = repo.revparse_single(rev).and_then(|o| o.peel_to_commit()) {
+                ignore_revs.insert(commit.id());
+            }
+        }
+        Ok(ignore_revs)
     }
 
-    pub fn git_checkout_remote(&self, json_string: &str) -> Result<()> {
+    pub fn get_blame(&self, json_str: &str) -> Result<BlameInfo> {
         let repo = self.borrow_repo()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let file_path = match json_hm.get("file_path") {
+            Some(s) => s,
+            None => bail!("file_path not included in payload from front-end."),
+        };
+        let sha = match json_hm.get("sha") {
+            Some(s) => s,
+            None => bail!("sha not included in payload from front-end."),
+        };
+        // Ignored revisions are used by default since that's what git does when the file is set up.
+        let use_ignore_revs = match json_hm.get("use_ignore_revs") {
+            Some(s) => s == "true",
+            None => true,
+        };
+        let detect_copies = match json_hm.get("detect_copies") {
+            Some(s) => s == "true",
+            None => false,
+        };
 
-        let json_hm: HashMap<String, String> = serde_json::from_str(json_string)?;
-        let remote_branch_shortname = match json_hm.get("branch_shorthand") {
-            Some(n) => n,
-            None => bail!("JSON Data is missing branch_shorthand attribute."),
+        let commit = if sha == "" {
+            repo.head()?.peel_to_commit()?
+        } else {
+            repo.find_commit(Oid::from_str(sha)?)?
         };
-        let remote_branch_full_name = match json_hm.get("full_branch_name") {
-            Some(n) => n,
-            None => bail!("JSON Data is missing full_branch_name attribute."),
+        let blob_oid = commit.tree()?.get_path(&decode_path(file_path)?)?.id();
+        let content = match self.load_blob(blob_oid)? {
+            BlobContent::Text(t) => t,
+            BlobContent::Binary(_) => bail!("Can't blame a binary file."),
+            BlobContent::TooLarge(_) => bail!("File is too large to blame."),
+        };
+        let ignore_revs = if use_ignore_revs {
+            self.get_ignore_revs()?
+        } else {
+            HashSet::new()
         };
 
-        // Look for a local branch that already exists for the specified remote branch. If one exists,
-        // check it out instead.
-        for local_b_result in repo.branches(Some(BranchType::Local))? {
-            let (local_b, _) = local_b_result?;
-            let local_upstream = match local_b.upstream() {
-                Ok(b) => b,
-                Err(_) => {
-                    continue;
-                },
-            };
-            let local_upstream_full_name = GitManager::get_utf8_string(local_upstream.get().name(), "Branch Name")?;
-            if local_upstream_full_name == remote_branch_full_name {
-                return self.git_checkout(local_b.get());
+        let mut blames = HashMap::new();
+        let mut parent_patches: HashMap<(Oid, String), Option<(Oid, String, Patch)>> = HashMap::new();
+        let mut commit_summaries: HashMap<Oid, (String, i64, String)> = HashMap::new();
+        let mut blame_lines = vec![];
+        for (line_index, line_content) in content.lines().enumerate() {
+            let mut blamed_oid = commit.id();
+            let mut blamed_path = file_path.clone();
+            let mut blamed_line_number = line_index + 1;
+            let mut ignored_sha = None;
+            // Keep passing the line to the parent commit until it lands on a commit that isn't ignored and didn't
+            // get the file from somewhere else.
+            loop {
+                let blame = match blames.get(&(blamed_oid, blamed_path.clone())) {
+                    Some(b) => b,
+                    None => {
+                        let blame = repo.blame_file(&decode_path(&blamed_path)?, Some(BlameOptions::new().newest_commit(blamed_oid)))?;
+                        blames.entry((blamed_oid, blamed_path.clone())).or_insert(blame)
+                    },
+                };
+                let hunk = match blame.get_line(blamed_line_number) {
+                    Some(h) => h,
+                    None => bail!("Blame is missing line {} of {}.", blamed_line_number, blamed_path),
+                };
+                let hunk_oid = hunk.final_commit_id();
+                let hunk_path = match hunk.path() {
+                    Some(p) => encode_path(p)?,
+                    None => blamed_path.clone(),
+                };
+                let hunk_line_number = hunk.orig_start_line() + blamed_line_number - hunk.final_start_line();
+                blamed_oid = hunk_oid;
+                blamed_path = hunk_path;
+                blamed_line_number = hunk_line_number;
+
+                let is_ignored = ignore_revs.contains(&hunk_oid);
+                let parent_patch_key = (hunk_oid, blamed_path.clone());
+                if !parent_patches.contains_key(&parent_patch_key) {
+                    // libgit2 only follows renames with its own threshold, so check for anything it missed when the commit added the file.
+                    let parent_patch = self.get_parent_file_patch(hunk_oid, &blamed_path, !is_ignored, detect_copies)?;
+                    parent_patches.insert(parent_patch_key.clone(), parent_patch);
+                }
+                let (parent_oid, parent_path, parent_line_number) = match parent_patches.get(&parent_patch_key) {
+                    Some(Some((parent_oid, parent_path, patch))) => match get_old_line_number(patch, blamed_line_number)? {
+                        Some(n) => (*parent_oid, parent_path.clone(), n),
+                        None => break,
+                    },
+                    _ => break,
+                };
+                if is_ignored {
+                    ignored_sha = Some(hunk_oid.to_string());
+                }
+                blamed_oid = parent_oid;
+                blamed_path = parent_path;
+                blamed_line_number = parent_line_number;
             }
-        }
 
-        // If there's no local branch, create a new one with the name used by the remote branch.
-        let remote_branch_name_parts: Vec<&str> = remote_branch_shortname.split("/").collect();
-        let mut local_branch_shortname = String::new();
-        for i in 1..remote_branch_name_parts.len() {
-            local_branch_shortname += remote_branch_name_parts[i];
-            if i < remote_branch_name_parts.len() - 1 {
-                local_branch_shortname += "/";
+            if !commit_summaries.contains_key(&blamed_oid) {
+                let blamed_commit = repo.find_commit(blamed_oid)?;
+                let author_name = String::from(GitManager::get_utf8_string(blamed_commit.author().name(), "Author Name")?);
+                let summary = String::from(GitManager::get_utf8_string(blamed_commit.summary(), "Commit Summary")?);
+                commit_summaries.insert(blamed_oid, (author_name, blamed_commit.author().when().seconds(), summary));
             }
+            let (author_name, author_time, summary) = match commit_summaries.get(&blamed_oid) {
+                Some(c) => c.clone(),
+                None => bail!("Commit summary missing for {}. This should never happen.", blamed_oid),
+            };
+            blame_lines.push(BlameLine {
+                line_number: line_index + 1,
+                content: String::from(line_content),
+                sha: blamed_oid.to_string(),
+                author_name,
+                author_time,
+                summary,
+                orig_path: blamed_path,
+                orig_line_number: blamed_line_number,
+                ignored_sha,
+            });
         }
-        let remote_branch = repo.find_branch(remote_branch_shortname, BranchType::Remote)?;
-        let commit = match remote_branch.get().target() {
-            Some(oid) => repo.find_commit(oid)?,
-            None => bail!("Selected remote branch isn't targeting a commit, can't checkout!"),
-        };
-        let mut local_branch = repo.branch(&*local_branch_shortname, &commit, false)?;
-        local_branch.set_upstream(Some(remote_branch_shortname))?;
 
-        self.git_checkout(local_branch.get())
+        Ok(BlameInfo {
+            file_path: file_path.clone(),
+            sha: commit.id().to_string(),
+            ignored_revs: ignore_revs.iter().map(|oid| oid.to_string()).collect(),
+            lines: blame_lines,
+        })
     }
 
-    fn git_stage(&self, status: u8, path: &String) -> Result<()> {
+    pub fn git_stage_all(&self) -> Result<Vec<LargeFile>> {
+        self.ensure_writable()?;
         let repo = self.borrow_repo()?;
 
         let mut index = repo.index()?;
-        if status == 2 {  // If file is deleted
-            index.remove_path(path.as_ref())?;
-        } else {
-            index.add_path(path.as_ref())?;
-        }
+        index.add_all(&["."], IndexAddOption::DEFAULT, None)?;
         index.write()?;
 
-        Ok(())
+        self.get_large_staged_files(&self.get_staged_changes()?, None)
     }
 
-    pub fn git_stage_from_json(&self, json_str: &str) -> Result<()> {
-        let diff_delta: ParseableDiffDelta = serde_json::from_str(json_str)?;
+    // Ignored directories are listed once instead of every file in them, like git status --ignored.
+    pub fn get_ignored_files(&self, json_string: &str) -> Result<Vec<IgnoredFile>> {
+        let directory: String = serde_json::from_str(json_string)?;
+        let repo = self.borrow_repo()?;
+        let working_dir = match repo.workdir() {
+            Some(d) => d,
+            None => bail!("Can't list ignored files in a repo without a working directory."),
+        };
 
-        self.git_stage(diff_delta.get_status(), diff_delta.get_path())?;
+        let mut status_options = StatusOptions::new();
+        // Untracked directories have to be walked too, otherwise ignored files inside them are missed.
+        status_options.include_ignored(true).include_untracked(true).recurse_untracked_dirs(true).recurse_ignored_dirs(false);
+        if !directory.is_empty() {
+            status_options.pathspec(directory.as_str());
+        }
+        let mut ignored_paths: Vec<Vec<u8>> = vec![];
+        for status_entry in repo.statuses(Some(&mut status_options))?.iter() {
+            if status_entry.status().is_ignored() {
+                ignored_paths.push(status_entry.path_bytes().to_vec());
+            }
+        }
+        if ignored_paths.is_empty() {
+            return Ok(vec![]);
+        }
 
-        Ok(())
+        // libgit2 can say if a path is ignored, but not by which rule, so ask the CLI like git check-ignore -v would.
+        let mut input = vec![];
+        for path in &ignored_paths {
+            input.extend_from_slice(path);
+            input.push(0);
+        }
+        let mut command = new_tool_command("git")?;
+        command.args(["check-ignore", "-v", "-n", "-z", "--stdin"]).current_dir(working_dir);
+        let output = GitManager::run_with_stdin(command, &input)?;
+        // Exit code 1 just means nothing matched.
+        if !output.status.success() && output.status.code() != Some(1) {
+            bail!("Failed to check ignore rules: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        // With -z each path is 4 fields: source, line number, pattern, and path (the first 3 are empty if nothing matched).
+        let fields: Vec<&[u8]> = output.stdout.split(|b| *b == 0).collect();
+        let mut ignored_files = vec![];
+        for chunk in fields.chunks_exact(4) {
+            ignored_files.push(IgnoredFile {
+                path: encode_path_bytes(chunk[3]),
+                source: String::from_utf8_lossy(chunk[0]).to_string(),
+                line_number: String::from_utf8_lossy(chunk[1]).to_string(),
+                pattern: String::from_utf8_lossy(chunk[2]).to_string(),
+            });
+        }
+        Ok(ignored_files)
     }
 
-    pub fn git_unstage(&self, json_str: &str) -> Result<()> {
+    // Stages an ignored file anyway, like git add --force.
+    pub fn git_force_add(&self, json_string: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let path_string: String = serde_json::from_str(json_string)?;
+        let path = decode_path(path_string.as_str())?;
         let repo = self.borrow_repo()?;
-        let diff_delta: ParseableDiffDelta = serde_json::from_str(json_str)?;
 
         let mut index = repo.index()?;
-        let status = diff_delta.get_status();
-        if status == 2 || status == 3 {  // If file is deleted or modified
-            let head_commit = match repo.head()?.target() {
-                Some(oid) => {
-                    repo.find_commit(oid)?
-                },
-                None => bail!("Head has no target commit"),
-            };
-            repo.reset_default(Some(head_commit.as_object()), [diff_delta.get_path()])?;
-        } else {
-            index.remove_path(diff_delta.get_path().as_ref())?;
-        }
+        index.add_all([&path], IndexAddOption::FORCE, None)?;
         index.write()?;
 
         Ok(())
     }
 
-    fn set_diff_find_similar(diff: &mut Diff) -> Result<()> {
-        let mut opts = DiffFindOptions::new();
-        opts.renames(true);
-        opts.copies(true);
+    // Gets the staged files over the large file warning size, optionally only the ones at or under a path that was just staged.
+    fn get_large_staged_files(&self, staged_diff: &Diff, under_path_opt: Option<&Path>) -> Result<Vec<LargeFile>> {
+        let repo = self.borrow_repo()?;
+        let large_file_warning_size = config_manager::get_config()?.borrow_large_file_warning_size().unwrap_or(config_manager::DEFAULT_LARGE_FILE_WARNING_SIZE);
+        let odb = repo.odb()?;
+        let mut large_files = vec![];
+        for delta in staged_diff.deltas() {
+            let new_file = delta.new_file();
+            // Submodules are only a commit id in the index, so there's no blob to check the size of.
+            if delta.status() == Delta::Deleted || new_file.mode() == FileMode::Commit {
+                continue;
+            }
+            let (path, path_bytes) = match (new_file.path(), new_file.path_bytes()) {
+                (Some(p), Some(b)) => (p, b),
+                _ => continue,
+            };
+            if let Some(under_path) = under_path_opt {
+                if !path.starts_with(under_path) {
+                    continue;
+                }
+            }
+            let size = match odb.read_header(new_file.id()) {
+                Ok((s, _)) => s,
+                Err(_) => continue,
+            };
+            if size > large_file_warning_size {
+                let suggested_pattern = match path.extension() {
+                    Some(extension) => format!("*.{}", extension.to_string_lossy()),
+                    None => path.to_string_lossy().into_owned(),
+                };
+                large_files.push(LargeFile {
+                    path: encode_path_bytes(path_bytes),
+                    display_path: path.to_string_lossy().into_owned(),
+                    size,
+                    suggested_pattern,
+                });
+            }
+        }
+        Ok(large_files)
+    }
 
-        diff.find_similar(Some(&mut opts))?;
-        Ok(())
+    fn find_conflict(index: &Index, path: &str) -> Result<IndexConflict> {
+        for conflict_result in index.conflicts()? {
+            let conflict = conflict_result?;
+            let entry_opt = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref());
+            if entry_opt.map(|e| encode_path_bytes(&e.path) == path).unwrap_or(false) {
+                return Ok(conflict);
+            }
+        }
+        bail!("{} isn't conflicted.", decode_path(path)?.display());
     }
 
-    pub fn get_unstaged_changes(&self) -> Result<Diff> {
+    // Regenerates the conflict markers from the index instead of reading the working directory, so they're there even after the file's been edited.
+    pub fn get_conflicts_info(&self, path: &str) -> Result<ConflictsInfo> {
         let repo = self.borrow_repo()?;
+        let decoded_path = decode_path(path)?;
+        let conflict_style = match repo.config()?.get_string("merge.conflictStyle") {
+            Ok(s) => s,
+            Err(e) if e.code() == ErrorCode::NotFound => String::from("merge"),
+            Err(e) => return Err(e.into()),
+        };
+        if !["merge", "diff3", "zdiff3"].contains(&conflict_style.as_str()) {
+            bail!("Unknown style '{}' given for merge.conflictStyle.", conflict_style);
+        }
 
-        let mut diff_options = DiffOptions::new();
-        diff_options.include_untracked(true);
-        diff_options.recurse_untracked_dirs(true);
-        diff_options.show_untracked_content(true);
+        let conflict = GitManager::find_conflict(&repo.index()?, path)?;
 
-        let mut diff = repo.diff_index_to_workdir(None, Some(&mut diff_options))?;
-        GitManager::set_diff_find_similar(&mut diff)?;
+        let mut conflicts_info = ConflictsInfo {
+            path: String::from(path),
+            display_path: decoded_path.to_string_lossy().into_owned(),
+            conflict_style: conflict_style.clone(),
+            kind: String::new(),
+            content: None,
+            conflict_count: 1,
+        };
+        let (our_entry, their_entry) = match (&conflict.our, &conflict.their) {
+            (Some(o), Some(t)) => (o, t),
+            (None, _) => {
+                conflicts_info.kind = String::from("deleted_by_us");
+                return Ok(conflicts_info);
+            },
+            (_, None) => {
+                conflicts_info.kind = String::from("deleted_by_them");
+                return Ok(conflicts_info);
+            },
+        };
+        conflicts_info.kind = String::from(if conflict.ancestor.is_some() { "both_modified" } else { "both_added" });
 
-        Ok(diff)
+        let our_blob = repo.find_blob(our_entry.id)?;
+        let their_blob = repo.find_blob(their_entry.id)?;
+        let ancestor_blob_opt = match &conflict.ancestor {
+            Some(e) => Some(repo.find_blob(e.id)?),
+            None => None,
+        };
+        if our_blob.is_binary() || their_blob.is_binary() || ancestor_blob_opt.as_ref().map(|b| b.is_binary()).unwrap_or(false) {
+            return Ok(conflicts_info);
+        }
+
+        // git2 doesn't expose libgit2's merge_file, so use git merge-file which also knows zdiff3. Both sides adding the file merges against an empty base like git does.
+        let temp_dir = env::temp_dir();
+        let our_path = temp_dir.join(format!("oxidized_git_merge_{}", our_entry.id));
+        let their_path = temp_dir.join(format!("oxidized_git_merge_{}", their_entry.id));
+        let ancestor_path = temp_dir.join(format!("oxidized_git_merge_{}", conflict.ancestor.as_ref().map(|e| e.id).unwrap_or(Oid::zero())));
+        fs::write(&our_path, our_blob.content())?;
+        fs::write(&their_path, their_blob.content())?;
+        fs::write(&ancestor_path, ancestor_blob_opt.as_ref().map(|b| b.content()).unwrap_or(&[]))?;
+        let mut command = new_tool_command("git")?;
+        command.args(["merge-file", "-p", "-L", "HEAD", "-L", "base", "-L", "theirs"]);
+        // The merge style is merge-file's default and doesn't have a flag.
+        if conflict_style != "merge" {
+            command.arg(format!("--{}", conflict_style));
+        }
+        let output_result = command
+            .arg(&our_path)
+            .arg(&ancestor_path)
+            .arg(&their_path)
+            .stdin(Stdio::null())
+            .output();
+        for temp_path in [&our_path, &their_path, &ancestor_path] {
+            let _ = fs::remove_file(temp_path);
+        }
+        let output = output_result?;
+        // merge-file exits with the number of conflicts, and a negative number (so 128 or more here) if it failed.
+        match output.status.code() {
+            Some(code) if (0..128).contains(&code) => {
+                conflicts_info.conflict_count = code as usize;
+                conflicts_info.content = Some(String::from_utf8_lossy(&output.stdout).into_owned());
+            },
+            _ => bail!("Failed to merge {}: {}", decoded_path.display(), String::from_utf8_lossy(&output.stderr).trim()),
+        }
+        Ok(conflicts_info)
     }
 
-    pub fn get_staged_changes(&self) -> Result<Diff> {
+    fn read_conflicted_file(&self, path: &str) -> Result<(PathBuf, String)> {
         let repo = self.borrow_repo()?;
-
-        let mut tree = None;
-        match repo.head() {
-            Ok(head_ref) => {
-                let commit = match head_ref.target() {
-                    Some(oid) => Some(repo.find_commit(oid)?),
-                    None => None,
-                };
-                tree = match commit {
-                    Some(c) => Some(c.tree()?),
-                    None => None,
-                };
-            },
-            Err(e) => {
-                if e.code() != ErrorCode::UnbornBranch {
-                    return Err(e.into());
-                }
-            },
+        let working_dir = match repo.workdir() {
+            Some(p) => p,
+            None => bail!("Repo doesn't have a working directory."),
         };
+        let full_path = GitManager::get_workdir_file_path(working_dir, &decode_path(path)?);
+        let contents = match String::from_utf8(fs::read(&full_path)?) {
+            Ok(s) => s,
+            Err(_) => bail!("{} isn't a text file, so its conflicts can't be resolved here.", full_path.display()),
+        };
+        Ok((full_path, contents))
+    }
 
-        let mut diff = repo.diff_tree_to_index(tree.as_ref(), None, None)?;
-        GitManager::set_diff_find_similar(&mut diff)?;
-
-        Ok(diff)
+    pub fn get_conflict_regions(&self, path: &str) -> Result<ConflictRegions> {
+        let (_, contents) = self.read_conflicted_file(path)?;
+        let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+        Ok(ConflictRegions {
+            path: String::from(path),
+            display_path: decode_path(path)?.to_string_lossy().into_owned(),
+            regions: parse_conflict_regions(&lines)?.into_iter().map(|(_, r)| r).collect(),
+            is_staged: false,
+        })
     }
 
-    fn get_file_index_in_diff(diff: &Diff, path: &str) -> Result<usize> {
-        let file_index_opt = diff.deltas().position(|dd| {
-            match dd.new_file().path() {
-                Some(p) => {
-                    match p.to_str() {
-                        Some(s) => path == s,
-                        None => false,
-                    }
-                },
-                None => false,
+    // Replaces one conflict region in the working directory file, and stages the file once there are no conflict markers left.
+    pub fn resolve_conflict_region(&self, json_str: &str) -> Result<ConflictRegions> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let path = match json_hm.get("path") {
+            Some(s) => s,
+            None => bail!("path not included in payload from front-end."),
+        };
+        let region_index = match json_hm.get("region_index") {
+            Some(s) => s.parse::<usize>()?,
+            None => bail!("region_index not included in payload from front-end."),
+        };
+        let resolution = match json_hm.get("resolution") {
+            Some(s) => s,
+            None => bail!("resolution not included in payload from front-end."),
+        };
+
+        let (full_path, contents) = self.read_conflicted_file(path)?;
+        let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+        let regions = parse_conflict_regions(&lines)?;
+        let (line_range, region) = match regions.get(region_index) {
+            Some(r) => r,
+            None => bail!("There's no conflict #{} in {}, it may have been resolved already.", region_index + 1, full_path.display()),
+        };
+        let mut replacement = match resolution.as_str() {
+            "ours" => region.ours.clone(),
+            "theirs" => region.theirs.clone(),
+            "both" => format!("{}{}", region.ours, region.theirs),
+            "custom" => match json_hm.get("text") {
+                Some(s) => s.clone(),
+                None => bail!("text not included in payload from front-end."),
+            },
+            _ => bail!("Unknown conflict resolution: {}", resolution),
+        };
+        // Custom text comes from a textarea, so give it the file's line endings and end it like the lines it replaces.
+        if resolution == "custom" && !replacement.is_empty() {
+            let line_ending = if lines[line_range.start].ends_with("\r\n") { "\r\n" } else { "\n" };
+            replacement = replacement.replace("\r\n", "\n").replace('\n', line_ending);
+            if !replacement.ends_with('\n') && lines[line_range.end - 1].ends_with('\n') {
+                replacement.push_str(line_ending);
             }
-        });
-        match file_index_opt {
-            Some(i) => Ok(i),
-            None => bail!("Selected file not found."),
         }
+
+        let mut new_contents = lines[..line_range.start].concat();
+        new_contents.push_str(&replacement);
+        new_contents.push_str(&lines[line_range.end..].concat());
+        fs::write(&full_path, &new_contents)?;
+
+        let new_lines: Vec<&str> = new_contents.split_inclusive('\n').collect();
+        let remaining_regions: Vec<ConflictRegion> = parse_conflict_regions(&new_lines)?.into_iter().map(|(_, r)| r).collect();
+        let is_staged = remaining_regions.is_empty() && !new_lines.iter().any(|l| is_conflict_marker(l, '=') || is_conflict_marker(l, '|'));
+        if is_staged {
+            let mut index = self.borrow_repo()?.index()?;
+            index.add_path(&decode_path(path)?)?;
+            index.write()?;
+        }
+        Ok(ConflictRegions {
+            path: path.clone(),
+            display_path: decode_path(path)?.to_string_lossy().into_owned(),
+            regions: remaining_regions,
+            is_staged,
+        })
     }
 
-    pub fn get_file_diff(&self, json_str: &str) -> Result<FileInfo> {
-        let repo = self.borrow_repo()?;
+    // Resolves a conflict by taking one side's version of the whole file, for binary files that can't be merged by hand.
+    pub fn resolve_binary(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
         let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
-
-        let file_path = match json_hm.get("file_path") {
+        let path = match json_hm.get("path") {
             Some(s) => s,
-            None => bail!("file_path not returned from front-end payload."),
+            None => bail!("path not included in payload from front-end."),
         };
-        let change_type = match json_hm.get("change_type") {
+        let side = match json_hm.get("side") {
             Some(s) => s,
-            None => bail!("change_type not returned from front-end payload."),
+            None => bail!("side not included in payload from front-end."),
         };
-        let sha = match json_hm.get("sha") {
-            Some(s) => s,
-            None => bail!("sha not returned from front-end payload."),
+
+        let repo = self.borrow_repo()?;
+        let working_dir = match repo.workdir() {
+            Some(p) => p,
+            None => bail!("Repo doesn't have a working directory."),
+        };
+        let decoded_path = decode_path(path)?;
+        let mut index = repo.index()?;
+        let conflict = GitManager::find_conflict(&index, path)?;
+        let entry_opt = match side.as_str() {
+            "ours" => conflict.our,
+            "theirs" => conflict.their,
+            _ => bail!("side must be ours or theirs, not {}.", side),
         };
 
-        let diff;
-        if change_type == "unstaged" {
-            diff = self.get_unstaged_changes()?;
-        } else if change_type == "staged" {
-            diff = self.get_staged_changes()?;
-        } else if change_type == "commit" {
-            let commit = repo.find_commit(Oid::from_str(sha)?)?;
-            diff = get_commit_changes(&commit, &repo)?;
-        } else {
-            bail!("change_type not a valid type. Needs to be 'staged', 'unstaged', or 'commit'");
+        let full_path = GitManager::get_workdir_file_path(working_dir, &decoded_path);
+        match entry_opt {
+            Some(entry) => {
+                if let Some(parent) = full_path.parent() {
+                    create_dir_all(parent)?;
+                }
+                fs::write(&full_path, repo.find_blob(entry.id)?.content())?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    let mode = if entry.mode == i32::from(FileMode::BlobExecutable) as u32 { 0o755 } else { 0o644 };
+                    fs::set_permissions(&full_path, fs::Permissions::from_mode(mode))?;
+                }
+                index.add_path(&decoded_path)?;
+            },
+            // The chosen side deleted the file, so resolving to it means deleting it too.
+            None => {
+                if full_path.exists() {
+                    fs::remove_file(&full_path)?;
+                }
+                index.remove_path(&decoded_path)?;
+            },
         }
+        index.write()?;
+        Ok(())
+    }
 
-        let file_index = GitManager::get_file_index_in_diff(&diff, file_path.as_str())?;
+    // Checks everything that could stop a commit (or make it one to regret) up front, so all the problems can be shown at once.
+    pub fn validate_commit(&self) -> Result<Vec<CommitProblem>> {
+        let repo = self.borrow_repo()?;
+        let mut problems = vec![];
+
+        let index = repo.index()?;
+        if index.has_conflicts() {
+            let mut conflicted_paths = vec![];
+            for conflict_result in index.conflicts()? {
+                let conflict = conflict_result?;
+                if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+                    conflicted_paths.push(String::from_utf8_lossy(&entry.path).into_owned());
+                }
+            }
+            problems.push(CommitProblem::new("unresolved_conflicts", "error", String::from("There are unresolved conflicts."), conflicted_paths));
+        }
 
-        let patch_opt = Patch::from_diff(&diff, file_index)?;
-        let mut file_lines = vec![];
-        let file_type = String::from(file_path.split(".").last().unwrap_or(""));
-        match patch_opt {
-            Some(mut patch) => {
-                patch.print(&mut |_diff_delta, _diff_hunk_opt, diff_line| {
-                    if diff_line.origin_value() == DiffLineType::FileHeader {
-                        if let Ok(s) = get_content_from_diff_line(&diff_line) {
-                            // Include file header if filemode has changed or the file was renamed.
-                            let is_filemode_change = !s.contains("+++") && (s.contains("old mode") || s.contains("new mode"));
-                            let is_renamed_file = s.contains("rename") || s.contains("similarity");
-                            if is_filemode_change || is_renamed_file {
-                                file_lines.push(LineInfo::SomeSeparator(s));
-                            }
-                        }
-                    } else if diff_line.origin_value() == DiffLineType::HunkHeader {
-                        if let Ok(s) = get_content_from_diff_line(&diff_line) {
-                            file_lines.push(LineInfo::SomeSeparator(s));
-                        }
-                    } else if let Ok(fli) = FileLineInfo::from_diff_line(diff_line, &file_type) {
-                        file_lines.push(LineInfo::SomeFileLineInfo(fli));
+        if let Err(e) = repo.signature() {
+            problems.push(CommitProblem::new("missing_identity", "error", format!("No name or email is set to commit with, set user.name and user.email in the git config: {}", e.message()), vec![]));
+        }
+
+        let diff = self.get_staged_changes()?;
+        if diff.deltas().len() == 0 {
+            problems.push(CommitProblem::new("nothing_staged", "error", String::from("Nothing is staged."), vec![]));
+        }
+
+        let config = config_manager::get_config()?;
+        let forbidden_paths = config.borrow_forbidden_paths().clone().unwrap_or_default();
+        let forbidden_pathspec_opt = if forbidden_paths.is_empty() {
+            None
+        } else {
+            Some(Pathspec::new(forbidden_paths.iter())?)
+        };
+
+        let mut forbidden_matches = vec![];
+        if let Some(forbidden_pathspec) = &forbidden_pathspec_opt {
+            for delta in diff.deltas() {
+                if delta.status() == Delta::Deleted {
+                    continue;
+                }
+                if let Some(path) = delta.new_file().path() {
+                    if forbidden_pathspec.matches_path(path, PathspecFlags::DEFAULT) {
+                        forbidden_matches.push(path.to_string_lossy().into_owned());
                     }
-                    true
-                })?;
-            },
-            None => bail!("Patch not found in diff."),
+                }
+            }
+        }
+        let large_files = self.get_large_staged_files(&diff, None)?;
+        if !large_files.is_empty() {
+            let large_file_warning_size = config.borrow_large_file_warning_size().unwrap_or(config_manager::DEFAULT_LARGE_FILE_WARNING_SIZE);
+            let message = format!("These files are bigger than {:.1} MB, consider Git LFS since they'll stay in the history for good.", large_file_warning_size as f64 / (1024.0 * 1024.0));
+            let large_paths = large_files.iter().map(|f| format!("{} ({:.1} MB)", f.display_path, f.size as f64 / (1024.0 * 1024.0))).collect();
+            problems.push(CommitProblem::new("large_files", "warning", message, large_paths));
+        }
+        if !forbidden_matches.is_empty() {
+            problems.push(CommitProblem::new("forbidden_paths", "error", String::from("These files match the paths that shouldn't be committed in the preferences."), forbidden_matches));
         }
 
-        let file_info = FileInfo::new(change_type.clone(), file_lines);
-        Ok(file_info)
+        Ok(problems)
     }
 
-    pub fn git_stage_all(&self) -> Result<()> {
+    // Runs a hook the way git would, returning an error with its output if it fails. Missing (or non-executable) hooks are skipped like git does.
+    fn run_hook(&self, hook_name: &str, args: &[&str]) -> Result<()> {
         let repo = self.borrow_repo()?;
-
-        let mut index = repo.index()?;
-        index.add_all(&["."], IndexAddOption::DEFAULT, None)?;
-        index.write()?;
-
+        let working_dir = match repo.workdir() {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let hook_path = self.get_hook_path(hook_name)?;
+        if !hook_path.is_file() {
+            return Ok(());
+        }
+        #[cfg(unix)]
+        let mut command = {
+            use std::os::unix::fs::PermissionsExt;
+            if fs::metadata(&hook_path)?.permissions().mode() & 0o111 == 0 {
+                return Ok(());
+            }
+            new_tool_command(&hook_path)?
+        };
+        // Windows can't run a script directly, so use the sh that comes with Git for Windows like git itself does.
+        #[cfg(not(unix))]
+        let mut command = {
+            let mut command = new_tool_command(get_git_sh_path()?)?;
+            command.arg(&hook_path);
+            command
+        };
+        let output = command
+            .args(args)
+            .current_dir(working_dir)
+            .env("PATH", get_hook_path_env(working_dir)?)
+            // Hooks can't open an editor from here, and git sets this to show there's nothing to edit.
+            .env("GIT_EDITOR", ":")
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            let mut hook_output = String::from_utf8_lossy(&output.stdout).into_owned();
+            hook_output.push_str(&String::from_utf8_lossy(&output.stderr));
+            bail!("The {} hook failed:\n{}", hook_name, hook_output.trim());
+        }
         Ok(())
     }
 
+    // Husky, lefthook and pre-commit all install themselves as regular hooks, so they're run by run_hook once they're installed.
+    pub fn get_hook_managers(&self) -> Result<Vec<String>> {
+        let repo = self.borrow_repo()?;
+        let working_dir = match repo.workdir() {
+            Some(p) => p,
+            None => return Ok(vec![]),
+        };
+        let mut hook_managers = vec![];
+        if working_dir.join(".husky").is_dir() {
+            hook_managers.push(String::from("husky"));
+        }
+        let lefthook_files = ["lefthook.yml", ".lefthook.yml", "lefthook.yaml", ".lefthook.yaml", "lefthook.toml", ".lefthook.toml", "lefthook.json", ".lefthook.json"];
+        if lefthook_files.iter().any(|f| working_dir.join(f).is_file()) {
+            hook_managers.push(String::from("lefthook"));
+        }
+        if working_dir.join(".pre-commit-config.yaml").is_file() {
+            hook_managers.push(String::from("pre-commit"));
+        }
+        Ok(hook_managers)
+    }
+
     fn git_commit(&self, full_message: String, author: &Signature, committer: &Signature, parent_commits: Vec<&Commit>) -> Result<()> {
-        if !self.has_staged_changes()? {
+        // Favoring ours can leave a merge's tree the same as HEAD's, but like git the merge still gets recorded.
+        if parent_commits.len() < 2 && !self.has_staged_changes()? {
             bail!("Attempted to commit with no staged changes! Maybe stage some changes first?");
         }
 
@@ -1032,25 +5443,112 @@ impl GitManager {
         Ok(())
     }
 
+    fn append_trailers(full_message: &mut String, trailers: &Vec<CommitTrailer>) -> Result<()> {
+        let mut trailer_lines: Vec<String> = vec![];
+        let existing_trailers = message_trailers_strs(full_message.as_str())?;
+        for trailer in trailers {
+            let is_duplicate = existing_trailers.iter().any(|(key, value)| {
+                key == trailer.key && value == trailer.value
+            });
+            if !is_duplicate {
+                trailer_lines.push(format!("{}: {}", trailer.key, trailer.value));
+            }
+        }
+
+        if trailer_lines.len() == 0 {
+            return Ok(());
+        }
+
+        // Trailers need to be in the last paragraph, so only add a blank line if there isn't a trailer block already.
+        if existing_trailers.len() > 0 {
+            full_message.push('\n');
+        } else {
+            full_message.push_str("\n\n");
+        }
+        full_message.push_str(trailer_lines.join("\n").as_str());
+
+        Ok(())
+    }
+
     pub fn git_commit_from_json(&self, json_string: &str) -> Result<()> {
         let repo = self.borrow_repo()?;
         // TODO: Add way to set signature in git config
         let signature = repo.signature()?;
 
-        let json_hm: HashMap<String, String> = serde_json::from_str(json_string)?;
-        let summary = match json_hm.get("summaryText") {
-            Some(s) => s,
+        self.run_hook("pre-commit", &[])?;
+        // Formatters run by pre-commit hooks (e.g. lint-staged) often restage what they change.
+        repo.index()?.read(false)?;
+
+        let json_value: Value = serde_json::from_str(json_string)?;
+        let summary = match json_value.get("summaryText") {
+            Some(v) => GitManager::get_string_from_serde_string(v.as_str())?,
             None => bail!("Front-end payload did not include summaryText"),
         };
-        let message = match json_hm.get("messageText") {
-            Some(s) => s,
+        let message = match json_value.get("messageText") {
+            Some(v) => GitManager::get_string_from_serde_string(v.as_str())?,
             None => bail!("Front-end payload did not include messageText"),
         };
+        // Scripts can send a real boolean, the commit form sends a string like the other checkboxes.
+        let sign_off = match json_value.get("signOff") {
+            Some(Value::Bool(b)) => *b,
+            Some(v) => GitManager::get_string_from_serde_string(v.as_str())? == "true",
+            None => false,
+        };
+        let mut trailers: Vec<CommitTrailer> = match json_value.get("trailers") {
+            Some(v) => serde_json::from_value(v.clone())?,
+            None => vec![],
+        };
+        // Optionally override the author date (in seconds since the epoch), e.g. for importing historical work.
+        let author_time_opt = match json_value.get("authorTime") {
+            Some(v) => {
+                let author_time_str = GitManager::get_string_from_serde_string(v.as_str())?;
+                if author_time_str == "" {
+                    None
+                } else {
+                    Some(author_time_str.parse::<i64>()?)
+                }
+            },
+            None => None,
+        };
+
+        let author = match author_time_opt {
+            Some(author_time) => {
+                let name = GitManager::get_utf8_string(signature.name(), "Author Name")?;
+                let email = GitManager::get_utf8_string(signature.email(), "Author Email")?;
+                let offset_minutes = self.current_local_offset.whole_minutes() as i32;
+                Signature::new(name, email, &Time::new(author_time, offset_minutes))?
+            },
+            None => signature.clone(),
+        };
 
-        let mut full_message = summary.clone();
+        let mut full_message = String::from(summary);
         if message != "" {
             full_message += "\n\n";
-            full_message += message.as_str();
+            full_message += message;
+        }
+
+        if sign_off {
+            let name = GitManager::get_utf8_string(signature.name(), "Committer Name")?;
+            let email = GitManager::get_utf8_string(signature.email(), "Committer Email")?;
+            trailers.push(CommitTrailer {
+                key: String::from("Signed-off-by"),
+                value: format!("{} <{}>", name, email),
+            });
+        }
+        GitManager::append_trailers(&mut full_message, &trailers)?;
+
+        // The message hooks get the message in a file they can rewrite, like with git commit -m.
+        let commit_msg_path = repo.path().join("COMMIT_EDITMSG");
+        fs::write(&commit_msg_path, &full_message)?;
+        let commit_msg_path_str = commit_msg_path.to_string_lossy();
+        self.run_hook("prepare-commit-msg", &[&commit_msg_path_str, "message"])?;
+        self.run_hook("commit-msg", &[&commit_msg_path_str])?;
+        let hooked_message = fs::read_to_string(&commit_msg_path)?;
+        if hooked_message != full_message {
+            full_message = message_prettify(hooked_message, None)?;
+            if full_message.is_empty() {
+                bail!("A commit message hook left the message empty, so the commit was aborted.");
+            }
         }
 
         let mut parents = vec![];
@@ -1073,11 +5571,54 @@ impl GitManager {
             c
         }).collect();
 
-        self.git_commit(full_message, &signature, &signature, parent_refs)?;
+        self.git_commit(full_message, &author, &signature, parent_refs)?;
+        // Like git, the commit has already been made so a failing post-commit hook doesn't change anything.
+        let _ = self.run_hook("post-commit", &[]);
+        Ok(())
+    }
+
+    // Commits everything (including untracked files) as a throwaway commit, so switching branches doesn't need a stash.
+    pub fn git_wip_commit(&self) -> Result<()> {
+        self.ensure_writable()?;
+        let repo = self.borrow_repo()?;
+        let signature = repo.signature()?;
+
+        let mut index = repo.index()?;
+        index.add_all(["."], IndexAddOption::DEFAULT, None)?;
+        // add_all doesn't stage deleted files.
+        index.update_all(["."], None)?;
+        index.write()?;
+
+        let mut parents = vec![];
+        if let Ok(head_ref) = repo.head() {
+            if let Some(oid) = head_ref.target() {
+                parents.push(repo.find_commit(oid)?);
+            }
+        }
+        let parent_refs: Vec<&Commit> = parents.iter().collect();
+
+        // This skips the commit hooks on purpose, like git commit --no-verify, since a WIP commit isn't meant to pass any checks.
+        self.git_commit(String::from(WIP_COMMIT_MESSAGE), &signature, &signature, parent_refs)?;
+        Ok(())
+    }
+
+    // Undoes git_wip_commit with a soft reset, leaving its changes staged.
+    pub fn git_unwip(&self) -> Result<()> {
+        self.ensure_writable()?;
+        let repo = self.borrow_repo()?;
+        let head_commit = repo.head()?.peel_to_commit()?;
+        if head_commit.message() != Some(WIP_COMMIT_MESSAGE) {
+            bail!("The latest commit isn't a WIP commit.");
+        }
+        if head_commit.parent_count() == 0 {
+            bail!("The WIP commit is the first commit in the repo, so there's nothing to reset to.");
+        }
+        repo.reset(head_commit.parent(0)?.as_object(), ResetType::Soft, None)?;
         Ok(())
     }
 
     pub fn git_discard_changes(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
         let repo = self.borrow_repo()?;
 
         let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
@@ -1094,8 +5635,10 @@ impl GitManager {
             None => bail!("status was not included in the payload from the front-end"),
         };
 
+        let decoded_path = decode_path(path.trim_end_matches('/'))?;
+        self.create_safety_snapshot("discarding changes", Some(&[&decoded_path]));
         let mut cb = CheckoutBuilder::new();
-        cb.path(path);
+        cb.path(&decoded_path);
         cb.force();
 
         if change_type == "unstaged" && status == 7 {  // if unstaged and untracked need to stage it to discard.
@@ -1124,7 +5667,50 @@ impl GitManager {
         }
 
         repo.checkout_head(Some(&mut cb))?;
+        self.mark_paths_dirty(&[path]);
+
+        Ok(())
+    }
+
+    // Puts one file back to how it was in a commit, in the working tree, the index, or both.
+    pub fn git_checkout_file_from(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let repo = self.borrow_repo()?;
+
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let sha = match json_hm.get("sha") {
+            Some(s) => s,
+            None => bail!("sha not included in payload from front-end."),
+        };
+        let path = match json_hm.get("path") {
+            Some(s) => s,
+            None => bail!("path not included in payload from front-end."),
+        };
+        let target = match json_hm.get("target") {
+            Some(s) => s.as_str(),
+            None => "both",
+        };
+        if target != "worktree" && target != "index" && target != "both" {
+            bail!("Unknown restore target: {}", target);
+        }
 
+        let commit = repo.find_commit(Oid::from_str(sha)?)?;
+        let file_path = decode_path(path)?;
+        if commit.tree()?.get_path(&file_path).is_err() {
+            bail!("{} doesn't exist in commit {}.", path, &sha[..sha.len().min(8)]);
+        }
+
+        self.create_safety_snapshot("restoring a file from a commit", Some(&[&file_path]));
+        if target == "index" {
+            // Like git reset <commit> -- <path>, this only touches the index entry.
+            repo.reset_default(Some(commit.as_object()), [&file_path])?;
+        } else {
+            let mut cb = CheckoutBuilder::new();
+            cb.path(&file_path);
+            cb.force();
+            cb.update_index(target == "both");
+            repo.checkout_tree(commit.as_object(), Some(&mut cb))?;
+        }
         Ok(())
     }
 
@@ -1196,6 +5782,74 @@ impl GitManager {
         Ok(())
     }
 
+    // Deletes a branch or tag by its full ref name. Remote branches are deleted on the remote as well.
+    pub fn git_delete_ref(&self, json_str: &str) -> Result<()> {
+        let ref_name_value: Value = serde_json::from_str(json_str)?;
+        let ref_name: &str = GitManager::get_string_from_serde_string(ref_name_value.as_str())?;
+        let mut reference = self.borrow_repo()?.find_reference(ref_name)?;
+        if reference.is_remote() {
+            self.git_delete_remote_branch(Branch::wrap(reference))?;
+        } else if reference.is_branch() {
+            Branch::wrap(reference).delete()?;
+        } else if reference.is_tag() {
+            reference.delete()?;
+        } else {
+            bail!("Only branches and tags can be deleted, not {}.", ref_name);
+        }
+        Ok(())
+    }
+
+    // Like git replace [-f] <original> <replacement>. libgit2 doesn't read refs/replace, so the graph and everything else here
+    // deliberately keep showing the original objects. The replacement only affects git itself and other tools that honor it.
+    pub fn git_replace(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let original = match json_hm.get("original") {
+            Some(s) => s,
+            None => bail!("original not included in payload from front-end."),
+        };
+        let replacement = match json_hm.get("replacement") {
+            Some(s) => s,
+            None => bail!("replacement not included in payload from front-end."),
+        };
+        let force = match json_hm.get("force") {
+            Some(s) => s == "true",
+            None => false,
+        };
+
+        let repo = self.borrow_repo()?;
+        let original_object = repo.revparse_single(original)?;
+        let replacement_object = repo.revparse_single(replacement)?;
+        if original_object.id() == replacement_object.id() {
+            bail!("An object can't replace itself.");
+        }
+        if !force && original_object.kind() != replacement_object.kind() {
+            bail!("{} and {} are different kinds of objects, so replacing one with the other needs force.", original, replacement);
+        }
+
+        let replace_ref_name = format!("refs/replace/{}", original_object.id());
+        if !force && repo.find_reference(&replace_ref_name).is_ok() {
+            bail!("{} has already been replaced. Use force to replace it again.", original_object.id());
+        }
+        repo.reference(&replace_ref_name, replacement_object.id(), force, &format!("replace: {} with {}", original_object.id(), replacement_object.id()))?;
+        Ok(())
+    }
+
+    pub fn git_delete_replace(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let sha_value: Value = serde_json::from_str(json_str)?;
+        let sha: &str = GitManager::get_string_from_serde_string(sha_value.as_str())?;
+        let repo = self.borrow_repo()?;
+        let original_oid = repo.revparse_single(sha)?.id();
+        let mut replace_ref = match repo.find_reference(&format!("refs/replace/{}", original_oid)) {
+            Ok(r) => r,
+            Err(e) if e.code() == ErrorCode::NotFound => bail!("{} hasn't been replaced.", original_oid),
+            Err(e) => return Err(e.into()),
+        };
+        replace_ref.delete()?;
+        Ok(())
+    }
+
     pub fn git_delete_tag(&self, json_str: &str) -> Result<()> {
         let tag_name_value: Value = serde_json::from_str(json_str)?;
         let tag_name: &str = GitManager::get_string_from_serde_string(tag_name_value.as_str())?;
@@ -1204,56 +5858,468 @@ impl GitManager {
         Ok(())
     }
 
-    pub fn git_fetch(&self) -> Result<()> {
+    // Runs the system git in the repo for anything libgit2 doesn't cover, calling on_output for each line printed.
+    pub fn run_git_command(&self, json_str: &str, on_output: &(dyn Fn(GitCommandOutput) + Sync)) -> Result<GitCommandResult> {
+        let json_value: Value = serde_json::from_str(json_str)?;
+        let args: Vec<String> = match json_value.get("args") {
+            Some(v) => serde_json::from_value(v.clone())?,
+            None => bail!("args not included in payload from front-end."),
+        };
+
+        // Find the subcommand, skipping over global options like "-c key=value".
+        let mut subcommand_opt = None;
+        let mut args_iter = args.iter();
+        while let Some(arg) = args_iter.next() {
+            if arg == "-c" || arg == "-C" {
+                args_iter.next();
+            } else if !arg.starts_with('-') {
+                subcommand_opt = Some(arg.as_str());
+                break;
+            }
+        }
+        let subcommand = match subcommand_opt {
+            Some(s) => s,
+            None => bail!("No git subcommand given."),
+        };
+
+        let refresh = if READ_ONLY_GIT_COMMANDS.contains(&subcommand) {
+            "none"
+        } else if CHANGES_ONLY_GIT_COMMANDS.contains(&subcommand) {
+            "changes"
+        } else {
+            "all"
+        };
+        self.run_git_args(&args, refresh, on_output)
+    }
+
+    // git2 doesn't do subtrees, so these go through the git CLI.
+    pub fn git_subtree(&self, json_str: &str, on_output: &(dyn Fn(GitCommandOutput) + Sync)) -> Result<GitCommandResult> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let operation = match json_hm.get("operation") {
+            Some(s) => s,
+            None => bail!("operation not included in payload from front-end."),
+        };
+        let prefix = match json_hm.get("prefix") {
+            Some(s) => s,
+            None => bail!("prefix not included in payload from front-end."),
+        };
+        let remote = match json_hm.get("remote") {
+            Some(s) => s,
+            None => bail!("remote not included in payload from front-end."),
+        };
+        let branch = match json_hm.get("branch") {
+            Some(s) => s,
+            None => bail!("branch not included in payload from front-end."),
+        };
+        let is_squashing = match json_hm.get("squash") {
+            Some(s) => s == "true",
+            None => false,
+        };
+
+        if !["add", "pull", "push"].contains(&operation.as_str()) {
+            bail!("Unrecognized subtree operation: {}", operation);
+        }
+        if prefix == "" || remote == "" || branch == "" {
+            bail!("Subtrees need a prefix, a remote, and a branch.");
+        }
+
+        let mut args = vec![String::from("subtree"), operation.clone(), format!("--prefix={}", prefix)];
+        // Squashing only makes sense when bringing history in.
+        if is_squashing && operation != "push" {
+            args.push(String::from("--squash"));
+        }
+        args.push(remote.clone());
+        args.push(branch.clone());
+        self.run_git_args(&args, "all", on_output)
+    }
+
+    fn run_git_args(&self, args: &[String], refresh: &str, on_output: &(dyn Fn(GitCommandOutput) + Sync)) -> Result<GitCommandResult> {
+        let repo = self.borrow_repo()?;
+        let working_dir = match repo.workdir() {
+            Some(p) => p,
+            None => repo.path(),
+        };
+        let mut child = new_tool_command("git")?
+            .args(args)
+            .current_dir(working_dir)
+            // Anything waiting on a prompt would hang forever since there's no terminal.
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout = match child.stdout.take() {
+            Some(s) => s,
+            None => bail!("Failed to read output of git."),
+        };
+        let stderr = match child.stderr.take() {
+            Some(s) => s,
+            None => bail!("Failed to read output of git."),
+        };
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for line in BufReader::new(stderr).lines().map_while(|l| l.ok()) {
+                    on_output(GitCommandOutput { stream: String::from("stderr"), line });
+                }
+            });
+            for line in BufReader::new(stdout).lines().map_while(|l| l.ok()) {
+                on_output(GitCommandOutput { stream: String::from("stdout"), line });
+            }
+        });
+        let exit_status = child.wait()?;
+
+        Ok(GitCommandResult {
+            exit_code: exit_status.code(),
+            refresh: String::from(refresh),
+        })
+    }
+
+    pub fn remove_stale_lock(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let repo = self.borrow_repo()?;
+
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let lock_path_str = match json_hm.get("lock_path") {
+            Some(s) => s.clone(),
+            None => bail!("lock_path not included in payload from front-end."),
+        };
+        let is_force = match json_hm.get("force") {
+            Some(s) => s == "true",
+            None => false,
+        };
+
+        let lock_path = PathBuf::from(lock_path_str);
+        // The lock may have been cleaned up by its owner in the meantime.
+        if !lock_path.exists() {
+            return Ok(());
+        }
+        let lock_path = lock_path.canonicalize()?;
+        let is_lock_file = match lock_path.extension() {
+            Some(ext) => ext == "lock",
+            None => false,
+        };
+        if !is_lock_file || !lock_path.starts_with(repo.path().canonicalize()?) {
+            bail!("Refusing to remove {} since it isn't a lock file in this repository.", lock_path.display());
+        }
+
+        if let Some(pid) = get_lock_owner_pid(&lock_path) {
+            return Err(StructuredError::new(
+                "lock_in_use",
+                format!("{} is still held open by process {}, so it isn't stale.", lock_path.display(), pid),
+                Some(json!({ "lock_path": lock_path, "pid": pid })),
+            ).into());
+        }
+        let age = fs::metadata(&lock_path)?.modified()?.elapsed().unwrap_or(Duration::ZERO);
+        if !is_force && age < STALE_LOCK_AGE {
+            return Err(StructuredError::new(
+                "lock_recent",
+                format!("{} was only created {} seconds ago, another git process may still be using it.", lock_path.display(), age.as_secs()),
+                Some(json!({ "lock_path": lock_path, "age_seconds": age.as_secs() })),
+            ).into());
+        }
+
+        fs::remove_file(&lock_path)?;
+        Ok(())
+    }
+
+    // Commits whose history is cut off on purpose, mapped to why: "shallow" for the boundary of a shallow clone and "grafted"
+    // for commits with parents overridden by info/grafts.
+    pub fn get_history_boundaries(&self) -> Result<HashMap<String, String>> {
+        let repo = self.borrow_repo()?;
+        let mut history_boundaries: HashMap<String, String> = HashMap::new();
+        for (file_name, boundary_type) in [("info/grafts", "grafted"), ("shallow", "shallow")] {
+            let contents = match fs::read_to_string(repo.path().join(file_name)) {
+                Ok(c) => c,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            // Each line starts with the commit's SHA. Grafts list the replacement parents after it.
+            for line in contents.lines() {
+                if let Some(sha) = line.split_whitespace().next() {
+                    if !sha.starts_with('#') {
+                        history_boundaries.insert(String::from(sha), String::from(boundary_type));
+                    }
+                }
+            }
+        }
+        Ok(history_boundaries)
+    }
+
+    // Like git fetch --deepen=<depth> or git fetch --unshallow. libgit2 can only fetch to an absolute depth, so this goes through the git CLI.
+    pub fn git_deepen(&mut self, json_str: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let unshallow = match json_hm.get("unshallow") {
+            Some(s) => s == "true",
+            None => bail!("unshallow not included in payload from front-end."),
+        };
         let repo = self.borrow_repo()?;
+        if !repo.is_shallow() {
+            bail!("This repository isn't shallow, so there's no more history to fetch.");
+        }
+
+        let depth_arg = if unshallow {
+            String::from("--unshallow")
+        } else {
+            let depth: usize = match json_hm.get("depth") {
+                Some(s) => s.trim().parse()?,
+                None => bail!("depth not included in payload from front-end."),
+            };
+            if depth == 0 {
+                bail!("Depth must be at least 1.");
+            }
+            format!("--deepen={}", depth)
+        };
+        let output = new_tool_command("git")?
+            .args(["fetch", depth_arg.as_str()])
+            .current_dir(repo.path())
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            bail!("Failed to fetch more history: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        // libgit2 caches commits with their shallow parents cut off, so the repo has to be reopened to see the new history.
+        let repo_path = repo.path().to_path_buf();
+        self.repo = Some(Repository::open(repo_path)?);
+        self.old_graph_starting_shas = vec![];
+        self.graph_layout = None;
+        // The commits at the old shallow boundary had no parents before.
+        self.clear_repo_caches();
+        Ok(())
+    }
+
+    pub fn git_fetch(&self) -> Result<()> {
+        GitManager::fetch_all_remotes(self.borrow_repo()?, &|_received_objects, _total_objects| ())
+    }
+
+    // Fetches every remote of a repo with prune, passing along how many objects have been received so far out of the total.
+    fn fetch_all_remotes(repo: &Repository, on_transfer_progress: &dyn Fn(usize, usize)) -> Result<()> {
         let remote_string_array = repo.remotes()?;
         let empty_refspecs: &[String] = &[];
         for remote_string_opt in remote_string_array.iter() {
             let remote_string = GitManager::get_utf8_string(remote_string_opt, "Remote Name")?;
             let mut remote = repo.find_remote(remote_string)?;
+            let mut callbacks = GitManager::get_remote_callbacks();
+            callbacks.transfer_progress(|progress| {
+                on_transfer_progress(progress.received_objects(), progress.total_objects());
+                true
+            });
             let mut fetch_options = FetchOptions::new();
             fetch_options.download_tags(AutotagOption::All);
             fetch_options.prune(FetchPrune::On);
-            fetch_options.remote_callbacks(GitManager::get_remote_callbacks());
+            fetch_options.remote_callbacks(callbacks);
             remote.fetch(empty_refspecs, Some(&mut fetch_options), None)?;
         }
         Ok(())
     }
 
-    pub fn git_fast_forward_branch(&self, json_str: &str) -> Result<()> {
+    // Recurses a fetch or pull into every submodule (and their submodules). Pulling also checks out the commit each submodule is pinned to.
+    pub fn git_update_submodules(&self, is_pulling: bool, on_progress: &(dyn Fn(SubmoduleProgress) + Sync)) -> Result<Vec<SubmoduleUpdateResult>> {
+        let mut submodule_update_results: Vec<SubmoduleUpdateResult> = vec![];
+        GitManager::update_submodules_in(self.borrow_repo()?, "", is_pulling, on_progress, &mut submodule_update_results)?;
+        Ok(submodule_update_results)
+    }
+
+    fn update_submodules_in(repo: &Repository, path_prefix: &str, is_pulling: bool, on_progress: &(dyn Fn(SubmoduleProgress) + Sync), submodule_update_results: &mut Vec<SubmoduleUpdateResult>) -> Result<()> {
+        for mut submodule in repo.submodules()? {
+            let path = format!("{}{}", path_prefix, GitManager::get_utf8_string(submodule.path().to_str(), "Submodule Path")?);
+            // One submodule failing shouldn't stop the rest from updating.
+            let submodule_update_result = match GitManager::update_submodule(&mut submodule, &path, is_pulling, on_progress) {
+                Ok(r) => r,
+                Err(e) => SubmoduleUpdateResult::new(path.clone(), "failed", e.to_string()),
+            };
+            let is_usable = submodule_update_result.status != "failed" && submodule_update_result.status != "not_initialized";
+            submodule_update_results.push(submodule_update_result);
+            if is_usable {
+                if let Ok(submodule_repo) = submodule.open() {
+                    GitManager::update_submodules_in(&submodule_repo, &format!("{}/", path), is_pulling, on_progress, submodule_update_results)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update_submodule(submodule: &mut Submodule, path: &str, is_pulling: bool, on_progress: &(dyn Fn(SubmoduleProgress) + Sync)) -> Result<SubmoduleUpdateResult> {
+        let on_transfer_progress = |received_objects: usize, total_objects: usize| {
+            // This gets called for every object, so only let the front-end know every so often.
+            if received_objects % 100 == 0 || received_objects == total_objects {
+                on_progress(SubmoduleProgress {
+                    path: String::from(path),
+                    received_objects,
+                    total_objects,
+                });
+            }
+        };
+        on_transfer_progress(0, 0);
+
+        let old_oid_opt = submodule.workdir_id();
+        match submodule.open() {
+            Ok(submodule_repo) => GitManager::fetch_all_remotes(&submodule_repo, &on_transfer_progress)?,
+            Err(_) if !is_pulling => return Ok(SubmoduleUpdateResult::new(String::from(path), "not_initialized", String::new())),
+            // Updating below will clone it.
+            Err(_) => (),
+        };
+        if !is_pulling {
+            return Ok(SubmoduleUpdateResult::new(String::from(path), "fetched", String::new()));
+        }
+
+        let mut callbacks = GitManager::get_remote_callbacks();
+        callbacks.transfer_progress(|progress| {
+            on_transfer_progress(progress.received_objects(), progress.total_objects());
+            true
+        });
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(callbacks);
+        let mut update_options = SubmoduleUpdateOptions::new();
+        update_options.fetch(fetch_options);
+        submodule.update(true, Some(&mut update_options))?;
+        submodule.reload(true)?;
+
+        let new_oid_opt = submodule.workdir_id();
+        let mut submodule_update_result = if old_oid_opt == new_oid_opt {
+            SubmoduleUpdateResult::new(String::from(path), "unchanged", String::new())
+        } else {
+            SubmoduleUpdateResult::new(String::from(path), "moved", String::new())
+        };
+        submodule_update_result.old_sha = old_oid_opt.map(|oid| oid.to_string());
+        submodule_update_result.new_sha = new_oid_opt.map(|oid| oid.to_string());
+        Ok(submodule_update_result)
+    }
+
+    // Fetches every remote (with prune) and then fast-forwards every local branch that can be.
+    pub fn git_sync(&self) -> Result<Vec<BranchSyncResult>> {
+        self.git_fetch()?;
+
+        let repo = self.borrow_repo()?;
+        let mut branch_shorthands: Vec<String> = vec![];
+        for branch_result in repo.branches(Some(BranchType::Local))? {
+            let (branch, _) = branch_result?;
+            branch_shorthands.push(String::from(GitManager::get_utf8_string(branch.get().shorthand(), "Branch Name")?));
+        }
+
+        let mut sync_results: Vec<BranchSyncResult> = vec![];
+        for branch_shorthand in branch_shorthands {
+            let local_branch = repo.find_branch(&branch_shorthand, BranchType::Local)?;
+            let remote_branch = match local_branch.upstream() {
+                Ok(b) => b,
+                Err(e) if e.code() == ErrorCode::NotFound => {
+                    sync_results.push(BranchSyncResult::new(branch_shorthand, "no_upstream", String::new()));
+                    continue;
+                },
+                Err(e) => {
+                    sync_results.push(BranchSyncResult::new(branch_shorthand, "failed", e.to_string()));
+                    continue;
+                },
+            };
+            let (local_oid, remote_oid) = match (local_branch.get().target(), remote_branch.get().target()) {
+                (Some(l), Some(r)) => (l, r),
+                _ => {
+                    sync_results.push(BranchSyncResult::new(branch_shorthand, "failed", String::from("Branch is not targeting a commit.")));
+                    continue;
+                },
+            };
+
+            let (ahead, behind) = repo.graph_ahead_behind(local_oid, remote_oid)?;
+            if behind == 0 {
+                sync_results.push(BranchSyncResult::new(branch_shorthand, "up_to_date", String::new()));
+            } else if ahead > 0 {
+                let message = format!("{} ahead and {} behind its upstream.", ahead, behind);
+                sync_results.push(BranchSyncResult::new(branch_shorthand, "needs_merge", message));
+            } else {
+                match self.fast_forward_local_branch(&branch_shorthand) {
+                    Ok(_) => {
+                        let message = format!("Fast-forwarded {} commit(s).", behind);
+                        sync_results.push(BranchSyncResult::new(branch_shorthand, "updated", message));
+                    },
+                    Err(e) => sync_results.push(BranchSyncResult::new(branch_shorthand, "failed", e.to_string())),
+                };
+            }
+        }
+        Ok(sync_results)
+    }
+
+    // Gets how many commits the checked out branch is ahead of and behind its upstream, if it has one.
+    pub fn get_ahead_behind(&self) -> Result<Option<(usize, usize)>> {
         let repo = self.borrow_repo()?;
+        if repo.head_detached()? {
+            return Ok(None);
+        }
+        let head = match repo.head() {
+            Ok(r) => r,
+            Err(e) if e.code() == ErrorCode::UnbornBranch => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let local_branch = Branch::wrap(head);
+        let remote_branch = match local_branch.upstream() {
+            Ok(b) => b,
+            Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let (local_oid, remote_oid) = match (local_branch.get().target(), remote_branch.get().target()) {
+            (Some(l), Some(r)) => (l, r),
+            _ => return Ok(None),
+        };
+        // Either commit can be missing from a corrupt object database, which shouldn't stop the rest of a refresh.
+        Ok(repo.graph_ahead_behind(local_oid, remote_oid).ok())
+    }
 
+    // Moves a local branch to its upstream without checking it out, refusing if it's not a fast-forward.
+    pub fn git_fast_forward_branch(&self, json_str: &str) -> Result<()> {
         let branch_shorthand_value: Value = serde_json::from_str(json_str)?;
         let branch_shorthand: &str = GitManager::get_string_from_serde_string(branch_shorthand_value.as_str())?;
 
         // Fetch first to make sure everything's up to date.
         self.git_fetch()?;
 
-        let mut local_branch = repo.find_branch(branch_shorthand, BranchType::Local)?;
+        self.fast_forward_local_branch(branch_shorthand)?;
+        Ok(())
+    }
 
+    // Moves a local branch to its upstream's tip, but only if that's a fast-forward. Returns whether the branch moved.
+    fn fast_forward_local_branch(&self, branch_shorthand: &str) -> Result<bool> {
+        let repo = self.borrow_repo()?;
+        let mut local_branch = repo.find_branch(branch_shorthand, BranchType::Local)?;
+        let local_target = match local_branch.get().target() {
+            Some(oid) => oid,
+            None => bail!("Local branch is not targeting a commit, cannot fast-forward."),
+        };
         let remote_branch = local_branch.upstream()?;
-        let remote_ref = remote_branch.get();
-        let remote_target = match remote_ref.target() {
+        let remote_target = match remote_branch.get().target() {
             Some(oid) => oid,
-            None => bail!("Remote branch is not targeting a commit, cannot pull."),
+            None => bail!("Remote branch is not targeting a commit, cannot fast-forward."),
         };
 
-        let head = repo.head()?;
-        let local_ref = local_branch.get_mut();
+        if local_target == remote_target || repo.graph_descendant_of(local_target, remote_target)? {
+            return Ok(false);
+        }
+        if !repo.graph_descendant_of(remote_target, local_target)? {
+            bail!("{} has diverged from its upstream and needs a merge or rebase, so it can't be fast-forwarded.", branch_shorthand);
+        }
 
         // If fast-forwarding the branch currently checked out, need to update the working
         // directory too.
-        if *local_ref == head {
-            let commit = match remote_ref.target() {
-                Some(oid) => repo.find_commit(oid)?,
-                None => bail!("Remote branch has no target commit."),
-            };
-            let tree = commit.tree()?;
-            repo.checkout_tree(tree.as_object(), None)?;
+        if local_branch.is_head() {
+            let commit = repo.find_commit(remote_target)?;
+            repo.checkout_tree(commit.tree()?.as_object(), None)?;
         }
+        local_branch.get_mut().set_target(remote_target, "oxidized_git fast-forward: setting new target for local ref")?;
+        Ok(true)
+    }
 
-        local_ref.set_target(remote_target, "oxidized_git fast-forward: setting new target for local ref")?;
+    // Pulls a branch without checking it out. Branches other than HEAD can only be fast-forwarded since there's no working directory to merge in.
+    pub fn git_pull_branch(&self, json_str: &str) -> Result<()> {
+        let branch_shorthand_value: Value = serde_json::from_str(json_str)?;
+        let branch_shorthand: &str = GitManager::get_string_from_serde_string(branch_shorthand_value.as_str())?;
+
+        if self.borrow_repo()?.find_branch(branch_shorthand, BranchType::Local)?.is_head() {
+            return self.git_pull();
+        }
 
+        self.git_fetch()?;
+        self.fast_forward_local_branch(branch_shorthand)?;
         Ok(())
     }
 
@@ -1337,13 +6403,43 @@ impl GitManager {
             remote_name_from_frontend_opt = None;
         }
 
-        let local_ref = repo.head()?;
+        self.push_local_ref(&repo.head()?, is_force, remote_name_from_frontend_opt)
+    }
+
+    // Pushes a local branch other than HEAD.
+    pub fn git_push_branch(&self, json_str: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let branch_shorthand = match json_hm.get("branch_shorthand") {
+            Some(b) => b,
+            None => bail!("branch_shorthand not included in payload from front-end."),
+        };
+        let is_force = match json_hm.get("isForcePush") {
+            Some(s) => s == "true",
+            None => bail!("isForcePush not included in payload from front-end."),
+        };
+        // Only needed if the branch doesn't have an upstream yet.
+        let remote_name_from_frontend_opt = json_hm.get("selectedRemote").map(|s| s.as_str());
+
+        let local_branch = self.borrow_repo()?.find_branch(branch_shorthand, BranchType::Local)?;
+        self.push_local_ref(local_branch.get(), is_force, remote_name_from_frontend_opt)
+    }
+
+    fn push_local_ref(&self, local_ref: &Reference, is_force: bool, remote_name_from_frontend_opt: Option<&str>) -> Result<()> {
+        let repo = self.borrow_repo()?;
         let local_full_name = GitManager::get_utf8_string(local_ref.name(), "Branch Name")?;
+        let local_shorthand = GitManager::get_utf8_string(local_ref.shorthand(), "Branch Name")?;
+
+        // Like git, prefer the branch's pushRemote, then remote.pushDefault, and only then the upstream's remote.
+        let config = repo.config()?;
+        let push_remote_name_opt = config.get_string(&format!("branch.{}.pushRemote", local_shorthand)).or(config.get_string("remote.pushDefault")).ok();
 
         let mut is_creating_new_remote_branch = false;
         let mut remote = match repo.branch_upstream_remote(local_full_name) {
             Ok(b) => {
-                let remote_name = GitManager::get_utf8_string(b.as_str(), "Remote Name")?;
+                let remote_name = match &push_remote_name_opt {
+                    Some(push_remote_name) => push_remote_name.as_str(),
+                    None => GitManager::get_utf8_string(b.as_str(), "Remote Name")?,
+                };
                 repo.find_remote(remote_name)?
             },
             Err(_e) => {
@@ -1358,6 +6454,7 @@ impl GitManager {
         let mut push_options = PushOptions::new();
         push_options.remote_callbacks(GitManager::get_remote_callbacks());
 
+        // Remote::push connects to remote.<name>.pushurl when it's set and only falls back to the fetch URL otherwise.
         let mut sb = String::from(local_full_name);
         if is_force {
             sb.insert(0, '+');
@@ -1376,11 +6473,71 @@ impl GitManager {
         Ok(())
     }
 
-    pub fn git_push_tag(&self, json_str: &str) -> Result<()> {
+    pub fn git_push_gerrit(&self, json_str: &str) -> Result<()> {
         let repo = self.borrow_repo()?;
 
         let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
-        let mut tag_full_name = match json_hm.get("tagFullName") {
+        let remote_name = match json_hm.get("selectedRemote") {
+            Some(s) => s.as_str(),
+            None => bail!("selectedRemote not included in payload from front-end."),
+        };
+        let target_branch_from_frontend = match json_hm.get("targetBranch") {
+            Some(s) => s.clone(),
+            None => bail!("targetBranch not included in payload from front-end."),
+        };
+        let topic = match json_hm.get("topic") {
+            Some(s) => s.clone(),
+            None => bail!("topic not included in payload from front-end."),
+        };
+        let reviewers = match json_hm.get("reviewers") {
+            Some(s) => s.clone(),
+            None => bail!("reviewers not included in payload from front-end."),
+        };
+
+        // If no target branch is given, default to the branch name of HEAD's upstream.
+        let target_branch;
+        if target_branch_from_frontend == "" {
+            let head_ref = repo.head()?;
+            let head_shorthand = GitManager::get_utf8_string(head_ref.shorthand(), "Branch Name")?;
+            let upstream_branch = repo.find_branch(head_shorthand, BranchType::Local)?.upstream()?;
+            let upstream_shorthand = GitManager::get_utf8_string(upstream_branch.get().shorthand(), "Branch Name")?;
+            target_branch = match upstream_shorthand.find("/") {
+                Some(i) => String::from(&upstream_shorthand[(i + 1)..]),
+                None => bail!("Upstream Branch doesn't seem to have a remote in its name?"),
+            };
+        } else {
+            target_branch = target_branch_from_frontend;
+        }
+
+        // Gerrit reads the topic and reviewers from the "%" options appended to the refspec.
+        let mut gerrit_options: Vec<String> = vec![];
+        if topic != "" {
+            gerrit_options.push(format!("topic={}", topic));
+        }
+        for reviewer in reviewers.split(",") {
+            let trimmed_reviewer = reviewer.trim();
+            if trimmed_reviewer != "" {
+                gerrit_options.push(format!("r={}", trimmed_reviewer));
+            }
+        }
+
+        let mut refspec = format!("HEAD:refs/for/{}", target_branch);
+        if gerrit_options.len() > 0 {
+            refspec += "%";
+            refspec += gerrit_options.join(",").as_str();
+        }
+
+        let mut remote = repo.find_remote(remote_name)?;
+        let mut push_options = PushOptions::new();
+        push_options.remote_callbacks(GitManager::get_remote_callbacks());
+        remote.push(&[refspec.as_str()], Some(&mut push_options))?;
+
+        Ok(())
+    }
+
+    pub fn git_push_tag(&self, json_str: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let tag_full_name = match json_hm.get("tagFullName") {
             Some(s) => s.clone(),
             None => bail!("tagFullName not included in payload from front-end."),
         };
@@ -1393,6 +6550,11 @@ impl GitManager {
             None => bail!("selectedRemote not included in payload from front-end."),
         };
 
+        self.push_tag_to_remote(remote_name, tag_full_name, is_force)
+    }
+
+    fn push_tag_to_remote(&self, remote_name: &str, mut tag_full_name: String, is_force: bool) -> Result<()> {
+        let repo = self.borrow_repo()?;
         let mut remote = repo.find_remote(remote_name)?;
 
         let mut push_options = PushOptions::new();
@@ -1408,22 +6570,219 @@ impl GitManager {
     }
 
     pub fn git_stash(&mut self, json_str: &str) -> Result<()> {
-        let message_value: Value = serde_json::from_str(json_str)?;
-        let message: &str = GitManager::get_string_from_serde_string(message_value.as_str())?;
-        let repo = self.borrow_repo_mut()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let message = match json_hm.get("message") {
+            Some(s) => s.as_str(),
+            None => bail!("message not included in payload from front-end."),
+        };
+        let include_untracked = match json_hm.get("include_untracked") {
+            Some(s) => s == "true",
+            None => bail!("include_untracked not included in payload from front-end."),
+        };
+        let keep_index = match json_hm.get("keep_index") {
+            Some(s) => s == "true",
+            None => false,
+        };
+        let staged_only = match json_hm.get("staged_only") {
+            Some(s) => s == "true",
+            None => false,
+        };
+
+        if staged_only {
+            return self.git_stash_staged(message);
+        }
+
+        let mut stash_flags = StashFlags::DEFAULT;
+        if include_untracked {
+            stash_flags |= StashFlags::INCLUDE_UNTRACKED;
+        }
+        if keep_index {
+            stash_flags |= StashFlags::KEEP_INDEX;
+        }
 
+        let repo = self.borrow_repo_mut()?;
         if message == "" {
-            repo.stash_save2(&repo.signature()?, None, Some(StashFlags::INCLUDE_UNTRACKED))?;
+            repo.stash_save2(&repo.signature()?, None, Some(stash_flags))?;
         } else {
-            repo.stash_save2(&repo.signature()?, Some(message), Some(StashFlags::INCLUDE_UNTRACKED))?;
+            repo.stash_save2(&repo.signature()?, Some(message), Some(stash_flags))?;
         }
 
         Ok(())
     }
 
-    pub fn git_apply_stash(&mut self, json_str: &str) -> Result<()> {
-        let repo = self.borrow_repo_mut()?;
+    // libgit2 can't stash only what's staged, so this goes through the git CLI (which needs to be at least 2.35).
+    fn git_stash_staged(&self, message: &str) -> Result<()> {
+        let repo = self.borrow_repo()?;
+        let working_dir = match repo.workdir() {
+            Some(p) => p,
+            None => bail!("Can't stash in a bare repository."),
+        };
+        let mut args = vec!["stash", "push", "--staged"];
+        if message != "" {
+            args.push("-m");
+            args.push(message);
+        }
+        let output = new_tool_command("git")?
+            .args(&args)
+            .current_dir(working_dir)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            bail!("Failed to stash staged changes: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(())
+    }
+
+    // LFS is set up per repo through gitattributes, or .lfsconfig for the server.
+    pub fn is_lfs_enabled(&self) -> Result<bool> {
+        let repo = self.borrow_repo()?;
+        let working_dir = match repo.workdir() {
+            Some(d) => d,
+            None => return Ok(false),
+        };
+        if working_dir.join(".lfsconfig").is_file() {
+            return Ok(true);
+        }
+        match fs::read_to_string(working_dir.join(".gitattributes")) {
+            Ok(s) => Ok(s.contains("filter=lfs")),
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn run_git_lfs(&self, args: &[&str], action: &str) -> Result<Vec<u8>> {
+        let repo = self.borrow_repo()?;
+        let working_dir = match repo.workdir() {
+            Some(p) => p,
+            None => bail!("Can't {} in a bare repository.", action),
+        };
+        if !self.is_lfs_enabled()? {
+            bail!("Can't {} since this repo doesn't use LFS.", action);
+        }
+        let output = new_tool_command("git")?
+            .arg("lfs")
+            .args(args)
+            .current_dir(working_dir)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            bail!("Failed to {}: {}", action, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(output.stdout)
+    }
+
+    // Like git lfs track, but also restages the file so it's stored as an LFS pointer instead of the whole file.
+    pub fn git_track_with_lfs(&self, json_str: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let pattern = match json_hm.get("pattern") {
+            Some(s) => s.trim(),
+            None => bail!("pattern not included in payload from front-end."),
+        };
+        let path = match json_hm.get("path") {
+            Some(s) => s,
+            None => bail!("path not included in payload from front-end."),
+        };
+        if pattern.is_empty() || pattern.contains(char::is_whitespace) {
+            bail!("The LFS pattern can't be empty or have spaces in it.");
+        }
+
+        let repo = self.borrow_repo()?;
+        let working_dir = match repo.workdir() {
+            Some(p) => p,
+            None => bail!("Can't track files with LFS in a bare repository."),
+        };
+        // The clean filter that turns the file into a pointer comes from git-lfs, so check for it before changing anything.
+        let version_output = new_tool_command("git")?
+            .args(["lfs", "version"])
+            .current_dir(working_dir)
+            .stdin(Stdio::null())
+            .output()?;
+        if !version_output.status.success() {
+            bail!("Git LFS isn't installed, see https://git-lfs.com to install it.");
+        }
+
+        let gitattributes_path = working_dir.join(".gitattributes");
+        let mut gitattributes = match fs::read_to_string(&gitattributes_path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let lfs_line = format!("{} filter=lfs diff=lfs merge=lfs -text", pattern);
+        if !gitattributes.lines().any(|l| l.trim() == lfs_line) {
+            if !gitattributes.is_empty() && !gitattributes.ends_with('\n') {
+                gitattributes.push('\n');
+            }
+            gitattributes.push_str(&lfs_line);
+            gitattributes.push('\n');
+            fs::write(&gitattributes_path, gitattributes)?;
+        }
+
+        // libgit2 doesn't run external filters, and --renormalize is needed since the file's already staged with the same stat info.
+        let decoded_path = decode_path(path)?;
+        let output = new_tool_command("git")?
+            .args(["add", "--renormalize", "--"])
+            .arg(".gitattributes")
+            .arg(&decoded_path)
+            .current_dir(working_dir)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            bail!("Failed to restage {} with LFS: {}", decoded_path.display(), String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(())
+    }
+
+    // Asks the LFS server for every lock and keeps them so the changes list can show who holds each file.
+    pub fn git_lfs_list_locks(&mut self) -> Result<Vec<LfsLock>> {
+        let stdout = self.run_git_lfs(&["locks", "--json"], "list LFS locks")?;
+        let locks_value: Value = serde_json::from_slice(&stdout)?;
+        let mut lfs_locks = vec![];
+        if let Some(locks) = locks_value.as_array() {
+            for lock in locks {
+                lfs_locks.push(LfsLock {
+                    id: String::from(lock["id"].as_str().unwrap_or("")),
+                    path: String::from(lock["path"].as_str().unwrap_or("")),
+                    owner: String::from(lock["owner"]["name"].as_str().unwrap_or("")),
+                    locked_at: String::from(lock["locked_at"].as_str().unwrap_or("")),
+                });
+            }
+        }
+        self.lfs_locks = lfs_locks.clone();
+        Ok(lfs_locks)
+    }
+
+    pub fn git_lfs_lock(&mut self, json_string: &str) -> Result<()> {
+        let path: String = serde_json::from_str(json_string)?;
+        let decoded_path = decode_path(&path)?;
+        self.run_git_lfs(&["lock", GitManager::get_utf8_string(decoded_path.to_str(), "File Path")?], "lock file")?;
+        self.git_lfs_list_locks()?;
+        Ok(())
+    }
+
+    pub fn git_lfs_unlock(&mut self, json_string: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_string)?;
+        let path = match json_hm.get("path") {
+            Some(s) => s,
+            None => bail!("path not included in payload from front-end."),
+        };
+        let force = match json_hm.get("force") {
+            Some(s) => s == "true",
+            None => bail!("force not included in payload from front-end."),
+        };
+        let mut args = vec!["unlock", path.as_str()];
+        // Needed to break someone else's lock.
+        if force {
+            args.push("--force");
+        }
+        self.run_git_lfs(&args, "unlock file")?;
+        self.git_lfs_list_locks()?;
+        Ok(())
+    }
 
+    pub fn git_apply_stash(&mut self, json_str: &str) -> Result<()> {
         let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
         let index = match json_hm.get("index") {
             Some(s) => {
@@ -1431,17 +6790,333 @@ impl GitManager {
             },
             None => bail!("index not included in payload from front-end."),
         };
-        let delete_stash = match json_hm.get("delete_stash") {
-            Some(s) => s == "true",
-            None => bail!("delete_stash not included in payload from front-end."),
+        let delete_stash = match json_hm.get("delete_stash") {
+            Some(s) => s == "true",
+            None => bail!("delete_stash not included in payload from front-end."),
+        };
+
+        if self.is_applying_stash()? {
+            bail!("A stash is already being applied, finish or abandon it first.");
+        }
+
+        let stash_oid = self.get_stash_oid(index)?;
+        let pre_apply_tree_oid = self.borrow_repo_mut()?.index()?.write_tree()?;
+
+        // libgit2 leaves conflicts in the index without failing, so only drop the stash once it's known to have applied cleanly.
+        self.borrow_repo_mut()?.stash_apply(index, None)?;
+
+        if self.has_conflicts()? {
+            let repo = self.borrow_repo()?;
+            fs::write(GitManager::get_stash_apply_file(repo), format!("{}\n{}\n{}\n", pre_apply_tree_oid, stash_oid, delete_stash))?;
+        } else if delete_stash {
+            self.borrow_repo_mut()?.stash_drop(index)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_stash_apply_file(repo: &Repository) -> PathBuf {
+        repo.path().join("STASH_APPLY")
+    }
+
+    pub fn is_applying_stash(&self) -> Result<bool> {
+        Ok(GitManager::get_stash_apply_file(self.borrow_repo()?).exists())
+    }
+
+    // Returns the index tree from before the stash was applied, the stash's commit, and whether the stash should be dropped afterwards.
+    fn read_stash_apply_file(&self) -> Result<(Oid, Oid, bool)> {
+        let stash_apply_string = fs::read_to_string(GitManager::get_stash_apply_file(self.borrow_repo()?))?;
+        let mut lines = stash_apply_string.lines();
+        let (pre_apply_tree_sha, stash_sha, delete_stash) = match (lines.next(), lines.next(), lines.next()) {
+            (Some(t), Some(s), Some(d)) => (t, s, d),
+            _ => bail!("The stash apply state file is malformed."),
+        };
+        Ok((Oid::from_str(pre_apply_tree_sha)?, Oid::from_str(stash_sha)?, delete_stash == "true"))
+    }
+
+    pub fn git_continue_stash_apply(&mut self) -> Result<()> {
+        if self.has_conflicts()? {
+            bail!("Resolve the conflicts before finishing the stash apply.");
+        }
+        let (_, stash_oid, delete_stash) = self.read_stash_apply_file()?;
+        if delete_stash {
+            // The stash may have moved to a different index (or been dropped already) since it was applied.
+            let repo = self.borrow_repo_mut()?;
+            let mut stash_index_opt = None;
+            repo.stash_foreach(|i, _, oid| {
+                if *oid == stash_oid {
+                    stash_index_opt = Some(i);
+                    return false;
+                }
+                true
+            })?;
+            if let Some(stash_index) = stash_index_opt {
+                repo.stash_drop(stash_index)?;
+            }
+        }
+        fs::remove_file(GitManager::get_stash_apply_file(self.borrow_repo()?))?;
+        Ok(())
+    }
+
+    pub fn git_abandon_stash_apply(&self) -> Result<()> {
+        let (pre_apply_tree_oid, stash_oid, _) = self.read_stash_apply_file()?;
+        let repo = self.borrow_repo()?;
+        let pre_apply_tree = repo.find_tree(pre_apply_tree_oid)?;
+
+        // Applying a stash only touches the paths the stash changed, and libgit2 refuses to apply over local changes to
+        // those paths, so restoring them from the pre-apply index gets back the working directory as it was.
+        let stash_commit = repo.find_commit(stash_oid)?;
+        let stash_diff = get_stash_changes(&stash_commit, repo, false, DEFAULT_CONTEXT_LINES)?;
+        let mut restore_paths = vec![];
+        for delta in stash_diff.deltas() {
+            for path_opt in [delta.old_file().path(), delta.new_file().path()] {
+                if let Some(path) = path_opt {
+                    if !restore_paths.contains(&path.to_path_buf()) {
+                        restore_paths.push(path.to_path_buf());
+                    }
+                }
+            }
+        }
+
+        let mut index = repo.index()?;
+        index.read_tree(&pre_apply_tree)?;
+        index.write()?;
+
+        let workdir = match repo.workdir() {
+            Some(d) => d,
+            None => bail!("Can't abandon a stash apply in a bare repo."),
+        };
+        let mut checkout_paths = vec![];
+        for path in &restore_paths {
+            if pre_apply_tree.get_path(path).is_ok() {
+                checkout_paths.push(path);
+            } else {
+                let full_path = GitManager::get_workdir_file_path(workdir, Path::new(path));
+                if full_path.is_file() {
+                    fs::remove_file(full_path)?;
+                }
+            }
+        }
+        if !checkout_paths.is_empty() {
+            let mut checkout_builder = CheckoutBuilder::new();
+            checkout_builder.force();
+            for path in checkout_paths {
+                checkout_builder.path(path);
+            }
+            repo.checkout_index(Some(&mut index), Some(&mut checkout_builder))?;
+        }
+
+        fs::remove_file(GitManager::get_stash_apply_file(repo))?;
+        Ok(())
+    }
+
+    // Stashes are kept in the reflog of refs/stash, newest first, which is what stash@{index} refers to.
+    fn get_stash_oid(&self, index: usize) -> Result<Oid> {
+        let reflog = self.borrow_repo()?.reflog("refs/stash")?;
+        match reflog.get(index) {
+            Some(entry) => Ok(entry.id_new()),
+            None => bail!("There's no stash at index {}.", index),
+        }
+    }
+
+    pub fn get_stash_diff(&self, json_str: &str) -> Result<StashDiff> {
+        let stash_index_str_value: Value = serde_json::from_str(json_str)?;
+        let stash_index_str: &str = GitManager::get_string_from_serde_string(stash_index_str_value.as_str())?;
+        let index = stash_index_str.parse::<usize>()?;
+
+        let repo = self.borrow_repo()?;
+        let stash_commit = repo.find_commit(self.get_stash_oid(index)?)?;
+        let sha = stash_commit.id().to_string();
+        let mut changed_files = get_parseable_diff_delta(get_stash_changes(&stash_commit, repo, false, DEFAULT_CONTEXT_LINES)?)?;
+        set_collapsed_files(repo, &mut changed_files)?;
+        let mut file_infos: Vec<FileInfo> = vec![];
+        for changed_file in &changed_files {
+            file_infos.push(self.get_file_diff_hunks(changed_file.get_path(), &String::from("stash"), &sha, 0, DEFAULT_CONTEXT_LINES)?);
+        }
+
+        Ok(StashDiff {
+            index,
+            sha,
+            message: String::from(GitManager::get_utf8_string(stash_commit.message(), "Stash Message")?),
+            changed_files,
+            file_infos,
+        })
+    }
+
+    // Gets the files that differ between a commit (or anything that resolves to one, like a tag) and the working directory.
+    // The hunks for each file come from get-file-diff with the "workdir" change type.
+    pub fn get_workdir_diff(&self, json_str: &str) -> Result<WorkdirDiff> {
+        let rev_value: Value = serde_json::from_str(json_str)?;
+        let rev = GitManager::get_string_from_serde_string(rev_value.as_str())?;
+
+        let repo = self.borrow_repo()?;
+        if repo.workdir().is_none() {
+            bail!("Can't compare with the working directory in a bare repo.");
+        }
+        let commit = repo.revparse_single(rev)?.peel_to_commit()?;
+        let ignore_eol = config_manager::get_config()?.borrow_ignore_eol_changes().unwrap_or(false);
+        let mut changed_files = get_parseable_diff_delta(get_workdir_changes(&commit, repo, ignore_eol, DEFAULT_CONTEXT_LINES)?)?;
+        set_collapsed_files(repo, &mut changed_files)?;
+        Ok(WorkdirDiff {
+            sha: commit.id().to_string(),
+            summary: String::from(GitManager::get_utf8_string(commit.summary(), "Commit Summary")?),
+            changed_files,
+        })
+    }
+
+    pub fn get_staged_patch(&self) -> Result<StagedPatch> {
+        let diff = self.get_staged_changes()?;
+
+        let mut patch_bytes: Vec<u8> = vec![];
+        diff.print(DiffFormat::Patch, |_diff_delta, _diff_hunk_opt, diff_line| {
+            // Only content lines need their origin added, the headers already include theirs.
+            if matches!(diff_line.origin_value(), DiffLineType::Context | DiffLineType::Addition | DiffLineType::Deletion) {
+                patch_bytes.push(diff_line.origin() as u8);
+            }
+            patch_bytes.extend_from_slice(diff_line.content());
+            true
+        })?;
+
+        let mut files = vec![];
+        for (i, delta) in diff.deltas().enumerate() {
+            let path_bytes = match delta.new_file().path_bytes() {
+                Some(b) => b,
+                None => bail!("Staged file is missing its path."),
+            };
+            let mut hunks = vec![];
+            if let Some(patch) = Patch::from_diff(&diff, i)? {
+                for hunk_index in 0..patch.num_hunks() {
+                    let (diff_hunk, line_count) = patch.hunk(hunk_index)?;
+                    let mut hunk_bytes = diff_hunk.header().to_vec();
+                    for line_index in 0..line_count {
+                        let diff_line = patch.line_in_hunk(hunk_index, line_index)?;
+                        if matches!(diff_line.origin_value(), DiffLineType::Context | DiffLineType::Addition | DiffLineType::Deletion) {
+                            hunk_bytes.push(diff_line.origin() as u8);
+                        }
+                        hunk_bytes.extend_from_slice(diff_line.content());
+                    }
+                    hunks.push(String::from_utf8_lossy(&hunk_bytes).into_owned());
+                }
+            }
+            files.push(StagedPatchFile {
+                delta: ParseableDiffDelta::new(delta.status() as u8, encode_path_bytes(path_bytes), String::from_utf8_lossy(path_bytes).into_owned(), None),
+                is_binary: delta.flags().is_binary(),
+                hunks,
+            });
+        }
+
+        Ok(StagedPatch {
+            patch: String::from_utf8_lossy(&patch_bytes).into_owned(),
+            files,
+        })
+    }
+
+    pub fn get_merge_base_diff(&self, json_str: &str) -> Result<MergeBaseDiff> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let branch_a = match json_hm.get("branch_a") {
+            Some(s) => s,
+            None => bail!("branch_a not included in payload from front-end."),
+        };
+        let branch_b = match json_hm.get("branch_b") {
+            Some(s) => s,
+            None => bail!("branch_b not included in payload from front-end."),
+        };
+
+        let repo = self.borrow_repo()?;
+        let a_commit = repo.revparse_single(branch_a)?.peel_to_commit()?;
+        let b_commit = repo.revparse_single(branch_b)?.peel_to_commit()?;
+        let ignore_eol = config_manager::get_config()?.borrow_ignore_eol_changes().unwrap_or(false);
+        let (merge_base_oid, diff) = get_merge_base_changes(&a_commit, &b_commit, repo, ignore_eol, DEFAULT_CONTEXT_LINES)?;
+        let mut changed_files = get_parseable_diff_delta(diff)?;
+        set_collapsed_files(repo, &mut changed_files)?;
+        Ok(MergeBaseDiff {
+            branch_a: branch_a.clone(),
+            branch_b: branch_b.clone(),
+            merge_base_sha: merge_base_oid.to_string(),
+            range: format!("{}...{}", a_commit.id(), b_commit.id()),
+            changed_files,
+        })
+    }
+
+    pub fn preview_merge(&self, json_str: &str) -> Result<MergePreview> {
+        // Takes the same merge options as merging, so the preview matches what the merge would do.
+        let merge_options = get_merge_options(&serde_json::from_str(json_str)?)?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let ours = match json_hm.get("ours") {
+            Some(s) => s,
+            None => bail!("ours not included in payload from front-end."),
+        };
+        let theirs = match json_hm.get("theirs") {
+            Some(s) => s,
+            None => bail!("theirs not included in payload from front-end."),
+        };
+
+        let repo = self.borrow_repo()?;
+        let ours_commit = repo.revparse_single(ours)?.peel_to_commit()?;
+        let theirs_commit = repo.revparse_single(theirs)?.peel_to_commit()?;
+        let merge_base_opt = match repo.merge_base(ours_commit.id(), theirs_commit.id()) {
+            Ok(oid) => Some(oid),
+            Err(e) if e.code() == ErrorCode::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut conflicted_files = vec![];
+        let outcome = if merge_base_opt == Some(theirs_commit.id()) {
+            "up_to_date"
+        } else if merge_base_opt == Some(ours_commit.id()) {
+            "fast_forward"
+        } else {
+            let merged_index = repo.merge_commits(&ours_commit, &theirs_commit, Some(&merge_options))?;
+            if merged_index.has_conflicts() {
+                for conflict_result in merged_index.conflicts()? {
+                    let conflict = conflict_result?;
+                    if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+                        conflicted_files.push(String::from_utf8_lossy(&entry.path).into_owned());
+                    }
+                }
+                "conflicts"
+            } else {
+                "clean"
+            }
+        };
+
+        Ok(MergePreview {
+            ours: ours.clone(),
+            theirs: theirs.clone(),
+            merge_base_sha: merge_base_opt.map(|oid| oid.to_string()).unwrap_or_default(),
+            outcome: String::from(outcome),
+            conflicted_files,
+        })
+    }
+
+    // Like git stash branch: makes a new branch on the commit the stash was made on, checks it out, and pops the stash onto it.
+    pub fn git_stash_to_branch(&mut self, json_str: &str) -> Result<()> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let index = match json_hm.get("index") {
+            Some(s) => s.parse::<usize>()?,
+            None => bail!("index not included in payload from front-end."),
+        };
+        let branch_name = match json_hm.get("branch_name") {
+            Some(s) => s,
+            None => bail!("branch_name not included in payload from front-end."),
         };
 
-        if delete_stash {
-            repo.stash_pop(index, None)?;
-        } else {
-            repo.stash_apply(index, None)?;
+        let stash_oid = self.get_stash_oid(index)?;
+        {
+            let repo = self.borrow_repo()?;
+            let base_commit = repo.find_commit(stash_oid)?.parent(0)?;
+            let mut branch = repo.branch(branch_name, &base_commit, false)?;
+            if let Err(e) = self.git_checkout(branch.get()) {
+                // Don't leave the new branch lying around if it couldn't be checked out.
+                branch.delete()?;
+                return Err(e);
+            }
         }
 
+        let repo = self.borrow_repo_mut()?;
+        let mut stash_apply_options = StashApplyOptions::new();
+        stash_apply_options.reinstantiate_index();
+        repo.stash_pop(index, Some(&mut stash_apply_options))?;
         Ok(())
     }
 
@@ -1514,13 +7189,417 @@ impl GitManager {
             None => bail!("message not included in payload from front-end."),
         };
 
+        let is_signing = match json_hm.get("sign") {
+            Some(s) => s == "true",
+            None => false,
+        };
+
         let git_object = repo.find_object(Oid::from_str(&*commit_sha)?, Some(ObjectType::Commit))?;
 
         if is_lightweight {
+            if is_signing {
+                bail!("Lightweight tags can't be signed.");
+            }
             repo.tag_lightweight(name, &git_object, false)?;
         } else {
-            let sig = repo.signature()?;
-            repo.tag(name, &git_object, &sig, message, false)?;
+            self.create_annotated_tag(name, &git_object, message, is_signing)?;
+        }
+
+        Ok(())
+    }
+
+    fn create_annotated_tag(&self, name: &str, target: &Object, message: &str, is_signing: bool) -> Result<()> {
+        let repo = self.borrow_repo()?;
+        let sig = repo.signature()?;
+
+        if !is_signing {
+            repo.tag(name, target, &sig, message, false)?;
+            return Ok(());
+        }
+
+        // libgit2 can't sign tags, so build the tag object by hand and sign it with the configured program.
+        let tag_ref_name = format!("refs/tags/{}", name);
+        if !Reference::is_valid_name(tag_ref_name.as_str()) {
+            bail!("'{}' is not a valid tag name.", name);
+        }
+        if repo.find_reference(tag_ref_name.as_str()).is_ok() {
+            bail!("Tag '{}' already exists.", name);
+        }
+        let target_type = match target.kind() {
+            Some(k) => k.str(),
+            None => bail!("Couldn't determine the type of the object being tagged."),
+        };
+
+        let mut buffer = format!(
+            "object {}\ntype {}\ntag {}\ntagger {}\n\n{}",
+            target.id(),
+            target_type,
+            name,
+            GitManager::get_signature_line(&sig)?,
+            message,
+        );
+        if !buffer.ends_with('\n') {
+            buffer.push('\n');
+        }
+        buffer += self.sign_buffer(buffer.as_str())?.as_str();
+
+        let tag_oid = repo.odb()?.write(ObjectType::Tag, buffer.as_bytes())?;
+        repo.reference(tag_ref_name.as_str(), tag_oid, false, "tag: create signed tag")?;
+
+        Ok(())
+    }
+
+    fn get_signature_line(sig: &Signature) -> Result<String> {
+        let name = GitManager::get_utf8_string(sig.name(), "Signature Name")?;
+        let email = GitManager::get_utf8_string(sig.email(), "Signature Email")?;
+        let when = sig.when();
+        let offset_minutes = when.offset_minutes().abs();
+        Ok(format!("{} <{}> {} {}{:02}{:02}", name, email, when.seconds(), when.sign(), offset_minutes / 60, offset_minutes % 60))
+    }
+
+    fn run_with_stdin(mut command: Command, input: &[u8]) -> Result<Output> {
+        command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        let mut child = command.spawn()?;
+        match child.stdin.take() {
+            Some(mut stdin) => stdin.write_all(input)?,
+            None => bail!("Failed to open stdin of external program."),
+        };
+        Ok(child.wait_with_output()?)
+    }
+
+    fn sign_buffer(&self, buffer: &str) -> Result<String> {
+        let repo = self.borrow_repo()?;
+        let config = repo.config()?;
+        let signing_format = config.get_string("gpg.format").unwrap_or(String::from("openpgp"));
+        let signing_key_opt = config.get_string("user.signingkey").ok();
+
+        let mut command;
+        if signing_format == "ssh" {
+            let signing_key = match signing_key_opt {
+                Some(k) => k,
+                None => bail!("user.signingkey must be set in your git config to sign with SSH."),
+            };
+            if signing_key.starts_with("key::") {
+                bail!("Literal SSH keys in user.signingkey aren't supported, please use a path to the key instead.");
+            }
+            command = new_tool_command(config.get_string("gpg.ssh.program").unwrap_or(String::from("ssh-keygen")))?;
+            command.args(["-Y", "sign", "-n", "git", "-f", signing_key.as_str()]);
+        } else if signing_format == "openpgp" {
+            command = new_tool_command(config.get_string("gpg.program").unwrap_or(String::from("gpg")))?;
+            command.args(["--status-fd=2", "-bsa"]);
+            if let Some(signing_key) = signing_key_opt {
+                command.args(["-u", signing_key.as_str()]);
+            }
+        } else {
+            bail!("Signing format '{}' is not supported.", signing_format);
+        }
+
+        let output = GitManager::run_with_stdin(command, buffer.as_bytes())?;
+        if !output.status.success() {
+            bail!("Failed to sign: {}", String::from_utf8_lossy(&output.stderr));
+        }
+        Ok(String::from(str::from_utf8(&output.stdout)?))
+    }
+
+    // Returns the signature status and any details about the signer.
+    fn verify_tag_signature(&self, tag: &Tag) -> Result<(String, Option<String>)> {
+        let repo = self.borrow_repo()?;
+        let config = repo.config()?;
+
+        let odb = repo.odb()?;
+        let odb_object = odb.read(tag.id())?;
+        let data = match str::from_utf8(odb_object.data()) {
+            Ok(d) => d,
+            Err(_) => return Ok((String::from("unknown"), Some(String::from("The tag's message isn't valid UTF-8, so its signature can't be checked.")))),
+        };
+        let signature_start_opt = [PGP_SIGNATURE_BEGIN, SSH_SIGNATURE_BEGIN].iter().filter_map(|m| data.find(m)).min();
+        let (payload, signature) = match signature_start_opt {
+            Some(i) => data.split_at(i),
+            None => return Ok((String::from("unsigned"), None)),
+        };
+
+        // Both programs need the signature in a file and read the signed payload from stdin.
+        let signature_path = std::env::temp_dir().join(format!("oxidized_git_{}.sig", tag.id()));
+        let is_ssh = signature.starts_with(SSH_SIGNATURE_BEGIN);
+        let program;
+        let mut command;
+        if is_ssh {
+            let allowed_signers_path = match config.get_path("gpg.ssh.allowedSignersFile") {
+                Ok(p) => p,
+                Err(_) => return Ok((String::from("unknown"), Some(String::from("gpg.ssh.allowedSignersFile is not set in your git config.")))),
+            };
+            let principal = match tag.tagger() {
+                Some(tagger) => String::from(GitManager::get_utf8_string(tagger.email(), "Tagger Email")?),
+                None => return Ok((String::from("unknown"), Some(String::from("Tag has no tagger to verify against.")))),
+            };
+            program = config.get_string("gpg.ssh.program").unwrap_or(String::from("ssh-keygen"));
+            command = new_tool_command(&program)?;
+            command.args(["-Y", "verify", "-n", "git", "-I", principal.as_str(), "-f"]);
+            command.arg(allowed_signers_path).arg("-s").arg(&signature_path);
+        } else {
+            program = config.get_string("gpg.program").unwrap_or(String::from("gpg"));
+            command = new_tool_command(&program)?;
+            command.args(["--status-fd=1", "--verify"]);
+            command.arg(&signature_path).arg("-");
+        }
+
+        fs::write(&signature_path, signature)?;
+        let output_result = GitManager::run_with_stdin(command, payload.as_bytes());
+        fs::remove_file(&signature_path)?;
+        // Not being able to check a signature (like when gpg isn't installed) shouldn't keep the tag's details from showing.
+        let output = match output_result {
+            Ok(o) => o,
+            Err(e) => return Ok((String::from("unknown"), Some(format!("Couldn't run {} to check the signature: {}", program, e)))),
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if is_ssh {
+            if output.status.success() {
+                return Ok((String::from("good"), Some(String::from(stdout.trim()))));
+            }
+            return Ok((String::from("bad"), Some(String::from(String::from_utf8_lossy(&output.stderr).trim()))));
+        }
+
+        for line in stdout.lines() {
+            if let Some(signer) = line.strip_prefix("[GNUPG:] GOODSIG ") {
+                return Ok((String::from("good"), Some(String::from(signer))));
+            } else if let Some(signer) = line.strip_prefix("[GNUPG:] BADSIG ") {
+                return Ok((String::from("bad"), Some(String::from(signer))));
+            }
+        }
+        Ok((String::from("unknown"), Some(String::from(String::from_utf8_lossy(&output.stderr).trim()))))
+    }
+
+    pub fn get_tag_details(&self, json_str: &str) -> Result<TagDetails> {
+        let full_tag_name: String = serde_json::from_str(json_str)?;
+        let repo = self.borrow_repo()?;
+
+        let reference = repo.find_reference(&full_tag_name)?;
+        let tag_opt = match reference.target() {
+            Some(oid) => repo.find_tag(oid).ok(),
+            None => None,
+        };
+        // Tags can point at trees and blobs too, which don't have a commit to select.
+        let commit_opt = reference.peel_to_commit().ok();
+        let time = match tag_opt.as_ref().and_then(|tag| tag.tagger()) {
+            Some(tagger) => Some(tagger.when().seconds()),
+            None => commit_opt.as_ref().map(|c| c.time().seconds()),
+        };
+
+        Ok(TagDetails {
+            full_tag_name,
+            target_sha: commit_opt.map_or(String::new(), |c| c.id().to_string()),
+            is_annotated: tag_opt.is_some(),
+            time,
+        })
+    }
+
+    pub fn get_tag_info(&self, json_str: &str) -> Result<TagInfo> {
+        let tag_name_value: Value = serde_json::from_str(json_str)?;
+        let tag_full_name: &str = GitManager::get_string_from_serde_string(tag_name_value.as_str())?;
+        let repo = self.borrow_repo()?;
+
+        let reference = repo.find_reference(tag_full_name)?;
+        let name = String::from(GitManager::get_utf8_string(reference.shorthand(), "Tag Name")?);
+        let oid = match reference.target() {
+            Some(o) => o,
+            None => bail!("Tag has no target."),
+        };
+
+        let tag = match repo.find_tag(oid) {
+            Ok(t) => t,
+            Err(_) => {
+                return Ok(TagInfo {
+                    name,
+                    target_sha: oid.to_string(),
+                    is_lightweight: true,
+                    tagger_name: None,
+                    tagger_email: None,
+                    tagger_time: None,
+                    message: None,
+                    signature_status: String::from("unsigned"),
+                    signature_details: None,
+                });
+            },
+        };
+
+        let (tagger_name, tagger_email, tagger_time) = match tag.tagger() {
+            Some(tagger) => (
+                Some(String::from(GitManager::get_utf8_string(tagger.name(), "Tagger Name")?)),
+                Some(String::from(GitManager::get_utf8_string(tagger.email(), "Tagger Email")?)),
+                Some(tagger.when().seconds()),
+            ),
+            None => (None, None, None),
+        };
+        // libgit2 leaves the signature at the end of the message, so strip it off.
+        let message = match tag.message() {
+            Some(m) => {
+                let signature_start_opt = [PGP_SIGNATURE_BEGIN, SSH_SIGNATURE_BEGIN].iter().filter_map(|s| m.find(s)).min();
+                match signature_start_opt {
+                    Some(i) => Some(String::from(&m[..i])),
+                    None => Some(String::from(m)),
+                }
+            },
+            None => None,
+        };
+        let (signature_status, signature_details) = self.verify_tag_signature(&tag)?;
+
+        Ok(TagInfo {
+            name,
+            target_sha: tag.target_id().to_string(),
+            is_lightweight: false,
+            tagger_name,
+            tagger_email,
+            tagger_time,
+            message,
+            signature_status,
+            signature_details,
+        })
+    }
+
+    fn get_latest_semver_tag(&self) -> Result<Option<(String, (u64, u64, u64))>> {
+        let repo = self.borrow_repo()?;
+
+        let mut latest_tag_opt: Option<(String, (u64, u64, u64))> = None;
+        for tag_name_opt in repo.tag_names(None)?.iter() {
+            let tag_name = GitManager::get_utf8_string(tag_name_opt, "Tag Name")?;
+            if let Some(version) = parse_semver_tag(tag_name) {
+                let is_newer = match &latest_tag_opt {
+                    Some((_, latest_version)) => version > *latest_version,
+                    None => true,
+                };
+                if is_newer {
+                    latest_tag_opt = Some((String::from(tag_name), version));
+                }
+            }
+        }
+
+        Ok(latest_tag_opt)
+    }
+
+    fn get_messages_since_tag(&self, tag_name_opt: Option<&str>) -> Result<Vec<String>> {
+        let repo = self.borrow_repo()?;
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push_head()?;
+        if let Some(tag_name) = tag_name_opt {
+            let tag_commit = repo.find_reference(&*format!("refs/tags/{}", tag_name))?.peel_to_commit()?;
+            revwalk.hide(tag_commit.id())?;
+        }
+        revwalk.set_sorting(Sort::TOPOLOGICAL)?;
+
+        let mut messages = vec![];
+        for oid_result in revwalk {
+            let commit = repo.find_commit(oid_result?)?;
+            messages.push(String::from(GitManager::get_utf8_string(commit.message(), "Commit Message")?));
+        }
+
+        Ok(messages)
+    }
+
+    fn get_changelog(messages: &Vec<String>) -> String {
+        let mut changelog = String::new();
+        for message in messages {
+            let summary = message.lines().next().unwrap_or("");
+            changelog += "- ";
+            changelog += summary;
+            changelog += "\n";
+        }
+        changelog
+    }
+
+    pub fn get_release_tag_info(&self) -> Result<ReleaseTagInfo> {
+        let latest_tag_opt = self.get_latest_semver_tag()?;
+
+        let (previous_tag_opt, suggested_tag, messages) = match latest_tag_opt {
+            Some((latest_tag, (major, minor, patch))) => {
+                let messages = self.get_messages_since_tag(Some(latest_tag.as_str()))?;
+
+                // Use conventional commit hints to decide which part of the version to bump.
+                let is_breaking = messages.iter().any(|m| {
+                    let summary_type = m.lines().next().unwrap_or("").split(':').next().unwrap_or("");
+                    summary_type.ends_with('!') || m.contains("BREAKING CHANGE")
+                });
+                // Only an exact "feat" type counts, so summaries like "feature flag cleanup" don't bump the minor version.
+                let is_feature = messages.iter().any(|m| {
+                    m.strip_prefix("feat").map_or(false, |rest| rest.starts_with(&[':', '(', '!'][..]))
+                });
+
+                let new_version;
+                if is_breaking {
+                    new_version = format!("{}.0.0", major + 1);
+                } else if is_feature {
+                    new_version = format!("{}.{}.0", major, minor + 1);
+                } else {
+                    new_version = format!("{}.{}.{}", major, minor, patch + 1);
+                }
+
+                // Keep the same "v" prefix style as the previous tag.
+                let suggested_tag;
+                if latest_tag.starts_with('v') {
+                    suggested_tag = String::from("v") + new_version.as_str();
+                } else {
+                    suggested_tag = new_version;
+                }
+
+                (Some(latest_tag), suggested_tag, messages)
+            },
+            None => (None, String::from("v0.1.0"), self.get_messages_since_tag(None)?),
+        };
+
+        Ok(ReleaseTagInfo::new(previous_tag_opt, suggested_tag, GitManager::get_changelog(&messages)))
+    }
+
+    pub fn git_create_release_tag(&self, json_str: &str) -> Result<()> {
+        let repo = self.borrow_repo()?;
+
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let name = match json_hm.get("name") {
+            Some(s) => s.clone(),
+            None => bail!("name not included in payload from front-end."),
+        };
+        let message = match json_hm.get("message") {
+            Some(s) => s.clone(),
+            None => bail!("message not included in payload from front-end."),
+        };
+        let is_pushing = match json_hm.get("push") {
+            Some(s) => s == "true",
+            None => bail!("push not included in payload from front-end."),
+        };
+        let is_signing = match json_hm.get("sign") {
+            Some(s) => s == "true",
+            None => false,
+        };
+
+        if parse_semver_tag(name.as_str()).is_none() {
+            bail!("Release tag name must be a semantic version like 'v1.2.3' or '1.2.3'.");
+        }
+
+        // If no message was given, generate the changelog since the previous release.
+        let tag_message;
+        if message == "" {
+            let latest_tag_opt = self.get_latest_semver_tag()?;
+            let messages = match &latest_tag_opt {
+                Some((latest_tag, _)) => self.get_messages_since_tag(Some(latest_tag.as_str()))?,
+                None => self.get_messages_since_tag(None)?,
+            };
+            tag_message = GitManager::get_changelog(&messages);
+        } else {
+            tag_message = message;
+        }
+
+        let head_commit = match repo.head()?.target() {
+            Some(oid) => repo.find_commit(oid)?,
+            None => bail!("HEAD has no target to create a release tag on."),
+        };
+        self.create_annotated_tag(name.as_str(), head_commit.as_object(), tag_message.as_str(), is_signing)?;
+
+        if is_pushing {
+            let remote_name = match json_hm.get("selectedRemote") {
+                Some(s) => s.as_str(),
+                None => bail!("selectedRemote not included in payload from front-end."),
+            };
+            self.push_tag_to_remote(remote_name, format!("refs/tags/{}", name), false)?;
         }
 
         Ok(())
@@ -1660,4 +7739,252 @@ impl GitManager {
 
         Ok(())
     }
+
+    // Uses core.hooksPath if it's set, which git resolves relative to the working directory (or the git directory for bare repos).
+    fn get_hooks_dir(&self) -> Result<PathBuf> {
+        let repo = self.borrow_repo()?;
+        match repo.config()?.get_path("core.hooksPath") {
+            Ok(hooks_path) => {
+                if hooks_path.is_absolute() {
+                    return Ok(hooks_path);
+                }
+                let base_dir = repo.workdir().unwrap_or(repo.path());
+                Ok(base_dir.join(hooks_path))
+            },
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(repo.path().join("hooks")),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn get_hook_path(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty() || name == "." || name == ".." || name.contains(|c| c == '/' || c == '\\') {
+            bail!("{} isn't a valid hook name.", name);
+        }
+        Ok(self.get_hooks_dir()?.join(name))
+    }
+
+    pub fn get_hooks(&self) -> Result<HooksInfo> {
+        let hooks_dir = self.get_hooks_dir()?;
+        let mut hooks = vec![];
+        // The hooks directory doesn't have to exist, e.g. core.hooksPath can point somewhere that hasn't been made yet.
+        if hooks_dir.is_dir() {
+            for entry_result in fs::read_dir(&hooks_dir)? {
+                let entry = entry_result?;
+                let metadata = entry.metadata()?;
+                if !metadata.is_file() {
+                    continue;
+                }
+                let name = entry.file_name().to_string_lossy().into_owned();
+                #[cfg(unix)]
+                let is_executable = {
+                    use std::os::unix::fs::PermissionsExt;
+                    metadata.permissions().mode() & 0o111 != 0
+                };
+                // Git for Windows runs any hook that exists, there's no executable bit to check.
+                #[cfg(not(unix))]
+                let is_executable = true;
+                hooks.push(GitHook {
+                    is_sample: name.ends_with(".sample"),
+                    is_known: KNOWN_HOOKS.contains(&name.as_str()),
+                    name,
+                    is_executable,
+                });
+            }
+        }
+        hooks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let samples = SAMPLE_HOOKS.iter().map(|(id, hook_name, description, _)| SampleHook {
+            id: String::from(*id),
+            hook_name: String::from(*hook_name),
+            description: String::from(*description),
+        }).collect();
+
+        Ok(HooksInfo {
+            hooks_dir: hooks_dir.to_string_lossy().into_owned(),
+            hooks,
+            samples,
+            known_hooks: KNOWN_HOOKS.iter().map(|h| String::from(*h)).collect(),
+        })
+    }
+
+    pub fn get_hook(&self, name: &str) -> Result<GitHookContents> {
+        let hook_path = self.get_hook_path(name)?;
+        let contents = match fs::read(&hook_path) {
+            Ok(b) => b,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => bail!("There's no {} hook in {}.", name, self.get_hooks_dir()?.display()),
+            Err(e) => return Err(e.into()),
+        };
+        let contents = match String::from_utf8(contents) {
+            Ok(s) => s,
+            Err(_) => bail!("The {} hook isn't a text file, so it can't be edited here.", name),
+        };
+        Ok(GitHookContents {
+            name: String::from(name),
+            contents,
+        })
+    }
+
+    // Writes a hook, creating it (and the hooks directory) as executable if it doesn't exist yet.
+    fn write_hook(&self, name: &str, contents: &str) -> Result<()> {
+        let hook_path = self.get_hook_path(name)?;
+        let is_new = !hook_path.exists();
+        create_dir_all(self.get_hooks_dir()?)?;
+        fs::write(&hook_path, contents)?;
+        #[cfg(unix)]
+        if is_new {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+        }
+        #[cfg(not(unix))]
+        let _ = is_new;
+        Ok(())
+    }
+
+    pub fn save_hook(&self, json_str: &str) -> Result<HooksInfo> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let name = match json_hm.get("name") {
+            Some(s) => s,
+            None => bail!("name not included in payload from front-end."),
+        };
+        let contents = match json_hm.get("contents") {
+            Some(s) => s,
+            None => bail!("contents not included in payload from front-end."),
+        };
+        // A carriage return after the shebang makes the interpreter name wrong, so the script would fail to start on unix.
+        self.write_hook(name, &contents.replace("\r\n", "\n"))?;
+        self.get_hooks()
+    }
+
+    pub fn set_hook_executable(&self, json_str: &str) -> Result<HooksInfo> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let name = match json_hm.get("name") {
+            Some(s) => s,
+            None => bail!("name not included in payload from front-end."),
+        };
+        let executable = match json_hm.get("executable") {
+            Some(s) => s == "true",
+            None => bail!("executable not included in payload from front-end."),
+        };
+        let hook_path = self.get_hook_path(name)?;
+        if !hook_path.is_file() {
+            bail!("There's no {} hook in {}.", name, self.get_hooks_dir()?.display());
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut permissions = fs::metadata(&hook_path)?.permissions();
+            // Only give execute permission to whoever can already read the file, like chmod +x with the default umask.
+            let mode = permissions.mode();
+            if executable {
+                permissions.set_mode(mode | ((mode & 0o444) >> 2));
+            } else {
+                permissions.set_mode(mode & !0o111);
+            }
+            fs::set_permissions(&hook_path, permissions)?;
+            self.get_hooks()
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = executable;
+            bail!("Hooks don't have an executable bit on Windows, rename the hook to disable it instead.")
+        }
+    }
+
+    pub fn install_sample_hook(&self, json_str: &str) -> Result<HooksInfo> {
+        self.ensure_writable()?;
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let sample_id = match json_hm.get("sample_id") {
+            Some(s) => s,
+            None => bail!("sample_id not included in payload from front-end."),
+        };
+        let (_, hook_name, _, script) = match SAMPLE_HOOKS.iter().find(|(id, _, _, _)| id == sample_id) {
+            Some(s) => s,
+            None => bail!("Unknown sample hook: {}", sample_id),
+        };
+        // Only one script can be each hook, so don't silently replace one the team already has.
+        if self.get_hook_path(hook_name)?.exists() {
+            bail!("There's already a {} hook, edit it to add this check instead.", hook_name);
+        }
+        self.write_hook(hook_name, script)?;
+        self.get_hooks()
+    }
+
+    // Generates an ed25519 keypair in ~/.ssh, sets it as the SSH credentials, and returns the public key.
+    #[allow(unused_unsafe)]
+    pub fn generate_ssh_key(&self, json_str: &str) -> Result<String> {
+        let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+        let file_name = match json_hm.get("file_name") {
+            Some(s) => {
+                if s == "" {
+                    String::from("id_ed25519")
+                } else {
+                    s.clone()
+                }
+            },
+            None => bail!("No file_name supplied from front-end."),
+        };
+        let comment = match json_hm.get("comment") {
+            Some(s) => s.clone(),
+            None => bail!("No comment supplied from front-end."),
+        };
+        let passphrase = match json_hm.get("passphrase") {
+            Some(s) => s.clone(),
+            None => bail!("No passphrase supplied from front-end."),
+        };
+
+        if file_name.contains('/') || file_name.contains('\\') {
+            bail!("Key file name can't contain path separators.");
+        }
+
+        let ssh_dir = match directories::BaseDirs::new() {
+            Some(base_dirs) => base_dirs.home_dir().join(".ssh"),
+            None => bail!("Couldn't find your home directory."),
+        };
+        let private_key_path = ssh_dir.join(&file_name);
+        let public_key_path = ssh_dir.join(format!("{}.pub", file_name));
+        if private_key_path.exists() || public_key_path.exists() {
+            bail!("A key named {} already exists in {}, please choose a different name.", file_name, ssh_dir.display());
+        }
+
+        if !ssh_dir.exists() {
+            create_dir_all(&ssh_dir)?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&ssh_dir, fs::Permissions::from_mode(0o700))?;
+            }
+        }
+
+        let mut private_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519)?;
+        private_key.set_comment(comment.as_str());
+        let public_key_str = private_key.public_key().to_openssh()?;
+
+        // Writing the private key already restricts its permissions to the current user on unix.
+        if passphrase != "" {
+            private_key.encrypt(&mut OsRng, passphrase.as_bytes())?.write_openssh_file(&private_key_path, LineEnding::default())?;
+        } else {
+            private_key.write_openssh_file(&private_key_path, LineEnding::default())?;
+        }
+        fs::write(&public_key_path, format!("{}\n", public_key_str))?;
+
+        let mut config = config_manager::get_config()?;
+        config.set_cred_type(String::from("SSH"));
+        config.set_public_key_path(public_key_path);
+        config.set_private_key_path(private_key_path);
+
+        if passphrase != "" {
+            config.set_uses_passphrase(true);
+            unsafe {
+                keytar::set_password("oxidized_git", "passphrase", &*passphrase)?;
+            }
+        } else {
+            config.set_uses_passphrase(false);
+        }
+
+        config.save()?;
+
+        Ok(public_key_str)
+    }
 }
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{bail, Result};
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::{fontdb, Options, PostProcessingSteps, Tree, TreeParsing, TreePostProc};
+use serde_json::Value;
+use crate::git_manager::GitManager;
+use crate::parseable_info::get_commit_svg_draw_properties_list;
+
+// There's no browser to measure text with, so use a monospace font and assume every character is this wide.
+const SINGLE_CHAR_WIDTH: f64 = 8.0;
+const FONT_SIZE: isize = 13;
+const Y_SPACING: isize = 24;  // If changing, be sure to update in svg_row.rs too
+const BRANCH_TEXT_SPACING: f64 = 5.0;  // If changing, be sure to update on front-end too
+const RIGHT_TEXT_SPACING: f64 = 10.0;  // If changing, be sure to update on front-end too
+const BACKGROUND_COLOR: &str = "#212529";
+// resvg doesn't know what "monospace" is on every OS, so list some common monospace fonts first.
+const FONT_FAMILY: &str = "DejaVu Sans Mono, Menlo, Consolas, Courier New, monospace";
+
+fn get_text_width(text: &str) -> f64 {
+    text.chars().count() as f64 * SINGLE_CHAR_WIDTH
+}
+
+fn get_text_content(element: &Value) -> &str {
+    element["textContent"].as_str().unwrap_or("")
+}
+
+fn get_attr_number(element: &Value, attr_name: &str) -> f64 {
+    element["attrs"][attr_name].as_f64().unwrap_or(0.0)
+}
+
+// Writes one of the elements from the SVGRow draw properties, replacing any attributes that the front-end would normally calculate.
+fn write_element(svg: &mut String, element: &Value, attr_overrides: &[(&str, f64)]) -> Result<()> {
+    let tag = match element["tag"].as_str() {
+        Some(t) => t,
+        None => bail!("Graph element is missing its tag."),
+    };
+    write!(svg, "<{}", tag)?;
+    if let Some(attrs) = element["attrs"].as_object() {
+        for (attr_name, attr_value) in attrs {
+            // Classes and data attributes are only used by the front-end.
+            if attr_name == "class" || attr_name.starts_with("data-") || attr_overrides.iter().any(|(n, _)| n == attr_name) {
+                continue;
+            }
+            let attr_value_string = match attr_value {
+                Value::String(s) => s.clone(),
+                v => v.to_string(),
+            };
+            write!(svg, " {}=\"{}\"", attr_name, html_escape::encode_double_quoted_attribute(&attr_value_string))?;
+        }
+    }
+    for (attr_name, attr_value) in attr_overrides {
+        write!(svg, " {}=\"{}\"", attr_name, attr_value)?;
+    }
+    match element["textContent"].as_str() {
+        Some(text) => writeln!(svg, ">{}</{}>", html_escape::encode_text(text), tag)?,
+        None => writeln!(svg, "/>")?,
+    };
+    Ok(())
+}
+
+fn get_graph_svg(commits_info: &Value) -> Result<String> {
+    let rows = match commits_info["svg_row_draw_properties"].as_array() {
+        Some(r) => r,
+        None => bail!("Commit graph is missing its rows."),
+    };
+    let mut branch_labels: HashMap<&str, &Vec<Value>> = HashMap::new();
+    if let Some(branch_draw_properties) = commits_info["branch_draw_properties"].as_array() {
+        for sha_and_labels in branch_draw_properties {
+            if let (Some(sha), Some(labels)) = (sha_and_labels[0].as_str(), sha_and_labels[1].as_array()) {
+                branch_labels.insert(sha, labels);
+            }
+        }
+    }
+
+    // Work out how wide everything is first since the author columns are right-aligned.
+    let mut text_end_x: f64 = 0.0;
+    let mut author_name_width: f64 = 0.0;
+    let mut author_time_width: f64 = 0.0;
+    for row in rows {
+        let elements = &row["elements"];
+        let mut current_x = get_attr_number(&elements["summary_text"], "x");
+        if let Some(labels) = branch_labels.get(row["sha"].as_str().unwrap_or("")) {
+            for label in labels.iter() {
+                current_x += get_text_width(get_text_content(&label[0])) + 10.0 + BRANCH_TEXT_SPACING;
+            }
+        }
+        current_x += get_text_width(get_text_content(&elements["summary_text"]));
+        text_end_x = text_end_x.max(current_x);
+        author_name_width = author_name_width.max(get_text_width(get_text_content(&elements["author_name"])));
+        author_time_width = author_time_width.max(get_text_width(get_text_content(&elements["author_time"])));
+    }
+    let width = text_end_x + author_name_width + author_time_width + RIGHT_TEXT_SPACING * 3.0;
+    let height = (rows.len() as isize + 1) * Y_SPACING;
+
+    let mut svg = String::new();
+    writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"{}\" font-size=\"{}\">", width, height, FONT_FAMILY, FONT_SIZE)?;
+    writeln!(svg, "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"{}\"/>", width, height, BACKGROUND_COLOR)?;
+
+    // Draw all the lines first so the commit circles end up on top of them.
+    for row in rows {
+        if let Some(child_lines) = row["elements"]["child_lines"].as_array() {
+            for child_line in child_lines {
+                write_element(&mut svg, child_line, &[])?;
+            }
+        }
+    }
+
+    for row in rows {
+        let elements = &row["elements"];
+        write_element(&mut svg, &elements["circle"], &[])?;
+
+        let pixel_y = get_attr_number(&elements["circle"], "cy");
+        let mut current_x = get_attr_number(&elements["summary_text"], "x");
+        if let Some(labels) = branch_labels.get(row["sha"].as_str().unwrap_or("")) {
+            for label in labels.iter() {
+                let box_width = get_text_width(get_text_content(&label[0])) + 10.0;
+                write_element(&mut svg, &label[1], &[("x", current_x - 5.0), ("y", get_attr_number(&label[1], "y") + pixel_y), ("width", box_width)])?;
+                write_element(&mut svg, &label[0], &[("x", current_x), ("y", get_attr_number(&label[0], "y") + pixel_y)])?;
+                current_x += box_width + BRANCH_TEXT_SPACING;
+            }
+        }
+        write_element(&mut svg, &elements["summary_text"], &[("x", current_x)])?;
+
+        let author_time_x = width - get_text_width(get_text_content(&elements["author_time"])) - RIGHT_TEXT_SPACING;
+        let author_name_x = author_time_x - get_text_width(get_text_content(&elements["author_name"])) - RIGHT_TEXT_SPACING;
+        write_element(&mut svg, &elements["author_name"], &[("x", author_name_x)])?;
+        write_element(&mut svg, &elements["author_time"], &[("x", author_time_x)])?;
+    }
+
+    writeln!(svg, "</svg>")?;
+    Ok(svg)
+}
+
+fn write_png(svg: &str, path: &Path) -> Result<()> {
+    let mut tree = Tree::from_str(svg, &Options::default())?;
+    let mut font_database = fontdb::Database::new();
+    font_database.load_system_fonts();
+    tree.postprocess(PostProcessingSteps::default(), &font_database);
+
+    let size = tree.size.to_int_size();
+    let mut pixmap = match Pixmap::new(size.width(), size.height()) {
+        Some(p) => p,
+        None => bail!("Graph is too large to export as a PNG."),
+    };
+    resvg::render(&tree, Transform::default(), &mut pixmap.as_mut());
+    pixmap.save_png(path)?;
+    Ok(())
+}
+
+pub fn export_graph(git_manager: &mut GitManager, json_str: &str) -> Result<()> {
+    let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+    let path = match json_hm.get("path") {
+        Some(p) => PathBuf::from(p),
+        None => bail!("path not included in payload from front-end."),
+    };
+    let format = match json_hm.get("format") {
+        Some(f) => f,
+        None => bail!("format not included in payload from front-end."),
+    };
+
+    // Force a refresh, otherwise nothing comes back if the graph hasn't changed since the last update.
+    let commits_info = serde_json::to_value(get_commit_svg_draw_properties_list(git_manager, true)?)?;
+    let svg = get_graph_svg(&commits_info)?;
+    match format.as_str() {
+        "svg" => fs::write(path, svg)?,
+        "png" => write_png(&svg, &path)?,
+        _ => bail!("Unrecognized graph export format: {}", format),
+    };
+    Ok(())
+}
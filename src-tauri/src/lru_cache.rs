@@ -0,0 +1,36 @@
+use std::collections::VecDeque;
+
+// A small cache that drops the least recently used entry when it's full. Lookups are linear, which is fine for the few dozen
+// entries it's used with.
+pub struct LruCache<K: PartialEq, V: Clone> {
+    capacity: usize,
+    // Most recently used first.
+    entries: VecDeque<(K, V)>,
+}
+
+impl<K: PartialEq, V: Clone> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let entry_index = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(entry_index)?;
+        let value = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(value)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.entries.retain(|(k, _)| k != &key);
+        self.entries.push_front((key, value));
+        self.entries.truncate(self.capacity);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
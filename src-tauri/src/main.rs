@@ -7,18 +7,35 @@ pub mod git_manager;
 pub mod config_manager;
 pub mod svg_row;
 pub mod parseable_info;
+pub mod structured_error;
+pub mod command_registry;
+pub mod graph_export;
+pub mod operation_journal;
+pub mod lru_cache;
+pub mod window_router;
 
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
-use tauri::{CustomMenuItem, Manager, Menu, MenuItem, Submenu, Window, WindowBuilder, WindowEvent, Wry};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, ClipboardManager, CustomMenuItem, Manager, Menu, MenuItem, Submenu, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem, Window, WindowBuilder, WindowEvent, Wry};
 use tauri::MenuEntry::NativeItem;
+use tauri::api::notification::Notification;
 use time::UtcOffset;
 use git_manager::GitManager;
-use parseable_info::{get_parseable_repo_info, get_files_changed_info_list};
+use parseable_info::{filter_branches, get_all_badges, get_full_state, get_graph_minimap, get_graph_rows, get_parseable_repo_info, get_files_changed_update};
+use structured_error::StructuredError;
+use command_registry::get_command_list;
+use graph_export::export_graph;
+use window_router::{get_window_kind, RoutedEmit, WindowRouter, MAIN_WINDOW_LABEL};
 
 fn handle_error(e: anyhow::Error, main_window: &Window<Wry>) {
+    if let Some(structured_error) = StructuredError::from_anyhow(&e) {
+        main_window.emit_routed("structured-error", structured_error).unwrap();
+        return;
+    }
     let error_string = format!("{:?}", e);
-    main_window.emit_all("error", error_string).unwrap();
+    main_window.emit_routed("error", error_string).unwrap();
 }
 
 fn emit_update_all(git_manager: &mut MutexGuard<GitManager>, force_refresh: bool, main_window: &Window<Wry>) {
@@ -26,921 +43,2889 @@ fn emit_update_all(git_manager: &mut MutexGuard<GitManager>, force_refresh: bool
     match result {
         Ok(repo_info_opt) => {
             if let Some(repo_info) = repo_info_opt {
-                main_window.emit_all("update_all", repo_info).unwrap();
+                main_window.emit_routed("update_all", repo_info).unwrap();
+                update_tray(git_manager, main_window);
             } else {
-                main_window.emit_all("no-open-repo", "").unwrap();
+                main_window.emit_routed("no-open-repo", "").unwrap();
+            }
+        },
+        Err(e) => handle_error(e, main_window),
+    };
+}
+
+// Runs an operation that can move refs and records what it moved in the operation journal.
+fn journal_operation<T>(git_manager: &mut GitManager, operation: &str, run: impl FnOnce(&mut GitManager) -> anyhow::Result<T>) -> anyhow::Result<T> {
+    git_manager.ensure_writable()?;
+    let refs_before_result = operation_journal::get_ref_states(git_manager);
+    let result = run(git_manager);
+    if let Ok(refs_before) = refs_before_result {
+        let error = result.as_ref().err().map(|e| e.to_string());
+        // The journal is only a record, so failing to write to it shouldn't fail the operation.
+        let _ = operation_journal::record_operation(git_manager, operation, &refs_before, error);
+    }
+    result
+}
+
+// Runs a journaled operation that takes no input on its own thread, for operations started from both the front-end and the menu.
+fn spawn_journal_operation(main_window: &Window<Wry>, git_manager_arc: &Arc<Mutex<GitManager>>, operation: &'static str, run: fn(&mut GitManager) -> anyhow::Result<()>) {
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    thread::spawn(move || {
+        let mut git_manager = git_manager_arc_c.lock().unwrap();
+        let result = journal_operation(&mut git_manager, operation, run);
+        match result {
+            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c),
+            Err(e) => handle_error(e, &main_window_c),
+        };
+    });
+}
+
+fn write_clipboard(main_window: &Window<Wry>, text: String) -> anyhow::Result<()> {
+    main_window.app_handle().clipboard_manager().write_text(text)?;
+    Ok(())
+}
+
+fn notify(main_window: &Window<Wry>, title: &str, body: &str) {
+    let identifier = main_window.config().tauri.bundle.identifier.clone();
+    // Not every desktop has a notification server running, so don't treat this as an error.
+    let _ = Notification::new(identifier).title(title).body(body).show();
+}
+
+// Lets the user know a long operation is done if they switched away from the window while waiting on it.
+fn notify_if_unfocused(main_window: &Window<Wry>, operation_name: &str, result: &anyhow::Result<()>) {
+    if main_window.is_focused().unwrap_or(true) {
+        return;
+    }
+    match result {
+        Ok(()) => notify(main_window, &format!("{} Finished", operation_name), &format!("{} finished successfully.", operation_name)),
+        Err(_) => notify(main_window, &format!("{} Failed", operation_name), &format!("{} failed, open Oxidized Git for details.", operation_name)),
+    };
+}
+
+// Recurses a fetch or pull into the repo's submodules if that's turned on in the preferences.
+fn update_submodules_if_enabled(git_manager: &MutexGuard<GitManager>, is_pulling: bool, main_window: &Window<Wry>) {
+    let recurse_submodules = match config_manager::get_config() {
+        Ok(c) => c.borrow_recurse_submodules().unwrap_or(false),
+        Err(_) => false,
+    };
+    if !recurse_submodules {
+        return;
+    }
+    let result = git_manager.git_update_submodules(is_pulling, &|submodule_progress| {
+        main_window.emit_routed("submodule-progress", submodule_progress).unwrap();
+    });
+    match result {
+        Ok(submodule_update_results) => {
+            if !submodule_update_results.is_empty() {
+                main_window.emit_routed("show-submodule-results", submodule_update_results).unwrap();
             }
         },
         Err(e) => handle_error(e, main_window),
     };
 }
 
+fn update_tray(git_manager: &MutexGuard<GitManager>, main_window: &Window<Wry>) {
+    let status = match git_manager.get_ahead_behind() {
+        Ok(Some((ahead, behind))) => format!("↑{} ↓{}", ahead, behind),
+        Ok(None) => String::from("No upstream branch"),
+        Err(_) => String::from("Unknown status"),
+    };
+    let tray_handle = main_window.app_handle().tray_handle();
+    // The tray is only a convenience (and some desktops don't have one), so failing to update it isn't treated as an error.
+    // Tooltips aren't supported on Linux, so the status is also shown as a disabled menu item.
+    let _ = tray_handle.get_item("tray-status").set_title(status.clone());
+    let _ = tray_handle.set_tooltip(&format!("Oxidized Git ({})", status));
+    #[cfg(target_os = "macos")]
+    let _ = tray_handle.set_title(&status);
+}
+
 fn emit_update_changes(git_manager: &MutexGuard<GitManager>, main_window: &Window<Wry>) {
-    let result = get_files_changed_info_list(git_manager);
+    let result = get_files_changed_update(git_manager);
     match result {
         Ok(changes_info_opt) => {
             if let Some(changes_info) = changes_info_opt {
-                main_window.emit_all("update_changes", changes_info).unwrap();
+                main_window.emit_routed("update_changes", changes_info).unwrap();
             }
         },
         Err(e) => handle_error(e, main_window),
     }
 }
 
-fn main() {
-    let current_local_offset = UtcOffset::current_local_offset().unwrap();
-    tauri::Builder::default()
-    .setup(move |app| {
-        let mut menu;
-        if std::env::consts::OS == "macos" {
-            menu = Menu::with_items([
-                Submenu::new("App", Menu::with_items([
-                    CustomMenuItem::new("preferences", "Preferences").into(),
-                    NativeItem(MenuItem::Separator),
-                    NativeItem(MenuItem::Quit),
-                ])).into(),
-                Submenu::new("File", Menu::with_items([
-                    CustomMenuItem::new("init", "Init New Repo").into(),
-                    CustomMenuItem::new("open", "Open Repo").into(),
-                    CustomMenuItem::new("clone", "Clone Repo").into(),
-                ])).into(),
-                Submenu::new("Edit", Menu::with_items([
-                    NativeItem(MenuItem::Undo),
-                    NativeItem(MenuItem::Redo),
-                    NativeItem(MenuItem::Separator),
-                    NativeItem(MenuItem::Copy),
-                    NativeItem(MenuItem::Paste),
-                    NativeItem(MenuItem::SelectAll),
-                ])).into(),
-                Submenu::new("View", Menu::with_items([
-                    CustomMenuItem::new("refresh", "Refresh").accelerator("CommandOrControl+R").into(),
-                ])).into(),
-                Submenu::new("Security", Menu::with_items([
-                    CustomMenuItem::new("credentials", "Set Credentials").into(),
-                ])).into(),
-            ]);
-        } else {
-            menu = Menu::with_items([
-                Submenu::new("File", Menu::with_items([
-                    CustomMenuItem::new("init", "Init New Repo").into(),
-                    CustomMenuItem::new("open", "Open Repo").into(),
-                    CustomMenuItem::new("clone", "Clone Repo").into(),
-                    NativeItem(MenuItem::Separator),
-                    CustomMenuItem::new("preferences", "Preferences").into(),
-                    NativeItem(MenuItem::Separator),
-                    NativeItem(MenuItem::Quit),
-                ])).into(),
-            ]);
-            if std::env::consts::OS == "windows" {
-                menu = menu.add_submenu(
-                    Submenu::new("Edit", Menu::with_items([
-                        NativeItem(MenuItem::Copy),
-                        NativeItem(MenuItem::Paste),
-                    ]))
-                );
+fn get_menu_item(id: &str, title: &str, keymap: &HashMap<String, String>) -> CustomMenuItem {
+    let menu_item = CustomMenuItem::new(id, title);
+    match keymap.get(id) {
+        Some(accelerator) if accelerator != "" => menu_item.accelerator(accelerator),
+        _ => menu_item,
+    }
+}
+
+fn get_system_tray() -> SystemTray {
+    let tray_menu = SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("tray-status", "No repo open").disabled())
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new("tray-fetch", "Fetch"))
+        .add_item(CustomMenuItem::new("tray-pull", "Pull"))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new("tray-show", "Open Window"))
+        .add_item(CustomMenuItem::new("tray-quit", "Quit"));
+    SystemTray::new().with_menu(tray_menu).with_tooltip("Oxidized Git")
+}
+
+fn handle_system_tray_event(app: &AppHandle<Wry>, event: SystemTrayEvent) {
+    if let SystemTrayEvent::MenuItemClick { id, .. } = event {
+        let main_window = match app.get_window(MAIN_WINDOW_LABEL) {
+            Some(w) => w,
+            None => return,
+        };
+        match id.as_str() {
+            "tray-fetch" | "tray-pull" => {
+                main_window.emit_routed("start-process", "").unwrap();
+                let git_manager_arc = app.state::<Arc<Mutex<GitManager>>>().inner().clone();
+                thread::spawn(move || {
+                    let mut git_manager = git_manager_arc.lock().unwrap();
+                    let (operation_name, result) = if id == "tray-fetch" {
+                        ("Fetch", journal_operation(&mut git_manager, "fetch", |gm| gm.git_fetch()))
+                    } else {
+                        ("Pull", journal_operation(&mut git_manager, "pull", |gm| gm.git_pull()))
+                    };
+                    notify_if_unfocused(&main_window, operation_name, &result);
+                    match result {
+                        Ok(()) => {
+                            update_submodules_if_enabled(&git_manager, id == "tray-pull", &main_window);
+                            emit_update_all(&mut git_manager, false, &main_window);
+                        },
+                        Err(e) => handle_error(e, &main_window),
+                    };
+                });
+            },
+            "tray-show" => {
+                main_window.show().unwrap();
+                main_window.unminimize().unwrap();
+                main_window.set_focus().unwrap();
+            },
+            "tray-quit" => app.exit(0),
+            &_ => {},
+        };
+    }
+}
+
+// Opens a window with one part of the main window's layout, or focuses it if one of that kind is already open.
+fn open_aux_window(main_window: &Window<Wry>, kind: &str, git_manager_arc: &Arc<Mutex<GitManager>>) -> anyhow::Result<()> {
+    let router_state = main_window.state::<Mutex<WindowRouter>>();
+    let label = {
+        let mut router = router_state.lock().unwrap();
+        let manager_id = match router.get_manager_id(main_window.label()) {
+            Some(id) => id,
+            None => anyhow::bail!("This window isn't bound to a repo."),
+        };
+        if let Some(open_label) = router.get_window_label(kind, manager_id) {
+            if let Some(open_window) = main_window.get_window(&open_label) {
+                open_window.unminimize()?;
+                open_window.set_focus()?;
+                return Ok(());
             }
-            menu = menu.add_submenu(
-                Submenu::new("View", Menu::with_items([
-                    CustomMenuItem::new("refresh", "Refresh").accelerator("CommandOrControl+R").into(),
-                ]))
-            );
-            menu = menu.add_submenu(
-                Submenu::new("Security", Menu::with_items([
-                    CustomMenuItem::new("credentials", "Set Credentials").into(),
-                ]))
-            );
+            router.unsubscribe(&open_label);
         }
+        router.subscribe(kind, manager_id)?
+    };
+    let window_result = WindowBuilder::new(
+        main_window,
+        label.clone(),
+        tauri::WindowUrl::App(format!("index.html?layout={}", kind).into()),
+    )
+    .title(format!("Oxidized Git ({})", kind))
+    .build();
+    let aux_window = match window_result {
+        Ok(w) => w,
+        Err(e) => {
+            router_state.lock().unwrap().unsubscribe(&label);
+            return Err(e.into());
+        },
+    };
+    register_listeners(&aux_window, git_manager_arc);
 
-        let main_window = WindowBuilder::new(
-            app,
-            "main-window".to_string(),
-            tauri::WindowUrl::App("index.html".into()),
-        )
-        .menu(menu)
-        .maximized(true)
-        .title("Oxidized Git")
-        .build()?;
-
-        let git_manager_arc: Arc<Mutex<GitManager>> = Arc::new(Mutex::new(GitManager::new(current_local_offset)));
+    let aux_window_c = aux_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    aux_window.on_window_event(move |event| {
+        match event {
+            WindowEvent::Focused(is_focused) => {
+                if *is_focused {
+                    aux_window_c.emit_routed("start-process", "").unwrap();
+                    let aux_window_c_c = aux_window_c.clone();
+                    let git_manager_arc_c_c = git_manager_arc_c.clone();
+                    thread::spawn(move || {
+                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                        emit_update_all(&mut git_manager, false, &aux_window_c_c);
+                    });
+                }
+            },
+            WindowEvent::Destroyed => {
+                aux_window_c.state::<Mutex<WindowRouter>>().lock().unwrap().unsubscribe(aux_window_c.label());
+            },
+            _ => {},
+        }
+    });
+    Ok(())
+}
 
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.on_window_event(move |event| {
-            match event {
-                WindowEvent::Focused(is_focused) => {
-                    if *is_focused {
-                        main_window_c.emit_all("start-process", "").unwrap();
-                        let main_window_c_c = main_window_c.clone();
-                        let git_manager_arc_c_c = git_manager_arc_c.clone();
-                        thread::spawn(move || {
-                            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                            emit_update_all(&mut git_manager, false, &main_window_c_c);
-                        });
-                    }
+fn register_listeners(main_window: &Window<Wry>, git_manager_arc: &Arc<Mutex<GitManager>>) {
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-commit-info", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_commit_info(s);
+                    match result {
+                        Ok(r) => main_window_c_c.emit_routed("commit-info", r).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
                 },
-                _ => {},
-            }
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.on_menu_event(move |event| {
-            match event.menu_item_id() {
-                "preferences" => {
-                    match config_manager::get_config() {
-                        Ok(c) => {
-                            main_window_c.emit_all("show-preferences", c).unwrap();
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("init", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.init_repo(s);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, true, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("open", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.open_repo(s);
+                    match result {
+                        Ok(repo_capabilities) => {
+                            main_window_c_c.emit_routed("repo-capabilities", repo_capabilities).unwrap();
+                            emit_update_all(&mut git_manager, true, &main_window_c_c);
                         },
-                        Err(e) => handle_error(e, &main_window_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
                     };
                 },
-                // Don't use a separate thread for init, open, or clone so as not to break the file dialog in Linux.
-                "init" => {
-                    main_window_c.emit_all("get-init", "").unwrap();
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("clone", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.clone_repo(s);
+                    notify_if_unfocused(&main_window_c_c, "Clone", &result);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, true, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
                 },
-                "open" => {
-                    main_window_c.emit_all("get-open", "").unwrap();
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("merge", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "merge", |gm| gm.git_merge(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
                 },
-                "clone" => {
-                    main_window_c.emit_all("get-clone", "").unwrap();
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("rebase", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "rebase", |gm| gm.git_rebase(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
                 },
-                "refresh" => {
-                    main_window_c.emit_all("start-process", "").unwrap();
-                    let main_window_c_c = main_window_c.clone();
-                    let git_manager_arc_c_c = git_manager_arc_c.clone();
-                    thread::spawn(move || {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        emit_update_all(&mut git_manager, false, &main_window_c_c);
-                    });
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("cherrypick", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "cherrypick", |gm| gm.git_cherrypick(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
                 },
-                "credentials" => {
-                    main_window_c.emit_all("get-credentials", "").unwrap();
-                }
-                &_ => {},
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
             };
         });
-
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("get-commit-info", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.get_commit_info(s);
-                        match result {
-                            Ok(r) => main_window_c_c.emit_all("commit-info", r).unwrap(),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("revert", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "revert", |gm| gm.git_revert(s));
+                    match result {
+                        Ok(mainline_info_opt) => {
+                            if let Some(mainline_info) = mainline_info_opt {
+                                main_window_c_c.emit_routed("get-revert-mainline", mainline_info).unwrap();
+                            } else {
+                                emit_update_all(&mut git_manager, false, &main_window_c_c);
+                            }
+                        },
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("init", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.init_repo(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, true, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("reset", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "reset", |gm| gm.git_reset(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("open", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.open_repo(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, true, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("add-remote", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_add_remote(s);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("clone", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.clone_repo(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, true, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("checkout", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "checkout", |gm| gm.git_checkout_from_json(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("merge", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_merge(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("checkout-detached-head", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "checkout-detached-head", |gm| gm.git_checkout_detached_head(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("rebase", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_rebase(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("checkout-remote", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "checkout-remote", |gm| gm.git_checkout_remote(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("cherrypick", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_cherrypick(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("save-preferences", move |event| {
+        match event.payload() {
+            Some(s) => {
+                match config_manager::save_config_from_json(s) {
+                    Ok(()) => {
+                        let main_window_c_c = main_window_c.clone();
+                        let git_manager_arc_c_c = git_manager_arc_c.clone();
+                        thread::spawn(move || {
+                            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                            emit_update_all(&mut git_manager, true, &main_window_c_c);
+                        });
                     },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
+                    Err(e) => handle_error(e, &main_window_c),
                 };
-            });
+            },
+            None => main_window_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+        };
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("save-https-credentials", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.set_https_credentials(s);
+                    match result {
+                        Ok(()) => (),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("revert", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_revert(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("save-ssh-credentials", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.set_ssh_credentials(s);
+                    match result {
+                        Ok(()) => (),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("reset", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_reset(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("stage", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_stage_from_json(s);
+                    match result {
+                        Ok(large_files) => {
+                            emit_update_changes(&git_manager, &main_window_c_c);
+                            if !large_files.is_empty() {
+                                main_window_c_c.emit_routed("large-files-staged", large_files).unwrap();
+                            }
+                        },
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("add-remote", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_add_remote(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("unstage", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_unstage(s);
+                    match result {
+                        Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("checkout", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_checkout_from_json(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("file-diff", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_file_diff(s);
+                    match result {
+                        Ok(file_lines) => main_window_c_c.emit_routed("show-file-lines", file_lines).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("checkout-detached-head", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_checkout_detached_head(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("stage-all", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = git_manager.git_stage_all();
+            match result {
+                Ok(large_files) => {
+                    emit_update_changes(&git_manager, &main_window_c_c);
+                    if !large_files.is_empty() {
+                        main_window_c_c.emit_routed("large-files-staged", large_files).unwrap();
+                    }
+                },
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("checkout-remote", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_checkout_remote(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("commit", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "commit", |gm| gm.git_commit_from_json(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("save-preferences", move |event| {
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("commit-push", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
             match event.payload() {
                 Some(s) => {
-                    match config_manager::save_config_from_json(s) {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "commit-push", |gm| gm.git_commit_from_json(s));
+                    match result {
                         Ok(()) => {
-                            let main_window_c_c = main_window_c.clone();
-                            let git_manager_arc_c_c = git_manager_arc_c.clone();
-                            thread::spawn(move || {
-                                let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                                emit_update_all(&mut git_manager, true, &main_window_c_c);
-                            });
+                            let result_2 = git_manager.git_push(None);
+                            notify_if_unfocused(&main_window_c_c, "Push", &result_2);
+                            match result_2 {
+                                Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                                Err(e) => handle_error(e, &main_window_c_c),
+                            };
                         },
-                        Err(e) => handle_error(e, &main_window_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
                     };
                 },
-                None => main_window_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-            };
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("save-https-credentials", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.set_https_credentials(s);
-                        match result {
-                            Ok(()) => (),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("abort", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "abort", |gm| gm.git_abort());
+            match result {
+                Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("save-ssh-credentials", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.set_ssh_credentials(s);
-                        match result {
-                            Ok(()) => (),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("continue-cherrypick", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "continue-cherrypick", |gm| gm.git_continue_cherrypick());
+            match result {
+                Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("stage", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_stage_from_json(s);
-                        match result {
-                            Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("continue-revert", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "continue-revert", |gm| gm.git_continue_revert());
+            match result {
+                Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("unstage", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_unstage(s);
-                        match result {
-                            Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("continue-merge", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "continue-merge", |gm| gm.git_continue_merge());
+            match result {
+                Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("file-diff", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.get_file_diff(s);
-                        match result {
-                            Ok(file_lines) => main_window_c_c.emit_all("show-file-lines", file_lines).unwrap(),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("abort-rebase", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "abort-rebase", |gm| gm.git_abort_rebase());
+            match result {
+                Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("stage-all", move |_event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                let git_manager = git_manager_arc_c_c.lock().unwrap();
-                let result = git_manager.git_stage_all();
-                match result {
-                    Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
-                    Err(e) => handle_error(e, &main_window_c_c),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("continue-rebase", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "continue-rebase", |gm| gm.git_continue_rebase());
+            match result {
+                Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("commit", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_commit_from_json(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("discard-changes", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_discard_changes(s);
+                    match result {
+                        Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("commit-push", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_commit_from_json(s);
-                        match result {
-                            Ok(()) => {
-                                let result_2 = git_manager.git_push(None);
-                                match result_2 {
-                                    Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                                    Err(e) => handle_error(e, &main_window_c_c),
-                                };
-                            },
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("delete-local-branch", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "delete-local-branch", |gm| gm.git_delete_local_branch(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("abort", move |_event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                let result = git_manager.git_abort();
-                match result {
-                    Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                    Err(e) => handle_error(e, &main_window_c_c),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("delete-remote-branch", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "delete-remote-branch", |gm| gm.git_delete_remote_branch_from_json(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("continue-cherrypick", move |_event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                let result = git_manager.git_continue_cherrypick();
-                match result {
-                    Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                    Err(e) => handle_error(e, &main_window_c_c),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("delete-tag", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "delete-tag", |gm| gm.git_delete_tag(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("continue-revert", move |_event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                let result = git_manager.git_continue_revert();
-                match result {
-                    Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                    Err(e) => handle_error(e, &main_window_c_c),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("delete-ref", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "delete-ref", |gm| gm.git_delete_ref(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("continue-merge", move |_event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                let result = git_manager.git_continue_merge();
-                match result {
-                    Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                    Err(e) => handle_error(e, &main_window_c_c),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("fetch", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "fetch", |gm| gm.git_fetch());
+            notify_if_unfocused(&main_window_c_c, "Fetch", &result);
+            match result {
+                Ok(()) => {
+                    update_submodules_if_enabled(&git_manager, false, &main_window_c_c);
+                    emit_update_all(&mut git_manager, false, &main_window_c_c);
+                },
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("abort-rebase", move |_event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                let result = git_manager.git_abort_rebase();
-                match result {
-                    Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                    Err(e) => handle_error(e, &main_window_c_c),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("fast-forward-branch", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "fast-forward-branch", |gm| gm.git_fast_forward_branch(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            }
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("continue-rebase", move |_event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                let result = git_manager.git_continue_rebase();
-                match result {
-                    Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                    Err(e) => handle_error(e, &main_window_c_c),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("pull", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "pull", |gm| gm.git_pull());
+            match result {
+                Ok(()) => {
+                    update_submodules_if_enabled(&git_manager, true, &main_window_c_c);
+                    emit_update_all(&mut git_manager, false, &main_window_c_c);
+                },
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("discard-changes", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_discard_changes(s);
-                        match result {
-                            Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("push", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "push", |gm| gm.git_push(Some(s)));
+                    notify_if_unfocused(&main_window_c_c, "Push", &result);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("delete-local-branch", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_delete_local_branch(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("push-tag", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "push-tag", |gm| gm.git_push_tag(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("delete-remote-branch", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_delete_remote_branch_from_json(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("stash", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "stash", |gm| gm.git_stash(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("delete-tag", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_delete_tag(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("apply-stash", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "apply-stash", |gm| gm.git_apply_stash(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("delete-stash", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "delete-stash", |gm| gm.git_delete_stash(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("branch", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "branch", |gm| gm.git_branch(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("tag", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "tag", |gm| gm.git_tag(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-release-tag-info", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = git_manager.get_release_tag_info();
+            match result {
+                Ok(release_tag_info) => main_window_c_c.emit_routed("show-release-tag-info", release_tag_info).unwrap(),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("create-release-tag", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "create-release-tag", |gm| gm.git_create_release_tag(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("push-gerrit", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "push-gerrit", |gm| gm.git_push_gerrit(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-tag-info", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_tag_info(s);
+                    match result {
+                        Ok(tag_info) => main_window_c_c.emit_routed("show-tag-info", tag_info).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("generate-ssh-key", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.generate_ssh_key(s);
+                    match result {
+                        Ok(public_key) => main_window_c_c.emit_routed("show-generated-ssh-key", public_key).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("remove-stale-lock", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.remove_stale_lock(s);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-more-hunks", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_more_hunks(s);
+                    match result {
+                        Ok(file_info) => main_window_c_c.emit_routed("show-more-hunks", file_info).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("run-git-command", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.run_git_command(s, &|output| {
+                        main_window_c_c.emit_routed("git-command-output", output).unwrap();
+                    });
+                    match result {
+                        Ok(git_command_result) => {
+                            let refresh = git_command_result.borrow_refresh().clone();
+                            main_window_c_c.emit_routed("git-command-finished", git_command_result).unwrap();
+                            if refresh == "all" {
+                                emit_update_all(&mut git_manager, false, &main_window_c_c);
+                            } else if refresh == "changes" {
+                                emit_update_changes(&git_manager, &main_window_c_c);
+                            }
+                        },
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-full-state", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = get_full_state(&mut git_manager);
+            match result {
+                Ok(full_state) => main_window_c_c.emit_routed("full-state", full_state).unwrap(),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("export-graph", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = export_graph(&mut git_manager, s);
+                    match result {
+                        Ok(()) => (),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("list-commands", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = get_command_list(&git_manager);
+            match result {
+                Ok(command_list) => main_window_c_c.emit_routed("show-command-list", command_list).unwrap(),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("export-log", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.export_log(s);
+                    match result {
+                        Ok(()) => (),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-blame", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_blame(s);
+                    match result {
+                        Ok(blame_info) => main_window_c_c.emit_routed("show-blame", blame_info).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-file-history", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_file_history(s);
+                    match result {
+                        Ok(file_history) => main_window_c_c.emit_routed("show-file-history", file_history).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-graph-minimap", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = get_graph_minimap(&mut git_manager, s);
+                    match result {
+                        Ok(graph_minimap) => main_window_c_c.emit_routed("show-graph-minimap", graph_minimap).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-graph-rows", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = get_graph_rows(&mut git_manager, s);
+                    match result {
+                        Ok(graph_rows) => main_window_c_c.emit_routed("show-graph-rows", graph_rows).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-all-badges", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = get_all_badges(&git_manager, s);
+                    match result {
+                        Ok(all_badges) => main_window_c_c.emit_routed("show-all-badges", all_badges).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("filter-branches", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = filter_branches(&mut git_manager, s);
+                    match result {
+                        Ok(filtered_branches) => main_window_c_c.emit_routed("show-filtered-branches", filtered_branches).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("cherrypick-multiple", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "cherrypick-multiple", |gm| gm.git_cherrypick_multiple(s));
+                    match result {
+                        Ok(commit_operation_results) => {
+                            main_window_c_c.emit_routed("show-commit-operation-results", commit_operation_results).unwrap();
+                            emit_update_all(&mut git_manager, false, &main_window_c_c);
+                        },
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("revert-multiple", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "revert-multiple", |gm| gm.git_revert_multiple(s));
+                    match result {
+                        Ok(commit_operation_results) => {
+                            main_window_c_c.emit_routed("show-commit-operation-results", commit_operation_results).unwrap();
+                            emit_update_all(&mut git_manager, false, &main_window_c_c);
+                        },
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("export-patches", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.export_patches(s);
+                    match result {
+                        Ok(commit_operation_results) => main_window_c_c.emit_routed("show-commit-operation-results", commit_operation_results).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-shas-text", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_shas_text(s);
+                    match result {
+                        Ok(shas_text) => main_window_c_c.emit_routed("copy-shas-text", shas_text).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-copyable-commit-fields", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_copyable_commit_fields(s);
+                    match result {
+                        Ok(copyable_commit_fields) => main_window_c_c.emit_routed("copyable-commit-fields", copyable_commit_fields).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("copy-commit-field", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_commit_field_to_copy(s);
+                    match result {
+                        Ok(text) => {
+                            if let Err(e) = write_clipboard(&main_window_c_c, text) {
+                                handle_error(e, &main_window_c_c);
+                            }
+                        },
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-branch-remotes", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_branch_remotes(s);
+                    match result {
+                        Ok(branch_remotes_info) => main_window_c_c.emit_routed("show-branch-remotes", branch_remotes_info).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("set-branch-remotes", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.set_branch_remotes(s);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-remote-head", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_remote_head(s);
+                    match result {
+                        Ok(remote_head_info) => main_window_c_c.emit_routed("show-remote-head", remote_head_info).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("set-remote-head", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "set-remote-head", |gm| gm.set_remote_head(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("pull-branch", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "pull-branch", |gm| gm.git_pull_branch(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("push-branch", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "push-branch", |gm| gm.git_push_branch(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("sync", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "sync", |gm| gm.git_sync());
+            match result {
+                Ok(branch_sync_results) => {
+                    main_window_c_c.emit_routed("show-sync-results", branch_sync_results).unwrap();
+                    emit_update_all(&mut git_manager, false, &main_window_c_c);
+                },
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("add-submodule", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_add_submodule(s);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("remove-submodule", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_remove_submodule(s);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("subtree", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_subtree(s, &|output| {
+                        main_window_c_c.emit_routed("git-command-output", output).unwrap();
+                    });
+                    match result {
+                        Ok(git_command_result) => {
+                            main_window_c_c.emit_routed("git-command-finished", git_command_result).unwrap();
+                            emit_update_all(&mut git_manager, false, &main_window_c_c);
+                        },
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("apply-patch-text", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.apply_patch_text(s);
+                    match result {
+                        Ok(patch_hunk_checks) => {
+                            main_window_c_c.emit_routed("show-patch-hunk-checks", patch_hunk_checks).unwrap();
+                            emit_update_changes(&git_manager, &main_window_c_c);
+                        },
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-stash-diff", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_stash_diff(s);
+                    match result {
+                        Ok(stash_diff) => main_window_c_c.emit_routed("show-stash-diff", stash_diff).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("stash-to-branch", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "stash-to-branch", |gm| gm.git_stash_to_branch(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("continue-stash-apply", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "continue-stash-apply", |gm| gm.git_continue_stash_apply());
+            match result {
+                Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("abandon-stash-apply", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = journal_operation(&mut git_manager, "abandon-stash-apply", |gm| gm.git_abandon_stash_apply());
+            match result {
+                Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("list-snapshots", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = git_manager.get_snapshots();
+            match result {
+                Ok(snapshots) => main_window_c_c.emit_routed("show-snapshots", snapshots).unwrap(),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("restore-snapshot", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "restore-snapshot", |gm| gm.restore_snapshot(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-operation-history", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = operation_journal::get_operation_history(&git_manager);
+            match result {
+                Ok(operation_history) => main_window_c_c.emit_routed("show-operation-history", operation_history).unwrap(),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("renormalize", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = git_manager.git_renormalize();
+            match result {
+                Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("list-ignored-files", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_ignored_files(s);
+                    match result {
+                        Ok(r) => main_window_c_c.emit_routed("show-ignored-files", r).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("force-add", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_force_add(s);
+                    match result {
+                        Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("expand-untracked", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.expand_untracked(s);
+                    match result {
+                        Ok(r) => main_window_c_c.emit_routed("untracked-expanded", r).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("set-index-flag", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.set_index_flag(s);
+                    match result {
+                        Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("lfs-list-locks", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = git_manager.git_lfs_list_locks();
+            match result {
+                Ok(r) => {
+                    main_window_c_c.emit_routed("show-lfs-locks", r).unwrap();
+                    // So the changes list shows the locks too.
+                    emit_update_changes(&git_manager, &main_window_c_c);
+                },
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("lfs-lock", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_lfs_lock(s);
+                    match result {
+                        Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("lfs-unlock", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_lfs_unlock(s);
+                    match result {
+                        Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-issue-link-rules", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = git_manager.get_issue_link_rules();
+            match result {
+                Ok(r) => main_window_c_c.emit_routed("show-issue-link-rules", r).unwrap(),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("save-issue-link-rules", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.set_issue_link_rules(s);
+                    match result {
+                        Ok(()) => (),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("wip-commit", move |_event| {
+        spawn_journal_operation(&main_window_c, &git_manager_arc_c, "wip-commit", |gm| gm.git_wip_commit());
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("unwip", move |_event| {
+        spawn_journal_operation(&main_window_c, &git_manager_arc_c, "unwip", |gm| gm.git_unwip());
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("checkout-with-strategy", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "checkout", |gm| gm.git_checkout_with_strategy(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("set-remote-urls", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.set_remote_urls(s);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("add-remote-refspec", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.add_remote_refspec(s);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("remove-remote-refspec", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.remove_remote_refspec(s);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("replace", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "replace", |gm| gm.git_replace(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("delete-replace", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "delete-replace", |gm| gm.git_delete_replace(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("deepen", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = journal_operation(&mut git_manager, "deepen", |gm| gm.git_deepen(s));
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("open-window", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let result = get_window_kind(s).and_then(|kind| open_aux_window(&main_window_c_c, &kind, &git_manager_arc_c_c));
+                    if let Err(e) = result {
+                        handle_error(e, &main_window_c_c);
+                    }
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("save-repo-session", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.save_repo_session(s);
+                    match result {
+                        Ok(()) => (),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("checkout-file-from", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_checkout_file_from(s);
+                    match result {
+                        Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-workdir-diff", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_workdir_diff(s);
+                    match result {
+                        Ok(workdir_diff) => main_window_c_c.emit_routed("show-workdir-diff", workdir_diff).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("diff-merge-base", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_merge_base_diff(s);
+                    match result {
+                        Ok(merge_base_diff) => main_window_c_c.emit_routed("show-merge-base-diff", merge_base_diff).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-staged-patch", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = git_manager.get_staged_patch();
+            match result {
+                Ok(staged_patch) => main_window_c_c.emit_routed("show-staged-patch", staged_patch).unwrap(),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("validate-commit", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = git_manager.validate_commit();
+            match result {
+                Ok(commit_problems) => main_window_c_c.emit_routed("commit-validation", commit_problems).unwrap(),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("track-with-lfs", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.git_track_with_lfs(s);
+                    match result {
+                        Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("list-hooks", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = git_manager.get_hooks();
+            match result {
+                Ok(hooks_info) => main_window_c_c.emit_routed("show-hooks", hooks_info).unwrap(),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-hook", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_hook(s);
+                    match result {
+                        Ok(hook) => main_window_c_c.emit_routed("show-hook", hook).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("save-hook", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.save_hook(s);
+                    match result {
+                        Ok(hooks_info) => main_window_c_c.emit_routed("show-hooks", hooks_info).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("set-hook-executable", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.set_hook_executable(s);
+                    match result {
+                        Ok(hooks_info) => main_window_c_c.emit_routed("show-hooks", hooks_info).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("install-sample-hook", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.install_sample_hook(s);
+                    match result {
+                        Ok(hooks_info) => main_window_c_c.emit_routed("show-hooks", hooks_info).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-conflicts-info", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_conflicts_info(s);
+                    match result {
+                        Ok(conflicts_info) => main_window_c_c.emit_routed("show-conflicts-info", conflicts_info).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-conflict-regions", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_conflict_regions(s);
+                    match result {
+                        Ok(conflict_regions) => main_window_c_c.emit_routed("show-conflict-regions", conflict_regions).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("resolve-conflict-region", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.resolve_conflict_region(s);
+                    match result {
+                        Ok(conflict_regions) => {
+                            emit_update_changes(&git_manager, &main_window_c_c);
+                            main_window_c_c.emit_routed("show-conflict-regions", conflict_regions).unwrap();
+                        },
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
+        });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("resolve-binary", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.resolve_binary(s);
+                    match result {
+                        Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("fetch", move |_event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                let result = git_manager.git_fetch();
-                match result {
-                    Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                    Err(e) => handle_error(e, &main_window_c_c),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("preview-merge", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.preview_merge(s);
+                    match result {
+                        Ok(merge_preview) => main_window_c_c.emit_routed("show-merge-preview", merge_preview).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("fast-forward-branch", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_fast_forward_branch(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                }
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("expand-hunk-context", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_hunk_context(s);
+                    match result {
+                        Ok(hunk_context) => main_window_c_c.emit_routed("show-hunk-context", hunk_context).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("pull", move |_event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                let result = git_manager.git_pull();
-                match result {
-                    Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                    Err(e) => handle_error(e, &main_window_c_c),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("set-branch-favorite", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.set_branch_favorite(s);
+                    match result {
+                        Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("push", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_push(Some(s));
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("show-more-tags", move |_event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+            let result = git_manager.show_more_tags();
+            match result {
+                Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                Err(e) => handle_error(e, &main_window_c_c),
+            };
         });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("push-tag", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_push_tag(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+    });
+    let main_window_c = main_window.clone();
+    let git_manager_arc_c = git_manager_arc.clone();
+    main_window.listen("get-tag-details", move |event| {
+        let main_window_c_c = main_window_c.clone();
+        let git_manager_arc_c_c = git_manager_arc_c.clone();
+        thread::spawn(move || {
+            match event.payload() {
+                Some(s) => {
+                    let git_manager = git_manager_arc_c_c.lock().unwrap();
+                    let result = git_manager.get_tag_details(s);
+                    match result {
+                        Ok(tag_details) => main_window_c_c.emit_routed("show-tag-details", tag_details).unwrap(),
+                        Err(e) => handle_error(e, &main_window_c_c),
+                    };
+                },
+                None => main_window_c_c.emit_routed("error", "Failed to receive payload from front-end").unwrap(),
+            };
         });
+    });
+}
+
+fn main() {
+    let current_local_offset = UtcOffset::current_local_offset().unwrap();
+    tauri::Builder::default()
+    .system_tray(get_system_tray())
+    .on_system_tray_event(handle_system_tray_event)
+    .setup(move |app| {
+        let keymap = config_manager::get_keymap();
+        let mut menu;
+        if std::env::consts::OS == "macos" {
+            menu = Menu::with_items([
+                Submenu::new("App", Menu::with_items([
+                    CustomMenuItem::new("preferences", "Preferences").into(),
+                    NativeItem(MenuItem::Separator),
+                    NativeItem(MenuItem::Quit),
+                ])).into(),
+                Submenu::new("File", Menu::with_items([
+                    CustomMenuItem::new("init", "Init New Repo").into(),
+                    get_menu_item("open", "Open Repo", &keymap).into(),
+                    CustomMenuItem::new("clone", "Clone Repo").into(),
+                ])).into(),
+                Submenu::new("Edit", Menu::with_items([
+                    NativeItem(MenuItem::Undo),
+                    NativeItem(MenuItem::Redo),
+                    NativeItem(MenuItem::Separator),
+                    NativeItem(MenuItem::Copy),
+                    NativeItem(MenuItem::Paste),
+                    NativeItem(MenuItem::SelectAll),
+                ])).into(),
+                Submenu::new("View", Menu::with_items([
+                    get_menu_item("refresh", "Refresh", &keymap).into(),
+                    CustomMenuItem::new("git-console", "Run Git Command").into(),
+                    CustomMenuItem::new("export-graph", "Export Graph").into(),
+                    CustomMenuItem::new("export-log", "Export Log").into(),
+                    CustomMenuItem::new("operation-history", "Operation History").into(),
+                    CustomMenuItem::new("ignored-files", "Ignored Files").into(),
+                    NativeItem(MenuItem::Separator),
+                    CustomMenuItem::new("open-history-window", "Open History Window").into(),
+                    CustomMenuItem::new("open-changes-window", "Open Changes Window").into(),
+                    CustomMenuItem::new("open-conflicts-window", "Open Conflicts Window").into(),
+                ])).into(),
+                Submenu::new("Repo", Menu::with_items([
+                    get_menu_item("fetch", "Fetch", &keymap).into(),
+                    get_menu_item("push", "Push", &keymap).into(),
+                    NativeItem(MenuItem::Separator),
+                    CustomMenuItem::new("add-submodule", "Add Submodule").into(),
+                    CustomMenuItem::new("remove-submodule", "Remove Submodule").into(),
+                    CustomMenuItem::new("subtree", "Subtree").into(),
+                    NativeItem(MenuItem::Separator),
+                    CustomMenuItem::new("wip-commit", "WIP Commit").into(),
+                    CustomMenuItem::new("unwip", "Undo WIP Commit").into(),
+                    CustomMenuItem::new("apply-patch-text", "Apply Patch Text").into(),
+                    CustomMenuItem::new("snapshots", "Safety Snapshots").into(),
+                    CustomMenuItem::new("renormalize", "Renormalize Line Endings").into(),
+                    CustomMenuItem::new("lfs-locks", "LFS Locks").into(),
+                    CustomMenuItem::new("issue-links", "Issue Links").into(),
+                    CustomMenuItem::new("hooks", "Hooks").into(),
+                ])).into(),
+                Submenu::new("Security", Menu::with_items([
+                    CustomMenuItem::new("credentials", "Set Credentials").into(),
+                ])).into(),
+            ]);
+        } else {
+            menu = Menu::with_items([
+                Submenu::new("File", Menu::with_items([
+                    CustomMenuItem::new("init", "Init New Repo").into(),
+                    get_menu_item("open", "Open Repo", &keymap).into(),
+                    CustomMenuItem::new("clone", "Clone Repo").into(),
+                    NativeItem(MenuItem::Separator),
+                    CustomMenuItem::new("preferences", "Preferences").into(),
+                    NativeItem(MenuItem::Separator),
+                    NativeItem(MenuItem::Quit),
+                ])).into(),
+            ]);
+            if std::env::consts::OS == "windows" {
+                menu = menu.add_submenu(
+                    Submenu::new("Edit", Menu::with_items([
+                        NativeItem(MenuItem::Copy),
+                        NativeItem(MenuItem::Paste),
+                    ]))
+                );
+            }
+            menu = menu.add_submenu(
+                Submenu::new("View", Menu::with_items([
+                    get_menu_item("refresh", "Refresh", &keymap).into(),
+                    CustomMenuItem::new("git-console", "Run Git Command").into(),
+                    CustomMenuItem::new("export-graph", "Export Graph").into(),
+                    CustomMenuItem::new("export-log", "Export Log").into(),
+                    CustomMenuItem::new("operation-history", "Operation History").into(),
+                    CustomMenuItem::new("ignored-files", "Ignored Files").into(),
+                    NativeItem(MenuItem::Separator),
+                    CustomMenuItem::new("open-history-window", "Open History Window").into(),
+                    CustomMenuItem::new("open-changes-window", "Open Changes Window").into(),
+                    CustomMenuItem::new("open-conflicts-window", "Open Conflicts Window").into(),
+                ]))
+            );
+            menu = menu.add_submenu(
+                Submenu::new("Repo", Menu::with_items([
+                    get_menu_item("fetch", "Fetch", &keymap).into(),
+                    get_menu_item("push", "Push", &keymap).into(),
+                    NativeItem(MenuItem::Separator),
+                    CustomMenuItem::new("add-submodule", "Add Submodule").into(),
+                    CustomMenuItem::new("remove-submodule", "Remove Submodule").into(),
+                    CustomMenuItem::new("subtree", "Subtree").into(),
+                    NativeItem(MenuItem::Separator),
+                    CustomMenuItem::new("wip-commit", "WIP Commit").into(),
+                    CustomMenuItem::new("unwip", "Undo WIP Commit").into(),
+                    CustomMenuItem::new("apply-patch-text", "Apply Patch Text").into(),
+                    CustomMenuItem::new("snapshots", "Safety Snapshots").into(),
+                    CustomMenuItem::new("renormalize", "Renormalize Line Endings").into(),
+                    CustomMenuItem::new("lfs-locks", "LFS Locks").into(),
+                    CustomMenuItem::new("issue-links", "Issue Links").into(),
+                    CustomMenuItem::new("hooks", "Hooks").into(),
+                ]))
+            );
+            menu = menu.add_submenu(
+                Submenu::new("Security", Menu::with_items([
+                    CustomMenuItem::new("credentials", "Set Credentials").into(),
+                ]))
+            );
+        }
+
+        let main_window = WindowBuilder::new(
+            app,
+            MAIN_WINDOW_LABEL.to_string(),
+            tauri::WindowUrl::App("index.html".into()),
+        )
+        .menu(menu)
+        .maximized(true)
+        .title("Oxidized Git")
+        .build()?;
+
+        let git_manager_arc: Arc<Mutex<GitManager>> = Arc::new(Mutex::new(GitManager::new(current_local_offset)));
+        // Reopen the repo from last time. If it's gone, the welcome view is shown like on a fresh start.
+        let _ = git_manager_arc.lock().unwrap().open_last_repo();
+        // The system tray event handler gets at the git manager through the app state.
+        app.manage(git_manager_arc.clone());
+
+        // Repo updates are sent to every window bound to the same git manager, see window_router.
+        let mut window_router = WindowRouter::new();
+        let manager_id = window_router.add_manager();
+        window_router.bind_main_window(MAIN_WINDOW_LABEL, manager_id);
+        app.manage(Mutex::new(window_router));
+
+        // Fetch in the background on the interval set in the preferences. This is checked every
+        // 30 seconds so changes to the preferences take effect without restarting.
         let main_window_c = main_window.clone();
         let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("stash", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_stash(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
+        thread::spawn(move || {
+            let mut last_fetch = Instant::now();
+            loop {
+                thread::sleep(Duration::from_secs(30));
+                let auto_fetch_minutes = match config_manager::get_config() {
+                    Ok(c) => c.borrow_auto_fetch_minutes().unwrap_or(0),
+                    Err(_) => 0,
                 };
-            });
+                if auto_fetch_minutes == 0 || last_fetch.elapsed() < Duration::from_secs(auto_fetch_minutes * 60) {
+                    continue;
+                }
+                last_fetch = Instant::now();
+                let mut git_manager = git_manager_arc_c.lock().unwrap();
+                if !git_manager.has_open_repo() {
+                    continue;
+                }
+                let ahead_behind_before = git_manager.get_ahead_behind();
+                // Don't interrupt the user with errors from a fetch they didn't ask for.
+                if git_manager.git_fetch().is_ok() {
+                    if let (Ok(Some((_, behind_before))), Ok(Some((_, behind_after)))) = (ahead_behind_before, git_manager.get_ahead_behind()) {
+                        if behind_after > behind_before {
+                            notify(&main_window_c, "New Upstream Commits", &format!("{} new commit(s) are ready to pull.", behind_after - behind_before));
+                        }
+                    }
+                    // The front-end stops its spinner once for each update_all, so count this refresh as its own process or
+                    // it would stop the spinner of something the user started.
+                    main_window_c.emit_routed("start-process", "").unwrap();
+                    emit_update_all(&mut git_manager, false, &main_window_c);
+                }
+            }
         });
+
         let main_window_c = main_window.clone();
         let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("apply-stash", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_apply_stash(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
-                            Err(e) => handle_error(e, &main_window_c_c),
-                        };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+        main_window.on_window_event(move |event| {
+            match event {
+                WindowEvent::Focused(is_focused) => {
+                    if *is_focused {
+                        main_window_c.emit_routed("start-process", "").unwrap();
+                        let main_window_c_c = main_window_c.clone();
+                        let git_manager_arc_c_c = git_manager_arc_c.clone();
+                        thread::spawn(move || {
+                            let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                            emit_update_all(&mut git_manager, false, &main_window_c_c);
+                        });
+                    }
+                },
+                _ => {},
+            }
         });
+
         let main_window_c = main_window.clone();
         let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("delete-stash", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
+        main_window.on_menu_event(move |event| {
+            match event.menu_item_id() {
+                "preferences" => {
+                    match config_manager::get_config() {
+                        Ok(c) => {
+                            main_window_c.emit_routed("show-preferences", c).unwrap();
+                        },
+                        Err(e) => handle_error(e, &main_window_c),
+                    };
+                },
+                // Don't use a separate thread for init, open, or clone so as not to break the file dialog in Linux.
+                "init" => {
+                    main_window_c.emit_routed("get-init", "").unwrap();
+                },
+                "open" => {
+                    main_window_c.emit_routed("get-open", "").unwrap();
+                },
+                "clone" => {
+                    main_window_c.emit_routed("get-clone", "").unwrap();
+                },
+                "refresh" => {
+                    main_window_c.emit_routed("start-process", "").unwrap();
+                    let main_window_c_c = main_window_c.clone();
+                    let git_manager_arc_c_c = git_manager_arc_c.clone();
+                    thread::spawn(move || {
+                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
+                        emit_update_all(&mut git_manager, false, &main_window_c_c);
+                    });
+                },
+                "credentials" => {
+                    main_window_c.emit_routed("get-credentials", "").unwrap();
+                },
+                "fetch" => {
+                    main_window_c.emit_routed("start-process", "").unwrap();
+                    let main_window_c_c = main_window_c.clone();
+                    let git_manager_arc_c_c = git_manager_arc_c.clone();
+                    thread::spawn(move || {
                         let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_delete_stash(s);
+                        let result = git_manager.git_fetch();
+                        notify_if_unfocused(&main_window_c_c, "Fetch", &result);
                         match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                            Ok(()) => {
+                                update_submodules_if_enabled(&git_manager, false, &main_window_c_c);
+                                emit_update_all(&mut git_manager, false, &main_window_c_c);
+                            },
                             Err(e) => handle_error(e, &main_window_c_c),
                         };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
-        });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("branch", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_branch(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                    });
+                },
+                // Pushing needs options from the user, so let the front-end show the push dialog.
+                "push" => {
+                    main_window_c.emit_routed("get-push", "").unwrap();
+                },
+                "git-console" => {
+                    main_window_c.emit_routed("show-git-console", "").unwrap();
+                },
+                // Both need input from the user, so let the front-end show the dialogs.
+                "add-submodule" => {
+                    main_window_c.emit_routed("get-add-submodule", "").unwrap();
+                },
+                "remove-submodule" => {
+                    main_window_c.emit_routed("get-remove-submodule", "").unwrap();
+                },
+                "subtree" => {
+                    main_window_c.emit_routed("get-subtree", "").unwrap();
+                },
+                "wip-commit" => {
+                    main_window_c.emit_routed("start-process", "").unwrap();
+                    spawn_journal_operation(&main_window_c, &git_manager_arc_c, "wip-commit", |gm| gm.git_wip_commit());
+                },
+                "unwip" => {
+                    main_window_c.emit_routed("start-process", "").unwrap();
+                    spawn_journal_operation(&main_window_c, &git_manager_arc_c, "unwip", |gm| gm.git_unwip());
+                },
+                "apply-patch-text" => {
+                    main_window_c.emit_routed("get-apply-patch-text", "").unwrap();
+                },
+                "snapshots" => {
+                    main_window_c.emit_routed("get-snapshots", "").unwrap();
+                },
+                "hooks" => {
+                    main_window_c.emit_routed("get-hooks", "").unwrap();
+                },
+                "renormalize" => {
+                    let main_window_c_c = main_window_c.clone();
+                    let git_manager_arc_c_c = git_manager_arc_c.clone();
+                    thread::spawn(move || {
+                        let git_manager = git_manager_arc_c_c.lock().unwrap();
+                        match git_manager.git_renormalize() {
+                            Ok(()) => emit_update_changes(&git_manager, &main_window_c_c),
                             Err(e) => handle_error(e, &main_window_c_c),
                         };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
-        });
-        let main_window_c = main_window.clone();
-        let git_manager_arc_c = git_manager_arc.clone();
-        main_window.listen("tag", move |event| {
-            let main_window_c_c = main_window_c.clone();
-            let git_manager_arc_c_c = git_manager_arc_c.clone();
-            thread::spawn(move || {
-                match event.payload() {
-                    Some(s) => {
-                        let mut git_manager = git_manager_arc_c_c.lock().unwrap();
-                        let result = git_manager.git_tag(s);
-                        match result {
-                            Ok(()) => emit_update_all(&mut git_manager, false, &main_window_c_c),
+                    });
+                },
+                // Don't use a separate thread so as not to break the file dialog in Linux.
+                "export-graph" => {
+                    main_window_c.emit_routed("get-export-graph", "").unwrap();
+                },
+                "export-log" => {
+                    main_window_c.emit_routed("show-export-log", "").unwrap();
+                },
+                "lfs-locks" => {
+                    main_window_c.emit_routed("get-lfs-locks", "").unwrap();
+                },
+                "issue-links" => {
+                    main_window_c.emit_routed("get-issue-links", "").unwrap();
+                },
+                "ignored-files" => {
+                    main_window_c.emit_routed("get-ignored-files", "").unwrap();
+                },
+                "operation-history" => {
+                    let main_window_c_c = main_window_c.clone();
+                    let git_manager_arc_c_c = git_manager_arc_c.clone();
+                    thread::spawn(move || {
+                        let git_manager = git_manager_arc_c_c.lock().unwrap();
+                        match operation_journal::get_operation_history(&git_manager) {
+                            Ok(operation_history) => main_window_c_c.emit_routed("show-operation-history", operation_history).unwrap(),
                             Err(e) => handle_error(e, &main_window_c_c),
                         };
-                    },
-                    None => main_window_c_c.emit_all("error", "Failed to receive payload from front-end").unwrap(),
-                };
-            });
+                    });
+                },
+                "open-history-window" | "open-changes-window" | "open-conflicts-window" => {
+                    let main_window_c_c = main_window_c.clone();
+                    let git_manager_arc_c_c = git_manager_arc_c.clone();
+                    let kind = event.menu_item_id().trim_start_matches("open-").trim_end_matches("-window").to_string();
+                    thread::spawn(move || {
+                        if let Err(e) = open_aux_window(&main_window_c_c, &kind, &git_manager_arc_c_c) {
+                            handle_error(e, &main_window_c_c);
+                        }
+                    });
+                },
+                &_ => {},
+            };
         });
 
+        register_listeners(&main_window, &git_manager_arc);
+
         Ok(())
     })
     .run(tauri::generate_context!())
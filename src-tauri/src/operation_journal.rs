@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::create_dir_all;
+use std::io::Write;
+use std::path::PathBuf;
+use anyhow::{bail, Result};
+use directories::ProjectDirs;
+use serde::{Serialize, Deserialize};
+use time::OffsetDateTime;
+use crate::git_manager::GitManager;
+use crate::parseable_info::get_ref_targets;
+
+// The journal is shared by every repo, so only this many of the most recent entries are kept.
+const MAX_JOURNAL_ENTRIES: usize = 1000;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RefChange {
+    ref_name: String,
+    // None if the ref didn't exist before or after the operation.
+    before: Option<String>,
+    after: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OperationEntry {
+    operation: String,
+    repo_path: String,
+    // Unix timestamp in seconds.
+    timestamp: i64,
+    ref_changes: Vec<RefChange>,
+    // Set if the operation failed, since a failed operation can still have moved refs (like a rebase stopping on a conflict).
+    error: Option<String>,
+}
+
+fn get_journal_path() -> Result<PathBuf> {
+    let pd = match ProjectDirs::from("com", "Oxidized Git", "Oxidized Git") {
+        Some(pd) => pd,
+        None => bail!("Failed to determine HOME directory on your OS"),
+    };
+    let mut journal_path_buf = pd.data_dir().to_path_buf();
+    journal_path_buf.push(PathBuf::from("operation_journal.jsonl"));
+    Ok(journal_path_buf)
+}
+
+// What every ref points to, plus HEAD as the name of the branch it's on (or a SHA when detached) so checkouts between
+// branches on the same commit still show up.
+pub fn get_ref_states(git_manager: &GitManager) -> Result<HashMap<String, String>> {
+    let mut ref_states = get_ref_targets(git_manager)?;
+    let repo = git_manager.borrow_repo()?;
+    if let Ok(head) = repo.head() {
+        if head.is_branch() {
+            ref_states.insert(String::from("HEAD"), String::from(GitManager::get_utf8_string(head.name(), "HEAD Name")?));
+        }
+    }
+    Ok(ref_states)
+}
+
+pub fn record_operation(git_manager: &GitManager, operation: &str, refs_before: &HashMap<String, String>, error: Option<String>) -> Result<()> {
+    let refs_after = get_ref_states(git_manager)?;
+
+    let mut ref_changes: Vec<RefChange> = vec![];
+    for (ref_name, before) in refs_before {
+        let after = refs_after.get(ref_name);
+        if after != Some(before) {
+            ref_changes.push(RefChange {
+                ref_name: ref_name.clone(),
+                before: Some(before.clone()),
+                after: after.cloned(),
+            });
+        }
+    }
+    for (ref_name, after) in &refs_after {
+        if !refs_before.contains_key(ref_name) {
+            ref_changes.push(RefChange {
+                ref_name: ref_name.clone(),
+                before: None,
+                after: Some(after.clone()),
+            });
+        }
+    }
+    ref_changes.sort_by(|a, b| a.ref_name.cmp(&b.ref_name));
+
+    let entry = OperationEntry {
+        operation: String::from(operation),
+        repo_path: git_manager.get_canonical_repo_path()?,
+        timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+        ref_changes,
+        error,
+    };
+
+    let journal_path = get_journal_path()?;
+    if let Some(prefix) = journal_path.parent() {
+        if !prefix.exists() {
+            create_dir_all(prefix)?;
+        }
+    }
+    let mut entries = read_entries()?;
+    if entries.len() >= MAX_JOURNAL_ENTRIES {
+        // Rewrite the whole file without the oldest entries, otherwise just append.
+        entries.push(entry);
+        let keep_from = entries.len() - MAX_JOURNAL_ENTRIES;
+        let mut journal_string = String::new();
+        for e in &entries[keep_from..] {
+            journal_string.push_str(&serde_json::to_string(e)?);
+            journal_string.push('\n');
+        }
+        fs::write(journal_path, journal_string)?;
+    } else {
+        let mut journal_file = fs::OpenOptions::new().create(true).append(true).open(journal_path)?;
+        writeln!(journal_file, "{}", serde_json::to_string(&entry)?)?;
+    }
+    Ok(())
+}
+
+fn read_entries() -> Result<Vec<OperationEntry>> {
+    let journal_path = get_journal_path()?;
+    if !journal_path.exists() {
+        return Ok(vec![]);
+    }
+    let mut entries: Vec<OperationEntry> = vec![];
+    for line in fs::read_to_string(journal_path)?.lines() {
+        // Skip anything that can't be read (like a line cut off by a crash) instead of losing the whole history.
+        if let Ok(entry) = serde_json::from_str::<OperationEntry>(line) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+// Newest first, only for the open repo.
+pub fn get_operation_history(git_manager: &GitManager) -> Result<Vec<OperationEntry>> {
+    let repo_path = git_manager.get_canonical_repo_path()?;
+    let mut entries: Vec<OperationEntry> = read_entries()?.into_iter().filter(|e| e.repo_path == repo_path).collect();
+    entries.reverse();
+    Ok(entries)
+}
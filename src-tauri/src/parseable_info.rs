@@ -1,12 +1,12 @@
-use std::cell::RefCell;
+use std::cmp::Reverse;
 use std::collections::{HashMap, VecDeque};
-use std::rc::Rc;
 use anyhow::{bail, Result};
-use git2::{BranchType, Diff, ErrorCode, Oid, RepositoryState};
+use git2::{BranchType, Commit, Diff, DiffDelta, ErrorCode, Oid, RepositoryState};
 use serde::{Serialize, Deserialize, Serializer};
 use time::{format_description, OffsetDateTime};
-use crate::git_manager::GitManager;
-use crate::svg_row::{RowProperty, SVGProperty, SVGRow};
+use crate::config_manager::{self, Config, CredentialsStatus, RepoSession};
+use crate::git_manager::{encode_path_bytes, set_collapsed_files, GitManager, LfsLock, RepoCapabilities};
+use crate::svg_row::{RowDrawProperties, SVGElement, SVGRow};
 
 #[derive(Clone)]
 pub enum SVGCommitInfoValue {
@@ -28,84 +28,32 @@ impl Serialize for SVGCommitInfoValue {
 }
 
 #[derive(Clone, Serialize)]
+// The rows themselves are fetched with get_graph_rows as they're scrolled to, so this only has what's needed to find them.
 pub struct CommitsInfo {
-    branch_draw_properties: Vec<(String, Vec<Vec<HashMap<String, SVGProperty>>>)>,
-    svg_row_draw_properties: Vec<HashMap<String, RowProperty>>,
+    branch_draw_properties: Vec<(String, Vec<Vec<SVGElement>>)>,
+    total_rows: usize,
+    // Full ref names (and HEAD) to the row their commit is on.
+    ref_rows: HashMap<String, usize>,
+    // None when the graph hasn't changed since it was last sent, so the rows already fetched can be kept.
+    sha_rows: Option<HashMap<String, usize>>,
 }
 
 impl CommitsInfo {
-    pub fn new(branch_draw_properties: Vec<(String, Vec<Vec<HashMap<String, SVGProperty>>>)>, svg_row_draw_properties: Vec<HashMap<String, RowProperty>>) -> Self {
+    pub fn new(branch_draw_properties: Vec<(String, Vec<Vec<SVGElement>>)>, total_rows: usize, ref_rows: HashMap<String, usize>, sha_rows: Option<HashMap<String, usize>>) -> Self {
         Self {
             branch_draw_properties,
-            svg_row_draw_properties,
+            total_rows,
+            ref_rows,
+            sha_rows,
         }
     }
 }
 
-#[derive(Clone, Serialize)]
-pub struct ParseableCommitInfo {
-    sha: String,
-    author_name: String,
-    author_time: String,
-    x: isize,
-    y: isize,
-    summary: String,
-    parent_shas: Vec<String>,
-    child_shas: Vec<String>,
-}
-
-impl ParseableCommitInfo {
-    pub fn new(sha: String, author_name: String, author_time: String, x: isize, y: isize, summary: String, parent_shas: Vec<String>, child_shas: Vec<String>) -> Self {
-        Self {
-            sha,
-            author_name,
-            author_time,
-            x,
-            y,
-            summary,
-            parent_shas,
-            child_shas,
-        }
-    }
-
-    pub fn borrow_sha(&self) -> &String {
-        &self.sha
-    }
-
-    pub fn borrow_author_name(&self) -> &String {
-        &self.author_name
-    }
-
-    pub fn borrow_author_time(&self) -> &String {
-        &self.author_time
-    }
-
-    pub fn borrow_x(&self) -> &isize {
-        &self.x
-    }
-
-    pub fn borrow_y(&self) -> &isize {
-        &self.y
-    }
-
-    pub fn borrow_summary(&self) -> &String {
-        &self.summary
-    }
-
-    pub fn borrow_parent_shas(&self) -> &Vec<String> {
-        &self.parent_shas
-    }
-
-    pub fn borrow_child_shas(&self) -> &Vec<String> {
-        &self.child_shas
-    }
-}
-
 #[derive(Clone)]
 pub enum RepoInfoValue {
     SomeCommitInfo(CommitsInfo),
     SomeBranchInfo(BranchesInfo),
-    SomeRemoteInfo(Vec<String>),
+    SomeRemoteInfo(Vec<RemoteInfo>),
     SomeGeneralInfo(HashMap<String, String>),
     SomeFilesChangedInfo(FilesChangedInfo),
 }
@@ -125,17 +73,55 @@ impl Serialize for RepoInfoValue {
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ParseableDiffDelta {
     status: u8,
+    // Percent-encoded where the path isn't valid UTF-8, this is what gets sent back to identify the file.
     path: String,
+    // Only for showing to the user, invalid bytes are replaced so it can't be used to find the file.
+    #[serde(default)]
+    display_path: String,
+    // Only set for case-only renames, which libgit2 doesn't see on case-insensitive filesystems.
+    #[serde(default)]
+    old_path: Option<String>,
+    // Only set for untracked directories, which are collapsed into one entry until they're expanded.
+    #[serde(default)]
+    untracked_file_count: Option<usize>,
+    // Set when the directory has more files than were counted.
+    #[serde(default)]
+    is_untracked_file_count_capped: bool,
+    // Generated files (like lockfiles) whose diffs are hidden until they're asked for.
+    #[serde(default)]
+    collapsed: bool,
 }
 
 impl ParseableDiffDelta {
-    pub fn new(status: u8, path: String) -> Self {
+    pub fn new(status: u8, path: String, display_path: String, old_path: Option<String>) -> Self {
         Self {
             status,
             path,
+            display_path,
+            old_path,
+            untracked_file_count: None,
+            is_untracked_file_count_capped: false,
+            collapsed: false,
         }
     }
 
+    pub fn from_diff_delta(diff_delta: &DiffDelta) -> Result<Self> {
+        let path_bytes = match diff_delta.new_file().path_bytes() {
+            Some(b) => b,
+            None => bail!("Possible invalid file path? I'm not actually sure why this error would occur. It looks like git didn't store a file path with a file or something."),
+        };
+        Ok(Self::new(diff_delta.status() as u8, encode_path_bytes(path_bytes), String::from_utf8_lossy(path_bytes).into_owned(), None))
+    }
+
+    pub fn set_untracked_file_count(&mut self, untracked_file_count: usize, is_capped: bool) {
+        self.untracked_file_count = Some(untracked_file_count);
+        self.is_untracked_file_count_capped = is_capped;
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        self.collapsed = collapsed;
+    }
+
     pub fn get_status(&self) -> u8 {
         self.status
     }
@@ -143,6 +129,34 @@ impl ParseableDiffDelta {
     pub fn get_path(&self) -> &String {
         &self.path
     }
+
+    pub fn borrow_old_path(&self) -> &Option<String> {
+        &self.old_path
+    }
+
+    pub fn borrow_untracked_file_count(&self) -> &Option<usize> {
+        &self.untracked_file_count
+    }
+}
+
+// A tracked file with assume-unchanged or skip-worktree set, which hides its changes from status.
+#[derive(Clone, Serialize)]
+pub struct FlaggedFile {
+    path: String,
+    display_path: String,
+    assume_unchanged: bool,
+    skip_worktree: bool,
+}
+
+impl FlaggedFile {
+    pub fn new(path: String, display_path: String, assume_unchanged: bool, skip_worktree: bool) -> Self {
+        Self {
+            path,
+            display_path,
+            assume_unchanged,
+            skip_worktree,
+        }
+    }
 }
 
 #[derive(Clone, Serialize)]
@@ -150,16 +164,90 @@ pub struct FilesChangedInfo {
     files_changed: usize,
     unstaged_files: Vec<ParseableDiffDelta>,
     staged_files: Vec<ParseableDiffDelta>,
+    flagged_files: Vec<FlaggedFile>,
+    // From the last time LFS locks were listed, since listing them needs the server.
+    lfs_locks: Vec<LfsLock>,
+    // Only set for an update after the app changed a few files itself, then the file lists only have the entries for these paths
+    // and the rest of the front-end's lists are still current.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated_paths: Option<Vec<String>>,
 }
 
 impl FilesChangedInfo {
-    pub fn new(files_changed: usize, unstaged_files: Vec<ParseableDiffDelta>, staged_files: Vec<ParseableDiffDelta>) -> Self {
+    pub fn new(files_changed: usize, unstaged_files: Vec<ParseableDiffDelta>, staged_files: Vec<ParseableDiffDelta>, flagged_files: Vec<FlaggedFile>, lfs_locks: Vec<LfsLock>) -> Self {
         Self {
             files_changed,
             unstaged_files,
             staged_files,
+            flagged_files,
+            lfs_locks,
+            updated_paths: None,
+        }
+    }
+
+    fn set_updated_paths(&mut self, updated_paths: Vec<String>) {
+        self.updated_paths = Some(updated_paths);
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct RemoteInfo {
+    name: String,
+    url: Option<String>,
+    // Only set when pushes go somewhere other than url, like a fork or a mirror.
+    push_url: Option<String>,
+    fetch_refspecs: Vec<String>,
+    push_refspecs: Vec<String>,
+}
+
+impl RemoteInfo {
+    pub fn new(name: String, url: Option<String>, push_url: Option<String>, fetch_refspecs: Vec<String>, push_refspecs: Vec<String>) -> Self {
+        Self {
+            name,
+            url,
+            push_url,
+            fetch_refspecs,
+            push_refspecs,
+        }
+    }
+}
+
+// What the tip of a branch was last touched with.
+#[derive(Clone, Serialize)]
+pub struct LastCommitInfo {
+    summary: String,
+    author_name: String,
+    // Seconds since the epoch.
+    time: i64,
+    // Like "3 days ago".
+    relative_time: String,
+}
+
+impl LastCommitInfo {
+    fn from_commit(commit: &Commit, now: i64) -> Self {
+        let author_signature = commit.author();
+        let time = author_signature.when().seconds();
+        Self {
+            summary: String::from_utf8_lossy(commit.summary_bytes().unwrap_or_default()).into_owned(),
+            author_name: String::from_utf8_lossy(author_signature.name_bytes()).into_owned(),
+            time,
+            relative_time: get_relative_time(now - time),
+        }
+    }
+}
+
+fn get_relative_time(seconds_ago: i64) -> String {
+    let units = [(60 * 60 * 24 * 365, "year"), (60 * 60 * 24 * 30, "month"), (60 * 60 * 24 * 7, "week"), (60 * 60 * 24, "day"), (60 * 60, "hour"), (60, "minute")];
+    for (unit_seconds, unit_name) in units {
+        let count = seconds_ago / unit_seconds;
+        if count == 1 {
+            return format!("1 {} ago", unit_name);
+        } else if count > 1 {
+            return format!("{} {}s ago", count, unit_name);
         }
     }
+    // Also covers commits dated in the future.
+    String::from("just now")
 }
 
 #[derive(Clone, Serialize)]
@@ -172,10 +260,14 @@ pub struct BranchInfo {
     ahead: usize,
     behind: usize,
     has_upstream: bool,
+    // None for refs that don't point at a commit.
+    last_commit: Option<LastCommitInfo>,
+    // Favorites are pinned to the top of the local branches.
+    is_favorite: bool,
 }
 
 impl BranchInfo {
-    pub fn new(target_sha: String, branch_shorthand: String, full_branch_name: String, is_head: bool, branch_type: String, ahead: usize, behind: usize, has_upstream: bool) -> Self {
+    pub fn new(target_sha: String, branch_shorthand: String, full_branch_name: String, is_head: bool, branch_type: String, ahead: usize, behind: usize, has_upstream: bool, last_commit: Option<LastCommitInfo>, is_favorite: bool) -> Self {
         Self {
             target_sha,
             branch_shorthand,
@@ -185,6 +277,8 @@ impl BranchInfo {
             ahead,
             behind,
             has_upstream,
+            last_commit,
+            is_favorite,
         }
     }
 }
@@ -246,6 +340,31 @@ impl BranchInfoTreeNode {
             };
         }
     }
+
+    // The newest tip commit anywhere under this node.
+    fn get_latest_commit_time(&self) -> i64 {
+        let own_time = match self.branch_info.as_ref().and_then(|b| b.last_commit.as_ref()) {
+            Some(last_commit) => last_commit.time,
+            None => i64::MIN,
+        };
+        self.children.iter().map(|child| child.get_latest_commit_time()).fold(own_time, i64::max)
+    }
+
+    fn is_favorite(&self) -> bool {
+        self.branch_info.as_ref().map_or(false, |b| b.is_favorite)
+    }
+
+    // Favorites come first, then the rest either by name or with the most recently committed first.
+    pub fn sort_children(&mut self, by_recent: bool) {
+        for child in &mut self.children {
+            child.sort_children(by_recent);
+        }
+        if by_recent {
+            self.children.sort_by_cached_key(|child| (!child.is_favorite(), Reverse(child.get_latest_commit_time()), child.text.clone()));
+        } else {
+            self.children.sort_by(|a, b| (!a.is_favorite()).cmp(&!b.is_favorite()).then(a.text.cmp(&b.text)));
+        }
+    }
 }
 
 #[derive(Clone, Serialize)]
@@ -253,25 +372,93 @@ pub struct BranchesInfo {
     local_branch_info_tree: BranchInfoTreeNode,
     remote_branch_info_tree: BranchInfoTreeNode,
     tag_branch_info_tree: BranchInfoTreeNode,
+    // Everything outside of refs/heads, refs/remotes, and refs/tags, like refs/notes and refs/replace.
+    other_ref_info_tree: BranchInfoTreeNode,
     stash_info_list: Vec<StashInfo>,
+    // The tags left out of tag_branch_info_tree until the user asks for more.
+    hidden_tag_count: usize,
 }
 
 impl BranchesInfo {
-    pub fn new(local_branch_info_tree: BranchInfoTreeNode, remote_branch_info_tree: BranchInfoTreeNode, tag_branch_info_tree: BranchInfoTreeNode, stash_info_list: Vec<StashInfo>) -> Self {
+    pub fn new(local_branch_info_tree: BranchInfoTreeNode, remote_branch_info_tree: BranchInfoTreeNode, tag_branch_info_tree: BranchInfoTreeNode, other_ref_info_tree: BranchInfoTreeNode, stash_info_list: Vec<StashInfo>, hidden_tag_count: usize) -> Self {
         Self {
             local_branch_info_tree,
             remote_branch_info_tree,
             tag_branch_info_tree,
+            other_ref_info_tree,
             stash_info_list,
+            hidden_tag_count,
         }
     }
 }
 
-fn get_oid_refs(git_manager: &GitManager) -> Result<HashMap<String, Vec<(String, String)>>> {
+// The laid out graph, kept on the GitManager so windows of rows can be served without redoing the layout.
+#[derive(Clone)]
+pub struct GraphLayout {
+    starting_shas: Vec<String>,
+    commit_limit: Option<usize>,
+    // Only the positions and text of each commit, the elements to draw are made for each window of rows as it's asked for.
+    svg_rows: Vec<SVGRow>,
+    // The x positions occupied on each row.
+    main_table: Vec<Vec<isize>>,
+    // How many x positions are occupied on each row.
+    branch_densities: Vec<usize>,
+    sha_rows: HashMap<String, usize>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct GraphRows {
+    start: usize,
+    end: usize,
+    total_rows: usize,
+    branch_draw_properties: Vec<(String, Vec<Vec<SVGElement>>)>,
+    svg_row_draw_properties: Vec<RowDrawProperties>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct FilteredBranches {
+    pattern: String,
+    branch_info_list: BranchesInfo,
+}
+
+#[derive(Clone, Serialize)]
+pub struct AllBadges {
+    sha: String,
+    branch_draw_properties: Vec<Vec<SVGElement>>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct GraphMinimapEntry {
+    start_row: usize,
+    row_count: usize,
+    // The most branch lines running side by side on any one row in this entry.
+    branch_density: usize,
+    refs: Vec<(String, String, String)>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct GraphMinimap {
+    total_rows: usize,
+    rows_per_entry: usize,
+    entries: Vec<GraphMinimapEntry>,
+}
+
+// Everything the front-end needs to rebuild itself after a reload without the back-end restarting.
+#[derive(Clone, Serialize)]
+pub struct FullState {
+    repo_info: Option<HashMap<String, RepoInfoValue>>,
+    repo_capabilities: Option<RepoCapabilities>,
+    operation_state: String,
+    credentials_status: CredentialsStatus,
+    config: Config,
+    repo_session: Option<RepoSession>,
+}
+
+fn get_oid_refs(git_manager: &GitManager) -> Result<HashMap<String, Vec<(String, String, String)>>> {
     let repo = git_manager.borrow_repo()?;
 
     // Get HashMap of Oids and their refs based on type (local, remote, or tag)
-    let mut oid_refs: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    let mut oid_refs: HashMap<String, Vec<(String, String, String)>> = HashMap::new();
 
     // Iterate over branches
     for branch_result in repo.branches(None)? {
@@ -283,6 +470,7 @@ fn get_oid_refs(git_manager: &GitManager) -> Result<HashMap<String, Vec<(String,
 
         let reference = branch.get();
         branch_string += GitManager::get_utf8_string(reference.shorthand(), "Ref Name")?;
+        let full_ref_name = String::from(GitManager::get_utf8_string(reference.name(), "Ref Name")?);
         match reference.target() {
             Some(oid) => {
                 let branch_type;
@@ -293,10 +481,10 @@ fn get_oid_refs(git_manager: &GitManager) -> Result<HashMap<String, Vec<(String,
                 }
                 match oid_refs.get_mut(&*oid.to_string()) {
                     Some(oid_ref_vec) => {
-                        oid_ref_vec.push((branch_string, branch_type));
+                        oid_ref_vec.push((branch_string, branch_type, full_ref_name));
                     },
                     None => {
-                        oid_refs.insert(oid.to_string(), vec![(branch_string, branch_type)]);
+                        oid_refs.insert(oid.to_string(), vec![(branch_string, branch_type, full_ref_name)]);
                     },
                 }
             },
@@ -310,10 +498,10 @@ fn get_oid_refs(git_manager: &GitManager) -> Result<HashMap<String, Vec<(String,
             Some(oid) => {
                 match oid_refs.get_mut(&*oid.to_string()) {
                     Some(oid_ref_vec) => {
-                        oid_ref_vec.push((String::from("* HEAD"), String::from("local")));
+                        oid_ref_vec.push((String::from("* HEAD"), String::from("local"), String::from("HEAD")));
                     },
                     None => {
-                        oid_refs.insert(oid.to_string(), vec![(String::from("* HEAD"), String::from("local"))]);
+                        oid_refs.insert(oid.to_string(), vec![(String::from("* HEAD"), String::from("local"), String::from("HEAD"))]);
                     },
                 }
             },
@@ -321,81 +509,113 @@ fn get_oid_refs(git_manager: &GitManager) -> Result<HashMap<String, Vec<(String,
         };
     }
 
-    // Iterate over tags
-    for reference_result in repo.references()? {
+    // Iterate over tags. This runs on every update, so only the tags are read rather than every ref.
+    for reference_result in repo.references_glob("refs/tags/*")? {
         let reference = reference_result?;
-        if reference.is_tag() {
-            let ref_name = GitManager::get_utf8_string(reference.shorthand(), "Tag Name")?;
+        let ref_name = GitManager::get_utf8_string(reference.shorthand(), "Tag Name")?;
+        let full_ref_name = GitManager::get_utf8_string(reference.name(), "Tag Name")?;
 
-            let oid = reference.peel_to_commit()?.id();
-            match oid_refs.get_mut(&*oid.to_string()) {
-                Some(oid_ref_vec) => {
-                    oid_ref_vec.push((ref_name.to_string(), "tag".to_string()));
-                }
-                None => {
-                    oid_refs.insert(oid.to_string(), vec![(ref_name.to_string(), "tag".to_string())]);
-                },
-            };
-        }
+        // Tags of trees and blobs don't have a commit to be drawn on.
+        let oid = match reference.peel_to_commit() {
+            Ok(c) => c.id(),
+            Err(_) => continue,
+        };
+        match oid_refs.get_mut(&*oid.to_string()) {
+            Some(oid_ref_vec) => {
+                oid_ref_vec.push((ref_name.to_string(), "tag".to_string(), full_ref_name.to_string()));
+            }
+            None => {
+                oid_refs.insert(oid.to_string(), vec![(ref_name.to_string(), "tag".to_string(), full_ref_name.to_string())]);
+            },
+        };
     }
     Ok(oid_refs)
 }
 
-fn get_general_info(git_manager: &GitManager) -> Result<HashMap<String, String>> {
+fn get_general_info(git_manager: &GitManager, repo_capabilities: &RepoCapabilities) -> Result<HashMap<String, String>> {
     let repo = git_manager.borrow_repo()?;
 
     let mut general_info: HashMap<String, String> = HashMap::new();
 
-    let project_name = match repo.workdir() {
-        Some(p) => {
-            match p.file_name() {
-                Some(d) => d,
-                None => bail!("Working directory path is empty?"),
-            }
-        },
-        None => bail!("Repo doesn't have a working directory?"),
+    // Bare repos don't have a working directory, so use the repo directory itself (i.e. "project.git").
+    let project_path = match repo.workdir() {
+        Some(p) => p,
+        None => repo.path(),
+    };
+    let project_name = match project_path.file_name() {
+        Some(d) => d,
+        None => bail!("Working directory path is empty?"),
     };
     general_info.insert(String::from("project_name"), String::from(GitManager::get_utf8_string(project_name.to_str(), "Project Containing Directory")?));
+    general_info.insert(String::from("repo_root"), String::from(GitManager::get_utf8_string(project_path.to_str(), "Project Path")?));
+    general_info.insert(String::from("discovered_from"), git_manager.borrow_discovered_from().clone().unwrap_or_default());
+
+    general_info.insert(String::from("has_workdir"), repo_capabilities.borrow_has_workdir().to_string());
+    general_info.insert(String::from("can_commit"), repo_capabilities.borrow_can_commit().to_string());
+    general_info.insert(String::from("read_only"), repo_capabilities.borrow_read_only().to_string());
+    general_info.insert(String::from("is_unborn"), repo_capabilities.borrow_is_unborn().to_string());
+    // Comma separated, e.g. "husky,lefthook".
+    general_info.insert(String::from("hook_managers"), git_manager.get_hook_managers()?.join(","));
+    // A freshly initialized repo's HEAD points at a branch that doesn't exist until the first commit is made.
+    if *repo_capabilities.borrow_is_unborn() {
+        let head_ref = repo.find_reference("HEAD")?;
+        let unborn_branch_name = match head_ref.symbolic_target() {
+            Some(s) => s.strip_prefix("refs/heads/").unwrap_or(s),
+            None => "",
+        };
+        general_info.insert(String::from("unborn_branch_name"), String::from(unborn_branch_name));
+    }
 
     general_info.insert(String::from("head_sha"), String::new());
-    match repo.head() {
-        Ok(head_ref) => {
-            if let Some(oid) = head_ref.target() {
-                general_info.insert(String::from("head_sha"), oid.to_string());
-            }
+    general_info.insert(String::from("head_has_upstream"), false.to_string());
+    // How far the checked out branch has diverged from its upstream, so the toolbar doesn't have to look through the branches.
+    general_info.insert(String::from("head_ahead"), 0.to_string());
+    general_info.insert(String::from("head_behind"), 0.to_string());
+    // A corrupt repo's HEAD can't be inspected, so leave the defaults.
+    if repo_capabilities.is_readable() {
+        if let Some((ahead, behind)) = git_manager.get_ahead_behind()? {
+            general_info.insert(String::from("head_ahead"), ahead.to_string());
+            general_info.insert(String::from("head_behind"), behind.to_string());
+        }
+        match repo.head() {
+            Ok(head_ref) => {
+                if let Some(oid) = head_ref.target() {
+                    general_info.insert(String::from("head_sha"), oid.to_string());
+                }
 
-            match repo.find_branch(GitManager::get_utf8_string(head_ref.shorthand(), "Branch Name")?, BranchType::Local) {
-                Ok(head_branch) => {
-                    match head_branch.upstream() {
-                        Ok(_) => {
-                            general_info.insert(String::from("head_has_upstream"), true.to_string());
-                        },
-                        Err(e) => {
-                            if e.code() == ErrorCode::NotFound {
-                                general_info.insert(String::from("head_has_upstream"), false.to_string());
-                            } else {
-                                return Err(e.into());
-                            }
-                        },
-                    }
-                },
-                Err(e) => {
-                    if e.code() == ErrorCode::NotFound {
-                        general_info.insert(String::from("head_has_upstream"), false.to_string());
-                    } else {
-                        return Err(e.into());
-                    }
-                },
-            };
-        },
-        Err(e) => {
-            if e.code() == ErrorCode::UnbornBranch {
-                general_info.insert(String::from("head_has_upstream"), false.to_string());
-            } else {
-                return Err(e.into());
-            }
-        },
-    };
+                match repo.find_branch(GitManager::get_utf8_string(head_ref.shorthand(), "Branch Name")?, BranchType::Local) {
+                    Ok(head_branch) => {
+                        match head_branch.upstream() {
+                            Ok(_) => {
+                                general_info.insert(String::from("head_has_upstream"), true.to_string());
+                            },
+                            Err(e) => {
+                                if e.code() == ErrorCode::NotFound {
+                                    general_info.insert(String::from("head_has_upstream"), false.to_string());
+                                } else {
+                                    return Err(e.into());
+                                }
+                            },
+                        }
+                    },
+                    Err(e) => {
+                        if e.code() == ErrorCode::NotFound {
+                            general_info.insert(String::from("head_has_upstream"), false.to_string());
+                        } else {
+                            return Err(e.into());
+                        }
+                    },
+                };
+            },
+            Err(e) => {
+                if e.code() == ErrorCode::UnbornBranch {
+                    general_info.insert(String::from("head_has_upstream"), false.to_string());
+                } else {
+                    return Err(e.into());
+                }
+            },
+        };
+    }
 
     // Check if an operation is in progress (this means that conflicts occurred during the operation).
     let repo_state = repo.state();
@@ -403,119 +623,302 @@ fn get_general_info(git_manager: &GitManager) -> Result<HashMap<String, String>>
     general_info.insert(String::from("is_reverting"), (repo_state == RepositoryState::Revert).to_string());
     general_info.insert(String::from("is_merging"), (repo_state == RepositoryState::Merge).to_string());
     general_info.insert(String::from("is_rebasing"), (repo_state == RepositoryState::Rebase || repo_state == RepositoryState::RebaseMerge || repo_state == RepositoryState::RebaseInteractive).to_string());
+    general_info.insert(String::from("is_applying_stash"), git_manager.is_applying_stash()?.to_string());
+    general_info.insert(String::from("is_lfs_enabled"), git_manager.is_lfs_enabled()?.to_string());
+    general_info.insert(String::from("git_dir"), String::from(GitManager::get_utf8_string(repo.path().to_str(), "Git Directory")?));
+    general_info.insert(String::from("has_gitdir_redirect"), git_manager.has_gitdir_redirect()?.to_string());
+    let main_repo_path_opt = git_manager.get_main_repo_path()?;
+    general_info.insert(String::from("is_worktree"), main_repo_path_opt.is_some().to_string());
+    general_info.insert(String::from("main_repo_path"), main_repo_path_opt.unwrap_or_default());
+    general_info.insert(String::from("commit_prefill"), git_manager.get_commit_prefill()?);
 
     Ok(general_info)
 }
 
-fn get_commit_info_list(git_manager: &GitManager, oid_list: Vec<Oid>) -> Result<Vec<ParseableCommitInfo>> {
-    let mut commit_list: Vec<ParseableCommitInfo> = vec![];
+// Lays out the graph, returning the SVGRows in order along with which x positions are occupied on each row.
+fn get_svg_rows(git_manager: &GitManager, oid_vec: &[Oid], previous_layout: Option<(&[SVGRow], &[Vec<isize>])>) -> Result<(Vec<SVGRow>, Vec<Vec<isize>>)> {
+    let mut svg_rows: Vec<SVGRow> = Vec::with_capacity(oid_vec.len());
+    let mut parent_oids_list: Vec<Vec<Oid>> = Vec::with_capacity(oid_vec.len());
+    if oid_vec.is_empty() {
+        return Ok((svg_rows, vec![]));
+    }
 
     let repo = git_manager.borrow_repo()?;
-    let mut children_oids_hm: HashMap<String, Vec<String>> = HashMap::new();
-    for (i, oid) in oid_list.iter().enumerate() {
+    let history_boundaries = git_manager.get_history_boundaries()?;
+    let current_local_offset = *git_manager.borrow_current_local_offset();
+    let today_local = OffsetDateTime::now_utc().to_offset(current_local_offset).date();
+    let time_format = format_description::parse("[hour repr:12]:[minute]:[second] [period case:upper]")?;
+    let datetime_format = format_description::parse("[year]-[month]-[day] [hour repr:12]:[minute]:[second] [period case:upper]")?;
+    for (i, oid) in oid_vec.iter().enumerate() {
         let commit = repo.find_commit(*oid)?;
 
         // Get commit summary
         let commit_summary = GitManager::get_utf8_string(commit.summary(), "Commit Summary")?;
 
         // Get parent Oids
-        let mut parent_shas: Vec<String> = vec![];
-        for parent in commit.parents() {
-            parent_shas.push(parent.id().to_string());
-            match children_oids_hm.get_mut(&*parent.id().to_string()) {
-                Some(children_oid_vec) => children_oid_vec.push(oid.to_string()),
-                None => {
-                    children_oids_hm.insert(parent.id().to_string(), vec![oid.to_string()]);
-                },
-            };
+        let parent_oids: Vec<Oid> = commit.parents().map(|parent| parent.id()).collect();
+
+        let mut boundary = history_boundaries.get(&oid.to_string()).cloned();
+        if boundary.is_none() && parent_oids.len() < commit.parent_count() {
+            boundary = Some(String::from("missing"));
         }
 
         let author_signature = commit.author();
         let author_name = String::from(GitManager::get_utf8_string(author_signature.name(), "Author Name")?);
 
         let author_time = author_signature.when().seconds();
-        let author_utc_datetime = OffsetDateTime::from_unix_timestamp(author_time)?;
-        let author_local_datetime = author_utc_datetime.to_offset(git_manager.borrow_current_local_offset().clone());
+        let author_local_datetime = OffsetDateTime::from_unix_timestamp(author_time)?.to_offset(current_local_offset);
+        let diff = today_local - author_local_datetime.date();
 
-        let now_utc = OffsetDateTime::now_utc();
-        let now_local = now_utc.to_offset(git_manager.borrow_current_local_offset().clone());
-        let diff = now_local.date() - author_local_datetime.date();
-
-        let time_format = format_description::parse("[hour repr:12]:[minute]:[second] [period case:upper]")?;
-        let datetime_format = format_description::parse("[year]-[month]-[day] [hour repr:12]:[minute]:[second] [period case:upper]")?;
         let formatted_datetime;
         if diff.whole_days() == 0 {
             formatted_datetime = format!("Today {}", author_local_datetime.time().format(&time_format)?);
         } else if diff.whole_days() == 1 {
             formatted_datetime = format!("Yesterday {}", author_local_datetime.time().format(&time_format)?);
         } else {
-            formatted_datetime = format!("{}", author_local_datetime.format(&datetime_format)?);
-        }
-
-        commit_list.push(ParseableCommitInfo::new(
-            oid.to_string(),
-            author_name,
-            formatted_datetime,
-            0,
-            i as isize,
-            String::from(commit_summary),
-            parent_shas,
-            vec![])
-        );
-    }
-
-    // Gather the child commits after running through the commit graph once in order
-    // to actually have populated entries.
-    for commit_info in commit_list.iter_mut() {
-        match children_oids_hm.get(&*commit_info.sha) {
-            Some(v) => {
-                commit_info.child_shas = v.clone();
-            },
-            None => (),
-        };
+            formatted_datetime = author_local_datetime.format(&datetime_format)?;
+        }
+
+        svg_rows.push(SVGRow::new(*oid, author_name, formatted_datetime, String::from(commit_summary), boundary, i as isize));
+        parent_oids_list.push(parent_oids);
     }
 
-    Ok(commit_list)
+    SVGRow::set_parent_and_child_rows(&mut svg_rows, oid_vec, &parent_oids_list);
+
+    let main_table = SVGRow::get_occupied_table(&mut svg_rows, previous_layout);
+    Ok((svg_rows, main_table))
 }
 
-fn get_commit_svg_draw_properties_list(git_manager: &mut GitManager, force_refresh: bool) -> Result<CommitsInfo> {
-    let mut commit_info_list = vec![];
-    if let Some(oid_vec) = git_manager.git_revwalk(force_refresh)? {
-        commit_info_list = get_commit_info_list(git_manager, oid_vec)?;
+pub fn get_ref_targets(git_manager: &GitManager) -> Result<HashMap<String, String>> {
+    let repo = git_manager.borrow_repo()?;
+    let mut ref_targets: HashMap<String, String> = HashMap::new();
+    for reference_result in repo.references()? {
+        let reference = reference_result?;
+        let ref_name = GitManager::get_utf8_string(reference.name(), "Ref Name")?;
+        // Refs like notes may not point at a commit.
+        if let Ok(commit) = reference.peel_to_commit() {
+            ref_targets.insert(String::from(ref_name), commit.id().to_string());
+        }
+    }
+    if let Ok(head) = repo.head() {
+        if let Ok(commit) = head.peel_to_commit() {
+            ref_targets.insert(String::from("HEAD"), commit.id().to_string());
+        }
     }
+    Ok(ref_targets)
+}
 
-    let mut svg_row_draw_properties: Vec<HashMap<String, RowProperty>> = vec![];
-    if commit_info_list.len() > 0 {
-        let mut svg_rows: Vec<Rc<RefCell<SVGRow>>> = vec![];
-        let mut svg_row_hm: HashMap<String, Rc<RefCell<SVGRow>>> = HashMap::new();
-        for commit_info in commit_info_list {
-            let svg_row_rc: Rc<RefCell<SVGRow>> = Rc::new(RefCell::new(SVGRow::from_commit_info(&commit_info)));
-            svg_row_hm.insert(commit_info.sha.clone(), svg_row_rc.clone());
-            svg_rows.push(svg_row_rc);
+pub fn get_commit_svg_draw_properties_list(git_manager: &mut GitManager, force_refresh: bool) -> Result<CommitsInfo> {
+    let graph_changed = git_manager.update_graph_starting_shas(force_refresh)?;
+    let oid_refs_hm = get_oid_refs(git_manager)?;
+    let ref_targets = get_ref_targets(git_manager)?;
+    let graph_layout = borrow_refreshed_graph_layout(git_manager)?;
+
+    let mut sha_rows = None;
+    if graph_changed {
+        sha_rows = Some(graph_layout.sha_rows.clone());
+    }
+
+    let mut ref_rows: HashMap<String, usize> = HashMap::new();
+    for (ref_name, sha) in ref_targets {
+        if let Some(row) = graph_layout.sha_rows.get(&sha) {
+            ref_rows.insert(ref_name, *row);
         }
+    }
+
+    let mut branch_draw_properties: Vec<(String, Vec<Vec<SVGElement>>)> = vec![];
+    for (k, v) in oid_refs_hm {
+        branch_draw_properties.push((k, SVGRow::get_branch_draw_properties(v)));
+    }
+
+    Ok(CommitsInfo::new(branch_draw_properties, graph_layout.svg_rows.len(), ref_rows, sha_rows))
+}
 
-        for svg_row_rc in &svg_rows {
-            svg_row_rc.borrow_mut().set_parent_and_child_svg_row_values(&svg_row_hm);
+// Makes the elements to draw for rows [start, end). Each row only has the lines drawn on it, which can come from commits
+// further down that have children above the window.
+fn get_svg_row_draw_properties(graph_layout: &GraphLayout, start: usize, end: usize) -> Vec<RowDrawProperties> {
+    let svg_rows = &graph_layout.svg_rows;
+    let mut svg_row_draw_properties: Vec<RowDrawProperties> = (start..end).map(|row_index| {
+        SVGRow::get_draw_properties(svg_rows, row_index, &graph_layout.main_table)
+    }).collect();
+
+    let mut lines_by_row: Vec<Vec<SVGElement>> = vec![vec![]; end - start];
+    for row_index in start..svg_rows.len() {
+        let child_lines = if row_index < end {
+            svg_row_draw_properties[row_index - start].take_child_lines()
+        } else if SVGRow::get_first_child_line_row(svg_rows, row_index) < end as isize {
+            SVGRow::get_child_lines(svg_rows, row_index)
+        } else {
+            continue;
+        };
+        for child_line in child_lines {
+            if let Some(row_y) = *child_line.borrow_row_y() {
+                let row_y = row_y as usize;
+                if row_y >= start && row_y < end {
+                    lines_by_row[row_y - start].push(child_line);
+                }
+            }
         }
+    }
+
+    for (row_properties, child_lines) in svg_row_draw_properties.iter_mut().zip(lines_by_row) {
+        row_properties.set_child_lines(child_lines);
+    }
+    svg_row_draw_properties
+}
 
-        let main_table = SVGRow::get_occupied_table(&svg_rows)?;
-        for svg_row_rc in svg_rows {
-            svg_row_draw_properties.push(svg_row_rc.borrow_mut().get_draw_properties(
-                &main_table,
-            ));
+// Lays the graph out again if the branches it starts from or the commit limit have changed since it was last cached.
+fn refresh_graph_layout(git_manager: &mut GitManager) -> Result<()> {
+    let starting_oids = git_manager.get_graph_starting_oids()?;
+    let starting_shas: Vec<String> = starting_oids.iter().map(|oid| oid.to_string()).collect();
+    let preferences = config_manager::get_config()?;
+    let mut commit_limit = None;
+    if *preferences.borrow_limit_commits() == Some(true) {
+        commit_limit = *preferences.borrow_commit_count();
+    }
+    if let Some(graph_layout) = git_manager.borrow_graph_layout() {
+        if graph_layout.starting_shas == starting_shas && graph_layout.commit_limit == commit_limit {
+            return Ok(());
         }
     }
 
+    let oid_vec = git_manager.get_graph_oid_list(&starting_oids)?;
+    let mut sha_rows: HashMap<String, usize> = HashMap::new();
+    for (i, oid) in oid_vec.iter().enumerate() {
+        sha_rows.insert(oid.to_string(), i);
+    }
+    // Keep the positions of the rows that are still there so the branches don't jump around when commits are added.
+    let previous_layout = git_manager.borrow_graph_layout().as_ref().map(|graph_layout| (&graph_layout.svg_rows[..], &graph_layout.main_table[..]));
+    let (svg_rows, main_table) = get_svg_rows(git_manager, &oid_vec, previous_layout)?;
+
+    let branch_densities = main_table.iter().map(|table_row| {
+        // Curved lines can mark the same x position more than once.
+        let mut occupied_x = table_row.clone();
+        occupied_x.sort();
+        occupied_x.dedup();
+        occupied_x.len()
+    }).collect();
+
+    git_manager.set_graph_layout(GraphLayout {
+        starting_shas,
+        commit_limit,
+        svg_rows,
+        main_table,
+        branch_densities,
+        sha_rows,
+    });
+    Ok(())
+}
+
+fn borrow_refreshed_graph_layout(git_manager: &mut GitManager) -> Result<&GraphLayout> {
+    refresh_graph_layout(git_manager)?;
+    match git_manager.borrow_graph_layout() {
+        Some(graph_layout) => Ok(graph_layout),
+        None => bail!("Graph layout is missing after refreshing it."),
+    }
+}
+
+// Gets rows [start, end) of the graph. Each row only has the lines drawn on it, so rows can be drawn without their neighbors.
+pub fn get_graph_rows(git_manager: &mut GitManager, json_str: &str) -> Result<GraphRows> {
+    let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+    let mut start: usize = match json_hm.get("start") {
+        Some(s) => s.parse()?,
+        None => bail!("start not included in payload from front-end."),
+    };
+    let mut end: usize = match json_hm.get("end") {
+        Some(e) => e.parse()?,
+        None => bail!("end not included in payload from front-end."),
+    };
+    if start > end {
+        bail!("start can't be after end when getting graph rows.");
+    }
+
+    // Refs like tags don't change the layout, so they're always looked up fresh.
     let oid_refs_hm = get_oid_refs(git_manager)?;
-    let mut branch_draw_properties: Vec<(String, Vec<Vec<HashMap<String, SVGProperty>>>)> = vec![];
+    let graph_layout = borrow_refreshed_graph_layout(git_manager)?;
+    let total_rows = graph_layout.svg_rows.len();
+    end = end.min(total_rows);
+    start = start.min(end);
+
+    let mut branch_draw_properties: Vec<(String, Vec<Vec<SVGElement>>)> = vec![];
     for (k, v) in oid_refs_hm {
-        branch_draw_properties.push((k, SVGRow::get_branch_draw_properties(v)));
+        if let Some(row) = graph_layout.sha_rows.get(&k) {
+            if *row >= start && *row < end {
+                branch_draw_properties.push((k, SVGRow::get_branch_draw_properties(v)));
+            }
+        }
     }
 
-    Ok(CommitsInfo::new(branch_draw_properties, svg_row_draw_properties))
+    Ok(GraphRows {
+        start,
+        end,
+        total_rows,
+        branch_draw_properties,
+        svg_row_draw_properties: get_svg_row_draw_properties(graph_layout, start, end),
+    })
+}
+
+// Gets every branch and tag badge of a commit, for when the graph only shows some of them.
+pub fn get_all_badges(git_manager: &GitManager, json_str: &str) -> Result<AllBadges> {
+    let sha: String = serde_json::from_str(json_str)?;
+    let mut oid_refs_hm = get_oid_refs(git_manager)?;
+    let branches_and_tags = oid_refs_hm.remove(&sha).unwrap_or_default();
+    Ok(AllBadges {
+        sha,
+        branch_draw_properties: SVGRow::get_all_branch_draw_properties(branches_and_tags),
+    })
 }
 
-fn get_branch_info_list(git_manager: &mut GitManager) -> Result<BranchesInfo> {
+pub fn filter_branches(git_manager: &mut GitManager, json_str: &str) -> Result<FilteredBranches> {
+    let pattern: String = serde_json::from_str(json_str)?;
+    let branch_info_list = get_branch_info_list(git_manager, Some(&pattern.to_lowercase()))?;
+    Ok(FilteredBranches {
+        pattern,
+        branch_info_list,
+    })
+}
+
+pub fn get_graph_minimap(git_manager: &mut GitManager, json_str: &str) -> Result<GraphMinimap> {
+    let json_hm: HashMap<String, String> = serde_json::from_str(json_str)?;
+    let rows_per_entry: usize = match json_hm.get("rows_per_entry") {
+        Some(r) => r.parse()?,
+        None => bail!("rows_per_entry not included in payload from front-end."),
+    };
+    if rows_per_entry == 0 {
+        bail!("rows_per_entry must be greater than 0.");
+    }
+
+    let oid_refs_hm = get_oid_refs(git_manager)?;
+    let graph_layout = borrow_refreshed_graph_layout(git_manager)?;
+    let mut entries: Vec<GraphMinimapEntry> = vec![];
+    for (entry_index, branch_densities) in graph_layout.branch_densities.chunks(rows_per_entry).enumerate() {
+        entries.push(GraphMinimapEntry {
+            start_row: entry_index * rows_per_entry,
+            row_count: branch_densities.len(),
+            branch_density: branch_densities.iter().max().cloned().unwrap_or(0),
+            refs: vec![],
+        });
+    }
+
+    for (sha, refs) in oid_refs_hm {
+        if let Some(row) = graph_layout.sha_rows.get(&sha) {
+            entries[row / rows_per_entry].refs.extend(refs);
+        }
+    }
+
+    Ok(GraphMinimap {
+        total_rows: graph_layout.svg_rows.len(),
+        rows_per_entry,
+        entries,
+    })
+}
+
+// When given a (lowercase) pattern, only the refs and stashes whose names contain it are included.
+fn get_branch_info_list(git_manager: &mut GitManager, pattern: Option<&str>) -> Result<BranchesInfo> {
+    let preferences = config_manager::get_config()?;
+    let favorite_branches = preferences.get_favorite_branches(&git_manager.get_canonical_repo_path()?);
+    let sort_by_recent = preferences.borrow_branch_sort_mode().as_deref() == Some("recent");
+    let tag_limit = git_manager.get_tag_limit();
     let repo = git_manager.borrow_repo_mut()?;
 
     // Get all remote heads to be excluded from branches info
@@ -529,24 +932,43 @@ fn get_branch_info_list(git_manager: &mut GitManager) -> Result<BranchesInfo> {
     let mut local_branch_info_tree = BranchInfoTreeNode::new(String::from(""), None);
     let mut remote_branch_info_tree = BranchInfoTreeNode::new(String::from(""), None);
     let mut tag_branch_info_tree = BranchInfoTreeNode::new(String::from(""), None);
+    let mut other_ref_info_tree = BranchInfoTreeNode::new(String::from(""), None);
+    let mut tag_names: Vec<(String, String)> = vec![];
+    let now = OffsetDateTime::now_utc().unix_timestamp();
     for reference_result in repo.references()? {
-        let reference = reference_result?;
-
-        let target_sha = match reference.peel_to_commit() {
-            Ok(c) => c.id().to_string(),
-            Err(_) => String::new(),
+        // A broken ref (like a loose ref file with garbage in it) shouldn't hide all the others.
+        let reference = match reference_result {
+            Ok(r) => r,
+            Err(_) => continue,
         };
 
         // Get branch name
         let branch_shorthand = String::from(GitManager::get_utf8_string(reference.shorthand(), "Branch Name")?);
 
+        // Get full branch name
+        let full_branch_name = String::from(GitManager::get_utf8_string(reference.name(), "Branch Name")?);
+
         // If this is the remote head, don't add it to the branches info
-        if remote_heads.contains(&branch_shorthand) {
+        if reference.is_remote() && remote_heads.contains(&branch_shorthand) {
             continue;
         }
 
-        // Get full branch name
-        let full_branch_name = String::from(GitManager::get_utf8_string(reference.name(), "Branch Name")?);
+        if let Some(p) = pattern {
+            if !branch_shorthand.to_lowercase().contains(p) {
+                continue;
+            }
+        }
+
+        // Only the names of tags are sent, the rest is looked up with get_tag_details when one is selected.
+        if reference.is_tag() {
+            tag_names.push((branch_shorthand, full_branch_name));
+            continue;
+        }
+
+        let (target_sha, last_commit) = match reference.peel_to_commit() {
+            Ok(c) => (c.id().to_string(), Some(LastCommitInfo::from_commit(&c, now))),
+            Err(_) => (String::new(), None),
+        };
 
         // Get if branch is head
         let mut is_head = false;
@@ -563,8 +985,8 @@ fn get_branch_info_list(git_manager: &mut GitManager) -> Result<BranchesInfo> {
             branch_type = String::from("local");
         } else if reference.is_remote() {
             branch_type = String::from("remote");
-        } else if reference.is_tag() {
-            branch_type = String::from("tag");
+        } else if full_branch_name.starts_with("refs/") {
+            branch_type = String::from("other");
         }
 
         // Get ahead/behind counts
@@ -580,9 +1002,11 @@ fn get_branch_info_list(git_manager: &mut GitManager) -> Result<BranchesInfo> {
                         Some(local_oid) => {
                             match remote_branch.get().target() {
                                 Some(remote_oid) => {
-                                    let (a, b) = repo.graph_ahead_behind(local_oid, remote_oid)?;
-                                    ahead = a;
-                                    behind = b;
+                                    // Either commit can be missing from a corrupt object database, the counts just stay at 0 then.
+                                    if let Ok((a, b)) = repo.graph_ahead_behind(local_oid, remote_oid) {
+                                        ahead = a;
+                                        behind = b;
+                                    }
                                 },
                                 None => (),
                             };
@@ -598,39 +1022,61 @@ fn get_branch_info_list(git_manager: &mut GitManager) -> Result<BranchesInfo> {
             };
         }
 
+        // Other refs are grouped by their full name so e.g. refs/notes/commits ends up under notes.
+        let tree_path = if branch_type == String::from("other") { &full_branch_name["refs/".len()..] } else { branch_shorthand.as_str() };
+        let is_favorite = branch_type == String::from("local") && favorite_branches.contains(&full_branch_name);
         let mut split_shorthand = VecDeque::new();
-        for s in branch_shorthand.split("/") {
-            split_shorthand.push_back(String::from(s));
+        if is_favorite {
+            // Pinned at the top level with its whole name rather than inside its folders.
+            split_shorthand.push_back(branch_shorthand.clone());
+        } else {
+            for s in tree_path.split("/") {
+                split_shorthand.push_back(String::from(s));
+            }
         }
-        let branch_info = BranchInfo::new(target_sha, branch_shorthand, full_branch_name, is_head, branch_type.clone(), ahead, behind, has_upstream);
+        let branch_info = BranchInfo::new(target_sha, branch_shorthand, full_branch_name, is_head, branch_type.clone(), ahead, behind, has_upstream, last_commit, is_favorite);
         if branch_type == String::from("local") {
             local_branch_info_tree.insert_split_shorthand(split_shorthand, Some(branch_info));
         } else if branch_type == String::from("remote") {
             remote_branch_info_tree.insert_split_shorthand(split_shorthand, Some(branch_info));
-        } else if branch_type == String::from("tag") {
-            tag_branch_info_tree.insert_split_shorthand(split_shorthand, Some(branch_info));
+        } else if branch_type == String::from("other") {
+            other_ref_info_tree.insert_split_shorthand(split_shorthand, Some(branch_info));
         }
     }
 
+    tag_names.sort();
+    let hidden_tag_count = tag_names.len().saturating_sub(tag_limit);
+    for (tag_shorthand, full_tag_name) in tag_names.into_iter().take(tag_limit) {
+        let split_shorthand: VecDeque<String> = tag_shorthand.split("/").map(String::from).collect();
+        let branch_info = BranchInfo::new(String::new(), tag_shorthand, full_tag_name, false, String::from("tag"), 0, 0, false, None, false);
+        tag_branch_info_tree.insert_split_shorthand(split_shorthand, Some(branch_info));
+    }
+
     // Add remote names in case a remote is present but has no branches.
-    for remote in remotes.iter() {
+    for remote in remotes.iter().filter(|_| pattern.is_none()) {
         let remote_name = String::from(GitManager::get_utf8_string(remote, "Remote Name")?);
         let mut split_shorthand = VecDeque::new();
         split_shorthand.push_back(remote_name);
         remote_branch_info_tree.insert_split_shorthand(split_shorthand, None);
     }
 
+    for tree in [&mut local_branch_info_tree, &mut remote_branch_info_tree, &mut tag_branch_info_tree, &mut other_ref_info_tree] {
+        tree.sort_children(sort_by_recent);
+    }
+
     let mut stash_info_list = vec![];
     repo.stash_foreach(|stash_index, stash_message, _stash_oid| {
         let stash_info = StashInfo::new(stash_index, format!("{}: {}", stash_index, stash_message));
-        stash_info_list.push(stash_info);
+        if pattern.map_or(true, |p| stash_info.message.to_lowercase().contains(p)) {
+            stash_info_list.push(stash_info);
+        }
         true
     })?;
 
-    Ok(BranchesInfo::new(local_branch_info_tree, remote_branch_info_tree, tag_branch_info_tree, stash_info_list))
+    Ok(BranchesInfo::new(local_branch_info_tree, remote_branch_info_tree, tag_branch_info_tree, other_ref_info_tree, stash_info_list, hidden_tag_count))
 }
 
-fn get_remote_info_list(git_manager: &GitManager) -> Result<Vec<String>> {
+fn get_remote_info_list(git_manager: &GitManager) -> Result<Vec<RemoteInfo>> {
     let repo = git_manager.borrow_repo()?;
 
     let mut remote_info_list = vec![];
@@ -638,7 +1084,20 @@ fn get_remote_info_list(git_manager: &GitManager) -> Result<Vec<String>> {
 
     for remote_name_opt in remote_string_array.iter() {
         let remote_name = GitManager::get_utf8_string(remote_name_opt, "Remote Name")?;
-        remote_info_list.push(String::from(remote_name));
+        // Skip a remote with a broken config rather than failing the whole refresh.
+        let remote = match repo.find_remote(remote_name) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        let mut fetch_refspecs = vec![];
+        for refspec_opt in remote.fetch_refspecs()?.iter() {
+            fetch_refspecs.push(String::from(GitManager::get_utf8_string(refspec_opt, "Refspec")?));
+        }
+        let mut push_refspecs = vec![];
+        for refspec_opt in remote.push_refspecs()?.iter() {
+            push_refspecs.push(String::from(GitManager::get_utf8_string(refspec_opt, "Refspec")?));
+        }
+        remote_info_list.push(RemoteInfo::new(String::from(remote_name), remote.url().map(String::from), remote.pushurl().map(String::from), fetch_refspecs, push_refspecs));
     }
     Ok(remote_info_list)
 }
@@ -646,39 +1105,66 @@ fn get_remote_info_list(git_manager: &GitManager) -> Result<Vec<String>> {
 pub fn get_parseable_diff_delta(diff: Diff) -> Result<Vec<ParseableDiffDelta>> {
     let mut files: Vec<ParseableDiffDelta> = vec![];
     for delta in diff.deltas() {
-        let status = delta.status() as u8;
-        let path = match delta.new_file().path() {
-            Some(p) => {
-                match p.to_str() {
-                    Some(s) => s,
-                    None => bail!("File Path uses invalid unicode. Not sure how your file system isn't corrupted..."),
-                }
-            },
-            None => bail!("Possible invalid file path? I'm not actually sure why this error would occur. It looks like git didn't store a file path with a file or something."),
-        };
-        files.push(ParseableDiffDelta::new(status, String::from(path)));
+        files.push(ParseableDiffDelta::from_diff_delta(&delta)?);
     }
     Ok(files)
 }
 
 pub fn get_files_changed_info_list(git_manager: &GitManager) -> Result<Option<FilesChangedInfo>> {
+    get_files_changed_info(git_manager, false)
+}
+
+// Like get_files_changed_info_list, but when only the paths the app just changed had to be checked again, only their entries are
+// sent so a huge list doesn't have to be sent and rebuilt for every file that's staged.
+pub fn get_files_changed_update(git_manager: &GitManager) -> Result<Option<FilesChangedInfo>> {
+    get_files_changed_info(git_manager, true)
+}
+
+fn get_files_changed_info(git_manager: &GitManager, allow_partial: bool) -> Result<Option<FilesChangedInfo>> {
     if !git_manager.has_open_repo() {
         return Ok(None);
     }
-    let unstaged_diff = git_manager.get_unstaged_changes()?;
-    let staged_diff = git_manager.get_staged_changes()?;
-    let files_changed = unstaged_diff.stats()?.files_changed() + staged_diff.stats()?.files_changed();
-    Ok(Some(FilesChangedInfo::new(files_changed, get_parseable_diff_delta(unstaged_diff)?, get_parseable_diff_delta(staged_diff)?)))
+    if !git_manager.get_repo_capabilities()?.borrow_has_workdir() {
+        return Ok(Some(FilesChangedInfo::new(0, vec![], vec![], vec![], vec![])));
+    }
+    let (mut unstaged_files, mut staged_files, updated_paths_opt) = git_manager.get_changed_files()?;
+    let case_renames = git_manager.get_case_renames()?;
+    // An untracked directory counts as every file in it rather than as a single entry.
+    let mut files_changed = staged_files.len() + case_renames.len();
+    for unstaged_file in unstaged_files.iter() {
+        files_changed += unstaged_file.untracked_file_count.unwrap_or(1).max(1);
+    }
+    let updated_paths_opt = updated_paths_opt.filter(|_| allow_partial);
+    if let Some(updated_paths) = &updated_paths_opt {
+        unstaged_files.retain(|f| updated_paths.contains(&f.path));
+        staged_files.retain(|f| updated_paths.contains(&f.path));
+    } else {
+        // A partial update only happens when none of the updated paths are case renames, so the ones already shown are still right.
+        unstaged_files.extend(case_renames);
+    }
+    set_collapsed_files(git_manager.borrow_repo()?, &mut unstaged_files)?;
+    set_collapsed_files(git_manager.borrow_repo()?, &mut staged_files)?;
+    let mut files_changed_info = FilesChangedInfo::new(files_changed, unstaged_files, staged_files, git_manager.get_flagged_files()?, git_manager.borrow_lfs_locks().clone());
+    if let Some(updated_paths) = updated_paths_opt {
+        files_changed_info.set_updated_paths(updated_paths.into_iter().collect());
+    }
+    Ok(Some(files_changed_info))
 }
 
 pub fn get_parseable_repo_info(git_manager: &mut GitManager, force_refresh: bool) -> Result<Option<HashMap<String, RepoInfoValue>>> {
     if !git_manager.has_open_repo() {
         return Ok(None);
     }
+    let repo_capabilities = git_manager.get_repo_capabilities()?;
     let mut repo_info: HashMap<String, RepoInfoValue> = HashMap::new();
-    repo_info.insert(String::from("general_info"), RepoInfoValue::SomeGeneralInfo(get_general_info(git_manager)?));
-    repo_info.insert(String::from("commit_info_list"), RepoInfoValue::SomeCommitInfo(get_commit_svg_draw_properties_list(git_manager, force_refresh)?));
-    repo_info.insert(String::from("branch_info_list"), RepoInfoValue::SomeBranchInfo(get_branch_info_list(git_manager)?));
+    repo_info.insert(String::from("general_info"), RepoInfoValue::SomeGeneralInfo(get_general_info(git_manager, &repo_capabilities)?));
+    // Walking a corrupt object database would fail part way through, so just show an empty graph instead.
+    if repo_capabilities.is_readable() {
+        repo_info.insert(String::from("commit_info_list"), RepoInfoValue::SomeCommitInfo(get_commit_svg_draw_properties_list(git_manager, force_refresh)?));
+    } else {
+        repo_info.insert(String::from("commit_info_list"), RepoInfoValue::SomeCommitInfo(CommitsInfo::new(vec![], 0, HashMap::new(), Some(HashMap::new()))));
+    }
+    repo_info.insert(String::from("branch_info_list"), RepoInfoValue::SomeBranchInfo(get_branch_info_list(git_manager, None)?));
     repo_info.insert(String::from("remote_info_list"), RepoInfoValue::SomeRemoteInfo(get_remote_info_list(git_manager)?));
     if let Some(fcil) = get_files_changed_info_list(git_manager)? {
         repo_info.insert(String::from("files_changed_info_list"), RepoInfoValue::SomeFilesChangedInfo(fcil));
@@ -687,3 +1173,42 @@ pub fn get_parseable_repo_info(git_manager: &mut GitManager, force_refresh: bool
     }
     Ok(Some(repo_info))
 }
+
+fn get_operation_state(repo_state: RepositoryState) -> &'static str {
+    match repo_state {
+        RepositoryState::Clean => "none",
+        RepositoryState::Merge => "merge",
+        RepositoryState::Revert => "revert",
+        RepositoryState::RevertSequence => "revert_sequence",
+        RepositoryState::CherryPick => "cherrypick",
+        RepositoryState::CherryPickSequence => "cherrypick_sequence",
+        RepositoryState::Bisect => "bisect",
+        RepositoryState::Rebase => "rebase",
+        RepositoryState::RebaseInteractive => "rebase_interactive",
+        RepositoryState::RebaseMerge => "rebase_merge",
+        RepositoryState::ApplyMailbox => "apply_mailbox",
+        RepositoryState::ApplyMailboxOrRebase => "apply_mailbox_or_rebase",
+    }
+}
+
+pub fn get_full_state(git_manager: &mut GitManager) -> Result<FullState> {
+    let config = config_manager::get_config()?;
+    let credentials_status = config.get_credentials_status();
+    let mut repo_capabilities = None;
+    let mut operation_state = String::from("none");
+    let mut repo_session = None;
+    if git_manager.has_open_repo() {
+        repo_capabilities = Some(git_manager.get_repo_capabilities()?);
+        repo_session = config.get_repo_session(&git_manager.get_canonical_repo_path()?);
+        operation_state = String::from(get_operation_state(git_manager.borrow_repo()?.state()));
+    }
+    Ok(FullState {
+        // The webview asking for this has nothing drawn yet, so send the graph even if it hasn't changed.
+        repo_info: get_parseable_repo_info(git_manager, true)?,
+        repo_capabilities,
+        operation_state,
+        credentials_status,
+        config,
+        repo_session,
+    })
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+// An error with a code the front-end can match on to offer a way to recover, rather than just showing the message.
+#[derive(Clone, Debug, Serialize)]
+pub struct StructuredError {
+    code: String,
+    message: String,
+    data: Option<Value>,
+}
+
+impl StructuredError {
+    pub fn new(code: &str, message: String, data: Option<Value>) -> Self {
+        Self {
+            code: String::from(code),
+            message,
+            data,
+        }
+    }
+
+    // Gets a structured error out of an anyhow error, converting known libgit2 errors where possible.
+    pub fn from_anyhow(e: &anyhow::Error) -> Option<Self> {
+        if let Some(structured_error) = e.downcast_ref::<StructuredError>() {
+            return Some(structured_error.clone());
+        }
+        if let Some(git_error) = e.downcast_ref::<git2::Error>() {
+            if git_error.code() == git2::ErrorCode::Locked {
+                return Some(StructuredError::from_lock_error(git_error));
+            }
+        }
+        None
+    }
+
+    fn from_lock_error(git_error: &git2::Error) -> Self {
+        // libgit2 reports these as "failed to lock file '<path>' for writing".
+        let message = git_error.message();
+        let lock_path_opt = match (message.find('\''), message.rfind('\'')) {
+            (Some(start), Some(end)) if start < end => Some(String::from(&message[start + 1..end])),
+            _ => None,
+        };
+        let friendly_message = match &lock_path_opt {
+            Some(lock_path) => format!("Another git process seems to be running in this repository ({} exists). If no other git process is running, the lock may be stale and can be removed.", lock_path),
+            None => String::from("Another git process seems to be running in this repository. If no other git process is running, the lock may be stale and can be removed."),
+        };
+        StructuredError::new("lock_file_exists", friendly_message, Some(json!({ "lock_path": lock_path_opt })))
+    }
+}
+
+impl fmt::Display for StructuredError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for StructuredError {}
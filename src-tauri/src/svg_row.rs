@@ -1,9 +1,7 @@
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
-use anyhow::{bail, Result};
+use git2::Oid;
 use serde::{Serialize, Serializer};
-use crate::parseable_info::ParseableCommitInfo;
+use serde::ser::SerializeMap;
 
 #[derive(Clone)]
 pub enum SVGPropertyAttrs {
@@ -20,54 +18,72 @@ impl Serialize for SVGPropertyAttrs {
     }
 }
 
+// The attributes of an SVG element, sent to the front-end as an object.
 #[derive(Clone)]
-pub enum SVGProperty {
-    SomeInt(isize),
-    SomeString(String),
-    SomeHashMap(HashMap<String, SVGPropertyAttrs>),
-}
+pub struct SVGAttrs(Vec<(&'static str, SVGPropertyAttrs)>);
 
-impl Serialize for SVGProperty {
+impl Serialize for SVGAttrs {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-        match &self {
-            SVGProperty::SomeInt(i) => i.serialize(serializer),
-            SVGProperty::SomeString(st) => st.serialize(serializer),
-            SVGProperty::SomeHashMap(hm) => hm.serialize(serializer),
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (attr_name, attr_value) in &self.0 {
+            map.serialize_entry(attr_name, attr_value)?;
         }
+        map.end()
     }
 }
 
-#[derive(Clone)]
-pub enum DrawProperty {
-    SomeHashMap(HashMap<String, SVGProperty>),
-    SomeVector(Vec<HashMap<String, SVGProperty>>),
-    SomeVectorVector(Vec<Vec<HashMap<String, SVGProperty>>>),
+#[derive(Clone, Serialize)]
+pub struct SVGElement {
+    tag: &'static str,
+    attrs: SVGAttrs,
+    #[serde(rename = "textContent", skip_serializing_if = "Option::is_none")]
+    text_content: Option<String>,
+    // Only for the lines to a commit's children, which row the line is drawn on.
+    #[serde(rename = "row-y", skip_serializing_if = "Option::is_none")]
+    row_y: Option<isize>,
 }
 
-impl Serialize for DrawProperty {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-        match &self {
-            DrawProperty::SomeHashMap(hm) => hm.serialize(serializer),
-            DrawProperty::SomeVector(v) => v.serialize(serializer),
-            DrawProperty::SomeVectorVector(v) => v.serialize(serializer),
+impl SVGElement {
+    fn new(tag: &'static str, attrs: Vec<(&'static str, SVGPropertyAttrs)>, text_content: Option<String>, row_y: Option<isize>) -> Self {
+        Self {
+            tag,
+            attrs: SVGAttrs(attrs),
+            text_content,
+            row_y,
         }
     }
+
+    pub fn borrow_row_y(&self) -> &Option<isize> {
+        &self.row_y
+    }
 }
 
-#[derive(Clone)]
-pub enum RowProperty {
-    SomeInt(isize),
-    SomeString(String),
-    SomeHashMap(HashMap<String, DrawProperty>),
+#[derive(Clone, Serialize)]
+pub struct RowElements {
+    child_lines: Vec<SVGElement>,
+    circle: SVGElement,
+    summary_text: SVGElement,
+    author_name: SVGElement,
+    author_time: SVGElement,
+    back_rect: SVGElement,
 }
 
-impl Serialize for RowProperty {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-        match &self {
-            RowProperty::SomeInt(i) => i.serialize(serializer),
-            RowProperty::SomeString(s) => s.serialize(serializer),
-            RowProperty::SomeHashMap(hm) => hm.serialize(serializer),
-        }
+#[derive(Clone, Serialize)]
+pub struct RowDrawProperties {
+    sha: String,
+    pixel_y: isize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    boundary: Option<String>,
+    elements: RowElements,
+}
+
+impl RowDrawProperties {
+    pub fn take_child_lines(&mut self) -> Vec<SVGElement> {
+        std::mem::take(&mut self.elements.child_lines)
+    }
+
+    pub fn set_child_lines(&mut self, child_lines: Vec<SVGElement>) {
+        self.elements.child_lines = child_lines;
     }
 }
 
@@ -80,126 +96,160 @@ const CIRCLE_RADIUS: isize = 5;
 const LINE_STROKE_WIDTH: isize = 2;
 const RECT_HEIGHT: isize = 18;
 const RECT_Y_OFFSET: isize = -(RECT_HEIGHT / 2);
+const MAX_BRANCH_BADGES: usize = 5;
 
+// One commit in the graph. Rows refer to each other by their index in the list of rows, so the whole graph can be dropped at once.
 #[derive(Clone)]
 pub struct SVGRow {
+    oid: Oid,
     sha: String,
     author_name: String,
     author_time: String,
     summary: String,
-    parent_oids: Vec<String>,
-    child_oids: Vec<String>,
-    has_parent_child_svg_rows_set: bool,
-    parent_svg_rows: Vec<Rc<RefCell<SVGRow>>>,
-    child_svg_rows: Vec<Rc<RefCell<SVGRow>>>,
+    boundary: Option<String>,
+    parent_rows: Vec<usize>,
+    // The x position the line to each parent runs down, in the same order as parent_rows. It only matters when there are rows
+    // between them, and it can differ from both ends when the parent has more than one child or the commit is an octopus merge.
+    parent_lanes: Vec<isize>,
+    child_rows: Vec<usize>,
     x: isize,
     y: isize,
 }
 
 impl SVGRow {
-    pub fn from_commit_info(commit_info: &ParseableCommitInfo) -> Self {
+    pub fn new(oid: Oid, author_name: String, author_time: String, summary: String, boundary: Option<String>, y: isize) -> Self {
         Self {
-            sha: commit_info.borrow_sha().clone(),
-            author_name: commit_info.borrow_author_name().clone(),
-            author_time: commit_info.borrow_author_time().clone(),
-            summary: commit_info.borrow_summary().clone(),
-            parent_oids: commit_info.borrow_parent_shas().clone(),
-            child_oids: commit_info.borrow_child_shas().clone(),
-            has_parent_child_svg_rows_set: false,
-            parent_svg_rows: vec![],
-            child_svg_rows: vec![],
-            x: commit_info.borrow_x().clone(),
-            y: commit_info.borrow_y().clone(),
+            oid,
+            sha: oid.to_string(),
+            author_name,
+            author_time,
+            summary,
+            boundary,
+            parent_rows: vec![],
+            parent_lanes: vec![],
+            child_rows: vec![],
+            x: 0,
+            y,
         }
     }
 
-    pub fn set_parent_and_child_svg_row_values(&mut self, all_svg_rows: &HashMap<String, Rc<RefCell<SVGRow>>>) {
-        for sha in &self.parent_oids {
-            match all_svg_rows.get(&*sha) {
-                Some(svg_row_rc) => {
-                    self.parent_svg_rows.push(svg_row_rc.clone());
-                },
-                // If a parent is not present, ignore it. It may be outside the revwalk range.
-                None => (),
-            };
+    // Links each row to the rows of its parents and children, in the same order as the rows are in. parent_oids has each row's
+    // parents, and parents that aren't in the rows (like ones outside the revwalk range) are left out.
+    pub fn set_parent_and_child_rows(svg_rows: &mut [SVGRow], oid_vec: &[Oid], parent_oids: &[Vec<Oid>]) {
+        let mut row_indexes: HashMap<Oid, usize> = HashMap::with_capacity(oid_vec.len());
+        for (row_index, oid) in oid_vec.iter().enumerate() {
+            row_indexes.insert(*oid, row_index);
         }
 
-        for sha in &self.child_oids {
-            match all_svg_rows.get(&*sha) {
-                Some(svg_row_rc) => {
-                    self.child_svg_rows.push(svg_row_rc.clone());
-                },
-                // If a child is not present, ignore it. It may be outside the revwalk range.
-                None => (),
-            };
+        for (row_index, row_parent_oids) in parent_oids.iter().enumerate() {
+            for parent_oid in row_parent_oids {
+                if let Some(parent_index) = row_indexes.get(parent_oid) {
+                    svg_rows[row_index].parent_rows.push(*parent_index);
+                    svg_rows[row_index].parent_lanes.push(0);
+                    svg_rows[*parent_index].child_rows.push(row_index);
+                }
+            }
         }
-
-        self.has_parent_child_svg_rows_set = true;
     }
 
-    fn get_color_string(x: isize) -> String {
+    fn get_color_string(x: isize) -> &'static str {
         let color_num = x % 4;
         if color_num == 0 {
-            String::from("#00CC19")
+            "#00CC19"
         } else if color_num == 1 {
-            String::from("#0198A6")
+            "#0198A6"
         } else if color_num == 2 {
-            String::from("#FF7800")
+            "#FF7800"
         } else {
-            String::from("#FF0D00")
+            "#FF0D00"
         }
     }
 
-    pub fn get_occupied_table(svg_rows: &Vec<Rc<RefCell<SVGRow>>>) -> Result<Vec<Vec<isize>>> {
-        let mut main_table: Vec<Vec<isize>> = vec![];
-
-        for svg_row_rc in svg_rows.iter() {
-            let mut svg_row = svg_row_rc.borrow_mut();
-
-            if !svg_row.has_parent_child_svg_rows_set {
-                bail!("SVGRow object didn't have parents or children set. Make sure 'set_parent_and_child_svg_row_values' is run before 'get_occupied_table'!");
+    // Gets how many rows are new when the rows are the previous layout's rows with commits added above them (and maybe some
+    // dropped off the bottom by the commit limit), or None if the rows have to be laid out from scratch.
+    fn get_new_row_count(svg_rows: &[SVGRow], previous_svg_rows: &[SVGRow]) -> Option<usize> {
+        let first_previous_row = previous_svg_rows.first()?;
+        let new_row_count = svg_rows.iter().position(|svg_row| svg_row.oid == first_previous_row.oid)?;
+        let kept_rows = &svg_rows[new_row_count..];
+        if kept_rows.len() > previous_svg_rows.len() {
+            return None;
+        }
+        for (svg_row, previous_svg_row) in kept_rows.iter().zip(previous_svg_rows) {
+            if svg_row.oid != previous_svg_row.oid {
+                return None;
             }
+        }
+        Some(new_row_count)
+    }
 
-            // Set the current node position as occupied (or find a position that's unoccupied and occupy it).
-            if svg_row.y < main_table.len() as isize {
-                while main_table[svg_row.y as usize].contains(&svg_row.x) {
-                    svg_row.x += 1;
+    // Rows are placed from the top down, so when commits are only added above the previous layout the rows that were already
+    // there keep their x positions and just the new rows are placed around them. That way making a commit on one branch doesn't
+    // move the lanes of the others.
+    pub fn get_occupied_table(svg_rows: &mut [SVGRow], previous_layout: Option<(&[SVGRow], &[Vec<isize>])>) -> Vec<Vec<isize>> {
+        let mut new_row_count = svg_rows.len();
+        let mut main_table: Vec<Vec<isize>> = vec![vec![]; svg_rows.len()];
+        if let Some((previous_svg_rows, previous_main_table)) = previous_layout {
+            if let Some(row_count) = SVGRow::get_new_row_count(svg_rows, previous_svg_rows) {
+                new_row_count = row_count;
+                for (svg_row, previous_svg_row) in svg_rows[new_row_count..].iter_mut().zip(previous_svg_rows) {
+                    svg_row.x = previous_svg_row.x;
+                    // Parents that dropped off the bottom aren't linked anymore, so match the lanes up by row.
+                    for (parent_position, parent_index) in svg_row.parent_rows.iter().enumerate() {
+                        let previous_parent_index = parent_index - new_row_count;
+                        if let Some(previous_position) = previous_svg_row.parent_rows.iter().position(|i| *i == previous_parent_index) {
+                            svg_row.parent_lanes[parent_position] = previous_svg_row.parent_lanes[previous_position];
+                        }
+                    }
+                }
+                for (table_row, previous_table_row) in main_table[new_row_count..].iter_mut().zip(previous_main_table) {
+                    *table_row = previous_table_row.clone();
                 }
-                main_table[svg_row.y as usize].push(svg_row.x);
-            } else {
-                main_table.push(vec![svg_row.x]);
             }
+        }
 
-            // Set the space of the line from the current node to its parents as occupied.
-            for parent_svg_row_rc in &svg_row.parent_svg_rows {
-                let mut parent_svg_row = parent_svg_row_rc.borrow_mut();
-                let mut moved_x_val = 0;
-                for i in (svg_row.y + 1)..parent_svg_row.y {
-                    let mut x_val = svg_row.x;
-                    if i < main_table.len() as isize {
-                        while main_table[i as usize].contains(&x_val) {
-                            x_val += 1;
-                            // Note: this has to stay in the loop so it's only set when x changes!
-                            // and not just to svg_row.x
-                            moved_x_val = x_val;
-                        }
-                        main_table[i as usize].push(x_val);
-                    } else {
-                        main_table.push(vec![x_val]);
-                    }
+        for row_index in 0..new_row_count {
+            let svg_row = &mut svg_rows[row_index];
+
+            // Set the current node position as occupied (or find a position that's unoccupied and occupy it).
+            while main_table[svg_row.y as usize].contains(&svg_row.x) {
+                svg_row.x += 1;
+            }
+            main_table[svg_row.y as usize].push(svg_row.x);
+
+            // Set the space of the line from the current node to its parents as occupied. Each line gets one x position that's free
+            // on every row in between, so the lines of octopus merges and of parents with several children can't run over each other.
+            let (x, y) = (svg_row.x, svg_row.y);
+            for parent_position in 0..svg_rows[row_index].parent_rows.len() {
+                let parent_index = svg_rows[row_index].parent_rows[parent_position];
+                let between_rows = &mut main_table[(y + 1) as usize..svg_rows[parent_index].y as usize];
+                let mut lane = x;
+                while between_rows.iter().any(|table_row| table_row.contains(&lane)) {
+                    lane += 1;
+                }
+                for table_row in between_rows.iter_mut() {
+                    table_row.push(lane);
+                }
+                svg_rows[row_index].parent_lanes[parent_position] = lane;
+                // This is used particularly for merging lines, rows from the previous layout stay where they are.
+                if parent_index < new_row_count {
+                    svg_rows[parent_index].x = if lane != x { lane } else { 0 };
                 }
-                // This is used particularly for merging lines
-                parent_svg_row.x = moved_x_val;
             }
         }
 
         // Loop through after everything's set in order to properly occupy spaces by curved lines just for summary text positions.
-        for svg_row_rc in svg_rows {
-            let svg_row = svg_row_rc.borrow();
-
-            for parent_svg_row_rc in &svg_row.parent_svg_rows {
-                let parent_svg_row = parent_svg_row_rc.borrow();
-                if svg_row.x < parent_svg_row.x {
+        // The previous layout's table already has the spaces for the lines between its own rows.
+        for svg_row in &svg_rows[..new_row_count] {
+            for (parent_index, lane) in svg_row.parent_rows.iter().zip(&svg_row.parent_lanes) {
+                let parent_svg_row = &svg_rows[*parent_index];
+                if parent_svg_row.y - svg_row.y > 1 {
+                    if *lane > svg_row.x {
+                        main_table[svg_row.y as usize].push(*lane);
+                    }
+                    if *lane > parent_svg_row.x {
+                        main_table[parent_svg_row.y as usize].push(*lane);
+                    }
+                } else if svg_row.x < parent_svg_row.x {
                     let x_val = parent_svg_row.x;
                     main_table[svg_row.y as usize].push(x_val);
                 } else if svg_row.x > parent_svg_row.x {
@@ -209,222 +259,421 @@ impl SVGRow {
             }
         }
 
-        Ok(main_table)
+        main_table
     }
 
-    pub fn get_draw_properties(&mut self, main_table: &Vec<Vec<isize>>) -> HashMap<String, RowProperty> {
-        let mut row_properties: HashMap<String, RowProperty> = HashMap::new();
-        let mut draw_properties: HashMap<String, DrawProperty> = HashMap::new();
+    // The first row any of the lines to the row's children are drawn on, they go from just below each child down to the row.
+    pub fn get_first_child_line_row(svg_rows: &[SVGRow], row_index: usize) -> isize {
+        let svg_row = &svg_rows[row_index];
+        svg_row.child_rows.iter().map(|child_index| svg_rows[*child_index].y + 1).min().unwrap_or(svg_row.y)
+    }
 
-        row_properties.insert(String::from("sha"), RowProperty::SomeString(self.sha.clone()));
+    fn get_line_style(x: isize) -> String {
+        format!("stroke:{};fill:transparent;stroke-width:{}", SVGRow::get_color_string(x), LINE_STROKE_WIDTH)
+    }
 
-        let pixel_x = self.x * X_SPACING + X_OFFSET;
-        let pixel_y = self.y * Y_SPACING + Y_OFFSET;
-        row_properties.insert(String::from("pixel_y"), RowProperty::SomeInt(pixel_y));
-        let color = SVGRow::get_color_string(self.x);
-        let mut child_lines: Vec<HashMap<String, SVGProperty>> = vec![];
-        // Draw the lines from the current node's children to itself.
-        for child_svg_row_rc in &self.child_svg_rows {
-            let child_svg_row = child_svg_row_rc.borrow();
-            let child_pixel_x = child_svg_row.x * X_SPACING + X_OFFSET;
-            let child_pixel_y = child_svg_row.y * Y_SPACING + Y_OFFSET;
-            let before_y = self.y - 1;
-            let before_pixel_y = before_y * Y_SPACING + Y_OFFSET;
-            if before_pixel_y != child_pixel_y {
-                let start_index;
-                let end_index;
-                let line_pixel_x;
-                if self.x > child_svg_row.x {
-                    line_pixel_x = pixel_x;
-                    start_index = child_svg_row.y + 1;
-                    end_index = before_y;
+    // A straight line down one row at the lane's x position.
+    fn get_lane_line(lane: isize, top_y: isize) -> SVGElement {
+        let line_pixel_x = lane * X_SPACING + X_OFFSET;
+        let style_str = format!("stroke:{};stroke-width:{}", SVGRow::get_color_string(lane), LINE_STROKE_WIDTH);
+        SVGElement::new("line", vec![
+            ("x1", SVGPropertyAttrs::SomeInt(line_pixel_x)),
+            ("y1", SVGPropertyAttrs::SomeInt(top_y * Y_SPACING + Y_OFFSET)),
+            ("x2", SVGPropertyAttrs::SomeInt(line_pixel_x)),
+            ("y2", SVGPropertyAttrs::SomeInt((top_y + 1) * Y_SPACING + Y_OFFSET)),
+            ("style", SVGPropertyAttrs::SomeString(style_str)),
+        ], None, Some(top_y + 1))
+    }
+
+    // The curve that branches off of a child to the right into the row below it.
+    fn get_branching_curve(child_x: isize, child_y: isize, x: isize, style_str: String, row_y: isize) -> SVGElement {
+        let child_pixel_x = child_x * X_SPACING + X_OFFSET;
+        let child_pixel_y = child_y * Y_SPACING + Y_OFFSET;
+        let pixel_x = x * X_SPACING + X_OFFSET;
+        let after_child_pixel_y = (child_y + 1) * Y_SPACING + Y_OFFSET;
+        let start_control_point_x = child_pixel_x + X_SPACING * 3 / 4;
+        let end_control_point_y = after_child_pixel_y - Y_SPACING * 3 / 4;
+        let d_str = format!("M {child_pixel_x} {child_pixel_y} C {start_control_point_x} {child_pixel_y}, {pixel_x} {end_control_point_y}, {pixel_x} {after_child_pixel_y}");
+        SVGElement::new("path", vec![
+            ("d", SVGPropertyAttrs::SomeString(d_str)),
+            ("style", SVGPropertyAttrs::SomeString(style_str)),
+        ], None, Some(row_y))
+    }
+
+    // The curve that merges into a node from the row above it.
+    fn get_merging_curve(before_x: isize, x: isize, y: isize, style_str: String) -> SVGElement {
+        let before_pixel_x = before_x * X_SPACING + X_OFFSET;
+        let before_pixel_y = (y - 1) * Y_SPACING + Y_OFFSET;
+        let pixel_x = x * X_SPACING + X_OFFSET;
+        let pixel_y = y * Y_SPACING + Y_OFFSET;
+        let start_control_point_y = before_pixel_y + Y_SPACING * 3 / 4;
+        let end_control_point_x = if before_x > x {
+            pixel_x + X_SPACING * 3 / 4
+        } else {
+            pixel_x - X_SPACING * 3 / 4
+        };
+        let d_str = format!("M {before_pixel_x} {before_pixel_y} C {before_pixel_x} {start_control_point_y}, {end_control_point_x} {pixel_y}, {pixel_x} {pixel_y}");
+        SVGElement::new("path", vec![
+            ("d", SVGPropertyAttrs::SomeString(d_str)),
+            ("style", SVGPropertyAttrs::SomeString(style_str)),
+        ], None, Some(y))
+    }
+
+    // Draw the lines from a node's children to itself. A line that skips rows runs down its lane, branching off of the child
+    // into it and merging from it into the node when the lane is to the right of them.
+    pub fn get_child_lines(svg_rows: &[SVGRow], row_index: usize) -> Vec<SVGElement> {
+        let svg_row = &svg_rows[row_index];
+        let pixel_x = svg_row.x * X_SPACING + X_OFFSET;
+        let pixel_y = svg_row.y * Y_SPACING + Y_OFFSET;
+        let before_y = svg_row.y - 1;
+        let before_pixel_y = before_y * Y_SPACING + Y_OFFSET;
+        let mut child_lines: Vec<SVGElement> = vec![];
+        for child_index in &svg_row.child_rows {
+            let child_svg_row = &svg_rows[*child_index];
+            if child_svg_row.y != before_y {
+                let lane = match child_svg_row.parent_rows.iter().position(|parent_index| *parent_index == row_index) {
+                    Some(parent_position) => child_svg_row.parent_lanes[parent_position],
+                    None => child_svg_row.x,
+                };
+                if lane > child_svg_row.x {
+                    // A lane that ends at the node runs all the way down to it.
+                    let end_index = if lane == svg_row.x { before_y } else { before_y - 1 };
+                    for i in (child_svg_row.y + 1)..=end_index {
+                        child_lines.push(SVGRow::get_lane_line(lane, i));
+                    }
+                    child_lines.push(SVGRow::get_branching_curve(child_svg_row.x, child_svg_row.y, lane, SVGRow::get_line_style(lane), svg_row.y));
+                    if lane == svg_row.x {
+                        continue;
+                    }
                 } else {
-                    line_pixel_x = child_pixel_x;
-                    start_index = child_svg_row.y;
-                    end_index = before_y - 1;
+                    for i in child_svg_row.y..before_y {
+                        child_lines.push(SVGRow::get_lane_line(lane, i));
+                    }
                 }
-                for i in start_index..=end_index {
-                    let top_pixel_y = i * Y_SPACING + Y_OFFSET;
-                    let bottom_pixel_y = (i + 1) * Y_SPACING + Y_OFFSET;
-
-                    let style_str = String::from("stroke:") + SVGRow::get_color_string((line_pixel_x - X_OFFSET) / X_SPACING).as_str() +
-                        ";stroke-width:" + LINE_STROKE_WIDTH.to_string().as_str();
-                    let line_attrs: HashMap<String, SVGPropertyAttrs> = HashMap::from([
-                        (String::from("x1"), SVGPropertyAttrs::SomeInt(line_pixel_x)),
-                        (String::from("y1"), SVGPropertyAttrs::SomeInt(top_pixel_y)),
-                        (String::from("x2"), SVGPropertyAttrs::SomeInt(line_pixel_x)),
-                        (String::from("y2"), SVGPropertyAttrs::SomeInt(bottom_pixel_y)),
-                        (String::from("style"), SVGPropertyAttrs::SomeString(style_str)),
-                    ]);
-                    child_lines.push(HashMap::from([
-                        (String::from("tag"), SVGProperty::SomeString(String::from("line"))),
-                        (String::from("attrs"), SVGProperty::SomeHashMap(line_attrs)),
-                        (String::from("row-y"), SVGProperty::SomeInt(i + 1)),
-                    ]));
+                if lane == svg_row.x {
+                    child_lines.push(SVGElement::new("line", vec![
+                        ("x1", SVGPropertyAttrs::SomeInt(pixel_x)),
+                        ("y1", SVGPropertyAttrs::SomeInt(before_pixel_y)),
+                        ("x2", SVGPropertyAttrs::SomeInt(pixel_x)),
+                        ("y2", SVGPropertyAttrs::SomeInt(pixel_y)),
+                        ("style", SVGPropertyAttrs::SomeString(SVGRow::get_line_style(lane))),
+                    ], None, Some(svg_row.y)));
+                } else {
+                    child_lines.push(SVGRow::get_merging_curve(lane, svg_row.x, svg_row.y, SVGRow::get_line_style(lane)));
                 }
+                continue;
             }
-            let mut style_str = String::from("stroke:");
-            let row_y = self.y;
-            if child_svg_row.x >= self.x {
+
+            let child_pixel_x = child_svg_row.x * X_SPACING + X_OFFSET;
+            let style_color = if child_svg_row.x >= svg_row.x {
                 // Sets the color for "branching" lines and straight lines
-                style_str += SVGRow::get_color_string(child_svg_row.x).as_str();
+                child_svg_row.x
             } else {
                 // Sets the color for "merging" lines
-                style_str += SVGRow::get_color_string(self.x).as_str();
-            }
-            style_str += ";fill:transparent;stroke-width:";
-            style_str += LINE_STROKE_WIDTH.to_string().as_str();
+                svg_row.x
+            };
+            let style_str = SVGRow::get_line_style(style_color);
             if child_pixel_x == pixel_x {
-                let line_attrs: HashMap<String, SVGPropertyAttrs> = HashMap::from([
-                    (String::from("x1"), SVGPropertyAttrs::SomeInt(child_pixel_x)),
-                    (String::from("y1"), SVGPropertyAttrs::SomeInt(before_pixel_y)),
-                    (String::from("x2"), SVGPropertyAttrs::SomeInt(pixel_x)),
-                    (String::from("y2"), SVGPropertyAttrs::SomeInt(pixel_y)),
-                    (String::from("style"), SVGPropertyAttrs::SomeString(style_str)),
-                ]);
-                child_lines.push(HashMap::from([
-                    (String::from("tag"), SVGProperty::SomeString(String::from("line"))),
-                    (String::from("attrs"), SVGProperty::SomeHashMap(line_attrs)),
-                    (String::from("row-y"), SVGProperty::SomeInt(row_y)),
-                ]));
+                child_lines.push(SVGElement::new("line", vec![
+                    ("x1", SVGPropertyAttrs::SomeInt(child_pixel_x)),
+                    ("y1", SVGPropertyAttrs::SomeInt(before_pixel_y)),
+                    ("x2", SVGPropertyAttrs::SomeInt(pixel_x)),
+                    ("y2", SVGPropertyAttrs::SomeInt(pixel_y)),
+                    ("style", SVGPropertyAttrs::SomeString(style_str)),
+                ], None, Some(svg_row.y)));
+            } else if child_pixel_x < pixel_x {
+                child_lines.push(SVGRow::get_branching_curve(child_svg_row.x, child_svg_row.y, svg_row.x, style_str, svg_row.y));
             } else {
-                let d_str;
-                if child_pixel_x < pixel_x {
-                    let after_child_pixel_y = (child_svg_row.y + 1) * Y_SPACING + Y_OFFSET;
-                    let start_control_point_x = child_pixel_x + X_SPACING * 3 / 4;
-                    let end_control_point_y = after_child_pixel_y - Y_SPACING * 3 / 4;
-                    d_str = format!("M {child_pixel_x} {child_pixel_y} C {start_control_point_x} {child_pixel_y}, {pixel_x} {end_control_point_y}, {pixel_x} {after_child_pixel_y}");
-                } else {
-                    let start_control_point_y = before_pixel_y + Y_SPACING * 3 / 4;
-                    let end_control_point_x = pixel_x + X_SPACING * 3 / 4;
-                    d_str = format!("M {child_pixel_x} {before_pixel_y} C {child_pixel_x} {start_control_point_y}, {end_control_point_x} {pixel_y}, {pixel_x} {pixel_y}");
-                }
-                let path_attrs: HashMap<String, SVGPropertyAttrs> = HashMap::from([
-                    (String::from("d"), SVGPropertyAttrs::SomeString(d_str)),
-                    (String::from("style"), SVGPropertyAttrs::SomeString(style_str)),
-                ]);
-                child_lines.push(HashMap::from([
-                    (String::from("tag"), SVGProperty::SomeString(String::from("path"))),
-                    (String::from("attrs"), SVGProperty::SomeHashMap(path_attrs)),
-                    (String::from("row-y"), SVGProperty::SomeInt(row_y)),
-                ]));
+                child_lines.push(SVGRow::get_merging_curve(child_svg_row.x, svg_row.x, svg_row.y, style_str));
             }
         }
-        draw_properties.insert(String::from("child_lines"), DrawProperty::SomeVector(child_lines));
-
-        // Now get the circle
-        let circle_attrs: HashMap<String, SVGPropertyAttrs> = HashMap::from([
-            (String::from("cx"), SVGPropertyAttrs::SomeInt(pixel_x)),
-            (String::from("cy"), SVGPropertyAttrs::SomeInt(pixel_y)),
-            (String::from("r"), SVGPropertyAttrs::SomeInt(CIRCLE_RADIUS)),
-            (String::from("stroke"), SVGPropertyAttrs::SomeString(color.clone())),
-            (String::from("stroke-width"), SVGPropertyAttrs::SomeInt(1)),
-            (String::from("fill"), SVGPropertyAttrs::SomeString(color.clone())),
-        ]);
-        draw_properties.insert(String::from("circle"), DrawProperty::SomeHashMap(HashMap::from([
-            (String::from("tag"), SVGProperty::SomeString(String::from("circle"))),
-            (String::from("attrs"), SVGProperty::SomeHashMap(circle_attrs)),
-        ])));
-
-        let largest_occupied_x = main_table[self.y as usize].iter().max().unwrap_or(&0);
+        child_lines
+    }
+
+    pub fn get_draw_properties(svg_rows: &[SVGRow], row_index: usize, main_table: &[Vec<isize>]) -> RowDrawProperties {
+        let svg_row = &svg_rows[row_index];
+        let pixel_x = svg_row.x * X_SPACING + X_OFFSET;
+        let pixel_y = svg_row.y * Y_SPACING + Y_OFFSET;
+        let color = SVGRow::get_color_string(svg_row.x);
+
+        // Now get the circle. Commits where history stops are drawn hollow and dashed.
+        let mut circle_attrs = vec![
+            ("cx", SVGPropertyAttrs::SomeInt(pixel_x)),
+            ("cy", SVGPropertyAttrs::SomeInt(pixel_y)),
+            ("r", SVGPropertyAttrs::SomeInt(CIRCLE_RADIUS)),
+            ("stroke", SVGPropertyAttrs::SomeString(String::from(color))),
+        ];
+        if svg_row.boundary.is_some() {
+            circle_attrs.push(("stroke-width", SVGPropertyAttrs::SomeInt(LINE_STROKE_WIDTH)));
+            circle_attrs.push(("fill", SVGPropertyAttrs::SomeString(String::from("transparent"))));
+            circle_attrs.push(("stroke-dasharray", SVGPropertyAttrs::SomeString(String::from("2,2"))));
+        } else {
+            circle_attrs.push(("stroke-width", SVGPropertyAttrs::SomeInt(1)));
+            circle_attrs.push(("fill", SVGPropertyAttrs::SomeString(String::from(color))));
+        }
+        let circle = SVGElement::new("circle", circle_attrs, None, None);
+
+        let largest_occupied_x = main_table[svg_row.y as usize].iter().max().unwrap_or(&0);
 
         // Get summary text
-        let text_attrs: HashMap<String, SVGPropertyAttrs> = HashMap::from([
-            (String::from("x"), SVGPropertyAttrs::SomeInt((largest_occupied_x + 1) * X_SPACING + X_OFFSET)),
-            (String::from("y"), SVGPropertyAttrs::SomeInt(pixel_y + TEXT_Y_OFFSET)),
-            (String::from("fill"), SVGPropertyAttrs::SomeString(String::from("white"))),
-            (String::from("data-original-txt"), SVGPropertyAttrs::SomeString(self.summary.clone())),
-        ]);
-        draw_properties.insert(String::from("summary_text"), DrawProperty::SomeHashMap(HashMap::from([
-            (String::from("tag"), SVGProperty::SomeString(String::from("text"))),
-            (String::from("attrs"), SVGProperty::SomeHashMap(text_attrs)),
-            (String::from("textContent"), SVGProperty::SomeString(self.summary.clone())),
-        ])));
+        let summary_text = SVGElement::new("text", vec![
+            ("x", SVGPropertyAttrs::SomeInt((largest_occupied_x + 1) * X_SPACING + X_OFFSET)),
+            ("y", SVGPropertyAttrs::SomeInt(pixel_y + TEXT_Y_OFFSET)),
+            ("fill", SVGPropertyAttrs::SomeString(String::from("white"))),
+            ("data-original-txt", SVGPropertyAttrs::SomeString(svg_row.summary.clone())),
+        ], Some(svg_row.summary.clone()), None);
 
         // Get author name
-        let text_attrs: HashMap<String, SVGPropertyAttrs> = HashMap::from([
-            (String::from("x"), SVGPropertyAttrs::SomeInt(0)),
-            (String::from("y"), SVGPropertyAttrs::SomeInt(pixel_y + TEXT_Y_OFFSET)),
-            (String::from("fill"), SVGPropertyAttrs::SomeString(String::from("white"))),
-        ]);
-        draw_properties.insert(String::from("author_name"), DrawProperty::SomeHashMap(HashMap::from([
-            (String::from("tag"), SVGProperty::SomeString(String::from("text"))),
-            (String::from("attrs"), SVGProperty::SomeHashMap(text_attrs)),
-            (String::from("textContent"), SVGProperty::SomeString(self.author_name.clone())),
-        ])));
+        let author_name = SVGElement::new("text", vec![
+            ("x", SVGPropertyAttrs::SomeInt(0)),
+            ("y", SVGPropertyAttrs::SomeInt(pixel_y + TEXT_Y_OFFSET)),
+            ("fill", SVGPropertyAttrs::SomeString(String::from("white"))),
+        ], Some(svg_row.author_name.clone()), None);
 
         // Get author time
-        let text_attrs: HashMap<String, SVGPropertyAttrs> = HashMap::from([
-            (String::from("x"), SVGPropertyAttrs::SomeInt(0)),
-            (String::from("y"), SVGPropertyAttrs::SomeInt(pixel_y + TEXT_Y_OFFSET)),
-            (String::from("fill"), SVGPropertyAttrs::SomeString(String::from("white"))),
-        ]);
-        draw_properties.insert(String::from("author_time"), DrawProperty::SomeHashMap(HashMap::from([
-            (String::from("tag"), SVGProperty::SomeString(String::from("text"))),
-            (String::from("attrs"), SVGProperty::SomeHashMap(text_attrs)),
-            (String::from("textContent"), SVGProperty::SomeString(self.author_time.clone())),
-        ])));
+        let author_time = SVGElement::new("text", vec![
+            ("x", SVGPropertyAttrs::SomeInt(0)),
+            ("y", SVGPropertyAttrs::SomeInt(pixel_y + TEXT_Y_OFFSET)),
+            ("fill", SVGPropertyAttrs::SomeString(String::from("white"))),
+        ], Some(svg_row.author_time.clone()), None);
 
         // Get background rectangle
-        let rect_attrs: HashMap<String, SVGPropertyAttrs> = HashMap::from([
-            (String::from("class"), SVGPropertyAttrs::SomeString(String::from("svg-hoverable-row"))),
-            (String::from("x"), SVGPropertyAttrs::SomeInt(pixel_x)),
-            (String::from("y"), SVGPropertyAttrs::SomeInt(pixel_y + RECT_Y_OFFSET)),
-            (String::from("width"), SVGPropertyAttrs::SomeInt(0)),
-            (String::from("height"), SVGPropertyAttrs::SomeInt(RECT_HEIGHT)),
-            (String::from("style"), SVGPropertyAttrs::SomeString(String::from("fill:white;fill-opacity:0.1;"))),
-        ]);
-        draw_properties.insert(String::from("back_rect"), DrawProperty::SomeHashMap(HashMap::from([
-            (String::from("tag"), SVGProperty::SomeString(String::from("rect"))),
-            (String::from("attrs"), SVGProperty::SomeHashMap(rect_attrs)),
-        ])));
-
-        row_properties.insert(String::from("elements"), RowProperty::SomeHashMap(draw_properties));
-
-        row_properties
+        let back_rect = SVGElement::new("rect", vec![
+            ("class", SVGPropertyAttrs::SomeString(String::from("svg-hoverable-row"))),
+            ("x", SVGPropertyAttrs::SomeInt(pixel_x)),
+            ("y", SVGPropertyAttrs::SomeInt(pixel_y + RECT_Y_OFFSET)),
+            ("width", SVGPropertyAttrs::SomeInt(0)),
+            ("height", SVGPropertyAttrs::SomeInt(RECT_HEIGHT)),
+            ("style", SVGPropertyAttrs::SomeString(String::from("fill:white;fill-opacity:0.1;"))),
+        ], None, None);
+
+        RowDrawProperties {
+            sha: svg_row.sha.clone(),
+            pixel_y,
+            boundary: svg_row.boundary.clone(),
+            elements: RowElements {
+                child_lines: SVGRow::get_child_lines(svg_rows, row_index),
+                circle,
+                summary_text,
+                author_name,
+                author_time,
+                back_rect,
+            },
+        }
     }
 
-    pub fn get_branch_draw_properties(branches_and_tags: Vec<(String, String)>) -> Vec<Vec<HashMap<String, SVGProperty>>> {
-        // Get the branch text
-        let mut branch_and_tags: Vec<Vec<HashMap<String, SVGProperty>>> = vec![];
-        for (branch_name, branch_type) in branches_and_tags.clone().into_iter() {
-            let mut branch_and_tag_properties: Vec<HashMap<String, SVGProperty>> = vec![];
-            let text_attrs: HashMap<String, SVGPropertyAttrs> = HashMap::from([
-                (String::from("x"), SVGPropertyAttrs::SomeInt(0)),
-                (String::from("y"), SVGPropertyAttrs::SomeInt(TEXT_Y_OFFSET)),
-                (String::from("fill"), SVGPropertyAttrs::SomeString(String::from("white"))),
-            ]);
-            branch_and_tag_properties.push(HashMap::from([
-                (String::from("tag"), SVGProperty::SomeString(String::from("text"))),
-                (String::from("attrs"), SVGProperty::SomeHashMap(text_attrs)),
-                (String::from("textContent"), SVGProperty::SomeString(branch_name.clone())),
-            ]));
-
-            let mut branch_rect_color = "yellow";
-            if branch_type == "local" {
-                branch_rect_color = "red";
-            } else if branch_type == "remote" {
-                branch_rect_color = "green";
-            } else if branch_type == "tag" {
-                branch_rect_color = "grey";
-            }
+    // HEAD comes first, then local branches, remote branches and tags.
+    fn get_badge_priority(branch_name: &str, branch_type: &str) -> usize {
+        if branch_name.starts_with("* ") {
+            0
+        } else if branch_type == "local" {
+            1
+        } else if branch_type == "remote" {
+            2
+        } else {
+            3
+        }
+    }
 
-            let style_str = String::from("fill:") + branch_rect_color + ";fill-opacity:0.5;";
-            let rect_attrs: HashMap<String, SVGPropertyAttrs> = HashMap::from([
-                (String::from("x"), SVGPropertyAttrs::SomeInt(0)),
-                (String::from("y"), SVGPropertyAttrs::SomeInt(RECT_Y_OFFSET)),
-                (String::from("rx"), SVGPropertyAttrs::SomeInt(10)),
-                (String::from("ry"), SVGPropertyAttrs::SomeInt(10)),
-                (String::from("width"), SVGPropertyAttrs::SomeInt(0)),
-                (String::from("height"), SVGPropertyAttrs::SomeInt(RECT_HEIGHT)),
-                (String::from("style"), SVGPropertyAttrs::SomeString(style_str)),
-            ]);
-            branch_and_tag_properties.push(HashMap::from([
-                (String::from("tag"), SVGProperty::SomeString(String::from("rect"))),
-                (String::from("attrs"), SVGProperty::SomeHashMap(rect_attrs)),
-            ]));
-            branch_and_tags.push(branch_and_tag_properties);
+    fn get_branch_badge(branch_name: String, branch_type: &str, full_ref_name: Option<String>, more_count: Option<usize>) -> Vec<SVGElement> {
+        let mut text_attrs = vec![
+            ("x", SVGPropertyAttrs::SomeInt(0)),
+            ("y", SVGPropertyAttrs::SomeInt(TEXT_Y_OFFSET)),
+            ("fill", SVGPropertyAttrs::SomeString(String::from("white"))),
+        ];
+        let mut rect_attrs = vec![
+            ("x", SVGPropertyAttrs::SomeInt(0)),
+            ("y", SVGPropertyAttrs::SomeInt(RECT_Y_OFFSET)),
+            ("rx", SVGPropertyAttrs::SomeInt(10)),
+            ("ry", SVGPropertyAttrs::SomeInt(10)),
+            ("width", SVGPropertyAttrs::SomeInt(0)),
+            ("height", SVGPropertyAttrs::SomeInt(RECT_HEIGHT)),
+        ];
+
+        let mut branch_rect_color = "yellow";
+        if branch_type == "local" {
+            branch_rect_color = "red";
+        } else if branch_type == "remote" {
+            branch_rect_color = "green";
+        } else if branch_type == "tag" {
+            branch_rect_color = "grey";
+        } else if branch_type == "more" {
+            branch_rect_color = "purple";
+        }
+        let style_str = String::from("fill:") + branch_rect_color + ";fill-opacity:0.5;";
+        rect_attrs.push(("style", SVGPropertyAttrs::SomeString(style_str)));
+
+        if let Some(full_ref_name) = full_ref_name {
+            // The branch name has decorations like "* " on it, so the front-end should use this when sending the ref back.
+            text_attrs.push(("data-ref-name", SVGPropertyAttrs::SomeString(full_ref_name.clone())));
+            rect_attrs.push(("data-ref-name", SVGPropertyAttrs::SomeString(full_ref_name)));
+        }
+        if let Some(more_count) = more_count {
+            // The front-end asks for the rest of the badges with get-all-badges when this is clicked.
+            text_attrs.push(("data-more-count", SVGPropertyAttrs::SomeInt(more_count as isize)));
+            rect_attrs.push(("data-more-count", SVGPropertyAttrs::SomeInt(more_count as isize)));
+        }
+
+        vec![
+            SVGElement::new("text", text_attrs, Some(branch_name), None),
+            SVGElement::new("rect", rect_attrs, None, None),
+        ]
+    }
+
+    // Gets every badge of a commit in priority order.
+    pub fn get_all_branch_draw_properties(mut branches_and_tags: Vec<(String, String, String)>) -> Vec<Vec<SVGElement>> {
+        branches_and_tags.sort_by(|a, b| {
+            SVGRow::get_badge_priority(&a.0, &a.1).cmp(&SVGRow::get_badge_priority(&b.0, &b.1)).then(a.0.cmp(&b.0))
+        });
+        branches_and_tags.into_iter().map(|(branch_name, branch_type, full_ref_name)| {
+            SVGRow::get_branch_badge(branch_name, &branch_type, Some(full_ref_name), None)
+        }).collect()
+    }
+
+    // Gets the badges to draw next to a commit. When it has more than MAX_BRANCH_BADGES refs, the ones that don't fit are summed
+    // up in an "and N more" badge so one commit's decorations can't push the rest of the row out of view.
+    pub fn get_branch_draw_properties(branches_and_tags: Vec<(String, String, String)>) -> Vec<Vec<SVGElement>> {
+        let mut branch_and_tags = SVGRow::get_all_branch_draw_properties(branches_and_tags);
+        if branch_and_tags.len() > MAX_BRANCH_BADGES {
+            // The summary badge takes up one of the spots.
+            let more_count = branch_and_tags.len() - (MAX_BRANCH_BADGES - 1);
+            branch_and_tags.truncate(MAX_BRANCH_BADGES - 1);
+            branch_and_tags.push(SVGRow::get_branch_badge(format!("and {} more", more_count), "more", None, Some(more_count)));
         }
         branch_and_tags
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_oid(n: u8) -> Oid {
+        Oid::from_bytes(&[n; 20]).unwrap()
+    }
+
+    // Makes linked rows from top to bottom, each given as its commit and its parents' commits.
+    fn get_svg_rows(commits: &[(u8, &[u8])]) -> Vec<SVGRow> {
+        let oid_vec: Vec<Oid> = commits.iter().map(|(n, _)| get_oid(*n)).collect();
+        let parent_oids: Vec<Vec<Oid>> = commits.iter().map(|(_, parents)| parents.iter().map(|n| get_oid(*n)).collect()).collect();
+        let mut svg_rows: Vec<SVGRow> = oid_vec.iter().enumerate().map(|(y, oid)| {
+            SVGRow::new(*oid, String::from("author"), String::from("time"), format!("commit {}", y), None, y as isize)
+        }).collect();
+        SVGRow::set_parent_and_child_rows(&mut svg_rows, &oid_vec, &parent_oids);
+        svg_rows
+    }
+
+    fn get_positions(svg_rows: &[SVGRow]) -> Vec<(isize, Vec<isize>)> {
+        svg_rows.iter().map(|svg_row| (svg_row.x, svg_row.parent_lanes.clone())).collect()
+    }
+
+    // Every row's commit and every line running past it need their own x position, or they'd be drawn over each other.
+    fn assert_no_overlap(svg_rows: &[SVGRow], main_table: &[Vec<isize>]) {
+        let mut occupied: Vec<Vec<isize>> = svg_rows.iter().map(|svg_row| vec![svg_row.x]).collect();
+        for svg_row in svg_rows {
+            for (parent_index, lane) in svg_row.parent_rows.iter().zip(&svg_row.parent_lanes) {
+                for y in (svg_row.y + 1)..svg_rows[*parent_index].y {
+                    occupied[y as usize].push(*lane);
+                }
+            }
+        }
+        for (y, row_occupied) in occupied.iter().enumerate() {
+            for (i, x) in row_occupied.iter().enumerate() {
+                assert!(!row_occupied[i + 1..].contains(x), "x {} is used twice on row {}: {:?}", x, y, row_occupied);
+                assert!(main_table[y].contains(x), "x {} on row {} isn't in the table: {:?}", x, y, main_table[y]);
+            }
+        }
+    }
+
+    // Two branches off of 1: 2 -> 4 on one and 3 on the other.
+    const TWO_BRANCHES: [(u8, &[u8]); 4] = [(4, &[2]), (3, &[1]), (2, &[1]), (1, &[])];
+
+    #[test]
+    fn commits_on_one_branch_keep_the_other_lanes() {
+        let mut previous_svg_rows = get_svg_rows(&TWO_BRANCHES);
+        let previous_main_table = SVGRow::get_occupied_table(&mut previous_svg_rows, None);
+
+        let mut svg_rows = get_svg_rows(&[(6, &[5]), (5, &[3]), (4, &[2]), (3, &[1]), (2, &[1]), (1, &[])]);
+        let main_table = SVGRow::get_occupied_table(&mut svg_rows, Some((&previous_svg_rows, &previous_main_table)));
+
+        assert_eq!(get_positions(&svg_rows[2..]), get_positions(&previous_svg_rows));
+        assert_eq!(svg_rows[0].x, svg_rows[1].x);
+        assert_eq!(svg_rows[1].parent_lanes, vec![svg_rows[3].x]);
+        assert_no_overlap(&svg_rows, &main_table);
+    }
+
+    #[test]
+    fn rows_dropped_off_the_bottom_keep_their_lanes() {
+        let mut previous_svg_rows = get_svg_rows(&TWO_BRANCHES);
+        let previous_main_table = SVGRow::get_occupied_table(&mut previous_svg_rows, None);
+
+        // The commit limit cut off 1, so 3 and 2 have no parents in the rows anymore.
+        let mut svg_rows = get_svg_rows(&[(5, &[4]), (4, &[2]), (3, &[1]), (2, &[1])]);
+        assert_eq!(SVGRow::get_new_row_count(&svg_rows, &previous_svg_rows), Some(1));
+        let main_table = SVGRow::get_occupied_table(&mut svg_rows, Some((&previous_svg_rows, &previous_main_table)));
+
+        for (svg_row, previous_svg_row) in svg_rows[1..].iter().zip(&previous_svg_rows) {
+            assert_eq!(svg_row.x, previous_svg_row.x);
+        }
+        assert_eq!(svg_rows[1].parent_lanes, previous_svg_rows[0].parent_lanes);
+        assert!(svg_rows[2].parent_lanes.is_empty() && svg_rows[3].parent_lanes.is_empty());
+        assert_eq!(svg_rows[0].x, svg_rows[1].x);
+        assert_no_overlap(&svg_rows, &main_table);
+        for row_index in 0..svg_rows.len() {
+            SVGRow::get_draw_properties(&svg_rows, row_index, &main_table);
+        }
+    }
+
+    #[test]
+    fn rewritten_history_is_laid_out_from_scratch() {
+        let mut previous_svg_rows = get_svg_rows(&TWO_BRANCHES);
+        let previous_main_table = SVGRow::get_occupied_table(&mut previous_svg_rows, None);
+
+        // 4 is still at the top, but 3 was amended into 5, so the rows below it don't line up with the previous layout.
+        let commits: [(u8, &[u8]); 4] = [(4, &[2]), (5, &[1]), (2, &[1]), (1, &[])];
+        let mut svg_rows = get_svg_rows(&commits);
+        assert_eq!(SVGRow::get_new_row_count(&svg_rows, &previous_svg_rows), None);
+        // And a history where the previous top commit is gone entirely.
+        assert_eq!(SVGRow::get_new_row_count(&get_svg_rows(&[(6, &[1]), (1, &[])]), &previous_svg_rows), None);
+
+        let main_table = SVGRow::get_occupied_table(&mut svg_rows, Some((&previous_svg_rows, &previous_main_table)));
+        let mut fresh_svg_rows = get_svg_rows(&commits);
+        let fresh_main_table = SVGRow::get_occupied_table(&mut fresh_svg_rows, None);
+        assert_eq!(get_positions(&svg_rows), get_positions(&fresh_svg_rows));
+        assert_eq!(main_table, fresh_main_table);
+    }
+
+    #[test]
+    fn octopus_merge_lines_get_their_own_lanes() {
+        // 7 merges 4, 3 and 2, with 5 in between so every line to a parent runs past at least one row.
+        let mut svg_rows = get_svg_rows(&[(7, &[4, 3, 2]), (5, &[1]), (4, &[1]), (3, &[1]), (2, &[1]), (1, &[])]);
+        let main_table = SVGRow::get_occupied_table(&mut svg_rows, None);
+
+        let parent_lanes = &svg_rows[0].parent_lanes;
+        assert_eq!(parent_lanes.len(), 3);
+        for (i, lane) in parent_lanes.iter().enumerate() {
+            assert!(!parent_lanes[i + 1..].contains(lane), "parent lanes {:?} aren't distinct", parent_lanes);
+        }
+        assert_no_overlap(&svg_rows, &main_table);
+        for row_index in 0..svg_rows.len() {
+            SVGRow::get_draw_properties(&svg_rows, row_index, &main_table);
+        }
+    }
+
+    #[test]
+    fn second_root_is_drawn() {
+        // 2 and 1 are both roots, like after merging in an unrelated history.
+        let mut svg_rows = get_svg_rows(&[(4, &[3, 2]), (3, &[1]), (2, &[]), (1, &[])]);
+        let main_table = SVGRow::get_occupied_table(&mut svg_rows, None);
+        assert_no_overlap(&svg_rows, &main_table);
+
+        for row_index in 0..svg_rows.len() {
+            let draw_properties = SVGRow::get_draw_properties(&svg_rows, row_index, &main_table);
+            assert_eq!(draw_properties.sha, svg_rows[row_index].sha);
+            assert!(serde_json::to_value(&draw_properties).is_ok());
+        }
+        let root_draw_properties = SVGRow::get_draw_properties(&svg_rows, 2, &main_table);
+        assert!(svg_rows[2].parent_rows.is_empty());
+        assert!(!root_draw_properties.elements.child_lines.is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use anyhow::{bail, Result};
+use serde::Serialize;
+use tauri::{Manager, Window, Wry};
+
+pub const MAIN_WINDOW_LABEL: &str = "main-window";
+
+// Repo updates go to every window showing the repo, everything else is a reply that only the window that asked should get.
+const SHARED_EVENTS: [&str; 3] = ["update_all", "update_changes", "no-open-repo"];
+
+// The kinds of auxiliary windows that can be opened next to the main window, each one is the main page with a different layout.
+const WINDOW_KINDS: [&str; 3] = ["history", "changes", "conflicts"];
+
+struct BoundWindow {
+    // None for the main window of a repo.
+    kind: Option<String>,
+    manager_id: usize,
+}
+
+// Keeps track of which GitManager each window is bound to, so repo updates only go to the windows showing that repo.
+pub struct WindowRouter {
+    // Window label -> what the window shows.
+    windows: HashMap<String, BoundWindow>,
+    next_window_id: usize,
+    next_manager_id: usize,
+}
+
+impl WindowRouter {
+    pub fn new() -> Self {
+        Self {
+            windows: HashMap::new(),
+            next_window_id: 1,
+            next_manager_id: 0,
+        }
+    }
+
+    // Gets an id to bind windows to for a newly created GitManager.
+    pub fn add_manager(&mut self) -> usize {
+        let manager_id = self.next_manager_id;
+        self.next_manager_id += 1;
+        manager_id
+    }
+
+    pub fn bind_main_window(&mut self, label: &str, manager_id: usize) {
+        self.windows.insert(String::from(label), BoundWindow {
+            kind: None,
+            manager_id,
+        });
+    }
+
+    pub fn get_manager_id(&self, label: &str) -> Option<usize> {
+        self.windows.get(label).map(|w| w.manager_id)
+    }
+
+    // Gets the label of the open window of this kind for the manager, so it can be focused instead of opening another one.
+    pub fn get_window_label(&self, kind: &str, manager_id: usize) -> Option<String> {
+        self.windows.iter()
+            .find(|(_, w)| w.manager_id == manager_id && w.kind.as_deref() == Some(kind))
+            .map(|(label, _)| label.clone())
+    }
+
+    // Reserves a label for a new window of this kind and binds it to the manager.
+    pub fn subscribe(&mut self, kind: &str, manager_id: usize) -> Result<String> {
+        if !WINDOW_KINDS.contains(&kind) {
+            bail!("Unknown window kind: {}", kind);
+        }
+        let label = format!("{}-window-{}", kind, self.next_window_id);
+        self.next_window_id += 1;
+        self.windows.insert(label.clone(), BoundWindow {
+            kind: Some(String::from(kind)),
+            manager_id,
+        });
+        Ok(label)
+    }
+
+    pub fn unsubscribe(&mut self, label: &str) {
+        self.windows.remove(label);
+    }
+
+    pub fn get_bound_labels(&self, manager_id: usize) -> Vec<String> {
+        self.windows.iter().filter(|(_, w)| w.manager_id == manager_id).map(|(label, _)| label.clone()).collect()
+    }
+}
+
+pub trait RoutedEmit {
+    fn emit_routed<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()>;
+}
+
+impl RoutedEmit for Window<Wry> {
+    // Sends an event to the windows that should get it rather than broadcasting it to every window.
+    fn emit_routed<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()> {
+        if !SHARED_EVENTS.contains(&event) {
+            return self.emit_to(self.label(), event, payload);
+        }
+        // Listeners are registered per window, so the window an event came in on is the one bound to the command.
+        let labels = match self.try_state::<Mutex<WindowRouter>>() {
+            Some(router) => {
+                let router = router.lock().unwrap();
+                match router.get_manager_id(self.label()) {
+                    Some(manager_id) => router.get_bound_labels(manager_id),
+                    None => vec![String::from(self.label())],
+                }
+            },
+            None => vec![String::from(self.label())],
+        };
+        for label in labels {
+            // A window that was just closed may not have been unsubscribed yet.
+            if self.get_window(&label).is_some() {
+                self.emit_to(&label, event, payload.clone())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn get_window_kind(payload: &str) -> Result<String> {
+    let payload_map: HashMap<String, String> = serde_json::from_str(payload)?;
+    let kind = match payload_map.get("kind") {
+        Some(k) => k,
+        None => bail!("kind not included in payload from front-end."),
+    };
+    Ok(kind.clone())
+}
@@ -1,9 +1,10 @@
 import {getVersion} from '@tauri-apps/api/app';
-import {writeText} from "@tauri-apps/api/clipboard";
-import {open} from '@tauri-apps/api/dialog';
+import {readText, writeText} from "@tauri-apps/api/clipboard";
+import {open, save} from '@tauri-apps/api/dialog';
 import {emit, listen} from "@tauri-apps/api/event";
 import {homeDir} from '@tauri-apps/api/path';
 import {relaunch} from '@tauri-apps/api/process';
+import {open as openUrl} from '@tauri-apps/api/shell';
 import {checkUpdate, installUpdate} from '@tauri-apps/api/updater';
 import {SVGManager} from "./svg_manager";
 import hljs from "highlight.js";
@@ -25,8 +26,24 @@ class Main {
         this.oldSelectedSHA = '';
         this.selectedCommitInfoFilePath = '';
         this.selectedFileChangedInfoFilePath = '';
+        this.filesChangedInfo = null;
+        this.lfsLocks = {};
+        this.remoteInfoList = [];
         this.commitFileDiffTableScrollTop = 0;
         this.fileDiffTableScrollTop = 0;
+        this.diffContextLines = 3;
+        // The last file-diff payload, so the diff can be shown again when the context lines change.
+        this.lastFileDiffRequest = null;
+        // Collapsed (generated) files whose diffs were asked for, so they stay expanded while the app is open.
+        this.shownCollapsedFiles = new Set();
+        // Set for the extra windows opened from the View menu, which only show part of the main window.
+        this.layout = new URLSearchParams(window.location.search).get('layout');
+        this.saveRepoSessionTimeout = null;
+        this.branchFilterTimeout = null;
+        // The unfiltered branch tree, so clearing the branch filter doesn't need the back-end.
+        this.branchInfoList = null;
+        // Set to commit or commit-push while waiting on validate-commit.
+        this.pendingCommitEvent = null;
     }
 
     run() {
@@ -43,6 +60,8 @@ class Main {
 
         self.showWelcomeView();
 
+        self.applyLayout();
+
         $('#summaryTxtCounter').text(self.SUMMARY_CHAR_SOFT_LIMIT.toString());
 
         checkUpdate().then(async function(updateResult) {
@@ -126,15 +145,468 @@ class Main {
             self.showCommitInfo(ev.payload);
         }).then();
 
+        listen("show-graph-rows", ev => {
+            self.svgManager.addGraphRows(ev.payload);
+        }).then();
+
+        listen("show-all-badges", ev => {
+            self.svgManager.showAllBadgesMenu(ev.payload);
+        }).then();
+
+        listen("show-filtered-branches", ev => {
+            // Ignore results for a filter that has since been changed.
+            if (ev.payload['pattern'] === $('#branchFilterTxt').val().trim()) {
+                self.updateBranchInfo(ev.payload['branch_info_list']);
+            }
+        }).then();
+
+        listen("show-tag-details", ev => {
+            const tagDetails = ev.payload;
+            self.svgManager.scrollToCommit(tagDetails['target_sha']);
+            self.svgManager.selectRowViaSha(tagDetails['target_sha']);
+            if (tagDetails['time'] !== null) {
+                const $tagItem = $('#tags .inner-branch-item').filter(function() {
+                    return $(this).data('full-branch-name') === tagDetails['full_tag_name'];
+                });
+                $tagItem.children('span').first().attr('title', (tagDetails['is_annotated'] ? 'Tagged ' : 'Committed ') + new Date(tagDetails['time'] * 1000).toLocaleString());
+            }
+        }).then();
+
+        listen("show-tag-info", ev => {
+            const tagInfo = ev.payload,
+                $tagInfoBody = $('#tagInfoBody');
+            $tagInfoBody.empty();
+            $tagInfoBody.append($('<h5></h5>').text(tagInfo['name']));
+            $tagInfoBody.append($('<p></p>').text('Target: ' + tagInfo['target_sha']));
+            if (tagInfo['is_lightweight']) {
+                $tagInfoBody.append($('<p class="text-grey"></p>').text('Lightweight tag'));
+            } else {
+                if (tagInfo['tagger_name'] !== null) {
+                    const formattedTaggerTime = new Date(tagInfo['tagger_time'] * 1000).toLocaleString();
+                    $tagInfoBody.append($('<p></p>').text('Tagger: ' + tagInfo['tagger_name'] + ' <' + tagInfo['tagger_email'] + '> - ' + formattedTaggerTime));
+                }
+                if (tagInfo['message'] !== null) {
+                    $tagInfoBody.append($('<p style="white-space: pre-wrap;"></p>').text(tagInfo['message']));
+                }
+            }
+            let signatureText = 'Signature: ' + tagInfo['signature_status'];
+            if (tagInfo['signature_details'] !== null) {
+                signatureText += ' (' + tagInfo['signature_details'] + ')';
+            }
+            $tagInfoBody.append($('<p></p>').text(signatureText));
+            $('#tagInfoModal').modal('show');
+        }).then();
+
+        listen("show-branch-remotes", ev => {
+            const branchRemotesInfo = ev.payload,
+                $branchRemoteSelect = $('#branchRemoteSelect'),
+                $branchPushRemoteSelect = $('#branchPushRemoteSelect');
+            $('#branchRemotesShorthand').text(branchRemotesInfo['branch_shorthand']);
+            $branchRemoteSelect.empty();
+            $branchPushRemoteSelect.empty();
+            $branchRemoteSelect.append($('<option value="">(None)</option>'));
+            $branchPushRemoteSelect.append($('<option value="">(Same as pull)</option>'));
+            branchRemotesInfo['remotes'].forEach((remoteName) => {
+                $branchRemoteSelect.append($('<option></option>').val(remoteName).text(remoteName));
+                $branchPushRemoteSelect.append($('<option></option>').val(remoteName).text(remoteName));
+            });
+            $branchRemoteSelect.val(branchRemotesInfo['remote'] === null ? '' : branchRemotesInfo['remote']);
+            $branchPushRemoteSelect.val(branchRemotesInfo['push_remote'] === null ? '' : branchRemotesInfo['push_remote']);
+            $('#branchRemotesModal').modal('show');
+        }).then();
+
+        listen("show-remote-head", ev => {
+            const remoteHeadInfo = ev.payload;
+            $('#remoteHeadRemoteName').text(remoteHeadInfo['remote_name']);
+            $('#remoteHeadLocal').text('Local HEAD: ' + (remoteHeadInfo['local_head'] === null ? '(Not set)' : remoteHeadInfo['local_head']));
+            $('#remoteHeadDefault').text("Remote's default: " + (remoteHeadInfo['remote_default_branch'] === null ? '(Unknown)' : remoteHeadInfo['remote_default_branch']));
+            $('#remoteHeadModal').modal('show');
+        }).then();
+
         listen("update_all", ev => {
             self.showRepoView();
             self.updateAll(ev.payload);
             self.removeProcessCount();
         }).then();
 
+        // The back-end keeps running when the webview reloads, so ask it for everything needed to pick up where it left off.
+        listen("full-state", ev => {
+            const fullState = ev.payload;
+            if (fullState['repo_info'] === null) {
+                self.showWelcomeView();
+            } else {
+                const repoSession = self.layout === null ? fullState['repo_session'] : null;
+                if (repoSession !== null) {
+                    self.svgManager.selectedSHA = repoSession['selected_sha'] ?? '';
+                }
+                self.showRepoView();
+                self.updateAll(fullState['repo_info']);
+                if (repoSession !== null) {
+                    self.restoreRepoSession(repoSession);
+                }
+            }
+            if (fullState['repo_capabilities'] !== null && fullState['repo_capabilities']['problems'].length > 0) {
+                self.showError('This repository has problems, so some features are disabled:\n' + fullState['repo_capabilities']['problems'].join('\n'));
+            }
+            self.removeProcessCount();
+        }).then(() => {
+            self.addProcessCount();
+            emit("get-full-state", "").then();
+        });
+
         listen("update_changes", ev => {
             self.showRepoView();
-            self.updateFilesChangedInfo(ev.payload);
+            if (ev.payload['updated_paths'] !== undefined) {
+                // Without the full lists to update, wait for the next full update.
+                if (self.filesChangedInfo !== null) {
+                    self.updateFilesChangedInfo(self.applyFilesChangedUpdate(ev.payload));
+                }
+            } else {
+                self.updateFilesChangedInfo(ev.payload);
+            }
+        }).then();
+
+        listen("untracked-expanded", ev => {
+            // Swap the collapsed directory for its contents, it collapses again on the next refresh.
+            if (self.filesChangedInfo !== null) {
+                const unstagedFiles = self.filesChangedInfo['unstaged_files'],
+                    dirIndex = unstagedFiles.findIndex((file) => file['path'] === ev.payload['path']);
+                if (dirIndex !== -1) {
+                    unstagedFiles.splice(dirIndex, 1, ...ev.payload['files']);
+                    self.updateFilesChangedInfo(self.filesChangedInfo);
+                }
+            }
+            self.removeProcessCount();
+        }).then();
+
+        listen("get-export-graph", async function(ev) {
+            await self.doExportGraph();
+        }).then();
+
+        listen("show-export-log", ev => {
+            $('#exportLogModal').modal('show');
+        }).then();
+
+        listen("show-blame", ev => {
+            const blameInfo = ev.payload,
+                $blameTableBody = $('#blameTableBody');
+            $blameTableBody.empty();
+            $('#blameModalLabel').text('Blame: ' + blameInfo['file_path']);
+            $('#blameModal').data('file-path', blameInfo['file_path']).data('sha', blameInfo['sha']);
+            blameInfo['lines'].forEach((line) => {
+                const $row = $('<tr></tr>');
+                $row.attr('title', line['summary']);
+                $row.append($('<td></td>').text(line['sha'].substring(0, 8)));
+                $row.append($('<td></td>').text(line['author_name']));
+                $row.append($('<td></td>').text(line['line_number']));
+                $row.append($('<td><pre class="m-0"></pre></td>').find('pre').text(line['content']).end());
+                $blameTableBody.append($row);
+            });
+            self.removeProcessCount();
+            $('#blameModal').modal('show');
+        }).then();
+
+        listen("show-file-history", ev => {
+            const $fileHistoryTableBody = $('#fileHistoryTableBody');
+            $fileHistoryTableBody.empty();
+            ev.payload.forEach((entry) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(entry['sha'].substring(0, 8)));
+                $row.append($('<td></td>').text(entry['author_name']));
+                $row.append($('<td></td>').text(entry['summary']));
+                if (entry['old_path'] !== null) {
+                    $row.append($('<td></td>').text(entry['status'] + ' from ' + entry['old_path']));
+                } else {
+                    $row.append($('<td></td>').text(entry['status'] + ' ' + entry['path']));
+                }
+                const $restoreBtn = $('<button type="button" class="btn btn-outline-primary btn-sm">Restore</button>');
+                $restoreBtn.click(() => {
+                    const $checkoutFileFromModal = $('#checkoutFileFromModal');
+                    $('#checkoutFileFromDescription').text('Restore ' + entry['path'] + ' to how it was in ' + entry['sha'].substring(0, 8) + '.');
+                    $checkoutFileFromModal.data('sha', entry['sha']);
+                    $checkoutFileFromModal.data('path', entry['path']);
+                    $('#fileHistoryModal').modal('hide');
+                    $checkoutFileFromModal.modal('show');
+                });
+                $row.append($('<td></td>').append($restoreBtn));
+                $fileHistoryTableBody.append($row);
+            });
+            self.removeProcessCount();
+            $('#fileHistoryModal').modal('show');
+        }).then();
+
+        listen("show-commit-operation-results", ev => {
+            const $commitOperationResultsTableBody = $('#commitOperationResultsTableBody');
+            $commitOperationResultsTableBody.empty();
+            ev.payload.forEach((result) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(result['sha'].substring(0, 8)));
+                $row.append($('<td></td>').text(result['status']));
+                $row.append($('<td></td>').text(result['message']));
+                $commitOperationResultsTableBody.append($row);
+            });
+            self.removeProcessCount();
+            $('#commitOperationResultsModal').modal('show');
+        }).then();
+
+        listen("show-sync-results", ev => {
+            const $syncResultsTableBody = $('#syncResultsTableBody');
+            $syncResultsTableBody.empty();
+            // Only list the branches that changed or need attention.
+            ev.payload.filter((result) => result['status'] !== 'up_to_date' && result['status'] !== 'no_upstream').forEach((result) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(result['branch_shorthand']));
+                $row.append($('<td></td>').text(result['status']));
+                $row.append($('<td></td>').text(result['message']));
+                $syncResultsTableBody.append($row);
+            });
+            if ($syncResultsTableBody.children().length === 0) {
+                $syncResultsTableBody.append($('<tr><td>Everything is up to date.</td></tr>'));
+            }
+            self.removeProcessCount();
+            $('#syncResultsModal').modal('show');
+        }).then();
+
+        listen("show-operation-history", ev => {
+            const $operationHistoryTableBody = $('#operationHistoryTableBody');
+            $operationHistoryTableBody.empty();
+            ev.payload.forEach((entry) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(new Date(entry['timestamp'] * 1000).toLocaleString()));
+                $row.append($('<td></td>').text(entry['operation']));
+                const $refChanges = $('<td class="font-monospace"></td>');
+                // HEAD is recorded as the branch it's on unless it's detached, so only shorten SHAs.
+                const formatTarget = (target) => target === null ? '(none)' : (target.startsWith('refs/') ? target : target.substring(0, 7));
+                entry['ref_changes'].forEach((refChange) => {
+                    $refChanges.append($('<div></div>').text(refChange['ref_name'] + ': ' + formatTarget(refChange['before']) + ' -> ' + formatTarget(refChange['after'])));
+                });
+                $row.append($refChanges);
+                if (entry['error'] === null) {
+                    $row.append($('<td></td>'));
+                } else {
+                    $row.append($('<td class="text-danger"></td>').text(entry['error']));
+                }
+                $operationHistoryTableBody.append($row);
+            });
+            if ($operationHistoryTableBody.children().length === 0) {
+                $operationHistoryTableBody.append($('<tr><td>No operations recorded for this repo yet.</td></tr>'));
+            }
+            $('#operationHistoryModal').modal('show');
+        }).then();
+
+        listen("get-lfs-locks", ev => {
+            self.addProcessCount();
+            emit("lfs-list-locks").then();
+        }).then();
+
+        listen("show-lfs-locks", ev => {
+            const $lfsLocksTableBody = $('#lfsLocksTableBody');
+            $lfsLocksTableBody.empty();
+            ev.payload.forEach((lfsLock) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(lfsLock['path']));
+                $row.append($('<td></td>').text(lfsLock['owner']));
+                $row.append($('<td></td>').text(new Date(lfsLock['locked_at']).toLocaleString()));
+                const $unlockBtn = $('<button type="button" class="btn btn-primary btn-sm">Unlock</button>'),
+                    $forceUnlockBtn = $('<button type="button" class="btn btn-danger btn-sm">Force Unlock</button>');
+                $unlockBtn.click(() => {
+                    emit("lfs-unlock", {path: lfsLock['path'], force: 'false'}).then();
+                    $('#lfsLocksModal').modal('hide');
+                });
+                // Breaks someone else's lock, so it's kept separate from the normal unlock.
+                $forceUnlockBtn.click(() => {
+                    emit("lfs-unlock", {path: lfsLock['path'], force: 'true'}).then();
+                    $('#lfsLocksModal').modal('hide');
+                });
+                $row.append($('<td></td>').append($unlockBtn).append(' ').append($forceUnlockBtn));
+                $lfsLocksTableBody.append($row);
+            });
+            if ($lfsLocksTableBody.children().length === 0) {
+                $lfsLocksTableBody.append($('<tr><td>No files are locked.</td></tr>'));
+            }
+            self.removeProcessCount();
+            $('#lfsLocksModal').modal('show');
+        }).then();
+
+        listen("get-issue-links", ev => {
+            self.addProcessCount();
+            emit("get-issue-link-rules").then();
+        }).then();
+
+        listen("show-issue-link-rules", ev => {
+            $('#issueLinkRulesTableBody').empty();
+            ev.payload.forEach((rule) => {
+                self.addIssueLinkRuleRow(rule['pattern'], rule['url_template']);
+            });
+            self.removeProcessCount();
+            $('#issueLinksModal').modal('show');
+        }).then();
+
+        listen("get-ignored-files", ev => {
+            self.addProcessCount();
+            $('#ignoredFilesDirectoryTxt').val('');
+            emit("list-ignored-files", '').then();
+        }).then();
+
+        listen("show-ignored-files", ev => {
+            const $ignoredFilesTableBody = $('#ignoredFilesTableBody');
+            $ignoredFilesTableBody.empty();
+            ev.payload.forEach((ignoredFile) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(ignoredFile['path']));
+                if (ignoredFile['source'] !== '') {
+                    $row.append($('<td></td>').text(ignoredFile['source'] + ':' + ignoredFile['line_number']));
+                    $row.append($('<td></td>').append($('<code></code>').text(ignoredFile['pattern'])));
+                } else {
+                    $row.append($('<td colspan="2"></td>').text('No matching rule found.'));
+                }
+                const $forceAddBtn = $('<button type="button" class="btn btn-primary btn-sm">Force Add</button>');
+                $forceAddBtn.click(() => {
+                    self.addProcessCount();
+                    emit("force-add", ignoredFile['path']).then();
+                    $('#ignoredFilesModal').modal('hide');
+                });
+                $row.append($('<td></td>').append($forceAddBtn));
+                $ignoredFilesTableBody.append($row);
+            });
+            if ($ignoredFilesTableBody.children().length === 0) {
+                $ignoredFilesTableBody.append($('<tr><td>No ignored files.</td></tr>'));
+            }
+            self.removeProcessCount();
+            $('#ignoredFilesModal').modal('show');
+        }).then();
+
+        listen("get-snapshots", ev => {
+            self.addProcessCount();
+            emit("list-snapshots").then();
+        }).then();
+
+        listen("get-hooks", ev => {
+            self.addProcessCount();
+            emit("list-hooks").then();
+        }).then();
+
+        listen("show-hooks", ev => {
+            const hooksInfo = ev.payload,
+                $hooksTableBody = $('#hooksTableBody'),
+                $sampleHooksTableBody = $('#sampleHooksTableBody'),
+                $knownHooksList = $('#knownHooksList');
+            $('#hooksDirTxt').text(hooksInfo['hooks_dir']);
+            const hookManagers = self.generalInfo['hook_managers'] ?? '';
+            if (hookManagers === '') {
+                $('#hookManagersTxt').hide();
+            } else {
+                $('#hookManagersTxt').text('This repo uses ' + hookManagers.split(',').join(', ') + ', whose hooks run when committing from here once they\'re installed.').show();
+            }
+            $hooksTableBody.empty();
+            $knownHooksList.empty();
+            hooksInfo['hooks'].forEach((hook) => {
+                const $row = $('<tr></tr>');
+                const $name = $('<td></td>').text(hook['name']);
+                if (hook['is_sample']) {
+                    $name.append($('<span class="text-grey"> (sample, not run by git)</span>'));
+                } else if (!hook['is_known']) {
+                    $name.append($('<span class="text-grey"> (not a hook git knows about)</span>'));
+                }
+                $row.append($name);
+                const $executableCheckBox = $('<input class="form-check-input" type="checkbox">').prop('checked', hook['is_executable']);
+                $executableCheckBox.change(() => {
+                    self.addProcessCount();
+                    emit("set-hook-executable", {name: hook['name'], executable: $executableCheckBox.is(':checked').toString()}).then();
+                });
+                $row.append($('<td class="text-nowrap"></td>').append($executableCheckBox, $('<span> Executable</span>')));
+                const $editBtn = $('<button type="button" class="btn btn-outline-primary btn-sm">Edit</button>');
+                $editBtn.click(() => {
+                    self.addProcessCount();
+                    emit("get-hook", hook['name']).then();
+                });
+                $row.append($('<td></td>').append($editBtn));
+                $hooksTableBody.append($row);
+            });
+            hooksInfo['known_hooks'].forEach((hookName) => {
+                $knownHooksList.append($('<option></option>').val(hookName));
+            });
+            if ($hooksTableBody.children().length === 0) {
+                $hooksTableBody.append($('<tr><td>No hooks yet.</td></tr>'));
+            }
+            $sampleHooksTableBody.empty();
+            hooksInfo['samples'].forEach((sample) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(sample['hook_name']));
+                $row.append($('<td></td>').text(sample['description']));
+                const $installBtn = $('<button type="button" class="btn btn-outline-primary btn-sm">Install</button>');
+                $installBtn.click(() => {
+                    self.addProcessCount();
+                    emit("install-sample-hook", {sample_id: sample['id']}).then();
+                });
+                $row.append($('<td></td>').append($installBtn));
+                $sampleHooksTableBody.append($row);
+            });
+            self.removeProcessCount();
+            $('#hooksModal').modal('show');
+        }).then();
+
+        listen("show-hook", ev => {
+            $('#hookNameTxt').val(ev.payload['name']);
+            $('#hookContentsTxt').val(ev.payload['contents']);
+            self.removeProcessCount();
+        }).then();
+
+        listen("show-snapshots", ev => {
+            const $snapshotsTableBody = $('#snapshotsTableBody');
+            $snapshotsTableBody.empty();
+            ev.payload.forEach((snapshot) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(new Date(snapshot['time'] * 1000).toLocaleString()));
+                $row.append($('<td></td>').text(snapshot['message']));
+                const $restoreBtn = $('<button type="button" class="btn btn-primary btn-sm">Restore</button>');
+                // The current state gets snapshotted before restoring, so this doesn't need a confirmation.
+                $restoreBtn.click(() => {
+                    self.addProcessCount();
+                    emit("restore-snapshot", snapshot['sha']).then();
+                    $('#snapshotsModal').modal('hide');
+                });
+                $row.append($('<td></td>').append($restoreBtn));
+                $snapshotsTableBody.append($row);
+            });
+            if ($snapshotsTableBody.children().length === 0) {
+                $snapshotsTableBody.append($('<tr><td>No snapshots yet.</td></tr>'));
+            }
+            self.removeProcessCount();
+            $('#snapshotsModal').modal('show');
+        }).then();
+
+        listen("submodule-progress", ev => {
+            const submoduleProgress = ev.payload;
+            let progressText = 'Updating submodule ' + submoduleProgress['path'];
+            if (submoduleProgress['total_objects'] > 0) {
+                progressText += ' (' + submoduleProgress['received_objects'] + '/' + submoduleProgress['total_objects'] + ' objects)';
+            }
+            $('#submoduleProgressText').text(progressText);
+        }).then();
+
+        listen("show-submodule-results", ev => {
+            const $submoduleResultsTableBody = $('#submoduleResultsTableBody');
+            $submoduleResultsTableBody.empty();
+            ev.payload.forEach((result) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(result['path']));
+                $row.append($('<td></td>').text(result['status']));
+                if (result['status'] === 'moved') {
+                    const oldSHA = result['old_sha'] === null ? '(none)' : result['old_sha'].substring(0, 8),
+                        newSHA = result['new_sha'] === null ? '(none)' : result['new_sha'].substring(0, 8);
+                    $row.append($('<td></td>').text(oldSHA + ' -> ' + newSHA));
+                } else {
+                    $row.append($('<td></td>').text(result['message']));
+                }
+                $submoduleResultsTableBody.append($row);
+            });
+            $('#submoduleProgressText').text('');
+            $('#submoduleResultsModal').modal('show');
+        }).then();
+
+        listen("copy-shas-text", ev => {
+            writeText(ev.payload).then();
         }).then();
 
         listen("get-init", async function(ev) {
@@ -145,6 +617,50 @@ class Main {
             await self.doOpen();
         }).then();
 
+        listen("get-push", ev => {
+            $('#openPushModalBtn').click();
+        }).then();
+
+        listen("get-add-submodule", ev => {
+            $('#addSubmoduleURLTxt').val('');
+            $('#addSubmodulePathTxt').val('');
+            $('#addSubmoduleModal').modal('show');
+        }).then();
+
+        listen("get-remove-submodule", ev => {
+            $('#removeSubmodulePathTxt').val('');
+            $('#removeSubmoduleMessage').text('');
+            $('#forceRemoveSubmoduleCheckBox').prop('checked', false);
+            $('#removeSubmoduleModal').modal('show');
+        }).then();
+
+        listen("get-subtree", ev => {
+            $('#subtreeModal').modal('show');
+        }).then();
+
+        listen("get-apply-patch-text", ev => {
+            $('#patchTextTxt').val('');
+            $('#patchHunkChecksTableBody').empty();
+            $('#applyPatchTextModal').modal('show');
+        }).then();
+
+        listen("show-patch-hunk-checks", ev => {
+            const $patchHunkChecksTableBody = $('#patchHunkChecksTableBody');
+            $patchHunkChecksTableBody.empty();
+            ev.payload.forEach((hunkCheck) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(hunkCheck['file_path']));
+                $row.append($('<td class="font-monospace"></td>').text(hunkCheck['hunk_header']));
+                if (hunkCheck['applies']) {
+                    $row.append($('<td class="text-success"></td>').text('Applies'));
+                } else {
+                    $row.append($('<td class="text-danger"></td>').text(hunkCheck['message']));
+                }
+                $patchHunkChecksTableBody.append($row);
+            });
+            self.removeProcessCount();
+        }).then();
+
         listen("get-clone", ev => {
             $('#cloneModal').modal('show');
         }).then();
@@ -155,14 +671,41 @@ class Main {
                 sshPrivateKeyDefaultPath = homePath + ".ssh/id_ed25519";
             $('#publicKeyPathTxt').val(sshPubKeyDefaultPath);
             $('#privateKeyPathTxt').val(sshPrivateKeyDefaultPath);
+            $('#generatedPublicKeyTxt').val('');
+            $('#generatedPublicKeyContainer').hide();
             $('#credentialsModal').modal('show');
         }).then();
 
+        listen("show-generated-ssh-key", ev => {
+            $('#generatedPublicKeyTxt').val(ev.payload);
+            $('#generatedPublicKeyContainer').show();
+        }).then();
+
         listen("show-preferences", ev => {
             const $limitCommitsCheckBox = $('#limitCommitsCheckBox'),
                 $commitCountNumber = $('#commitCountNumber');
             $limitCommitsCheckBox.prop('checked', ev.payload['limit_commits']);
             $commitCountNumber.val(ev.payload['commit_count']);
+            // Older config files may not have a max blob size yet.
+            $('#maxBlobSizeNumber').val(Math.round((ev.payload['max_blob_size'] ?? 10 * 1024 * 1024) / (1024 * 1024)));
+            $('#autoFetchMinutesNumber').val(ev.payload['auto_fetch_minutes'] ?? 0);
+            $('#renameThresholdNumber').val(ev.payload['rename_threshold'] ?? 50);
+            $('#copyThresholdNumber').val(ev.payload['copy_threshold'] ?? 50);
+            $('#recurseSubmodulesCheckBox').prop('checked', ev.payload['recurse_submodules'] ?? false);
+            $('#snapshotLimitNumber').val(ev.payload['snapshot_limit'] ?? 20);
+            $('#ignoreEolChangesCheckBox').prop('checked', ev.payload['ignore_eol_changes'] ?? false);
+            $('#diffAlgorithmSelect').val(ev.payload['diff_algorithm'] ?? 'myers');
+            $('#showFunctionNamesCheckBox').prop('checked', ev.payload['show_function_names'] ?? true);
+            $('#branchSortModeSelect').val(ev.payload['branch_sort_mode'] ?? 'alphabetical');
+            $('#interhunkLinesNumber').val(ev.payload['interhunk_lines'] ?? 0);
+            $('#collapseAttributesTxt').val((ev.payload['collapse_attributes'] ?? ['linguist-generated']).join('\n'));
+            $('#prefillCommitFromBranchCheckBox').prop('checked', ev.payload['prefill_commit_from_branch'] ?? false);
+            $('#branchPrefillPatternTxt').val(ev.payload['branch_prefill_pattern'] ?? '([A-Z][A-Z0-9]+-[0-9]+)');
+            $('#branchPrefillTemplateTxt').val(ev.payload['branch_prefill_template'] ?? '$1: ');
+            $('#largeFileWarningSizeNumber').val(Math.round((ev.payload['large_file_warning_size'] ?? 50 * 1024 * 1024) / (1024 * 1024)));
+            $('#forbiddenPathsTxt').val((ev.payload['forbidden_paths'] ?? []).join('\n'));
+            $('#extraPathEntriesTxt').val((ev.payload['extra_path_entries'] ?? []).join('\n'));
+            $('#extraEnvVarsTxt').val(Object.entries(ev.payload['extra_env_vars'] ?? {}).map(([name, value]) => name + '=' + value).join('\n'));
             if ($limitCommitsCheckBox.is(':checked')) {
                 $commitCountNumber.prop('disabled', false);
             } else {
@@ -171,8 +714,292 @@ class Main {
             $('#preferencesModal').modal('show');
         }).then();
 
+        listen("get-revert-mainline", ev => {
+            const $revertMainlineSelect = $('#revertMainlineSelect');
+            $revertMainlineSelect.empty();
+            ev.payload['parents'].forEach((parent, i) => {
+                const $option = $('<option></option>');
+                $option.val((i + 1).toString());
+                $option.text(parent['sha'].substring(0, 7) + ' - ' + parent['summary']);
+                $revertMainlineSelect.append($option);
+            });
+            $('#revertSha').text(ev.payload['sha']);
+            $('#commitRevertCheckBox').prop('checked', ev.payload['is_committing']);
+            $('#revertMainlineContainer').show();
+            $('#revertModal').modal('show');
+        }).then();
+
+        listen("show-stash-diff", ev => {
+            const stashDiff = ev.payload,
+                $stashDiffFiles = $('#stashDiffFiles');
+            $('#stashDiffModalLabel').text('stash@{' + stashDiff['index'] + '}');
+            $('#stashDiffMessage').text(stashDiff['message']);
+            $('#stashDiffApplyBtn').attr('data-stash-index', stashDiff['index']);
+            $stashDiffFiles.empty();
+            $('#stashFileDiffTable').empty();
+            stashDiff['changed_files'].forEach((file, i) => {
+                const $fileBtn = $('<button type="button" class="btn btn-outline-dark btn-sm rounded-0"></button>');
+                $fileBtn.text(file['display_path']);
+                self.prependFileIcon($fileBtn, file['status']);
+                $fileBtn.click(() => {
+                    $stashDiffFiles.find('.active').removeClass('active');
+                    $fileBtn.addClass('active');
+                    self.showFileDiffUnlessCollapsed(file, 'stash', () => self.showFileDiff(stashDiff['file_infos'][i], false));
+                });
+                $stashDiffFiles.append($fileBtn);
+            });
+            $stashDiffFiles.children().first().click();
+            $('#stashDiffModal').modal('show');
+        }).then();
+
+        listen("show-workdir-diff", ev => {
+            const workdirDiff = ev.payload,
+                $workdirDiffFiles = $('#workdirDiffFiles');
+            $('#workdirDiffCommit').text('Changes in the working directory since ' + workdirDiff['sha'].substring(0, 8) + ' (' + workdirDiff['summary'] + ')');
+            $workdirDiffFiles.empty();
+            $('#workdirFileDiffTable').empty();
+            if (workdirDiff['changed_files'].length === 0) {
+                $workdirDiffFiles.append($('<p class="text-grey"></p>').text('The working directory matches this commit.'));
+            }
+            workdirDiff['changed_files'].forEach((file) => {
+                const $fileBtn = $('<button type="button" class="btn btn-outline-dark btn-sm rounded-0"></button>');
+                $fileBtn.text(file['display_path']);
+                self.prependFileIcon($fileBtn, file['status']);
+                $fileBtn.click(() => {
+                    $workdirDiffFiles.find('.active').removeClass('active');
+                    $fileBtn.addClass('active');
+                    self.showFileDiffUnlessCollapsed(file, 'workdir', () => self.requestFileDiff(file['path'], 'workdir', workdirDiff['sha']));
+                });
+                $workdirDiffFiles.append($fileBtn);
+            });
+            $workdirDiffFiles.children('button').first().click();
+            $('#workdirDiffModal').modal('show');
+        }).then();
+
+        listen("show-merge-base-diff", ev => {
+            const mergeBaseDiff = ev.payload,
+                $mergeBaseDiffFiles = $('#mergeBaseDiffFiles');
+            $('#mergeBaseDiffDescription').text(mergeBaseDiff['branch_a'] + '...' + mergeBaseDiff['branch_b'] + ': changes on ' + mergeBaseDiff['branch_b'] + ' since merge base ' + mergeBaseDiff['merge_base_sha'].substring(0, 8));
+            $mergeBaseDiffFiles.empty();
+            $('#mergeBaseFileDiffTable').empty();
+            if (mergeBaseDiff['changed_files'].length === 0) {
+                $mergeBaseDiffFiles.append($('<p class="text-grey"></p>').text('Nothing has changed since the merge base.'));
+            }
+            mergeBaseDiff['changed_files'].forEach((file) => {
+                const $fileBtn = $('<button type="button" class="btn btn-outline-dark btn-sm rounded-0"></button>');
+                $fileBtn.text(file['display_path']);
+                self.prependFileIcon($fileBtn, file['status']);
+                $fileBtn.click(() => {
+                    $mergeBaseDiffFiles.find('.active').removeClass('active');
+                    $fileBtn.addClass('active');
+                    self.showFileDiffUnlessCollapsed(file, 'merge-base', () => self.requestFileDiff(file['path'], 'merge-base', mergeBaseDiff['range']));
+                });
+                $mergeBaseDiffFiles.append($fileBtn);
+            });
+            $mergeBaseDiffFiles.children('button').first().click();
+            $('#mergeBaseDiffModal').modal('show');
+        }).then();
+
+        listen("show-merge-preview", ev => {
+            const mergePreview = ev.payload,
+                $mergePreviewConflicts = $('#mergePreviewConflicts');
+            let description = '';
+            if (mergePreview['outcome'] === 'up_to_date') {
+                description = 'Already up to date, ' + mergePreview['theirs'] + ' has nothing that isn\'t in ' + mergePreview['ours'] + '.';
+            } else if (mergePreview['outcome'] === 'fast_forward') {
+                description = 'This would be a fast-forward, so there can\'t be any conflicts.';
+            } else if (mergePreview['outcome'] === 'clean') {
+                description = 'This would merge cleanly.';
+            } else {
+                description = 'This would conflict in ' + mergePreview['conflicted_files'].length + ' file(s), rebasing instead may be easier:';
+            }
+            if (mergePreview['merge_base_sha'] === '') {
+                description += ' The branches don\'t share any history.';
+            }
+            $('#mergePreviewDescription').text(description);
+            $mergePreviewConflicts.empty();
+            mergePreview['conflicted_files'].forEach((path) => {
+                $mergePreviewConflicts.append($('<li></li>').text(path));
+            });
+            $('#mergePreviewModal').modal('show');
+        }).then();
+
+        listen("show-staged-patch", ev => {
+            const stagedPatch = ev.payload,
+                $stagedPatchFiles = $('#stagedPatchFiles');
+            $stagedPatchFiles.empty();
+            $('#copyStagedPatchBtn').data('patch', stagedPatch['patch']);
+            if (stagedPatch['files'].length === 0) {
+                $stagedPatchFiles.append($('<p class="text-grey little-padding-left"></p>').text('Nothing is staged.'));
+            }
+            stagedPatch['files'].forEach((file) => {
+                const $fileHeader = $('<h6 class="little-padding-left little-padding-top"></h6>').text(file['delta']['display_path']);
+                self.prependFileIcon($fileHeader, file['delta']['status']);
+                $stagedPatchFiles.append($fileHeader);
+                if (file['is_binary']) {
+                    $stagedPatchFiles.append($('<p class="text-grey little-padding-left"></p>').text('Binary file'));
+                }
+                file['hunks'].forEach((hunk) => {
+                    const $hunkPre = $('<pre class="little-padding-left"></pre>');
+                    hunk.replace(/\n$/, '').split('\n').forEach((line) => {
+                        const $line = $('<div></div>').text(line);
+                        if (line.startsWith('+')) {
+                            $line.addClass('added-code-line');
+                        } else if (line.startsWith('-')) {
+                            $line.addClass('removed-code-line');
+                        } else if (line.startsWith('@@')) {
+                            $line.addClass('text-grey');
+                        }
+                        $hunkPre.append($line);
+                    });
+                    $stagedPatchFiles.append($hunkPre);
+                });
+            });
+            $('#stagedPatchModal').modal('show');
+        }).then();
+
+        listen("commit-validation", ev => {
+            const commitProblems = ev.payload;
+            if (self.pendingCommitEvent === null) {
+                return;
+            }
+            if (commitProblems.length === 0) {
+                self.doPendingCommit();
+                return;
+            }
+            const $commitProblemsList = $('#commitProblemsList');
+            $commitProblemsList.empty();
+            let hasError = false;
+            commitProblems.forEach((commitProblem) => {
+                hasError = hasError || commitProblem['severity'] === 'error';
+                const $problem = $('<li></li>').text(commitProblem['message']);
+                $problem.addClass(commitProblem['severity'] === 'error' ? 'text-danger' : 'text-warning');
+                if (commitProblem['paths'].length > 0) {
+                    const $paths = $('<ul class="text-black"></ul>');
+                    commitProblem['paths'].forEach((path) => {
+                        $paths.append($('<li></li>').text(path));
+                    });
+                    $problem.append($paths);
+                }
+                $commitProblemsList.append($problem);
+            });
+            // Errors would make the commit fail (or are things that must never be committed), so only warnings can be skipped.
+            if (hasError) {
+                $('#commitAnywayBtn').hide();
+                self.pendingCommitEvent = null;
+            } else {
+                $('#commitAnywayBtn').show();
+            }
+            $('#commitProblemsModal').modal('show');
+        }).then();
+
+        listen("show-conflicts-info", ev => {
+            const conflictsInfo = ev.payload,
+                $conflictContent = $('#conflictContent');
+            $('#conflictsModalLabel').text(conflictsInfo['display_path']);
+            $('#conflictsModal').data('path', conflictsInfo['path']);
+            $conflictContent.empty();
+            // Without text to merge by hand, the only way to resolve it is to take one side's whole file.
+            $('#resolveBinaryOursBtn').toggle(conflictsInfo['content'] === null);
+            $('#resolveBinaryTheirsBtn').toggle(conflictsInfo['content'] === null);
+            if (conflictsInfo['kind'] === 'deleted_by_us') {
+                $('#conflictSummaryTxt').text('This side deleted the file while the other side changed it.');
+            } else if (conflictsInfo['kind'] === 'deleted_by_them') {
+                $('#conflictSummaryTxt').text('The other side deleted the file while this side changed it.');
+            } else if (conflictsInfo['content'] === null) {
+                $('#conflictSummaryTxt').text('The file is binary, so there are no conflict markers to show.');
+            } else {
+                $('#conflictSummaryTxt').text(conflictsInfo['conflict_count'] + ' conflict(s), shown in the ' + conflictsInfo['conflict_style'] + ' style (set by merge.conflictStyle).');
+                conflictsInfo['content'].split('\n').forEach((line) => {
+                    const $line = $('<div class="little-padding-left"></div>').text(line === '' ? ' ' : line);
+                    if (line.startsWith('<<<<<<<') || line.startsWith('>>>>>>>') || line.startsWith('=======')) {
+                        $line.addClass('text-warning');
+                    } else if (line.startsWith('|||||||')) {
+                        $line.addClass('text-info');
+                    }
+                    $conflictContent.append($line);
+                });
+            }
+            self.removeProcessCount();
+            $('#conflictsModal').modal('show');
+        }).then();
+
+        listen("show-conflict-regions", ev => {
+            const conflictRegions = ev.payload,
+                $conflictRegions = $('#conflictRegions');
+            $('#conflictRegionsModalLabel').text('Resolve ' + conflictRegions['display_path']);
+            $conflictRegions.empty();
+            if (conflictRegions['is_staged']) {
+                $conflictRegions.append($('<p class="text-success"></p>').text('Every conflict is resolved, so the file has been staged.'));
+            } else if (conflictRegions['regions'].length === 0) {
+                $conflictRegions.append($('<p></p>').text('There are no conflict markers left in the file, stage it to mark it as resolved.'));
+            }
+            conflictRegions['regions'].forEach((region, regionIndex) => {
+                const $region = $('<div class="little-padding-bottom"></div>');
+                $region.append($('<h6></h6>').text('Conflict ' + (regionIndex + 1) + ' (line ' + region['start_line'] + ')'));
+                const $sides = $('<div class="row"></div>');
+                const sides = [['Ours', region['ours']], ['Theirs', region['theirs']]];
+                if (region['base'] !== null) {
+                    sides.splice(1, 0, ['Base', region['base']]);
+                }
+                sides.forEach(([label, text]) => {
+                    const $side = $('<div class="col"></div>');
+                    $side.append($('<p class="text-grey"></p>').text(label));
+                    $side.append($('<pre class="bg-dark text-white little-padding-left overflow-auto" style="max-height: 30vh;"></pre>').text(text));
+                    $sides.append($side);
+                });
+                $region.append($sides);
+                const resolve = (resolution, text) => {
+                    self.addProcessCount();
+                    emit("resolve-conflict-region", {path: conflictRegions['path'], region_index: regionIndex.toString(), resolution: resolution, text: text}).then();
+                };
+                const $customTxt = $('<textarea class="form-control form-control-sm font-monospace little-padding-bottom" rows="4"></textarea>').val(region['ours'] + region['theirs']);
+                const $oursBtn = $('<button type="button" class="btn btn-outline-primary btn-sm">Take Ours</button>').click(() => resolve('ours', ''));
+                const $theirsBtn = $('<button type="button" class="btn btn-outline-primary btn-sm">Take Theirs</button>').click(() => resolve('theirs', ''));
+                const $bothBtn = $('<button type="button" class="btn btn-outline-primary btn-sm">Take Both</button>').click(() => resolve('both', ''));
+                const $customBtn = $('<button type="button" class="btn btn-outline-primary btn-sm">Use Text Below</button>').click(() => resolve('custom', $customTxt.val()));
+                $region.append($('<div class="little-padding-bottom"></div>').append($oursBtn, ' ', $theirsBtn, ' ', $bothBtn, ' ', $customBtn));
+                $region.append($customTxt);
+                $conflictRegions.append($region);
+            });
+            self.removeProcessCount();
+            $('#conflictRegionsModal').modal('show');
+        }).then();
+
+        listen("large-files-staged", ev => {
+            const $largeFilesTableBody = $('#largeFilesTableBody');
+            $largeFilesTableBody.empty();
+            ev.payload.forEach((largeFile) => {
+                const $row = $('<tr></tr>');
+                $row.append($('<td></td>').text(largeFile['display_path']));
+                $row.append($('<td class="text-nowrap"></td>').text((largeFile['size'] / (1024 * 1024)).toFixed(1) + ' MB'));
+                const $patternTxt = $('<input type="text" class="form-control form-control-sm">').val(largeFile['suggested_pattern']);
+                $row.append($('<td></td>').append($patternTxt));
+                const $trackBtn = $('<button type="button" class="btn btn-primary btn-sm text-nowrap">Track With LFS</button>');
+                $trackBtn.click(() => {
+                    self.addProcessCount();
+                    emit("track-with-lfs", {pattern: $patternTxt.val(), path: largeFile['path']}).then();
+                    $row.remove();
+                    if ($largeFilesTableBody.children().length === 0) {
+                        $('#largeFilesModal').modal('hide');
+                    }
+                });
+                $row.append($('<td></td>').append($trackBtn));
+                $largeFilesTableBody.append($row);
+            });
+            $('#largeFilesModal').modal('show');
+        }).then();
+
         listen("show-file-lines", ev => {
-            self.showFileDiff(ev.payload);
+            self.showFileDiff(ev.payload, false);
+        }).then();
+
+        listen("show-more-hunks", ev => {
+            self.showFileDiff(ev.payload, true);
+        }).then();
+
+        listen("show-hunk-context", ev => {
+            self.showHunkContext(ev.payload);
         }).then();
 
         listen("error", ev => {
@@ -180,6 +1007,35 @@ class Main {
             self.showError(ev.payload);
         }).then();
 
+        listen("repo-capabilities", ev => {
+            if (ev.payload['problems'].length > 0) {
+                self.showError('This repository has problems, so some features are disabled:\n' + ev.payload['problems'].join('\n'));
+            }
+        }).then();
+
+        listen("show-git-console", ev => {
+            $('#gitCommandOutput').text('');
+            $('#gitConsoleModal').modal('show');
+        }).then();
+
+        listen("git-command-output", ev => {
+            const $gitCommandOutput = $('#gitCommandOutput');
+            $gitCommandOutput.text($gitCommandOutput.text() + ev.payload['line'] + '\n');
+        }).then();
+
+        listen("git-command-finished", ev => {
+            const $gitCommandOutput = $('#gitCommandOutput');
+            $gitCommandOutput.text($gitCommandOutput.text() + '(exited with code ' + ev.payload['exit_code'] + ')\n');
+            if (ev.payload['refresh'] !== 'all') {
+                self.removeProcessCount();
+            }
+        }).then();
+
+        listen("structured-error", ev => {
+            self.removeProcessCount();
+            self.showStructuredError(ev.payload);
+        }).then();
+
         $('#updateBtn').click(async function() {
             const $updaterSpinner = $('#updaterSpinner');
             $updaterSpinner.show();
@@ -208,10 +1064,12 @@ class Main {
         $('#commits-tab').click(() => {
             self.svgManager.setVisibleCommits();
             self.truncateFilePathText();
+            self.saveRepoSession();
         });
 
         $('#changes-tab').click(() => {
             self.truncateFilePathText();
+            self.saveRepoSession();
         });
 
         $('#commit-diff-tab').click(() => {
@@ -225,10 +1083,12 @@ class Main {
 
         $('#addRemoteBtn').click(() => {
             const $addRemoteNameTxt = $('#addRemoteNameTxt'),
-                $addRemoteURLTxt = $('#addRemoteURLTxt');
-            emit("add-remote", {remote_name: $addRemoteNameTxt.val(), remote_url: $addRemoteURLTxt.val()}).then();
+                $addRemoteURLTxt = $('#addRemoteURLTxt'),
+                $addRemotePushURLTxt = $('#addRemotePushURLTxt');
+            emit("add-remote", {remote_name: $addRemoteNameTxt.val(), remote_url: $addRemoteURLTxt.val(), push_url: $addRemotePushURLTxt.val()}).then();
             $addRemoteNameTxt.val('');
             $addRemoteURLTxt.val('');
+            $addRemotePushURLTxt.val('');
             $('#addRemoteModal').modal('hide');
         });
 
@@ -240,11 +1100,53 @@ class Main {
             }
         });
 
+        $('#addIssueLinkRuleBtn').click(() => {
+            self.addIssueLinkRuleRow('', '');
+        });
+
+        $('#saveIssueLinkRulesBtn').click(() => {
+            const patterns = [],
+                urlTemplates = [];
+            $('#issueLinkRulesTableBody tr').each(function() {
+                const pattern = $(this).find('.issue-link-pattern-txt').val();
+                if (pattern !== '') {
+                    patterns.push(pattern);
+                    urlTemplates.push($(this).find('.issue-link-url-txt').val());
+                }
+            });
+            emit("save-issue-link-rules", {patterns: patterns, url_templates: urlTemplates}).then();
+            $('#issueLinksModal').modal('hide');
+        });
+
+        $('#ignoredFilesFilterBtn').click(() => {
+            self.addProcessCount();
+            emit("list-ignored-files", $('#ignoredFilesDirectoryTxt').val()).then();
+        });
+
         $('#savePreferencesBtn').click(() => {
             self.addProcessCount();
             emit("save-preferences", {
                 limit_commits: $('#limitCommitsCheckBox').is(':checked'),
                 commit_count: parseInt($('#commitCountNumber').val()),
+                max_blob_size: parseInt($('#maxBlobSizeNumber').val()) * 1024 * 1024,
+                auto_fetch_minutes: parseInt($('#autoFetchMinutesNumber').val()),
+                rename_threshold: parseInt($('#renameThresholdNumber').val()),
+                copy_threshold: parseInt($('#copyThresholdNumber').val()),
+                recurse_submodules: $('#recurseSubmodulesCheckBox').is(':checked'),
+                snapshot_limit: parseInt($('#snapshotLimitNumber').val()),
+                ignore_eol_changes: $('#ignoreEolChangesCheckBox').is(':checked'),
+                diff_algorithm: $('#diffAlgorithmSelect').val(),
+                show_function_names: $('#showFunctionNamesCheckBox').is(':checked'),
+                branch_sort_mode: $('#branchSortModeSelect').val(),
+                interhunk_lines: parseInt($('#interhunkLinesNumber').val()),
+                collapse_attributes: $('#collapseAttributesTxt').val().split('\n').map((a) => a.trim()).filter((a) => a !== ''),
+                prefill_commit_from_branch: $('#prefillCommitFromBranchCheckBox').is(':checked'),
+                branch_prefill_pattern: $('#branchPrefillPatternTxt').val(),
+                branch_prefill_template: $('#branchPrefillTemplateTxt').val(),
+                large_file_warning_size: parseInt($('#largeFileWarningSizeNumber').val()) * 1024 * 1024,
+                forbidden_paths: $('#forbiddenPathsTxt').val().split('\n').map((p) => p.trim()).filter((p) => p !== ''),
+                extra_path_entries: $('#extraPathEntriesTxt').val().split('\n').map((p) => p.trim()).filter((p) => p !== ''),
+                extra_env_vars: self.parseEnvVars($('#extraEnvVarsTxt').val()),
             }).then();
             $('#preferencesModal').modal('hide');
         });
@@ -316,28 +1218,134 @@ class Main {
             $('#credentialsModal').modal('hide');
         });
 
+        $('#generateSSHKeyBtn').click(() => {
+            const $sshKeyFileNameTxt = $('#sshKeyFileNameTxt'),
+                $sshKeyCommentTxt = $('#sshKeyCommentTxt'),
+                $passphraseTxt = $('#passphraseTxt');
+            emit("generate-ssh-key", {
+                file_name: $sshKeyFileNameTxt.val(),
+                comment: $sshKeyCommentTxt.val(),
+                passphrase: $passphraseTxt.val(),
+            }).then();
+            $sshKeyFileNameTxt.val("");
+            $sshKeyCommentTxt.val("");
+            $passphraseTxt.val("");
+        });
+
+        $('#blameIgnoreRevsCheckBox, #detectCopiesCheckBox').change(() => {
+            const $blameModal = $('#blameModal');
+            self.showBlame($blameModal.data('file-path'), $blameModal.data('sha'));
+        });
+
+        $('#exportLogBtn').click(async function() {
+            const format = $('#exportLogFormatSelect').val(),
+                extension = format === 'text' ? 'txt' : format,
+                selected = await save({
+                defaultPath: await homeDir(),
+                filters: [{name: format.toUpperCase(), extensions: [extension]}],
+            });
+            if (selected !== null) {
+                emit("export-log", {range: $('#exportLogRangeTxt').val(), format: format, path: selected}).then();
+                $('#exportLogModal').modal('hide');
+            }
+        });
+
+        $('#runGitCommandBtn').click(() => {
+            const $gitCommandTxt = $('#gitCommandTxt');
+            // Split on whitespace, keeping quoted arguments together.
+            const args = ($gitCommandTxt.val().match(/"[^"]*"|'[^']*'|\S+/g) || []).map((arg) => {
+                return arg.replace(/^(["'])(.*)\1$/, '$2');
+            });
+            self.addProcessCount();
+            $('#gitCommandOutput').text('$ git ' + $gitCommandTxt.val() + '\n');
+            emit("run-git-command", {args: args}).then();
+        });
+
+        $('.checkout-strategy-btn').click(function() {
+            const $checkoutConflictModal = $('#checkoutConflictModal');
+            self.addProcessCount();
+            emit("checkout-with-strategy", {
+                target: $checkoutConflictModal.data('target'),
+                target_type: $checkoutConflictModal.data('target_type'),
+                strategy: $(this).data('strategy'),
+            }).then();
+            $checkoutConflictModal.modal('hide');
+        });
+
+        $('#saveRemoteURLsBtn').click(() => {
+            emit("set-remote-urls", {
+                remote_name: $('#editRemoteName').text(),
+                url: $('#editRemoteURLTxt').val(),
+                push_url: $('#editRemotePushURLTxt').val(),
+            }).then();
+            $('#editRemoteURLsModal').modal('hide');
+        });
+
+        $('#addRefspecBtn').click(() => {
+            const $newRefspecTxt = $('#newRefspecTxt');
+            emit("add-remote-refspec", {
+                remote_name: $('#refspecsRemoteName').text(),
+                direction: $('#newRefspecDirectionSelect').val(),
+                refspec: $newRefspecTxt.val(),
+            }).then();
+            $newRefspecTxt.val('');
+        });
+
+        $('#checkoutWithNameBtn').click(() => {
+            const $branchNameTakenModal = $('#branchNameTakenModal');
+            self.addProcessCount();
+            emit("checkout-remote", {
+                full_branch_name: $branchNameTakenModal.data('full_branch_name'),
+                branch_shorthand: $branchNameTakenModal.data('branch_shorthand'),
+                local_name: $('#alternativeBranchNameTxt').val(),
+            }).then();
+            $branchNameTakenModal.modal('hide');
+        });
+
+        $('#removeLockBtn').click(() => {
+            self.addProcessCount();
+            emit("remove-stale-lock", {
+                lock_path: $('#lockPath').text(),
+                force: $('#forceRemoveLockCheckBox').is(':checked').toString(),
+            }).then();
+            $('#lockModal').modal('hide');
+        });
+
         $('#stageAllBtn').click(() => {
             emit("stage-all").then();
         });
 
+        // Check for problems first so they can all be shown at once instead of the commit failing on the first one.
         $('#commitBtn').click(() => {
-            self.addProcessCount();
-            const $summaryTxt = $('#summaryTxt'),
-                $messageTxt = $('#messageTxt');
-            emit("commit", {summaryText: $summaryTxt.val(), messageText: $messageTxt.val()}).then();
-            $summaryTxt.val("");
-            $messageTxt.val("");
-            self.updateSummaryTxtCounter();
+            self.pendingCommitEvent = 'commit';
+            emit("validate-commit", "").then();
         });
 
         $('#commitPushBtn').click(() => {
+            self.pendingCommitEvent = 'commit-push';
+            emit("validate-commit", "").then();
+        });
+
+        $('#resolveBinaryOursBtn').click(() => {
             self.addProcessCount();
-            const $summaryTxt = $('#summaryTxt'),
-                $messageTxt = $('#messageTxt');
-            emit("commit-push", {summaryText: $summaryTxt.val(), messageText: $messageTxt.val()}).then();
-            $summaryTxt.val("");
-            $messageTxt.val("");
-            self.updateSummaryTxtCounter();
+            emit("resolve-binary", {path: $('#conflictsModal').data('path'), side: 'ours'}).then();
+            $('#conflictsModal').modal('hide');
+        });
+
+        $('#resolveBinaryTheirsBtn').click(() => {
+            self.addProcessCount();
+            emit("resolve-binary", {path: $('#conflictsModal').data('path'), side: 'theirs'}).then();
+            $('#conflictsModal').modal('hide');
+        });
+
+        $('#saveHookBtn').click(() => {
+            self.addProcessCount();
+            emit("save-hook", {name: $('#hookNameTxt').val().trim(), contents: $('#hookContentsTxt').val()}).then();
+        });
+
+        $('#commitAnywayBtn').click(() => {
+            $('#commitProblemsModal').modal('hide');
+            self.doPendingCommit();
         });
 
         $('#abortCherrypickBtn').click(() => {
@@ -348,6 +1356,45 @@ class Main {
             emit("continue-cherrypick").then();
         });
 
+        $('#viewStagedPatchBtn').click(() => {
+            emit("get-staged-patch", "").then();
+        });
+
+        $('#copyStagedPatchBtn').click(() => {
+            writeText($('#copyStagedPatchBtn').data('patch')).then();
+        });
+
+        $('#checkoutFileFromBtn').click(() => {
+            const $checkoutFileFromModal = $('#checkoutFileFromModal');
+            emit("checkout-file-from", {
+                sha: $checkoutFileFromModal.data('sha'),
+                path: $checkoutFileFromModal.data('path'),
+                target: $('#checkoutFileFromTargetSelect').val(),
+            }).then();
+            $checkoutFileFromModal.modal('hide');
+        });
+
+        $('#deepenBtn').click(() => {
+            self.addProcessCount();
+            emit("deepen", {depth: $('#deepenDepthTxt').val(), unshallow: 'false'}).then();
+            $('#deepenModal').modal('hide');
+        });
+
+        $('#unshallowBtn').click(() => {
+            self.addProcessCount();
+            emit("deepen", {depth: '', unshallow: 'true'}).then();
+            $('#deepenModal').modal('hide');
+        });
+
+        $('#replaceBtn').click(() => {
+            emit("replace", {
+                original: $('#replaceOriginalSha').text(),
+                replacement: $('#replacementTxt').val(),
+                force: $('#forceReplaceCheckBox').is(':checked').toString(),
+            }).then();
+            $('#replaceModal').modal('hide');
+        });
+
         $('#cherrypickBtn').click(() => {
             const $cherrypickSha = $('#cherrypickSha');
             emit("cherrypick", {sha: $cherrypickSha.text(), isCommitting: $('#commitCherrypickCheckBox').is(':checked').toString()}).then();
@@ -355,6 +1402,35 @@ class Main {
             $cherrypickSha.text('');
         });
 
+        $('#branchFilterTxt').on('input', () => {
+            clearTimeout(self.branchFilterTimeout);
+            self.branchFilterTimeout = setTimeout(() => {
+                self.refreshBranchInfo();
+            }, 200);
+        });
+
+        $('#diffContextLinesNumber').change(() => {
+            const contextLines = parseInt($('#diffContextLinesNumber').val());
+            if (isNaN(contextLines) || contextLines < 0) {
+                return;
+            }
+            self.diffContextLines = contextLines;
+            if (self.lastFileDiffRequest !== null) {
+                self.requestFileDiff(self.lastFileDiffRequest['file_path'], self.lastFileDiffRequest['change_type'], self.lastFileDiffRequest['sha']);
+            }
+        });
+
+        $('#mergeWithOptionsBtn').click(() => {
+            emit("merge", self.getMergeOptionsPayload()).then();
+            $('#mergeOptionsModal').modal('hide');
+        });
+
+        $('#rebaseWithOptionsBtn').click(() => {
+            self.addProcessCount();
+            emit("rebase", self.getMergeOptionsPayload()).then();
+            $('#mergeOptionsModal').modal('hide');
+        });
+
         $('#abortRevertBtn').click(() => {
             emit("abort").then();
         });
@@ -364,8 +1440,13 @@ class Main {
         });
 
         $('#revertBtn').click(() => {
-            const $revertSha = $('#revertSha');
-            emit("revert", {sha: $revertSha.text(), isCommitting: $('#commitRevertCheckBox').is(':checked').toString()}).then();
+            const $revertSha = $('#revertSha'),
+                $revertMainlineContainer = $('#revertMainlineContainer');
+            const revertOptions = {sha: $revertSha.text(), isCommitting: $('#commitRevertCheckBox').is(':checked').toString()};
+            if ($revertMainlineContainer.is(':visible')) {
+                revertOptions['mainline'] = $('#revertMainlineSelect').val();
+            }
+            emit("revert", revertOptions).then();
             $('#revertModal').modal('hide');
             $revertSha.text('');
         });
@@ -387,11 +1468,87 @@ class Main {
             emit("continue-rebase").then();
         });
 
+        $('#abandonStashApplyBtn').click(() => {
+            emit("abandon-stash-apply").then();
+        });
+
+        $('#continueStashApplyBtn').click(() => {
+            emit("continue-stash-apply").then();
+        });
+
         $('#fetchBtn').click(() => {
             self.addProcessCount();
             emit("fetch").then();
         });
 
+        $('#addSubmoduleBtn').click(() => {
+            self.addProcessCount();
+            emit("add-submodule", {
+                url: $('#addSubmoduleURLTxt').val(),
+                path: $('#addSubmodulePathTxt').val(),
+            }).then();
+            $('#addSubmoduleModal').modal('hide');
+        });
+
+        $('#removeSubmoduleBtn').click(() => {
+            self.addProcessCount();
+            emit("remove-submodule", {
+                path: $('#removeSubmodulePathTxt').val(),
+                force: $('#forceRemoveSubmoduleCheckBox').is(':checked').toString(),
+            }).then();
+            $('#removeSubmoduleModal').modal('hide');
+        });
+
+        $('#subtreeBtn').click(() => {
+            const operation = $('#subtreeOperationSelect').val(),
+                prefix = $('#subtreePrefixTxt').val(),
+                remote = $('#subtreeRemoteTxt').val(),
+                branch = $('#subtreeBranchTxt').val();
+            self.addProcessCount();
+            // Subtree operations can take a while, so show their output in the git console as they go.
+            $('#gitCommandOutput').text('$ git subtree ' + operation + ' --prefix=' + prefix + ' ' + remote + ' ' + branch + '\n');
+            $('#subtreeModal').modal('hide');
+            $('#gitConsoleModal').modal('show');
+            emit("subtree", {
+                operation: operation,
+                prefix: prefix,
+                remote: remote,
+                branch: branch,
+                squash: $('#subtreeSquashCheckBox').is(':checked').toString(),
+            }).then();
+        });
+
+        $('#pastePatchTextBtn').click(async function() {
+            const clipboardText = await readText();
+            if (clipboardText !== null) {
+                $('#patchTextTxt').val(clipboardText);
+            }
+        });
+
+        $('#checkPatchTextBtn').click(() => {
+            self.addProcessCount();
+            emit("apply-patch-text", {
+                content: $('#patchTextTxt').val(),
+                to_index: $('#patchToIndexCheckBox').is(':checked').toString(),
+                dry_run: 'true',
+            }).then();
+        });
+
+        $('#applyPatchTextBtn').click(() => {
+            self.addProcessCount();
+            emit("apply-patch-text", {
+                content: $('#patchTextTxt').val(),
+                to_index: $('#patchToIndexCheckBox').is(':checked').toString(),
+                dry_run: 'false',
+            }).then();
+            $('#applyPatchTextModal').modal('hide');
+        });
+
+        $('#syncBtn').click(() => {
+            self.addProcessCount();
+            emit("sync").then();
+        });
+
         $('#pullBtn').click(() => {
             self.addProcessCount();
             emit("pull").then();
@@ -403,6 +1560,7 @@ class Main {
             } else {
                 $('#remoteSelect').show();
             }
+            $('#pushBranchShorthand').text('');
             $('#forcePushCheckBox').prop('checked', false);
             $('#pushModal').modal('show');
         });
@@ -411,10 +1569,19 @@ class Main {
             self.addProcessCount();
             // Note: By default, pushing will try to use the local branch's upstream first
             // instead of the selected remote from the front-end
-            emit("push", {
-                selectedRemote: $('#remoteSelect').val(),
-                isForcePush: $('#forcePushCheckBox').is(':checked').toString(),
-            }).then();
+            const pushBranchShorthand = $('#pushBranchShorthand').text();
+            if (pushBranchShorthand === '') {
+                emit("push", {
+                    selectedRemote: $('#remoteSelect').val(),
+                    isForcePush: $('#forcePushCheckBox').is(':checked').toString(),
+                }).then();
+            } else {
+                emit("push-branch", {
+                    branch_shorthand: pushBranchShorthand,
+                    selectedRemote: $('#remoteSelect').val(),
+                    isForcePush: $('#forcePushCheckBox').is(':checked').toString(),
+                }).then();
+            }
             $('#pushModal').modal('hide');
         });
 
@@ -430,17 +1597,61 @@ class Main {
             $('#pushTagModal').modal('hide');
         });
 
+        $('#saveBranchRemotesBtn').click(() => {
+            self.addProcessCount();
+            emit("set-branch-remotes", {
+                branch_shorthand: $('#branchRemotesShorthand').text(),
+                remote: $('#branchRemoteSelect').val(),
+                push_remote: $('#branchPushRemoteSelect').val(),
+            }).then();
+            $('#branchRemotesModal').modal('hide');
+        });
+
+        $('#setRemoteHeadAutoBtn').click(() => {
+            self.addProcessCount();
+            emit("set-remote-head", {
+                remote_name: $('#remoteHeadRemoteName').text(),
+                branch: '',
+            }).then();
+            $('#remoteHeadModal').modal('hide');
+        });
+
         $('#openStashModalBtn').click(() => {
             $('#stashModal').modal('show');
         });
 
+        $('#stashStagedOnlyCheckBox').change(function() {
+            // Stashing only what's staged leaves everything else alone, so the other options don't apply.
+            $('#stashIncludeUntrackedCheckBox').prop('disabled', this.checked);
+            $('#stashKeepIndexCheckBox').prop('disabled', this.checked);
+        });
+
         $('#stashBtn').click(() => {
             const $stashTxt = $('#stashTxt');
-            emit("stash", $stashTxt.val()).then();
+            emit("stash", {
+                message: $stashTxt.val(),
+                include_untracked: $('#stashIncludeUntrackedCheckBox').is(':checked').toString(),
+                keep_index: $('#stashKeepIndexCheckBox').is(':checked').toString(),
+                staged_only: $('#stashStagedOnlyCheckBox').is(':checked').toString(),
+            }).then();
             $stashTxt.val('');
             $('#stashModal').modal('hide');
         });
 
+        $('#stashToBranchBtn').click(() => {
+            self.addProcessCount();
+            emit("stash-to-branch", {
+                index: $('#stashToBranchIndex').text(),
+                branch_name: $('#stashToBranchNameTxt').val(),
+            }).then();
+            $('#stashToBranchModal').modal('hide');
+        });
+
+        $('#stashDiffApplyBtn').click(() => {
+            $('#stashDiffModal').modal('hide');
+            self.applyStash(parseInt($('#stashDiffApplyBtn').attr('data-stash-index')));
+        });
+
         $('#applyStashBtn').click(() => {
             const $stashIndex = $('#stashIndex');
             emit("apply-stash", {index: $stashIndex.text(), delete_stash: $('#deleteStashCheckBox').is(':checked').toString()}).then();
@@ -480,16 +1691,19 @@ class Main {
             const $tagSha = $('#tagSha'),
                 $lightweightTagCheckbox = $('#lightweightTagCheckbox'),
                 $tagTxt = $('#tagTxt'),
-                $tagMessageTxt = $('#tagMessageTxt');
+                $tagMessageTxt = $('#tagMessageTxt'),
+                $signTagCheckBox = $('#signTagCheckBox');
             emit("tag", {
                 tag_sha: $tagSha.text(),
                 is_lightweight: $lightweightTagCheckbox.is(':checked').toString(),
                 name: $tagTxt.val(),
-                message: $tagMessageTxt.val()
+                message: $tagMessageTxt.val(),
+                sign: $signTagCheckBox.is(':checked').toString()
             }).then();
             $tagSha.text('');
             $tagTxt.val('');
             $tagMessageTxt.val('');
+            $signTagCheckBox.prop('checked', false);
             $('#tagModal').modal('hide');
         });
 
@@ -530,6 +1744,125 @@ class Main {
         $('#errorModal').modal('show');
     }
 
+    showStructuredError(structuredError) {
+        const code = structuredError['code'],
+            data = structuredError['data'];
+        if ((code === 'lock_file_exists' || code === 'lock_recent') && data !== null && data['lock_path'] !== null) {
+            $('#lockMessage').text(structuredError['message']);
+            $('#lockPath').text(data['lock_path']);
+            $('#forceRemoveLockCheckBox').prop('checked', code === 'lock_recent');
+            $('#lockModal').modal('show');
+        } else if (code === 'checkout_conflict' && data !== null) {
+            const $checkoutConflictModal = $('#checkoutConflictModal'),
+                $checkoutConflictPaths = $('#checkoutConflictPaths');
+            $checkoutConflictPaths.empty();
+            data['paths'].forEach((path) => {
+                $checkoutConflictPaths.append($('<li></li>').text(path));
+            });
+            $checkoutConflictModal.data('target', data['target']);
+            $checkoutConflictModal.data('target_type', data['target_type']);
+            $checkoutConflictModal.modal('show');
+        } else if (code === 'branch_name_taken' && data !== null) {
+            const $branchNameTakenModal = $('#branchNameTakenModal');
+            $('#takenBranchName').text(data['branch_name']);
+            $('#alternativeBranchNameTxt').val(data['branch_name']);
+            $branchNameTakenModal.data('branch_shorthand', data['branch_shorthand']);
+            $branchNameTakenModal.data('full_branch_name', data['full_branch_name']);
+            $branchNameTakenModal.modal('show');
+        } else if (code === 'submodule_has_local_work' && data !== null) {
+            $('#removeSubmodulePathTxt').val(data['path']);
+            $('#removeSubmoduleMessage').text(structuredError['message']);
+            $('#forceRemoveSubmoduleCheckBox').prop('checked', false);
+            $('#removeSubmoduleModal').modal('show');
+        } else {
+            this.showError(structuredError['message']);
+        }
+    }
+
+    applyLayout() {
+        if (this.layout === null) {
+            return;
+        }
+        // The branches and tabs belong to the main window, so only leave the part this window is for.
+        $('.resizable-column-branches').hide();
+        $('#commits-tab').closest('.nav-tabs').hide();
+        if (this.layout === 'history') {
+            $('#commits-tab').tab('show');
+        } else {
+            $('#changes-tab').tab('show');
+        }
+    }
+
+    // Lines without an = are skipped, and only the first = splits the name from the value so values can have their own.
+    parseEnvVars(envVarsText) {
+        const envVars = {};
+        envVarsText.split('\n').forEach((line) => {
+            const equalsIndex = line.indexOf('=');
+            if (equalsIndex > 0) {
+                envVars[line.substring(0, equalsIndex).trim()] = line.substring(equalsIndex + 1);
+            }
+        });
+        return envVars;
+    }
+
+    // The back-end reads every option as a string, like the other payloads.
+    getMergeOptionsPayload() {
+        return {
+            target: $('#mergeOptionsTarget').text(),
+            favor: $('#mergeFavorSelect').val(),
+            find_renames: $('#mergeFindRenamesCheckBox').is(':checked').toString(),
+            rename_threshold: $('#mergeRenameThresholdTxt').val(),
+            rename_limit: $('#mergeRenameLimitTxt').val(),
+            diff_algorithm: $('#mergeDiffAlgorithmSelect').val(),
+        };
+    }
+
+    doPendingCommit() {
+        const self = this,
+            $summaryTxt = $('#summaryTxt'),
+            $messageTxt = $('#messageTxt');
+        if (self.pendingCommitEvent === null) {
+            return;
+        }
+        self.addProcessCount();
+        emit(self.pendingCommitEvent, {summaryText: $summaryTxt.val(), messageText: $messageTxt.val(), signOff: $('#signOffCheckBox').is(':checked').toString()}).then();
+        self.pendingCommitEvent = null;
+        $summaryTxt.val("");
+        $messageTxt.val("");
+        self.updateSummaryTxtCounter();
+    }
+
+    // Remembers where the user is in the repo so it can be restored the next time it's opened.
+    saveRepoSession() {
+        const self = this;
+        // The extra windows only show part of the repo, so let the main window be the one that's remembered.
+        if (self.layout !== null) {
+            return;
+        }
+        clearTimeout(self.saveRepoSessionTimeout);
+        self.saveRepoSessionTimeout = setTimeout(() => {
+            if ($('#repoView').is(':hidden')) {
+                return;
+            }
+            emit('save-repo-session', {
+                selected_sha: self.svgManager.selectedSHA === '' ? null : self.svgManager.selectedSHA,
+                active_tab: $('#changes-tab').hasClass('active') ? 'changes' : 'commits',
+                scroll_anchor_sha: self.svgManager.getAnchorSHA(),
+            }).then();
+        }, 1000);
+    }
+
+    restoreRepoSession(repoSession) {
+        const self = this;
+        if (repoSession['active_tab'] === 'changes') {
+            $('#changes-tab').tab('show');
+            self.truncateFilePathText();
+        }
+        if (repoSession['scroll_anchor_sha'] !== undefined) {
+            self.svgManager.scrollToCommit(repoSession['scroll_anchor_sha']);
+        }
+    }
+
     showWelcomeView() {
         $('#repoView').hide();
         $('#welcomeView').show();
@@ -553,6 +1886,33 @@ class Main {
         }
     }
 
+    async doExportGraph() {
+        const selected = await save({
+            defaultPath: await homeDir(),
+            filters: [
+                {name: 'SVG Image', extensions: ['svg']},
+                {name: 'PNG Image', extensions: ['png']},
+            ],
+        });
+        if (selected !== null) {
+            const format = selected.toLowerCase().endsWith('.png') ? 'png' : 'svg';
+            emit("export-graph", {path: selected, format: format}).then();
+        }
+    }
+
+    async doExportPatches(shas) {
+        const self = this,
+            selected = await open({
+            directory: true,
+            multiple: false,
+            defaultPath: await homeDir(),
+        });
+        if (selected !== null) {
+            self.addProcessCount();
+            emit("export-patches", {shas: shas, path: selected}).then();
+        }
+    }
+
     async doOpen() {
         const self = this,
             selected = await open({
@@ -598,6 +1958,7 @@ class Main {
         this.processCount--;
         if (this.processCount <= 0) {
             $('#mainSpinner').hide();
+            $('#submoduleProgressText').text('');
             // This should only happen when an error occurs on something that doesn't use the spinner
             if (this.processCount < 0) {
                 this.processCount = 0;
@@ -612,52 +1973,162 @@ class Main {
         $('#fileDiffTable').empty();
     }
 
-    selectRow($row, rowClassToDeselect, filePath, changeType, sha) {
+    selectRow($row, rowClassToDeselect, file, changeType, sha) {
         const self = this;
         self.unselectRows(rowClassToDeselect);
         $row.addClass('selected-row');
         $row.removeClass('hoverable-row');
         if (changeType === 'commit') {
-            self.selectedCommitInfoFilePath = filePath;
+            self.selectedCommitInfoFilePath = file['path'];
         } else if (changeType === 'unstaged' || changeType === 'staged') {
-            self.selectedFileChangedInfoFilePath = filePath;
+            self.selectedFileChangedInfoFilePath = file['path'];
         }
-        emit('file-diff', {file_path: filePath, change_type: changeType, sha: sha}).then();
+        self.showFileDiffUnlessCollapsed(file, changeType, () => self.requestFileDiff(file['path'], changeType, sha));
     }
 
-    showFileDiff(file_info) {
+    showFileDiffUnlessCollapsed(file, changeType, showDiff) {
         const self = this;
-        let $fileDiffTable;
-        if (file_info['change_type'] === 'commit') {
-            $fileDiffTable = $('#commitFileDiffTable');
-        } else if (file_info['change_type'] === 'unstaged' || file_info['change_type'] === 'staged') {
-            $fileDiffTable = $('#fileDiffTable');
+        if (!file['collapsed'] || self.shownCollapsedFiles.has(file['path'])) {
+            showDiff();
+            return;
         }
-
+        const $fileDiffTable = self.getFileDiffTable(changeType),
+            $collapsedRow = $('<tr><td colspan="4"><p class="text-grey">This file is marked as generated, so its diff is collapsed.</p><button type="button" class="btn btn-outline-light btn-sm">Show Diff</button></td></tr>');
+        $collapsedRow.find('button').click(() => {
+            self.shownCollapsedFiles.add(file['path']);
+            showDiff();
+        });
         $fileDiffTable.empty();
+        $fileDiffTable.append($collapsedRow);
+    }
+
+    requestFileDiff(filePath, changeType, sha) {
+        const self = this;
+        self.lastFileDiffRequest = {file_path: filePath, change_type: changeType, sha: sha, context_lines: self.diffContextLines.toString()};
+        emit('file-diff', self.lastFileDiffRequest).then();
+    }
+
+    getFileDiffTable(changeType) {
+        if (changeType === 'commit') {
+            return $('#commitFileDiffTable');
+        } else if (changeType === 'stash') {
+            return $('#stashFileDiffTable');
+        } else if (changeType === 'workdir') {
+            return $('#workdirFileDiffTable');
+        } else if (changeType === 'merge-base') {
+            return $('#mergeBaseFileDiffTable');
+        }
+        return $('#fileDiffTable');
+    }
+
+    getFileLineRow(line) {
+        let fileLineRow = '<tr><td class="line-no text-unselectable">';
+        if (typeof line === 'string') {
+            fileLineRow += '</td><td class="line-no text-unselectable"></td><td class="text-unselectable"></td><td class="line-content"><pre><code class="language-plaintext text-grey">' + line + '</code></pre></td></tr>';
+        } else {
+            if (line['origin'] === '+') {
+                fileLineRow = '<tr class="added-code-line"><td class="line-no text-unselectable">';
+            } else if (line['origin'] === '-') {
+                fileLineRow = '<tr class="removed-code-line"><td class="line-no text-unselectable">';
+            }
+            if (line['old_lineno'] !== null) {
+                fileLineRow += line['old_lineno'];
+            }
+            fileLineRow += '</td><td class="line-no text-unselectable">';
+            if (line['new_lineno'] !== null) {
+                fileLineRow += line['new_lineno'];
+            }
+            fileLineRow += '</td><td class="text-unselectable">' + line['origin'] + '</td><td class="line-content"><pre><code class="language-' + line['file_type'] + '">' + line['content'] + '</code></pre></td></tr>';
+        }
+        return $(fileLineRow);
+    }
+
+    // Lines between two hunks can come back for both of them, so lines another hunk already expanded to aren't added again.
+    showHunkContext(hunkContext) {
+        const self = this,
+            $fileDiffTable = self.getFileDiffTable(hunkContext['change_type']),
+            $hunkHeaderRow = $fileDiffTable.find('.hunk-header-row').eq(hunkContext['hunk_index']);
+        $fileDiffTable.find('.expanded-context-row[data-hunk-index="' + hunkContext['hunk_index'] + '"]').remove();
+        const shownLinenos = new Set($fileDiffTable.find('.expanded-context-row').map((i, row) => $(row).attr('data-new-lineno')).get());
+        const getContextRows = (lines) => lines.filter((line) => !shownLinenos.has(line['new_lineno'].toString())).map((line) => {
+            return self.getFileLineRow(line).addClass('expanded-context-row').attr('data-hunk-index', hunkContext['hunk_index']).attr('data-new-lineno', line['new_lineno']);
+        });
+        const $hunkRows = $hunkHeaderRow.nextUntil('.hunk-header-row, .load-more-hunks-row');
+        ($hunkRows.length > 0 ? $hunkRows.last() : $hunkHeaderRow).after(getContextRows(hunkContext['lines_after']));
+        $hunkHeaderRow.after(getContextRows(hunkContext['lines_before']));
+        hljs.highlightAll();
+    }
+
+    showFileDiff(file_info, isAppending) {
+        const self = this,
+            $fileDiffTable = self.getFileDiffTable(file_info['change_type']);
+
+        if (isAppending) {
+            $fileDiffTable.find('.load-more-hunks-row').remove();
+        } else {
+            $fileDiffTable.empty();
+        }
+        if (file_info['eol_only'] && !isAppending) {
+            const $eolRow = $('<tr><td class="line-no text-unselectable"></td><td class="line-no text-unselectable"></td><td class="text-unselectable"></td><td class="line-content"><pre><code class="language-plaintext text-grey"></code></pre></td></tr>');
+            let eolMessage = 'Only line endings changed in this file.';
+            if (file_info['eol_settings'].length > 0) {
+                eolMessage += ' Line ending settings: ' + file_info['eol_settings'].join(', ');
+            }
+            $eolRow.find('code').text(eolMessage);
+            $fileDiffTable.append($eolRow);
+        }
         file_info['file_lines'].forEach((line) => {
-            let fileLineRow = '<tr><td class="line-no text-unselectable">';
-            if (typeof line === 'string') {
-                fileLineRow += '</td><td class="line-no text-unselectable"></td><td class="text-unselectable"></td><td class="line-content"><pre><code class="language-plaintext text-grey">' + line + '</code></pre></td></tr>';
-            } else {
-                if (line['origin'] === '+') {
-                    fileLineRow = '<tr class="added-code-line"><td class="line-no text-unselectable">';
-                } else if (line['origin'] === '-') {
-                    fileLineRow = '<tr class="removed-code-line"><td class="line-no text-unselectable">';
-                }
-                if (line['old_lineno'] !== null) {
-                    fileLineRow += line['old_lineno'];
-                }
-                fileLineRow += '</td><td class="line-no text-unselectable">';
-                if (line['new_lineno'] !== null) {
-                    fileLineRow += line['new_lineno'];
-                }
-                fileLineRow += '</td><td class="text-unselectable">' + line['origin'] + '</td><td class="line-content"><pre><code class="language-' + line['file_type'] + '">' + line['content'] + '</code></pre></td></tr>';
+            const $fileLineRow = self.getFileLineRow(line);
+            if (typeof line === 'string' && line.startsWith('@@')) {
+                const hunkIndex = $fileDiffTable.find('.hunk-header-row').length,
+                    $expandBtn = $('<button type="button" class="btn btn-outline-light btn-sm py-0" title="Show More Context"><i class="fa-solid fa-arrows-up-down"></i></button>');
+                $expandBtn.click(() => {
+                    // Each click shows 10 more lines on each side.
+                    const extraLines = ($fileLineRow.data('extraLines') ?? 0) + 10;
+                    $fileLineRow.data('extraLines', extraLines);
+                    emit('expand-hunk-context', {
+                        file_path: file_info['file_path'],
+                        change_type: file_info['change_type'],
+                        sha: file_info['sha'],
+                        hunk_index: hunkIndex.toString(),
+                        context_lines: file_info['context_lines'].toString(),
+                        extra_lines: extraLines.toString(),
+                    }).then();
+                });
+                $fileLineRow.addClass('hunk-header-row');
+                $fileLineRow.children('td').first().append($expandBtn);
             }
-            $fileDiffTable.append($(fileLineRow));
+            $fileDiffTable.append($fileLineRow);
         });
+        if (file_info['skipped_blob'] !== null) {
+            const skippedBlob = file_info['skipped_blob'],
+                $skippedRow = $('<tr><td class="line-no text-unselectable"></td><td class="line-no text-unselectable"></td><td class="text-unselectable"></td><td class="line-content"><pre><code class="language-plaintext text-grey"></code></pre></td></tr>');
+            if (skippedBlob['kind'] === 'binary') {
+                $skippedRow.find('code').text('Binary file (' + skippedBlob['size'] + ' bytes) not shown.');
+            } else {
+                $skippedRow.find('code').text('File is too large to show (' + skippedBlob['size'] + ' bytes). The limit can be changed in Preferences.');
+            }
+            $fileDiffTable.append($skippedRow);
+        }
+        if (file_info['truncated']) {
+            const $loadMoreRow = $('<tr class="load-more-hunks-row"><td colspan="4"><button type="button" class="btn btn-outline-light btn-sm">Load More</button></td></tr>');
+            $loadMoreRow.find('button').click(() => {
+                emit('get-more-hunks', {
+                    file_path: file_info['file_path'],
+                    change_type: file_info['change_type'],
+                    sha: file_info['sha'],
+                    hunk_offset: file_info['next_hunk_offset'].toString(),
+                    context_lines: file_info['context_lines'].toString(),
+                }).then();
+            });
+            $fileDiffTable.append($loadMoreRow);
+        }
         hljs.highlightAll();
 
+        if (isAppending) {
+            return;
+        }
+
         if (file_info['change_type'] === 'commit') {
             $('#commitFileDiffTableContainer').scrollTop(self.commitFileDiffTableScrollTop);
             self.commitFileDiffTableScrollTop = 0;
@@ -667,6 +2138,40 @@ class Main {
         }
     }
 
+    addIssueLinkRuleRow(pattern, urlTemplate) {
+        const $row = $('<tr><td><input type="text" class="form-control issue-link-pattern-txt" placeholder="#([0-9]+)"></td><td><input type="text" class="form-control issue-link-url-txt" placeholder="https://example.com/issues/$1"></td><td><button type="button" class="btn btn-outline-danger btn-sm"><i class="fa-regular fa-trash-can"></i></button></td></tr>');
+        $row.find('.issue-link-pattern-txt').val(pattern);
+        $row.find('.issue-link-url-txt').val(urlTemplate);
+        $row.find('button').click(() => {
+            $row.remove();
+        });
+        $('#issueLinkRulesTableBody').append($row);
+    }
+
+    // The message is already HTML escaped by the back-end, and the link offsets are into that escaped text.
+    appendLinkedMessage($messageTxt, message, issueLinks) {
+        let lastEnd = 0;
+        issueLinks.forEach((issueLink) => {
+            $messageTxt.append(message.slice(lastEnd, issueLink['start']));
+            const $link = $('<a href="#"></a>').html(message.slice(issueLink['start'], issueLink['end'])).attr('title', issueLink['url']);
+            $link.click((e) => {
+                e.preventDefault();
+                openUrl(issueLink['url']).then();
+            });
+            $messageTxt.append($link);
+            lastEnd = issueLink['end'];
+        });
+        $messageTxt.append(message.slice(lastEnd));
+    }
+
+    formatTimeOffset(offsetMinutes) {
+        const sign = offsetMinutes < 0 ? '-' : '+',
+            absOffset = Math.abs(offsetMinutes),
+            hours = String(Math.floor(absOffset / 60)).padStart(2, '0'),
+            minutes = String(absOffset % 60).padStart(2, '0');
+        return sign + hours + minutes;
+    }
+
     showCommitInfo(commit_info) {
         const self = this,
             $commitInfo = $('#commit-info'),
@@ -684,19 +2189,32 @@ class Main {
         const $newCommitInfo = $(
             '<p>' +
             commit_info['sha'] +
-            '</p><p style="white-space: pre-wrap;">' +
-            commit_info['message'] +
-            '</p><table><tr><td><h5>Author</h5></td><td><h5>Committer</h5></td></tr><tr><td>' +
+            '</p><p class="commit-message-txt" style="white-space: pre-wrap;"></p><table><tr><td><h5>Author</h5></td><td><h5>Committer</h5></td></tr><tr><td>' +
             commit_info['author_name'] +
             '</td><td class="little-padding-left">' +
             commit_info['committer_name'] +
             '</td></tr><tr><td>' +
-            formattedAuthorTime +
+            commit_info['author_email'] +
             '</td><td class="little-padding-left">' +
-            formattedCommitterTime +
+            commit_info['committer_email'] +
+            '</td></tr><tr><td>' +
+            formattedAuthorTime + ' (' + self.formatTimeOffset(commit_info['author_time_offset']) + ')' +
+            '</td><td class="little-padding-left">' +
+            formattedCommitterTime + ' (' + self.formatTimeOffset(commit_info['committer_time_offset']) + ')' +
             '</td></tr></table>'
         );
+        self.appendLinkedMessage($newCommitInfo.filter('.commit-message-txt'), commit_info['message'], commit_info['issue_links']);
         $commitInfo.append($newCommitInfo);
+        if (commit_info['change_id'] !== null) {
+            const $changeId = $('<p class="text-grey"></p>');
+            $changeId.text('Change-Id: ' + commit_info['change_id']);
+            $commitInfo.append($changeId);
+        }
+        if (commit_info['replaced_by'] !== null) {
+            const $replacedBy = $('<p class="text-grey"></p>');
+            $replacedBy.text('Replaced by ' + commit_info['replaced_by'] + ' in refs/replace, which the graph doesn\'t follow.');
+            $commitInfo.append($replacedBy);
+        }
 
         const textJQueryElements = [];
         commit_info['changed_files'].forEach(function(file) {
@@ -709,12 +2227,12 @@ class Main {
                 return file['path'] === self.selectedCommitInfoFilePath;
             });
             if (changedFileIndex !== -1) {
-                self.selectRow(textJQueryElements[changedFileIndex], 'commitChangeFilePath', commit_info['changed_files'][changedFileIndex]['path'], 'commit', commit_info['sha']);
+                self.selectRow(textJQueryElements[changedFileIndex], 'commitChangeFilePath', commit_info['changed_files'][changedFileIndex], 'commit', commit_info['sha']);
                 foundFileToSelect = true;
             }
         }
         if (!foundFileToSelect && commit_info['changed_files'].length > 0) {
-            self.selectRow(textJQueryElements[0], 'commitChangeFilePath', commit_info['changed_files'][0]['path'], 'commit', commit_info['sha']);
+            self.selectRow(textJQueryElements[0], 'commitChangeFilePath', commit_info['changed_files'][0], 'commit', commit_info['sha']);
         }
         self.oldSelectedSHA = commit_info['sha'];
 
@@ -729,15 +2247,35 @@ class Main {
         self.updateGeneralInfo(repo_info["general_info"]);
         self.svgManager.updateGraph(repo_info["commit_info_list"], repo_info["general_info"]["head_sha"]);
         self.updateFilesChangedInfo(repo_info['files_changed_info_list']);
-        self.updateBranchInfo(repo_info["branch_info_list"]);
+        self.branchInfoList = repo_info["branch_info_list"];
+        self.refreshBranchInfo();
         self.updateRemoteInfo(repo_info["remote_info_list"]);
     }
 
     updateGeneralInfo(general_info) {
-        const self = this;
+        const self = this,
+            oldCommitPrefill = self.generalInfo['commit_prefill'] ?? '',
+            $summaryTxt = $('#summaryTxt');
         self.generalInfo = general_info;
 
-        $('#projectName').text(self.generalInfo['project_name']);
+        // Only replace the summary if the user hasn't typed anything past the last prefill.
+        if ($summaryTxt.val() === '' || $summaryTxt.val() === oldCommitPrefill) {
+            $summaryTxt.val(self.generalInfo['commit_prefill'] ?? '');
+            self.updateSummaryTxtCounter();
+        }
+
+        const $projectName = $('#projectName');
+        $projectName.text(self.generalInfo['project_name']);
+        let projectNameTitle = self.generalInfo['repo_root'];
+        if (self.generalInfo['discovered_from'] !== '') {
+            projectNameTitle += '\nFound by searching up from ' + self.generalInfo['discovered_from'];
+        }
+        $projectName.attr('title', projectNameTitle + '\nGit directory: ' + self.generalInfo['git_dir']);
+        if (self.generalInfo['is_worktree'] === 'true') {
+            const $worktreeOf = $('<small class="text-grey"></small>');
+            $worktreeOf.text(' (worktree of ' + self.generalInfo['main_repo_path'] + ')');
+            $projectName.append($worktreeOf);
+        }
 
         if (self.generalInfo['is_cherrypicking'] === "true") {
             self.showCherrypickControls();
@@ -747,9 +2285,32 @@ class Main {
             self.showMergeControls();
         } else if (self.generalInfo['is_rebasing'] === "true") {
             self.showRebaseControls();
+        } else if (self.generalInfo['is_applying_stash'] === "true") {
+            self.showStashApplyControls();
         } else {
             self.showCommitControls();
         }
+
+        if (self.generalInfo['is_unborn'] === 'true') {
+            const $unbornMessage = $('<p class="text-grey"></p>');
+            $unbornMessage.text('No commits yet on ' + self.generalInfo['unborn_branch_name'] + '. Stage some changes and commit to create it.');
+            $('#commit-info').empty().append($unbornMessage);
+        }
+
+        const headHasUpstream = self.generalInfo['head_has_upstream'] === 'true',
+            $headDivergence = $('#headDivergence');
+        $headDivergence.text('\u2191' + self.generalInfo['head_ahead'] + ' \u2193' + self.generalInfo['head_behind']);
+        $headDivergence.attr('title', self.generalInfo['head_ahead'] + ' commit(s) to push, ' + self.generalInfo['head_behind'] + ' commit(s) to pull');
+        $headDivergence.prop('hidden', !headHasUpstream);
+        // Pulling needs an upstream to pull from.
+        $('#pullBtn').prop('disabled', !headHasUpstream);
+
+        $('#readOnlyBanner').prop('hidden', self.generalInfo['read_only'] !== 'true');
+
+        // Bare, corrupt, or read-only repos can still be browsed, but not committed to.
+        const canCommit = self.generalInfo['can_commit'] !== 'false';
+        $('#commitBtn').prop('disabled', !canCommit);
+        $('#commitPushBtn').prop('disabled', !canCommit);
     }
 
     prependFileIcon($row, status) {
@@ -763,6 +2324,8 @@ class Main {
             $row.prepend('<i class="fa-solid fa-circle-arrow-right" style="color:mediumpurple;"></i> ');
         } else if (status === 5) {  // Copied
             $row.prepend('<i class="fa-regular fa-copy" style="color:green;"></i> ');
+        } else if (status === 8) {  // Typechange
+            $row.prepend('<i class="fa-solid fa-right-left" style="color:goldenrod;"></i> ');
         } else if (status === 10) {  // Conflicted
             $row.prepend('<i class="fa-solid fa-triangle-exclamation" style="color:yellow;"></i> ');
         } else {  // Everything else
@@ -804,18 +2367,41 @@ class Main {
             // the next inner div is the "unshrunken" text size (i.e. what size the text should fit in)
             // and the last inner div is the size of the text width.
             // This is all used for truncating the text.
-            $text = $('<div class="hoverable-row text-unselectable flex-auto-in-row display-flex-row ' + rowClassToDeselect + '"><div class="flex-auto-in-row display-flex-row"><div><p class="file-path-txt" data-original-txt="' + file['path'] + '">' + file['path'] + '</p></div></div></div>');
+            $text = $('<div class="hoverable-row text-unselectable flex-auto-in-row display-flex-row ' + rowClassToDeselect + '"><div class="flex-auto-in-row display-flex-row"><div><p class="file-path-txt"></p></div></div></div>');
+        // The path is only an identifier, it's percent-encoded if the filename isn't valid unicode.
+        let displayPath = file['display_path'];
+        if (file['untracked_file_count'] !== null && file['untracked_file_count'] !== undefined) {
+            displayPath += ' (' + file['untracked_file_count'] + (file['is_untracked_file_count_capped'] ? '+' : '') + ' files)';
+        }
+        $text.find('.file-path-txt').attr('data-original-txt', displayPath).text(displayPath);
         self.prependFileIcon($text, file['status']);
+        if ((changeType === 'unstaged' || changeType === 'staged') && Object.hasOwn(self.lfsLocks, file['path'])) {
+            $text.append($('<i class="fa-solid fa-lock little-padding-left"></i>').attr('title', 'Locked by ' + self.lfsLocks[file['path']]));
+        }
+        if (file['collapsed']) {
+            $text.append($('<i class="fa-solid fa-gears little-padding-left text-grey"></i>').attr('title', 'Generated file, its diff is collapsed'));
+        }
         $text.click((e) => {
             e.stopPropagation();
             $('#contextMenu').hide();
-            self.selectRow($text, rowClassToDeselect, file['path'], changeType, sha);
+            // Untracked directories are collapsed and have no diff, so clicking one shows what's in it instead.
+            if (displayPath !== file['display_path']) {
+                self.addProcessCount();
+                emit('expand-untracked', file['path']).then();
+            } else {
+                self.selectRow($text, rowClassToDeselect, file, changeType, sha);
+            }
         });
         if (changeType === 'unstaged' || changeType === 'staged') {
             $text.contextmenu((e) => {
                 e.preventDefault();
                 self.showFileChangeContextMenu(e, file['path'], changeType, file['status']);
             });
+        } else if (changeType === 'commit') {
+            $text.contextmenu((e) => {
+                e.preventDefault();
+                self.showCommitFileContextMenu(e, file['path'], sha);
+            });
         }
         const $row = $('<div class="display-flex-row little-padding-bottom"></div>');
         $row.append($text);
@@ -826,9 +2412,27 @@ class Main {
         return $text;
     }
 
+    // Swaps the entries for the updated paths into the current lists, everything else in the update replaces what's there.
+    applyFilesChangedUpdate(filesChangedUpdate) {
+        const self = this,
+            updatedPaths = new Set(filesChangedUpdate['updated_paths']),
+            filesChangedInfo = Object.assign({}, filesChangedUpdate);
+        delete filesChangedInfo['updated_paths'];
+        ['unstaged_files', 'staged_files'].forEach((filesKey) => {
+            filesChangedInfo[filesKey] = self.filesChangedInfo[filesKey].filter((file) => !updatedPaths.has(file['path'])).concat(filesChangedUpdate[filesKey]);
+            filesChangedInfo[filesKey].sort((a, b) => a['path'] < b['path'] ? -1 : (a['path'] > b['path'] ? 1 : 0));
+        });
+        return filesChangedInfo;
+    }
+
     updateFilesChangedInfo(files_changed_info_list) {
         const self = this;
 
+        self.filesChangedInfo = files_changed_info_list;
+        self.lfsLocks = {};
+        (files_changed_info_list['lfs_locks'] ?? []).forEach((lfsLock) => {
+            self.lfsLocks[lfsLock['path']] = lfsLock['owner'];
+        });
         self.fileDiffTableScrollTop = $('#fileDiffTableContainer').scrollTop();
         self.unselectRows('changeFilePath');
 
@@ -844,15 +2448,19 @@ class Main {
         $unstagedChanges.empty();
         $stagedChanges.empty();
 
-        const textJQueryElements = [];
+        // The row of each file shown, by change type and then path, so the selected file can be found again.
+        const fileRows = {unstaged: new Map(), staged: new Map()};
         // Unstaged changes
         files_changed_info_list['unstaged_files'].forEach(function(unstagedFile) {
+            if (self.layout === 'conflicts' && unstagedFile['status'] !== 10) {  // Conflicted
+                return;
+            }
             const $button = $('<button type="button" class="btn btn-success btn-sm right"><i class="fa-solid fa-plus"></i></button>');
             $button.click(function(e) {
                 e.stopPropagation();
                 emit('stage', unstagedFile).then();
             });
-            textJQueryElements.push(self.addFileChangeRow($unstagedChanges, $button, 'changeFilePath', unstagedFile, 'unstaged', ''));
+            fileRows['unstaged'].set(unstagedFile['path'], {file: unstagedFile, $element: self.addFileChangeRow($unstagedChanges, $button, 'changeFilePath', unstagedFile, 'unstaged', '')});
         });
 
         // Staged changes
@@ -862,21 +2470,41 @@ class Main {
                 e.stopPropagation();
                 emit('unstage', stagedFile).then();
             });
-            textJQueryElements.push(self.addFileChangeRow($stagedChanges, $button, 'changeFilePath', stagedFile, 'staged', ''));
+            fileRows['staged'].set(stagedFile['path'], {file: stagedFile, $element: self.addFileChangeRow($stagedChanges, $button, 'changeFilePath', stagedFile, 'staged', '')});
         });
 
-        let changeType = 'unstaged';
-        let changedFileIndex = files_changed_info_list['unstaged_files'].findIndex(function(file) {
-            return file['path'] === self.selectedFileChangedInfoFilePath;
-        });
-        if (changedFileIndex === -1) {
-            changeType = 'staged';
-            changedFileIndex = files_changed_info_list['staged_files'].findIndex(function(file) {
-                return file['path'] === self.selectedFileChangedInfoFilePath;
+        // Files with assume-unchanged or skip-worktree don't show up as changed, so list them separately.
+        const $flaggedFiles = $('#flaggedFiles'),
+            flaggedFiles = files_changed_info_list['flagged_files'] ?? [];
+        $flaggedFiles.empty();
+        flaggedFiles.forEach((flaggedFile) => {
+            const flags = [];
+            if (flaggedFile['assume_unchanged']) {
+                flags.push('assume-unchanged');
+            }
+            if (flaggedFile['skip_worktree']) {
+                flags.push('skip-worktree');
+            }
+            const $row = $('<div class="display-flex-row little-padding-bottom"><p class="flex-auto-in-row"></p></div>');
+            $row.find('p').text(flaggedFile['display_path'] + ' (' + flags.join(', ') + ')');
+            const $clearBtn = $('<button type="button" class="btn btn-warning btn-sm right">Clear</button>');
+            $clearBtn.click(() => {
+                if (flaggedFile['assume_unchanged']) {
+                    emit('set-index-flag', {path: flaggedFile['path'], flag: 'assume_unchanged', value: 'false'}).then();
+                }
+                if (flaggedFile['skip_worktree']) {
+                    emit('set-index-flag', {path: flaggedFile['path'], flag: 'skip_worktree', value: 'false'}).then();
+                }
             });
-        }
-        if (changedFileIndex !== -1) {
-            self.selectRow(textJQueryElements[changedFileIndex], 'changeFilePath', files_changed_info_list[changeType + '_files'][changedFileIndex]['path'], changeType, '');
+            $row.append($clearBtn);
+            $flaggedFiles.append($row);
+        });
+        $('#flaggedFilesSection').prop('hidden', flaggedFiles.length === 0);
+
+        const changeType = fileRows['unstaged'].has(self.selectedFileChangedInfoFilePath) ? 'unstaged' : 'staged',
+            selectedFileRow = fileRows[changeType].get(self.selectedFileChangedInfoFilePath);
+        if (selectedFileRow !== undefined) {
+            self.selectRow(selectedFileRow['$element'], 'changeFilePath', selectedFileRow['file'], changeType, '');
         }
 
         // This is a hacky way of waiting until the flexbox has shrunk before truncating text.
@@ -901,7 +2529,11 @@ class Main {
                     $innerListItem.append($('<span class="text-overflow-ellipsis flex-auto-in-row">' + child['text'] + '</span>'));
                 } else {
                     $innerListItem.addClass('hoverable-row text-unselectable inner-branch-item');
+                    $innerListItem.data('full-branch-name', child['branch_info']['full_branch_name']);
                     let childText = '';
+                    if (child['branch_info']['is_favorite'] === true) {
+                        childText += '<i class="fa-solid fa-star text-warning"></i> ';
+                    }
                     if (child['branch_info']['is_head'] === true) {
                         childText += '* ';
                     } else if (child['branch_info']['branch_type'] === 'local' && child['branch_info']['has_upstream'] === false) {
@@ -910,7 +2542,15 @@ class Main {
                         $innerListItem.attr('title', 'This branch has no upstream, consider pushing it!');
                     }
                     childText += child['text'];
-                    $innerListItem.append($('<span class="text-overflow-ellipsis flex-auto-in-row">' + childText + '</span>'));
+                    const $childTextSpan = $('<span class="text-overflow-ellipsis flex-auto-in-row">' + childText + '</span>');
+                    const lastCommit = child['branch_info']['last_commit'];
+                    if (lastCommit !== null) {
+                        $childTextSpan.attr('title', lastCommit['summary'] + '\n' + lastCommit['author_name'] + ', ' + lastCommit['relative_time']);
+                    }
+                    $innerListItem.append($childTextSpan);
+                    if (lastCommit !== null) {
+                        $innerListItem.append($('<small class="right text-grey little-padding-left"></small>').text(lastCommit['relative_time']));
+                    }
                     if (child['branch_info']['behind'] !== 0) {
                         const $behindCount = $('<span class="right"><i class="fa-solid fa-arrow-down"></i>' + child['branch_info']['behind'] + '</span>');
                         $innerListItem.append($behindCount);
@@ -930,14 +2570,24 @@ class Main {
                             self.addProcessCount();
                             emit("checkout", child['branch_info']['full_branch_name']).then();
                         });
+                    } else if (child['branch_info']['branch_type'] === 'other' && child['branch_info']['target_sha'] !== '') {
+                        $innerListItem.on('dblclick', function() {
+                            self.addProcessCount();
+                            emit("checkout-detached-head", child['branch_info']['target_sha']).then();
+                        });
                     }
                     $innerListItem.click(function() {
-                        self.svgManager.scrollToCommit(child['branch_info']['target_sha']);
-                        self.svgManager.selectRowViaSha(child['branch_info']['target_sha']);
+                        self.svgManager.scrollToRef(child['branch_info']['full_branch_name']);
+                        if (child['branch_info']['branch_type'] === 'tag') {
+                            // Tags only come with their names, so find out what they point at first.
+                            emit("get-tag-details", child['branch_info']['full_branch_name']).then();
+                        } else {
+                            self.svgManager.selectRowViaSha(child['branch_info']['target_sha']);
+                        }
                     });
                     $innerListItem.contextmenu(function(e) {
                         e.preventDefault();
-                        self.showBranchContextMenu(e, child['branch_info']['branch_shorthand'], child['branch_info']['full_branch_name'], child['branch_info']['branch_type'], child['branch_info']['has_upstream']);
+                        self.showBranchContextMenu(e, child['branch_info']['branch_shorthand'], child['branch_info']['full_branch_name'], child['branch_info']['branch_type'], child['branch_info']['has_upstream'], child['branch_info']['is_favorite']);
                     });
 
                     if ($innerListItem.attr('data-bs-toggle') !== undefined) {
@@ -952,11 +2602,24 @@ class Main {
         });
     }
 
+    // Large repos can have thousands of branches, so filtering is left to the back-end.
+    refreshBranchInfo() {
+        const pattern = $('#branchFilterTxt').val().trim();
+        if (pattern === '') {
+            if (this.branchInfoList !== null) {
+                this.updateBranchInfo(this.branchInfoList);
+            }
+        } else {
+            emit("filter-branches", pattern).then();
+        }
+    }
+
     updateBranchInfo(branch_info_list) {
         const self = this,
             $localBranches = $('#localBranches'),
             $remoteBranches = $('#remoteBranches'),
             $tags = $('#tags'),
+            $otherRefs = $('#otherRefs'),
             $stashes = $('#stashes');
 
         let activeTreeIds = [];
@@ -967,12 +2630,23 @@ class Main {
         $localBranches.empty();
         $remoteBranches.empty();
         $tags.empty();
+        $otherRefs.empty();
         $stashes.empty();
 
         // The root node is empty, so get its children.
         self.buildBranchResultHTML(branch_info_list['local_branch_info_tree']['children'], $localBranches, "localBranches");
         self.buildBranchResultHTML(branch_info_list['remote_branch_info_tree']['children'], $remoteBranches, "remoteBranches");
         self.buildBranchResultHTML(branch_info_list['tag_branch_info_tree']['children'], $tags, "tags");
+        if (branch_info_list['hidden_tag_count'] > 0) {
+            const $showMoreTagsItem = $('<li class="hoverable-row text-unselectable inner-branch-item text-grey"></li>');
+            $showMoreTagsItem.text('Show More Tags (' + branch_info_list['hidden_tag_count'] + ' Hidden)');
+            $showMoreTagsItem.click(() => {
+                self.addProcessCount();
+                emit("show-more-tags").then();
+            });
+            $tags.append($showMoreTagsItem);
+        }
+        self.buildBranchResultHTML(branch_info_list['other_ref_info_tree']['children'], $otherRefs, "otherRefs");
 
         branch_info_list['stash_info_list'].forEach((stashInfo) => {
             const $stashItem = $('<li class="hoverable-row text-unselectable inner-branch-item"></li>');
@@ -997,6 +2671,10 @@ class Main {
     }
 
     updateRemoteInfo(remote_info_list) {
+        this.remoteInfoList = remote_info_list;
+        if ($('#remoteRefspecsModal').hasClass('show')) {
+            this.showRemoteRefspecs($('#refspecsRemoteName').text());
+        }
         if (remote_info_list.length > 0) {
             const $remoteSelect = $('#remoteSelect'),
                 $remoteTagSelect = $('#remoteTagSelect');
@@ -1004,11 +2682,12 @@ class Main {
             $remoteTagSelect.empty();
 
             remote_info_list.forEach((remoteResult) => {
+                const remoteName = remoteResult['name'];
                 let option = '';
-                if (remoteResult === 'origin') {
-                    option = '<option value="' + remoteResult + '" selected>' + remoteResult + '</option>';
+                if (remoteName === 'origin') {
+                    option = '<option value="' + remoteName + '" selected>' + remoteName + '</option>';
                 } else {
-                    option = '<option value="' + remoteResult + '">' + remoteResult + '</option>';
+                    option = '<option value="' + remoteName + '">' + remoteName + '</option>';
                 }
                 $remoteSelect.append(option);
                 $remoteTagSelect.append(option);
@@ -1016,6 +2695,28 @@ class Main {
         }
     }
 
+    showRemoteRefspecs(remoteName) {
+        const remoteInfo = this.remoteInfoList.find((r) => r['name'] === remoteName),
+            $remoteRefspecsTableBody = $('#remoteRefspecsTableBody');
+        $('#refspecsRemoteName').text(remoteName);
+        $remoteRefspecsTableBody.empty();
+        if (remoteInfo === undefined) {
+            return;
+        }
+        [['fetch', remoteInfo['fetch_refspecs']], ['push', remoteInfo['push_refspecs']]].forEach(([direction, refspecs]) => {
+            refspecs.forEach((refspec) => {
+                const $removeBtn = $('<button type="button" class="btn btn-outline-danger btn-sm"><i class="fa-solid fa-trash"></i></button>');
+                $removeBtn.click(() => {
+                    emit("remove-remote-refspec", {remote_name: remoteName, direction: direction, refspec: refspec}).then();
+                });
+                $remoteRefspecsTableBody.append($('<tr></tr>')
+                    .append($('<td></td>').text(direction))
+                    .append($('<td></td>').append($('<code></code>').text(refspec)))
+                    .append($('<td></td>').append($removeBtn)));
+            });
+        });
+    }
+
     showRemoteBranchesHeaderContextMenu(event) {
         const $contextMenu = $('#contextMenu');
         $contextMenu.empty();
@@ -1046,10 +2747,90 @@ class Main {
         });
         $contextMenu.append($discardBtn);
 
+        if (status === 10) {  // Conflicted
+            const $showConflictBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-triangle-exclamation"></i> Show Conflict</button>');
+            $showConflictBtn.click(() => {
+                this.addProcessCount();
+                emit("get-conflicts-info", path).then();
+            });
+            $contextMenu.append($showConflictBtn);
+
+            const $resolveConflictsBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-code-merge"></i> Resolve Conflicts</button>');
+            $resolveConflictsBtn.click(() => {
+                this.addProcessCount();
+                emit("get-conflict-regions", path).then();
+            });
+            $contextMenu.append($resolveConflictsBtn);
+        }
+
+        if (this.generalInfo['is_lfs_enabled'] === 'true') {
+            if (Object.hasOwn(this.lfsLocks, path)) {
+                const $unlockBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-lock-open"></i> Unlock (LFS)</button>');
+                $unlockBtn.click(() => {
+                    emit("lfs-unlock", {path: path, force: 'false'}).then();
+                });
+                $contextMenu.append($unlockBtn);
+            } else {
+                const $lockBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-lock"></i> Lock (LFS)</button>');
+                $lockBtn.click(() => {
+                    emit("lfs-lock", path).then();
+                });
+                $contextMenu.append($lockBtn);
+            }
+        }
+
+        // Only files already in the index can be flagged.
+        if (status !== 1 && status !== 7) {
+            const $assumeUnchangedBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-eye-slash"></i> Assume Unchanged</button>');
+            $assumeUnchangedBtn.click(() => {
+                emit("set-index-flag", {path: path, flag: 'assume_unchanged', value: 'true'}).then();
+            });
+            $contextMenu.append($assumeUnchangedBtn);
+
+            const $skipWorktreeBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-eye-slash"></i> Skip Worktree</button>');
+            $skipWorktreeBtn.click(() => {
+                emit("set-index-flag", {path: path, flag: 'skip_worktree', value: 'true'}).then();
+            });
+            $contextMenu.append($skipWorktreeBtn);
+        }
+
+        $contextMenu.show();
+    }
+
+    showCommitFileContextMenu(event, path, sha) {
+        const self = this,
+            $contextMenu = $('#contextMenu');
+        $contextMenu.empty();
+        $contextMenu.css('left', event.pageX + 'px');
+        $contextMenu.css('top', event.pageY + 'px');
+
+        const $blameBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-user-pen"></i> Blame</button>');
+        $blameBtn.click(() => {
+            self.showBlame(path, sha);
+        });
+        $contextMenu.append($blameBtn);
+
+        const $fileHistoryBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-clock-rotate-left"></i> File History</button>');
+        $fileHistoryBtn.click(() => {
+            self.addProcessCount();
+            emit("get-file-history", {file_path: path, sha: sha, follow_renames: 'true', detect_copies: 'false'}).then();
+        });
+        $contextMenu.append($fileHistoryBtn);
+
         $contextMenu.show();
     }
 
-    showBranchContextMenu(event, branchShorthand, branchFullName, branchType, hasUpstream) {
+    showBlame(path, sha) {
+        this.addProcessCount();
+        emit("get-blame", {
+            file_path: path,
+            sha: sha,
+            use_ignore_revs: $('#blameIgnoreRevsCheckBox').is(':checked').toString(),
+            detect_copies: $('#detectCopiesCheckBox').is(':checked').toString(),
+        }).then();
+    }
+
+    showBranchContextMenu(event, branchShorthand, branchFullName, branchType, hasUpstream, isFavorite) {
         const self = this,
             $contextMenu = $('#contextMenu');
         $contextMenu.empty();
@@ -1065,13 +2846,46 @@ class Main {
             });
             $contextMenu.append($pushTagBtn);
 
+            const $tagInfoBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-circle-info"></i> Tag Info</button>');
+            $tagInfoBtn.click(() => {
+                emit("get-tag-info", branchFullName).then();
+            });
+            $contextMenu.append($tagInfoBtn);
+
             const $deleteBtn = $('<button type="button" class="btn btn-outline-danger btn-sm rounded-0 cm-item"><i class="fa-regular fa-trash-can"></i> Delete</button>');
             $deleteBtn.click(() => {
                 self.addProcessCount();
                 emit("delete-tag", branchShorthand).then();
             });
             $contextMenu.append($deleteBtn);
+        } else if (branchType === 'other') {
+            const $copyRefNameBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-regular fa-copy"></i> Copy Ref Name</button>');
+            $copyRefNameBtn.click(() => {
+                writeText(branchFullName).then();
+            });
+            $contextMenu.append($copyRefNameBtn);
+
+            if (branchFullName.startsWith('refs/replace/')) {
+                const $deleteReplaceBtn = $('<button type="button" class="btn btn-outline-danger btn-sm rounded-0 cm-item"><i class="fa-regular fa-trash-can"></i> Delete Replace Ref</button>');
+                $deleteReplaceBtn.click(() => {
+                    emit("delete-replace", branchFullName.substring('refs/replace/'.length)).then();
+                });
+                $contextMenu.append($deleteReplaceBtn);
+            }
         } else {
+            // What merging the branch into HEAD would bring in, ignoring anything that's only changed on HEAD since.
+            const $mergeBaseDiffBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-code-compare"></i> Review Changes Since Merge Base</button>');
+            $mergeBaseDiffBtn.click(() => {
+                emit("diff-merge-base", {branch_a: 'HEAD', branch_b: branchFullName}).then();
+            });
+            $contextMenu.append($mergeBaseDiffBtn);
+
+            const $previewMergeBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-code-merge"></i> Preview Merge Into Current Branch</button>');
+            $previewMergeBtn.click(() => {
+                emit("preview-merge", {ours: 'HEAD', theirs: branchFullName}).then();
+            });
+            $contextMenu.append($previewMergeBtn);
+
             const $deleteBtn = $('<button type="button" class="btn btn-outline-danger btn-sm rounded-0 cm-item"><i class="fa-regular fa-trash-can"></i> Delete</button>');
             if (branchType === 'local') {
                 $deleteBtn.click(() => {
@@ -1084,18 +2898,95 @@ class Main {
                     $('#deleteLocalBranchModal').modal('show');
                 });
 
-                const $fastForwardBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-arrow-down"></i> Fast-forward to Remote Branch</button>');
-                $fastForwardBtn.click(() => {
+                const $favoriteBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"></button>');
+                if (isFavorite === true) {
+                    $favoriteBtn.html('<i class="fa-regular fa-star"></i> Unpin from Top');
+                } else {
+                    $favoriteBtn.html('<i class="fa-solid fa-star"></i> Pin to Top');
+                }
+                $favoriteBtn.click(() => {
+                    self.addProcessCount();
+                    emit("set-branch-favorite", {full_branch_name: branchFullName, is_favorite: (isFavorite !== true).toString()}).then();
+                });
+                $contextMenu.append($favoriteBtn);
+
+                // Without an upstream there's nothing to fast-forward to.
+                if (hasUpstream === true) {
+                    const $fastForwardBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-arrow-down"></i> Fast-forward to Remote Branch</button>');
+                    $fastForwardBtn.click(() => {
+                        self.addProcessCount();
+                        emit("fast-forward-branch", branchShorthand).then();
+                    });
+
+                    $contextMenu.append($fastForwardBtn);
+                }
+
+                const $pullBranchBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-cloud-arrow-down"></i> Pull</button>');
+                $pullBranchBtn.click(() => {
                     self.addProcessCount();
-                    emit("fast-forward-branch", branchShorthand).then();
+                    emit("pull-branch", branchShorthand).then();
+                });
+                $contextMenu.append($pullBranchBtn);
+
+                const $pushBranchBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-cloud-arrow-up"></i> Push</button>');
+                $pushBranchBtn.click(() => {
+                    if (hasUpstream === true) {
+                        $('#remoteSelect').hide();
+                    } else {
+                        $('#remoteSelect').show();
+                    }
+                    $('#pushBranchShorthand').text(branchShorthand);
+                    $('#forcePushCheckBox').prop('checked', false);
+                    $('#pushModal').modal('show');
                 });
+                $contextMenu.append($pushBranchBtn);
 
-                $contextMenu.append($fastForwardBtn);
+                const $configureRemotesBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-gear"></i> Configure Remotes</button>');
+                $configureRemotesBtn.click(() => {
+                    emit("get-branch-remotes", branchShorthand).then();
+                });
+                $contextMenu.append($configureRemotesBtn);
             } else if (branchType === 'remote') {
                 $deleteBtn.click(() => {
                     self.addProcessCount();
                     emit("delete-remote-branch", branchShorthand).then();
                 });
+
+                // Remote branch shorthands look like <remote>/<branch>.
+                const slashIndex = branchShorthand.indexOf('/'),
+                    remoteName = branchShorthand.substring(0, slashIndex),
+                    remoteBranchName = branchShorthand.substring(slashIndex + 1);
+
+                const $setRemoteHeadBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-bullseye"></i> Set as Remote HEAD</button>');
+                $setRemoteHeadBtn.click(() => {
+                    self.addProcessCount();
+                    emit("set-remote-head", {remote_name: remoteName, branch: remoteBranchName}).then();
+                });
+                $contextMenu.append($setRemoteHeadBtn);
+
+                const $remoteHeadInfoBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-circle-info"></i> Remote HEAD Info</button>');
+                $remoteHeadInfoBtn.click(() => {
+                    emit("get-remote-head", remoteName).then();
+                });
+                $contextMenu.append($remoteHeadInfoBtn);
+
+                const $editRemoteURLsBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-link"></i> Edit Remote URLs</button>');
+                $editRemoteURLsBtn.click(() => {
+                    const remoteInfo = self.remoteInfoList.find((r) => r['name'] === remoteName);
+                    $('#editRemoteName').text(remoteName);
+                    $('#editRemoteURLTxt').val(remoteInfo === undefined || remoteInfo['url'] === null ? '' : remoteInfo['url']);
+                    $('#editRemotePushURLTxt').val(remoteInfo === undefined || remoteInfo['push_url'] === null ? '' : remoteInfo['push_url']);
+                    $('#editRemoteURLsModal').modal('show');
+                });
+                $contextMenu.append($editRemoteURLsBtn);
+
+                const $editRefspecsBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-code-branch"></i> Edit Refspecs</button>');
+                $editRefspecsBtn.click(() => {
+                    $('#newRefspecTxt').val('');
+                    self.showRemoteRefspecs(remoteName);
+                    $('#remoteRefspecsModal').modal('show');
+                });
+                $contextMenu.append($editRefspecsBtn);
             } else {
                 $deleteBtn.click(() => {
                     alert("Not implemented, sorry!");
@@ -1126,6 +3017,20 @@ class Main {
         });
         $contextMenu.append($applyBtn);
 
+        const $previewBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-eye"></i> Preview Stash</button>');
+        $previewBtn.click(() => {
+            emit("get-stash-diff", stashIndex.toString()).then();
+        });
+        $contextMenu.append($previewBtn);
+
+        const $toBranchBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-code-branch"></i> Create Branch from Stash</button>');
+        $toBranchBtn.click(() => {
+            $('#stashToBranchIndex').text(stashIndex.toString());
+            $('#stashToBranchNameTxt').val('');
+            $('#stashToBranchModal').modal('show');
+        });
+        $contextMenu.append($toBranchBtn);
+
         const $deleteBtn = $('<button type="button" class="btn btn-outline-danger btn-sm rounded-0 cm-item"><i class="fa-regular fa-trash-can"></i> Delete Stash</button>');
         $deleteBtn.click(() => {
             emit("delete-stash", stashIndex).then();
@@ -1143,6 +3048,7 @@ class Main {
         $('#rebaseControls').hide();
         $('#cherrypickControls').hide();
         $('#revertControls').hide();
+        $('#stashApplyControls').hide();
     }
 
     showMergeControls() {
@@ -1153,6 +3059,7 @@ class Main {
         $('#rebaseControls').hide();
         $('#cherrypickControls').hide();
         $('#revertControls').hide();
+        $('#stashApplyControls').hide();
     }
 
     showRebaseControls() {
@@ -1163,6 +3070,7 @@ class Main {
         $('#rebaseControls').show();
         $('#cherrypickControls').hide();
         $('#revertControls').hide();
+        $('#stashApplyControls').hide();
     }
 
     showCherrypickControls() {
@@ -1173,6 +3081,18 @@ class Main {
         $('#rebaseControls').hide();
         $('#cherrypickControls').show();
         $('#revertControls').hide();
+        $('#stashApplyControls').hide();
+    }
+
+    showStashApplyControls() {
+        $('#conflictWarningBanner').show();
+
+        $('#commitControls').hide();
+        $('#mergeControls').hide();
+        $('#rebaseControls').hide();
+        $('#cherrypickControls').hide();
+        $('#revertControls').hide();
+        $('#stashApplyControls').show();
     }
 
     showRevertControls() {
@@ -1183,6 +3103,7 @@ class Main {
         $('#rebaseControls').hide();
         $('#cherrypickControls').hide();
         $('#revertControls').show();
+        $('#stashApplyControls').hide();
     }
 }
 
@@ -1,4 +1,3 @@
-import {writeText} from "@tauri-apps/api/clipboard";
 import {emit} from "@tauri-apps/api/event";
 
 /**
@@ -11,17 +10,28 @@ export class SVGManager {
     RIGHT_TEXT_SPACING = 10;
     SCROLL_RENDERING_MARGIN = 100;
     SCROLLBAR_WIDTH = 12;  // If changing, be sure to update in CSS!
+    GRAPH_PAGE_ROWS = 100;
     /**
      * Constructs the svg manager.
      */
     constructor(mainJS) {
         this.commitColumn = document.getElementById('commitColumn');
         this.commitTableSVG = document.getElementById('commitTableSVG');
+        // Rows are fetched a page at a time as they're scrolled to, so the ones that haven't come back yet are left empty.
         this.rows = [];
+        this.totalRows = 0;
+        this.requestedPages = new Set();
+        this.shaRows = {};
+        this.rowSHAs = [];
+        this.refRows = {};
+        this.branchDrawProperties = {};
         this.commitsTop = -99;
         this.commitsBottom = -99;
         this.selectedSHA = '';
+        this.selectedSHAs = [];
         this.mainJS = mainJS;
+        // Where the "and N more" badge was clicked, so the list of all badges opens there once it comes back.
+        this.allBadgesMenuPosition = {x: 0, y: 0};
         this.setScrollEvent();
     }
 
@@ -44,64 +54,105 @@ export class SVGManager {
         const self = this,
             singleCharWidth = self.getSingleCharWidth();
 
-        for (let i = 0; i < self.rows.length; i++) {
-            self.removeBranchLabels(self.rows[i]);
+        self.refRows = commitsInfo['ref_rows'];
+        self.branchDrawProperties = {};
+        for (const [sha, branches] of commitsInfo['branch_draw_properties']) {
+            self.branchDrawProperties[sha] = branches;
         }
 
-        const graphWidth = Number(self.commitTableSVG.getAttribute('width'));
-        if (commitsInfo['svg_row_draw_properties'].length > 0) {
+        if (commitsInfo['sha_rows'] !== null) {
+            // The graph changed, so the rows fetched so far are out of date.
+            self.shaRows = commitsInfo['sha_rows'];
+            self.rowSHAs = [];
+            for (const [sha, rowIndex] of Object.entries(self.shaRows)) {
+                self.rowSHAs[rowIndex] = sha;
+            }
+            self.totalRows = commitsInfo['total_rows'];
             self.rows = [];
+            self.requestedPages.clear();
+            self.commitTableSVG.innerHTML = '';
+        } else {
+            self.rows.forEach((row, rowIndex) => {
+                self.removeBranchLabels(row);
+                self.addBranchLabels(rowIndex, singleCharWidth);
+                self.truncateSummaryTxt(rowIndex, singleCharWidth);
+            });
+        }
 
-            for (let i = 0; i < commitsInfo['svg_row_draw_properties'].length; i++) {
-                const commit = commitsInfo['svg_row_draw_properties'][i];
-                const elements = commit['elements'];
-                let row = {'sha': commit['sha'], 'pixel_y': commit['pixel_y'], 'lines': [], 'branches': [], 'circle': null, 'summaryTxt': null, 'authorName': null, 'authorTime': null, 'backRect': null};
-                for (const childLine of elements['child_lines']) {
-                    const line = self.makeSVG(childLine['tag'], childLine['attrs']);
-                    if (childLine['row-y'] < i) {
-                        self.rows[childLine['row-y']]['lines'].push(line);
-                    } else if (childLine['row-y'] === i) {
-                        row['lines'].push(line);
-                    } else {
-                        console.error("ERROR: A child line is trying to be added after the current node!");
-                    }
-                }
-                row['circle'] = self.makeSVG(elements['circle']['tag'], elements['circle']['attrs']);
-
-                const summaryTxt = self.makeSVG(elements['summary_text']['tag'], elements['summary_text']['attrs']);
-                summaryTxt.textContent = elements['summary_text']['textContent'];
-                row['summaryTxt'] = summaryTxt;
-
-                const authorTimeX = graphWidth - (elements['author_time']['textContent'].length * singleCharWidth) - self.RIGHT_TEXT_SPACING;
-                elements['author_time']['attrs']['x'] = authorTimeX;
-                const authorTime = self.makeSVG(elements['author_time']['tag'], elements['author_time']['attrs']);
-                authorTime.textContent = elements['author_time']['textContent'];
-                row['authorTime'] = authorTime;
-
-                elements['author_name']['attrs']['x'] = authorTimeX - (elements['author_name']['textContent'].length * singleCharWidth) - self.RIGHT_TEXT_SPACING;
-                const authorName = self.makeSVG(elements['author_name']['tag'], elements['author_name']['attrs']);
-                authorName.textContent = elements['author_name']['textContent'];
-                row['authorName'] = authorName;
-
-                elements['back_rect']['attrs']['width'] = graphWidth - elements['circle']['attrs']['cx'];
-                const backRect = self.makeSVG(elements['back_rect']['tag'], elements['back_rect']['attrs']);
-                backRect.onclick = self.getClickFunction(commit['sha']);
-                backRect.ondblclick = self.getDblClickFunction(commit['sha']);
-                backRect.oncontextmenu = self.getContextFunction(commit['sha']);
-                row['backRect'] = backRect;
-
-                self.rows.push(row);
-            }
+        self.commitTableSVG.setAttribute('height', ((self.totalRows + 1) * self.Y_SPACING).toString());
+        self.setVisibleCommits();
+        self.selectRowOnRefresh(headSHA);
+    }
+
+    /**
+     * Adds a page of rows from get-graph-rows.
+     */
+    addGraphRows(graphRows) {
+        const self = this,
+            singleCharWidth = self.getSingleCharWidth(),
+            graphWidth = Number(self.commitTableSVG.getAttribute('width')),
+            rowDrawProperties = graphRows['svg_row_draw_properties'];
+
+        // Rows asked for before the graph last changed are out of date, the page is asked for again after the change.
+        if (graphRows['total_rows'] !== self.totalRows || rowDrawProperties.some((commit, i) => self.rowSHAs[graphRows['start'] + i] !== commit['sha'])) {
+            return;
         }
 
-        self.addBranchLabels(commitsInfo['branch_draw_properties'], singleCharWidth);
-        for (let i = 0; i < self.rows.length; i++) {
-            self.truncateSummaryTxt(i, singleCharWidth);
+        for (const [sha, branches] of graphRows['branch_draw_properties']) {
+            self.branchDrawProperties[sha] = branches;
         }
+        rowDrawProperties.forEach((commit, i) => {
+            const rowIndex = graphRows['start'] + i;
+            self.rows[rowIndex] = self.makeRow(commit, graphWidth, singleCharWidth);
+            self.addBranchLabels(rowIndex, singleCharWidth);
+            self.truncateSummaryTxt(rowIndex, singleCharWidth);
+        });
 
-        self.setVisibleCommits();
-        self.commitTableSVG.setAttribute('height', ((self.rows.length + 1) * self.Y_SPACING).toString());
-        self.selectRowOnRefresh(headSHA);
+        self.renderVisibleCommits();
+    }
+
+    makeRow(commit, graphWidth, singleCharWidth) {
+        const self = this,
+            elements = commit['elements'];
+        let row = {'sha': commit['sha'], 'pixel_y': commit['pixel_y'], 'lines': [], 'branches': [], 'circle': null, 'summaryTxt': null, 'authorName': null, 'authorTime': null, 'backRect': null};
+        // Each row comes with the lines drawn on it, including the ones from commits further down.
+        for (const childLine of elements['child_lines']) {
+            row['lines'].push(self.makeSVG(childLine['tag'], childLine['attrs']));
+        }
+        row['circle'] = self.makeSVG(elements['circle']['tag'], elements['circle']['attrs']);
+        if (commit['boundary'] !== undefined) {
+            const boundaryTitle = self.makeSVG('title', {});
+            boundaryTitle.textContent = self.getBoundaryDescription(commit['boundary']);
+            row['circle'].appendChild(boundaryTitle);
+        }
+
+        const summaryTxt = self.makeSVG(elements['summary_text']['tag'], elements['summary_text']['attrs']);
+        summaryTxt.textContent = elements['summary_text']['textContent'];
+        row['summaryTxt'] = summaryTxt;
+
+        const authorTimeX = graphWidth - (elements['author_time']['textContent'].length * singleCharWidth) - self.RIGHT_TEXT_SPACING;
+        elements['author_time']['attrs']['x'] = authorTimeX;
+        const authorTime = self.makeSVG(elements['author_time']['tag'], elements['author_time']['attrs']);
+        authorTime.textContent = elements['author_time']['textContent'];
+        row['authorTime'] = authorTime;
+
+        elements['author_name']['attrs']['x'] = authorTimeX - (elements['author_name']['textContent'].length * singleCharWidth) - self.RIGHT_TEXT_SPACING;
+        const authorName = self.makeSVG(elements['author_name']['tag'], elements['author_name']['attrs']);
+        authorName.textContent = elements['author_name']['textContent'];
+        row['authorName'] = authorName;
+
+        elements['back_rect']['attrs']['width'] = graphWidth - elements['circle']['attrs']['cx'];
+        const backRect = self.makeSVG(elements['back_rect']['tag'], elements['back_rect']['attrs']);
+        backRect.onclick = self.getClickFunction(commit['sha']);
+        backRect.ondblclick = self.getDblClickFunction(commit['sha']);
+        backRect.oncontextmenu = self.getContextFunction(commit['sha'], commit['boundary']);
+        row['backRect'] = backRect;
+        // The row may have been selected before it was fetched.
+        if (self.selectedSHAs.includes(commit['sha'])) {
+            self.setRowSelectedClass(row, true);
+        }
+
+        return row;
     }
 
     selectRowOnRefresh(headSHA) {
@@ -112,9 +163,7 @@ export class SVGManager {
         let selectedIndex = 0;
         let foundOldSelected = false;
         if (self.selectedSHA !== '') {
-            const tempIndex = self.rows.findIndex(function(row) {
-                return row['sha'] === self.selectedSHA;
-            });
+            const tempIndex = self.getRowIndex(self.selectedSHA);
             if (tempIndex !== -1) {
                 selectedIndex = tempIndex;
                 foundOldSelected = true;
@@ -123,15 +172,13 @@ export class SVGManager {
             }
         }
         if (!foundOldSelected && headSHA !== '') {
-            const tempIndex = self.rows.findIndex(function(row) {
-                return row['sha'] === headSHA;
-            });
+            const tempIndex = self.getRowIndex(headSHA);
             if (tempIndex !== -1) {
                 selectedIndex = tempIndex;
             }
         }
-        if (selectedIndex >= 0 && selectedIndex < self.rows.length) {
-            self.selectRow(self.rows[selectedIndex]['backRect'], self.rows[selectedIndex]['sha']);
+        if (selectedIndex >= 0 && selectedIndex < self.totalRows) {
+            self.selectRow(self.rowSHAs[selectedIndex]);
         }
     }
 
@@ -149,37 +196,38 @@ export class SVGManager {
         }
     }
 
-    addBranchLabels(branchDrawProperties, singleCharWidth) {
-        const self = this;
-
-        for (let i = 0; i < branchDrawProperties.length; i++) {
-            const rowIndex = self.rows.findIndex(function (row) {
-                return row['sha'] === branchDrawProperties[i][0];
-            });
-            if (rowIndex !== -1) {
-                const summaryTxtElem = self.rows[rowIndex]['summaryTxt'];
-                const pixel_y = Number(self.rows[rowIndex]['circle'].getAttribute('cy'));
-                let currentPixelX = Number(summaryTxtElem.getAttribute('x'));
-                for (let j = 0; j < branchDrawProperties[i][1].length; j++) {
-                    const branch = branchDrawProperties[i][1][j];
-                    branch[0]['attrs']['x'] = currentPixelX;
-                    branch[0]['attrs']['y'] += pixel_y;
-                    const txtElem = self.makeSVG(branch[0]['tag'], branch[0]['attrs']);
-                    const box_width = singleCharWidth * branch[0]['textContent'].length + 10;
-
-                    branch[1]['attrs']['x'] = currentPixelX - 5;
-                    branch[1]['attrs']['y'] += pixel_y;
-                    branch[1]['attrs']['width'] = box_width;
-                    const rectElem = self.makeSVG(branch[1]['tag'], branch[1]['attrs']);
-                    txtElem.textContent = branch[0]['textContent'];
-
-                    self.rows[rowIndex]['branches'].push(rectElem);
-                    self.rows[rowIndex]['branches'].push(txtElem);
-
-                    currentPixelX += box_width + self.BRANCH_TEXT_SPACING;
-                    summaryTxtElem.setAttribute('x', currentPixelX.toString());
+    addBranchLabels(rowIndex, singleCharWidth) {
+        const self = this,
+            row = self.rows[rowIndex],
+            branches = self.branchDrawProperties[row['sha']] ?? [],
+            summaryTxtElem = row['summaryTxt'],
+            pixel_y = Number(row['circle'].getAttribute('cy'));
+        let currentPixelX = Number(summaryTxtElem.getAttribute('x'));
+        for (const branch of branches) {
+            // The same badges are drawn again on refreshes that don't change the graph, so they aren't changed in place.
+            const txtAttrs = Object.assign({}, branch[0]['attrs'], {x: currentPixelX, y: branch[0]['attrs']['y'] + pixel_y});
+            const txtElem = self.makeSVG(branch[0]['tag'], txtAttrs);
+            const box_width = singleCharWidth * branch[0]['textContent'].length + 10;
+
+            const rectAttrs = Object.assign({}, branch[1]['attrs'], {x: currentPixelX - 5, y: branch[1]['attrs']['y'] + pixel_y, width: box_width});
+            const rectElem = self.makeSVG(branch[1]['tag'], rectAttrs);
+            txtElem.textContent = branch[0]['textContent'];
+            for (const elem of [rectElem, txtElem]) {
+                if (branch[0]['attrs']['data-more-count'] !== undefined) {
+                    elem.onclick = self.getMoreBadgesClickFunction(row['sha']);
+                    elem.oncontextmenu = self.getMoreBadgesClickFunction(row['sha']);
+                    continue;
                 }
+                elem.onclick = self.getClickFunction(row['sha']);
+                elem.ondblclick = self.getBranchDblClickFunction(branch[0]['attrs']['data-ref-name']);
+                elem.oncontextmenu = self.getBranchContextFunction(branch[0]['attrs']['data-ref-name']);
             }
+
+            row['branches'].push(rectElem);
+            row['branches'].push(txtElem);
+
+            currentPixelX += box_width + self.BRANCH_TEXT_SPACING;
+            summaryTxtElem.setAttribute('x', currentPixelX.toString());
         }
     }
 
@@ -195,21 +243,24 @@ export class SVGManager {
         const self = this;
 
         let df = document.createDocumentFragment();
-        for (let i = self.commitsTop; i <= self.commitsBottom; i++) {
-            self.rows[i]['lines'].forEach((line) => {
+        // Rows that haven't been fetched yet are left blank until they come back.
+        const visibleRows = self.rows.slice(self.commitsTop, self.commitsBottom + 1).filter((row) => row !== undefined);
+        visibleRows.forEach((row) => {
+            row['lines'].forEach((line) => {
                 df.appendChild(line);
             });
-        }
+        });
 
-        for (let i = self.commitsTop; i <= self.commitsBottom; i++) {
-            df.appendChild(self.rows[i]['circle']);
-            df.appendChild(self.rows[i]['summaryTxt']);
-            df.appendChild(self.rows[i]['authorName']);
-            df.appendChild(self.rows[i]['authorTime']);
-            self.rows[i]['branches'].forEach((branch) => {
+        for (const row of visibleRows) {
+            df.appendChild(row['circle']);
+            df.appendChild(row['summaryTxt']);
+            df.appendChild(row['authorName']);
+            df.appendChild(row['authorTime']);
+            df.appendChild(row['backRect']);
+            // The branch badges go on top of the back rect so they can be clicked on.
+            row['branches'].forEach((branch) => {
                 df.appendChild(branch);
             });
-            df.appendChild(self.rows[i]['backRect']);
         }
 
         self.commitTableSVG.innerHTML = '';
@@ -223,49 +274,95 @@ export class SVGManager {
             newGraphWidth = $('#mainBody').width() - self.commitTableSVG.getBoundingClientRect().left - self.SCROLLBAR_WIDTH;
 
         self.commitTableSVG.setAttribute('width', newGraphWidth.toString());
-        for (let i = 0; i < self.rows.length; i++) {
-            const authorTimeX = newGraphWidth - (self.rows[i]['authorTime'].textContent.length * singleCharWidth) - self.RIGHT_TEXT_SPACING;
-            self.rows[i]['authorTime'].setAttribute('x', authorTimeX.toString());
-            self.rows[i]['authorName'].setAttribute('x', (authorTimeX - (self.rows[i]['authorName'].textContent.length * singleCharWidth) - self.RIGHT_TEXT_SPACING).toString());
-            self.rows[i]['backRect'].setAttribute('width', (newGraphWidth - Number(self.rows[i]['circle'].getAttribute('cx'))).toString());
-            self.truncateSummaryTxt(i, singleCharWidth);
-        }
+        self.rows.forEach((row, rowIndex) => {
+            const authorTimeX = newGraphWidth - (row['authorTime'].textContent.length * singleCharWidth) - self.RIGHT_TEXT_SPACING;
+            row['authorTime'].setAttribute('x', authorTimeX.toString());
+            row['authorName'].setAttribute('x', (authorTimeX - (row['authorName'].textContent.length * singleCharWidth) - self.RIGHT_TEXT_SPACING).toString());
+            row['backRect'].setAttribute('width', (newGraphWidth - Number(row['circle'].getAttribute('cx'))).toString());
+            self.truncateSummaryTxt(rowIndex, singleCharWidth);
+        });
     }
 
     setVisibleCommits() {
         const self = this;
-        if (self.rows.length > 0) {
+        if (self.totalRows > 0) {
             const renderingAreaTop = self.commitColumn.scrollTop - self.SCROLL_RENDERING_MARGIN,
                 renderingAreaBottom = self.commitColumn.scrollTop + self.commitColumn.clientHeight + self.SCROLL_RENDERING_MARGIN;
 
             // Convert from pixels to index.
             self.commitsTop = Math.max(Math.round((renderingAreaTop - self.Y_OFFSET) / self.Y_SPACING), 0);
-            self.commitsBottom = Math.min(Math.round((renderingAreaBottom - self.Y_OFFSET) / self.Y_SPACING), self.rows.length - 1);
+            self.commitsBottom = Math.min(Math.round((renderingAreaBottom - self.Y_OFFSET) / self.Y_SPACING), self.totalRows - 1);
 
+            self.requestVisiblePages();
             self.renderVisibleCommits();
         }
     }
 
+    // Asks for the pages of rows in view that haven't been asked for yet, they're drawn once they come back.
+    requestVisiblePages() {
+        const self = this,
+            firstPage = Math.floor(self.commitsTop / self.GRAPH_PAGE_ROWS),
+            lastPage = Math.floor(self.commitsBottom / self.GRAPH_PAGE_ROWS);
+        for (let page = firstPage; page <= lastPage; page++) {
+            if (!self.requestedPages.has(page)) {
+                self.requestedPages.add(page);
+                emit("get-graph-rows", {start: (page * self.GRAPH_PAGE_ROWS).toString(), end: ((page + 1) * self.GRAPH_PAGE_ROWS).toString()}).then();
+            }
+        }
+    }
+
+    getRowIndex(sha) {
+        const rowIndex = this.shaRows[sha];
+        if (rowIndex === undefined || rowIndex >= this.totalRows) {
+            return -1;
+        }
+        return rowIndex;
+    }
+
+    // Gets the commit in the middle of the view, which stays put when new commits push the rows down unlike scrollTop.
+    getAnchorSHA() {
+        const self = this,
+            rowIndex = Math.round((self.commitColumn.scrollTop + self.commitColumn.clientHeight / 2 - self.Y_OFFSET) / self.Y_SPACING);
+        if (rowIndex < 0 || rowIndex >= self.totalRows) {
+            return null;
+        }
+        return self.rowSHAs[rowIndex];
+    }
+
+    scrollToRef(refName) {
+        const self = this,
+            rowIndex = self.refRows[refName];
+        if (rowIndex !== undefined) {
+            self.scrollToRow(rowIndex);
+        }
+    }
+
     scrollToCommit(sha) {
         const self = this;
         if (sha !== '') {
-            const rowIndex = self.rows.findIndex(function(row) {
-                return row['sha'] === sha;
-            });
+            const rowIndex = self.getRowIndex(sha);
             if (rowIndex !== -1) {
-                const rowPixelY = rowIndex * self.Y_SPACING + self.Y_OFFSET;
-                const halfClientHeight = self.commitColumn.clientHeight / 2;
-                // scrollTop automatically bounds itself for negative numbers or numbers greater than the max scroll position.
-                self.commitColumn.scrollTop = rowPixelY - halfClientHeight;
-                self.setVisibleCommits();
+                self.scrollToRow(rowIndex);
             }
         }
     }
 
+    scrollToRow(rowIndex) {
+        const self = this;
+        if (rowIndex < self.totalRows) {
+            const rowPixelY = rowIndex * self.Y_SPACING + self.Y_OFFSET;
+            const halfClientHeight = self.commitColumn.clientHeight / 2;
+            // scrollTop automatically bounds itself for negative numbers or numbers greater than the max scroll position.
+            self.commitColumn.scrollTop = rowPixelY - halfClientHeight;
+            self.setVisibleCommits();
+        }
+    }
+
     setScrollEvent() {
         const self = this;
         self.commitColumn.addEventListener('scroll', () => {
             self.setVisibleCommits();
+            self.mainJS.saveRepoSession();
         });
     }
 
@@ -275,6 +372,15 @@ export class SVGManager {
      * @param {Object<string, number|string>} attrs
      * @return {SVGElement|SVGGraphicsElement}
      */
+    getBoundaryDescription(boundary) {
+        if (boundary === 'shallow') {
+            return 'History stops here because this is a shallow clone.';
+        } else if (boundary === 'grafted') {
+            return "This commit's parents are overridden by .git/info/grafts.";
+        }
+        return "This commit's parents aren't in the repository.";
+    }
+
     makeSVG(tag, attrs) {
         const el = document.createElementNS('http://www.w3.org/2000/svg', tag);
         // eslint-disable-next-line guard-for-in
@@ -284,64 +390,254 @@ export class SVGManager {
         return el;
     }
 
+    setRowSelectedClass(row, isSelected) {
+        if (isSelected) {
+            row['backRect'].classList.add('svg-selected-row');
+            row['backRect'].classList.remove('svg-hoverable-row');
+        } else {
+            row['backRect'].classList.remove('svg-selected-row');
+            row['backRect'].classList.add('svg-hoverable-row');
+        }
+    }
+
+    // Rows that haven't been fetched yet get their selected class when they're made.
+    setSHASelectedClass(sha, isSelected) {
+        const self = this,
+            rowIndex = self.getRowIndex(sha);
+        if (rowIndex !== -1 && self.rows[rowIndex] !== undefined) {
+            self.setRowSelectedClass(self.rows[rowIndex], isSelected);
+        }
+    }
+
     unselectAllRows() {
-        const svgRowElements = document.querySelectorAll('.svg-selected-row');
-        svgRowElements.forEach((svgRowElement) => {
-            svgRowElement.classList.remove('svg-selected-row');
-            svgRowElement.classList.add('svg-hoverable-row');
+        const self = this;
+        // Selected rows that are scrolled out of view aren't in the document, so go through the rows instead of querying for them.
+        self.selectedSHAs.forEach((selectedSHA) => {
+            self.setSHASelectedClass(selectedSHA, false);
         });
+        self.selectedSHAs = [];
 
         $('#commit-info').empty();
         $('#commitChanges').empty();
         $('#commitFileDiffTable').empty();
     }
 
-    selectRow(backRectElement, sha) {
+    selectRow(sha) {
         const self = this;
         self.unselectAllRows();
-        backRectElement.classList.add('svg-selected-row');
-        backRectElement.classList.remove('svg-hoverable-row');
         self.selectedSHA = sha;
+        self.selectedSHAs = [sha];
+        self.setSHASelectedClass(sha, true);
         // Will call start-process from back-end
         emit("get-commit-info", sha).then();
+        self.mainJS.saveRepoSession();
     }
 
     selectRowViaSha(sha) {
         const self = this;
-        const row = self.rows.find(function(row) {
-            return row['sha'] === sha;
-        });
-        if (row !== undefined) {
-            self.selectRow(row['backRect'], sha);
+        if (self.getRowIndex(sha) !== -1) {
+            self.selectRow(sha);
+        }
+    }
+
+    addToSelection(sha) {
+        const self = this;
+        if (self.getRowIndex(sha) !== -1 && !self.selectedSHAs.includes(sha)) {
+            self.setSHASelectedClass(sha, true);
+            self.selectedSHAs.push(sha);
+        }
+    }
+
+    removeFromSelection(sha) {
+        const self = this;
+        // The commit being shown always stays selected.
+        if (self.getRowIndex(sha) !== -1 && sha !== self.selectedSHA) {
+            self.setSHASelectedClass(sha, false);
+            self.selectedSHAs = self.selectedSHAs.filter((selectedSHA) => selectedSHA !== sha);
+        }
+    }
+
+    selectRange(sha) {
+        const self = this,
+            startIndex = self.getRowIndex(self.selectedSHA),
+            endIndex = self.getRowIndex(sha);
+        if (startIndex !== -1 && endIndex !== -1) {
+            for (let i = Math.min(startIndex, endIndex); i <= Math.max(startIndex, endIndex); i++) {
+                self.addToSelection(self.rowSHAs[i]);
+            }
         }
     }
 
     getClickFunction(sha) {
         const self = this;
         return function(event) {
-            self.selectRow(event.target, sha);
+            if (event.ctrlKey || event.metaKey) {
+                if (self.selectedSHAs.includes(sha)) {
+                    self.removeFromSelection(sha);
+                } else {
+                    self.addToSelection(sha);
+                }
+            } else if (event.shiftKey && self.selectedSHA !== '') {
+                self.selectRange(sha);
+            } else {
+                self.selectRow(sha);
+            }
         };
     }
 
+    showMultipleCommitsContextMenu(event) {
+        const self = this,
+            shas = [...self.selectedSHAs],
+            $contextMenu = $('#contextMenu');
+        $contextMenu.empty();
+        $contextMenu.css('left', event.pageX + 'px');
+        $contextMenu.css('top', event.pageY + 'px');
+
+        const $cherrypickBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-bullseye"></i> Cherrypick ' + shas.length + ' Commits</button>');
+        $cherrypickBtn.click(function() {
+            self.mainJS.addProcessCount();
+            emit("cherrypick-multiple", {shas: shas}).then();
+        });
+        $contextMenu.append($cherrypickBtn);
+
+        const $revertBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-rotate-left"></i> Revert ' + shas.length + ' Commits</button>');
+        $revertBtn.click(function() {
+            self.mainJS.addProcessCount();
+            emit("revert-multiple", {shas: shas}).then();
+        });
+        $contextMenu.append($revertBtn);
+
+        const $octopusMergeBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-code-merge"></i> Merge ' + shas.length + ' Commits Together</button>');
+        $octopusMergeBtn.click(function() {
+            self.mainJS.addProcessCount();
+            emit("merge", shas).then();
+        });
+        $contextMenu.append($octopusMergeBtn);
+
+        const $exportPatchesBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-file-export"></i> Export ' + shas.length + ' Patches</button>');
+        $exportPatchesBtn.click(async function() {
+            await self.mainJS.doExportPatches(shas);
+        });
+        $contextMenu.append($exportPatchesBtn);
+
+        const $copyShasBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-regular fa-clipboard"></i> Copy ' + shas.length + ' SHAs</button>');
+        $copyShasBtn.click(function() {
+            emit("get-shas-text", {shas: shas}).then();
+        });
+        $contextMenu.append($copyShasBtn);
+
+        $contextMenu.show();
+    }
+
     getDblClickFunction(sha) {
         return function(event) {
             emit("checkout-detached-head", sha).then();
         }
     }
 
+    getBranchDblClickFunction(refName) {
+        const self = this;
+        return function(event) {
+            event.stopPropagation();
+            self.mainJS.addProcessCount();
+            emit("checkout", refName).then();
+        };
+    }
+
+    getMoreBadgesClickFunction(sha) {
+        const self = this;
+        return function(event) {
+            event.preventDefault();
+            event.stopPropagation();
+            self.allBadgesMenuPosition = {x: event.pageX, y: event.pageY};
+            emit("get-all-badges", sha).then();
+        };
+    }
+
+    /**
+     * Lists every branch and tag of a commit, each one opens the same menu as right-clicking its badge.
+     */
+    showAllBadgesMenu(allBadges) {
+        const self = this,
+            $contextMenu = $('#contextMenu');
+        $contextMenu.empty();
+        $contextMenu.css('left', self.allBadgesMenuPosition.x + 'px');
+        $contextMenu.css('top', self.allBadgesMenuPosition.y + 'px');
+
+        for (const branch of allBadges['branch_draw_properties']) {
+            const $badgeBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"></button>');
+            $badgeBtn.text(branch[0]['textContent']);
+            $badgeBtn.click(function(event) {
+                self.getBranchContextFunction(branch[0]['attrs']['data-ref-name'])(event);
+            });
+            $contextMenu.append($badgeBtn);
+        }
+
+        $contextMenu.show();
+    }
+
+    getBranchContextFunction(refName) {
+        const self = this;
+        return function(event) {
+            event.preventDefault();
+            event.stopPropagation();
+            const $contextMenu = $('#contextMenu');
+            $contextMenu.empty();
+            $contextMenu.css('left', event.pageX + 'px');
+            $contextMenu.css('top', event.pageY + 'px');
+
+            const $checkoutBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-check"></i> Checkout</button>');
+            $checkoutBtn.click(function() {
+                self.mainJS.addProcessCount();
+                emit("checkout", refName).then();
+            });
+            $contextMenu.append($checkoutBtn);
+
+            const $mergeBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-code-merge"></i> Merge</button>');
+            $mergeBtn.click(function() {
+                self.mainJS.addProcessCount();
+                emit("merge", refName).then();
+            });
+            $contextMenu.append($mergeBtn);
+
+            if (refName !== 'HEAD') {
+                const $deleteBtn = $('<button type="button" class="btn btn-outline-danger btn-sm rounded-0 cm-item"><i class="fa-regular fa-trash-can"></i> Delete</button>');
+                $deleteBtn.click(function() {
+                    self.mainJS.addProcessCount();
+                    emit("delete-ref", refName).then();
+                });
+                $contextMenu.append($deleteBtn);
+            }
+
+            $contextMenu.show();
+        };
+    }
+
     /**
      * Gets the function to be called by oncontextmenu
      * @return {(function(*): void)|*}
      */
-    getContextFunction(sha) {
+    getContextFunction(sha, boundary) {
         const self = this;
         return function(event) {
             event.preventDefault();
+            if (self.selectedSHAs.length > 1 && self.selectedSHAs.includes(sha)) {
+                self.showMultipleCommitsContextMenu(event);
+                return;
+            }
             const $contextMenu = $('#contextMenu');
             $contextMenu.empty();
             $contextMenu.css('left', event.pageX + 'px');
             $contextMenu.css('top', event.pageY + 'px');
 
+            if (boundary === 'shallow') {
+                const $deepenBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-angles-down"></i> Fetch More History</button>');
+                $deepenBtn.click(function() {
+                    $('#deepenModal').modal('show');
+                });
+                $contextMenu.append($deepenBtn);
+            }
+
             const $tagBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-tag"></i> Tag</button>');
             $tagBtn.click(function() {
                 $('#tagSha').text(sha);
@@ -362,6 +658,13 @@ export class SVGManager {
             });
             $contextMenu.append($rebaseBtn);
 
+            const $mergeOptionsBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-sliders"></i> Merge/Rebase With Options...</button>');
+            $mergeOptionsBtn.click(function() {
+                $('#mergeOptionsTarget').text(sha);
+                $('#mergeOptionsModal').modal('show');
+            });
+            $contextMenu.append($mergeOptionsBtn);
+
             const $cherrypickBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-bullseye"></i> Cherrypick Commit</button>');
             $cherrypickBtn.click(function() {
                 $('#cherrypickSha').text(sha);
@@ -370,9 +673,25 @@ export class SVGManager {
             });
             $contextMenu.append($cherrypickBtn);
 
+            const $compareWorkdirBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-code-compare"></i> Compare With Working Directory</button>');
+            $compareWorkdirBtn.click(function() {
+                emit("get-workdir-diff", sha).then();
+            });
+            $contextMenu.append($compareWorkdirBtn);
+
+            const $replaceBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-right-left"></i> Replace Commit</button>');
+            $replaceBtn.click(function() {
+                $('#replaceOriginalSha').text(sha);
+                $('#replacementTxt').val('');
+                $('#forceReplaceCheckBox').prop('checked', false);
+                $('#replaceModal').modal('show');
+            });
+            $contextMenu.append($replaceBtn);
+
             const $revertBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-solid fa-rotate-left"></i> Revert Commit</button>');
             $revertBtn.click(function() {
                 $('#revertSha').text(sha);
+                $('#revertMainlineContainer').hide();
                 $('#commitRevertCheckBox').prop('checked', true);
                 $('#revertModal').modal('show');
             });
@@ -380,10 +699,28 @@ export class SVGManager {
 
             const $copyShaBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-regular fa-clipboard"></i> Copy SHA</button>');
             $copyShaBtn.click(function() {
-                writeText(sha).then();
+                emit("copy-commit-field", {sha: sha, field: 'sha'}).then();
             });
             $contextMenu.append($copyShaBtn);
 
+            const $copyShortShaBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-regular fa-clipboard"></i> Copy Short SHA</button>');
+            $copyShortShaBtn.click(function() {
+                emit("copy-commit-field", {sha: sha, field: 'short_sha'}).then();
+            });
+            $contextMenu.append($copyShortShaBtn);
+
+            const $copyMessageBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-regular fa-clipboard"></i> Copy Message</button>');
+            $copyMessageBtn.click(function() {
+                emit("copy-commit-field", {sha: sha, field: 'message'}).then();
+            });
+            $contextMenu.append($copyMessageBtn);
+
+            const $copyAuthorBtn = $('<button type="button" class="btn btn-outline-light btn-sm rounded-0 cm-item"><i class="fa-regular fa-clipboard"></i> Copy Author</button>');
+            $copyAuthorBtn.click(function() {
+                emit("copy-commit-field", {sha: sha, field: 'author'}).then();
+            });
+            $contextMenu.append($copyAuthorBtn);
+
             const $softResetBtn = $('<button type="button" class="btn btn-outline-danger btn-sm rounded-0 cm-item"><i class="fa-solid fa-rotate-left"></i> Soft Reset to Here</button>');
             $softResetBtn.click(function() {
                 emit("reset", {sha: sha, type: "soft"}).then();